@@ -0,0 +1,95 @@
+//! Global allocator, that counts allocations. Used to benchmark memory usage
+//! instead of time.
+//!
+//! Memory usage is deterministic, so each case is measured once and results
+//! are printed before time measurements. To use it, register the allocator
+//! in the benchmark:
+//!
+//! ```ignore
+//! mod alloc;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: alloc::Counting = alloc::Counting;
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of allocations and reallocations since the start of the program
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// Number of bytes, requested by allocations and reallocations since the start
+/// of the program. Deallocations do not decrease it
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// Number of bytes, that are allocated now
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+/// Maximum of `LIVE` since the last reset in [`measure`]
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocator, that counts allocations and delegates them to the system allocator
+pub struct Counting;
+
+impl Counting {
+    fn grow(&self, size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(size, Ordering::Relaxed);
+        let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.grow(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        self.grow(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Memory, used by a benchmarked function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Usage {
+    /// Number of allocations, including reallocations, for example, when a `Vec` grows
+    pub allocations: usize,
+    /// Number of allocated bytes
+    pub allocated: usize,
+    /// Maximum number of bytes, that were allocated at the same time
+    pub peak: usize,
+}
+
+/// Calls `f` once and returns memory, that was used by it, including memory
+/// of its result. Allocations are counted only if the [`Counting`] allocator
+/// is registered as a global allocator
+pub fn measure<R>(f: impl FnOnce() -> R) -> Usage {
+    let allocations = ALLOCATIONS.load(Ordering::SeqCst);
+    let allocated = ALLOCATED.load(Ordering::SeqCst);
+    let live = LIVE.load(Ordering::SeqCst);
+    PEAK.store(live, Ordering::SeqCst);
+
+    drop(f());
+
+    Usage {
+        allocations: ALLOCATIONS.load(Ordering::SeqCst) - allocations,
+        allocated: ALLOCATED.load(Ordering::SeqCst) - allocated,
+        peak: PEAK.load(Ordering::SeqCst) - live,
+    }
+}
+
+/// Measures memory, used by `f`, and prints it in a line with the `name`
+pub fn report<R>(name: &str, f: impl FnOnce() -> R) -> Usage {
+    let usage = measure(f);
+    println!(
+        "{:<48} {:>8} allocations {:>10} bytes allocated {:>10} bytes peak",
+        name, usage.allocations, usage.allocated, usage.peak,
+    );
+    usage
+}
@@ -1,10 +1,17 @@
-use criterion::{self, criterion_group, criterion_main, Criterion};
-use fast_xml::events::Event;
+use criterion::{self, criterion_group, Criterion};
+use fast_xml::events::{BytesStart, BytesText, Event};
 use fast_xml::Reader;
 use pretty_assertions::assert_eq;
+use std::borrow::Cow;
+
+mod alloc;
+
+#[global_allocator]
+static ALLOCATOR: alloc::Counting = alloc::Counting;
 
 static SAMPLE: &[u8] = include_bytes!("../tests/sample_rss.xml");
 static PLAYERS: &[u8] = include_bytes!("../tests/players.xml");
+static SVG: &[u8] = include_bytes!("../tests/documents/attributes_dense.svg");
 
 /// Benchmarks the `Reader::read_event` function with all XML well-formless
 /// checks disabled (with and without trimming content of #text nodes)
@@ -170,6 +177,80 @@ fn bytes_text_unescaped(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmarks the `Reader::decode()` method on texts of an ASCII-only document
+/// with and without `Reader::assume_ascii` (includes time of `read_event`
+/// benchmark)
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Reader::decode");
+    for &assume_ascii in &[false, true] {
+        group.bench_function(format!("assume_ascii = {}", assume_ascii), |b| {
+            b.iter(|| {
+                let mut buf = Vec::new();
+                let mut r = Reader::from_reader(SAMPLE);
+                r.check_end_names(false)
+                    .check_comments(false)
+                    .trim_text(true)
+                    .assume_ascii(assume_ascii);
+                let mut nbtxt = criterion::black_box(0);
+                loop {
+                    match r.read_event(&mut buf) {
+                        Ok(Event::Text(ref e)) => nbtxt += r.decode(e).unwrap().len(),
+                        Ok(Event::Eof) => break,
+                        _ => (),
+                    }
+                    buf.clear();
+                }
+
+                // Texts are not unescaped. Windows has \r\n instead of \n,
+                // so the length is checked only for other platforms
+                #[cfg(not(windows))]
+                assert_eq!(
+                    nbtxt, 50685,
+                    "Overall length (in bytes) of all text contents of ./tests/sample_rss.xml"
+                );
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Benchmarks the `BytesText::unescape()` method on the text without entities,
+/// which should not allocate, and with entities for comparison
+fn bytes_text_unescape(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BytesText::unescape");
+    group.bench_function("without entities", |b| {
+        let text = BytesText::from_escaped_str(
+            "Lorem ipsum dolor sit amet, consectetur adipiscing elit; sed do eiusmod \
+             tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, \
+             quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo \
+             consequat; duis aute irure dolor in reprehenderit in voluptate velit esse \
+             cillum dolore eu fugiat nulla pariatur.",
+        );
+        b.iter(|| {
+            let unescaped = criterion::black_box(&text).unescape().unwrap();
+            // Text without entities should be borrowed, so no allocation happens
+            assert!(matches!(unescaped, Cow::Borrowed(_)));
+            criterion::black_box(unescaped);
+        })
+    });
+
+    group.bench_function("with entities", |b| {
+        let text = BytesText::from_escaped_str(
+            "Lorem ipsum dolor sit amet, consectetur adipiscing elit; sed do eiusmod \
+             tempor incididunt ut labore &amp; dolore magna aliqua. Ut enim ad minim veniam, \
+             quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo \
+             consequat; duis aute irure dolor in &lt;reprehenderit&gt; in voluptate velit \
+             esse cillum dolore eu fugiat nulla pariatur.",
+        );
+        b.iter(|| {
+            let unescaped = criterion::black_box(&text).unescape().unwrap();
+            assert!(matches!(unescaped, Cow::Owned(_)));
+            criterion::black_box(unescaped);
+        })
+    });
+    group.finish();
+}
+
 /// Benchmarks, how fast individual event parsed
 fn one_event(c: &mut Criterion) {
     let mut group = c.benchmark_group("One event");
@@ -332,12 +413,127 @@ fn attributes(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmarks parsing and unescaping attributes in an attribute-dense document
+fn attributes_dense(c: &mut Criterion) {
+    let mut group = c.benchmark_group("attributes_dense");
+    group.bench_function("attributes", |b| {
+        b.iter(|| {
+            let mut r = Reader::from_reader(SVG);
+            r.check_end_names(false).check_comments(false);
+            let mut count = criterion::black_box(0);
+            let mut buf = Vec::new();
+            loop {
+                match r.read_event(&mut buf) {
+                    Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                        for attr in e.attributes() {
+                            let _attr = attr.unwrap();
+                            count += 1
+                        }
+                    }
+                    Ok(Event::Eof) => break,
+                    _ => (),
+                }
+                buf.clear();
+            }
+            assert_eq!(count, 3322);
+        })
+    });
+
+    group.bench_function("unescaped_value", |b| {
+        b.iter(|| {
+            let mut r = Reader::from_reader(SVG);
+            r.check_end_names(false).check_comments(false);
+            let mut count = criterion::black_box(0);
+            let mut buf = Vec::new();
+            loop {
+                match r.read_event(&mut buf) {
+                    Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                        for attr in e.attributes() {
+                            let _value = attr.unwrap().unescaped_value().unwrap();
+                            count += 1
+                        }
+                    }
+                    Ok(Event::Eof) => break,
+                    _ => (),
+                }
+                buf.clear();
+            }
+            assert_eq!(count, 3322);
+        })
+    });
+    group.finish();
+}
+
+/// Creates an element with `count` attributes
+fn many_attributes(count: usize) -> String {
+    let mut tag = String::from("tag");
+    for i in 0..count {
+        tag.push_str(&format!(r#" attr{}="value {}""#, i, i));
+    }
+    tag
+}
+
+/// Measures memory, used by iteration over attributes of elements with 1 000
+/// and 10 000 attributes. Duplicate checks of the `attributes()` iterator
+/// remember all seen names, so used memory grows with the number of attributes,
+/// while `for_each_attribute` parses attributes one by one with a constant
+/// extra memory
+fn attributes_many() {
+    let mut peaks = Vec::new();
+    for &count in &[1_000, 10_000] {
+        let tag = many_attributes(count);
+        let start = BytesStart::borrowed(tag.as_bytes(), 3);
+
+        alloc::report(
+            &format!("attributes_many/{}/with_checks = true", count),
+            || {
+                let mut found = 0;
+                for attr in start.attributes() {
+                    let _attr = attr.unwrap();
+                    found += 1;
+                }
+                assert_eq!(found, count);
+            },
+        );
+        alloc::report(
+            &format!("attributes_many/{}/with_checks = false", count),
+            || {
+                let mut found = 0;
+                for attr in start.attributes().with_checks(false) {
+                    let _attr = attr.unwrap();
+                    found += 1;
+                }
+                assert_eq!(found, count);
+            },
+        );
+        let usage = alloc::report(
+            &format!("attributes_many/{}/for_each_attribute", count),
+            || {
+                let mut found = 0;
+                start.for_each_attribute(|_attr| found += 1).unwrap();
+                assert_eq!(found, count);
+            },
+        );
+        peaks.push(usage.peak);
+    }
+    assert_eq!(peaks[0], peaks[1]);
+}
+
 criterion_group!(
     benches,
     read_event,
     bytes_text_unescaped,
+    bytes_text_unescape,
+    decode,
     read_namespaced_event,
     one_event,
-    attributes
+    attributes,
+    attributes_dense
 );
-criterion_main!(benches);
+
+fn main() {
+    attributes_many();
+
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
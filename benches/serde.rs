@@ -0,0 +1,93 @@
+use criterion::{self, criterion_group, Criterion};
+use fast_xml::de::{from_reader, from_str, Deserializer};
+use serde::Deserialize;
+
+mod alloc;
+
+#[global_allocator]
+static ALLOCATOR: alloc::Counting = alloc::Counting;
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    id: usize,
+    #[serde(rename = "$value")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct List {
+    item: Vec<Item>,
+}
+
+/// Generates a list with 10 000 items
+fn list() -> String {
+    let mut xml = String::from("<list>");
+    for i in 0..10_000 {
+        xml.push_str(&format!(r#"<item id="{0}">item {0}</item>"#, i));
+    }
+    xml.push_str("</list>");
+    xml
+}
+
+/// Checks that all items of the list are deserialized
+fn check(list: &List) {
+    assert_eq!(list.item.len(), 10_000);
+    let last = &list.item[9_999];
+    assert_eq!(last.id, 9_999);
+    assert_eq!(last.name, "item 9999");
+}
+
+/// Measures allocations when deserializing a long `Vec` from in-memory input,
+/// where the sequence size is known in advance and a `Vec` is allocated once,
+/// and from a `BufRead` source, where a `Vec` grows while reading. Note, that
+/// the latter also copies strings from events, because they cannot be borrowed
+/// from the input
+fn sequence() {
+    let xml = list();
+    let hinted = alloc::report("sequence/from_str (with size hint)", || {
+        let list: List = from_str(&xml).unwrap();
+        check(&list);
+        assert_eq!(list.item.capacity(), 10_000);
+    });
+    let growing = alloc::report("sequence/from_reader (without size hint)", || {
+        let list: List = from_reader(xml.as_bytes()).unwrap();
+        check(&list);
+    });
+    assert!(hinted.allocations < growing.allocations);
+}
+
+/// Benchmarks the cost of `Deserializer::validate_balanced_upfront` for
+/// a well-formed document and the gain for a document truncated at the end
+fn validation(c: &mut Criterion) {
+    let xml = list();
+    let truncated = &xml[..xml.len() - "</item></list>".len()];
+    let mut group = c.benchmark_group("validation");
+    for (name, validate) in [("lazy", false), ("upfront", true)] {
+        group.bench_function(format!("well-formed ({})", name), |b| {
+            b.iter(|| {
+                let mut de = Deserializer::from_str(&xml);
+                de.validate_balanced_upfront(validate);
+                let list = List::deserialize(&mut de).unwrap();
+                check(&list);
+            })
+        });
+
+        group.bench_function(format!("truncated ({})", name), |b| {
+            b.iter(|| {
+                let mut de = Deserializer::from_str(truncated);
+                de.validate_balanced_upfront(validate);
+                assert!(List::deserialize(&mut de).is_err());
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, validation);
+
+fn main() {
+    sequence();
+
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
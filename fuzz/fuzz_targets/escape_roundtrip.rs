@@ -0,0 +1,21 @@
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+
+use fast_xml::escape::{escape, partial_escape, unescape};
+
+fuzz_target!(|data: &[u8]| {
+    // Escaped data never contains raw `&`, so unescaping gives the input back
+    let escaped = escape(data);
+    assert_eq!(&*unescape(&escaped).expect("escaped data is valid"), data);
+    let escaped = partial_escape(data);
+    assert_eq!(&*unescape(&escaped).expect("escaped data is valid"), data);
+
+    // Unescaped data is escaped to the form, that is unescaped to the same data
+    if let Ok(unescaped) = unescape(data) {
+        let escaped = escape(&unescaped);
+        assert_eq!(
+            unescape(&escaped).expect("escaped data is valid"),
+            unescaped
+        );
+    }
+});
@@ -23,18 +23,25 @@ fuzz_target!(|data: &[u8]| {
                 }
             }
             Ok(Event::Text(ref e)) | Ok(Event::Comment(ref e))
-            | Ok(Event::CData(ref e)) | Ok(Event::PI(ref e))
-            | Ok(Event::DocType(ref e)) => {
+            | Ok(Event::PI(ref e)) | Ok(Event::DocType(ref e)) => {
                 if e.unescaped().is_err() {
                     break;
                 }
             }
+            Ok(Event::CData(e)) => {
+                if e.escape().unescaped().is_err() {
+                    break;
+                }
+            }
             Ok(Event::Decl(ref e)) => {
                 let _ = e.version();
                 let _ = e.encoding();
                 let _ = e.standalone();
             }
             Ok(Event::End(_)) => (),
+            Ok(Event::GeneralRef(ref e)) => {
+                let _ = e.name();
+            }
             Ok(Event::Eof) | Err(..) => break,
         }
         buf.clear();
@@ -1,10 +1,32 @@
 //! Serde `Deserializer` module
 
-use crate::de::deserialize_bool;
+use crate::de::{deserialize_bool, keep_number, parse_float};
 use crate::{errors::serialize::DeError, errors::Error, escape::unescape, reader::Decoder};
 use serde::de::{DeserializeSeed, EnumAccess, VariantAccess, Visitor};
 use serde::{self, forward_to_deserialize_any, serde_if_integer128};
 use std::borrow::Cow;
+use std::ops::Deref;
+
+/// A value of the [`EscapedDeserializer`]
+#[derive(Clone, Debug)]
+enum Value<'de, 'a> {
+    /// A value borrowed from the input of the deserializer. Strings, that
+    /// require neither unescaping nor decoding, are borrowed from it
+    Input(&'de [u8]),
+    /// A value borrowed from a buffer of an event or owned
+    Buffer(Cow<'a, [u8]>),
+}
+
+impl<'de, 'a> Deref for Value<'de, 'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Value::Input(value) => value,
+            Value::Buffer(value) => value,
+        }
+    }
+}
 
 /// A deserializer for a xml escaped and encoded value
 ///
@@ -14,29 +36,74 @@ use std::borrow::Cow;
 /// when converting to float, we don't expect any escapable character
 /// anyway
 #[derive(Clone, Debug)]
-pub struct EscapedDeserializer<'a> {
+pub struct EscapedDeserializer<'de, 'a> {
     decoder: Decoder,
     /// Possible escaped value of text/CDATA or attribute value
-    escaped_value: Cow<'a, [u8]>,
+    escaped_value: Value<'de, 'a>,
     /// If `true`, value requires unescaping before using
     escaped: bool,
+    /// If `true`, special float values accepted only in the XML Schema spelling
+    xsd_floats: bool,
+    /// A function that is applied to the text of numbers before parsing
+    number_sanitizer: fn(&str) -> Cow<str>,
 }
 
-impl<'a> EscapedDeserializer<'a> {
+impl<'de, 'a> EscapedDeserializer<'de, 'a> {
     pub fn new(escaped_value: Cow<'a, [u8]>, decoder: Decoder, escaped: bool) -> Self {
         EscapedDeserializer {
             decoder,
-            escaped_value,
+            escaped_value: Value::Buffer(escaped_value),
             escaped,
+            xsd_floats: false,
+            number_sanitizer: keep_number,
         }
     }
-    fn unescaped(&self) -> Result<Cow<[u8]>, DeError> {
-        if self.escaped {
-            unescape(&self.escaped_value).map_err(|e| DeError::InvalidXml(Error::EscapeError(e)))
-        } else {
-            Ok(Cow::Borrowed(&self.escaped_value))
+    /// Creates a deserializer of a value, borrowed from the input of the
+    /// deserializer. Strings, deserialized from such value, are borrowed
+    /// if they require neither unescaping nor decoding
+    pub fn from_input(escaped_value: &'de [u8], decoder: Decoder, escaped: bool) -> Self {
+        EscapedDeserializer {
+            decoder,
+            escaped_value: Value::Input(escaped_value),
+            escaped,
+            xsd_floats: false,
+            number_sanitizer: keep_number,
         }
     }
+    /// Creates a deserializer of a text, that was already unescaped and decoded
+    /// from the encoding of the document, for example, a value buffered by serde.
+    /// Such text is always in UTF-8, so it is not decoded again
+    pub fn from_text(text: Cow<'a, str>) -> Self {
+        let text = match text {
+            Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+            Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+        };
+        Self::new(text, Decoder::utf8(), false)
+    }
+    /// Changes how special values of `f32` and `f64` are recognized.
+    /// See [`Deserializer::xsd_floats`](super::Deserializer::xsd_floats)
+    pub fn with_xsd_floats(mut self, val: bool) -> Self {
+        self.xsd_floats = val;
+        self
+    }
+    /// Changes how the text of numbers is prepared before parsing.
+    /// See [`Deserializer::number_sanitizer`](super::Deserializer::number_sanitizer)
+    pub fn with_number_sanitizer(mut self, sanitizer: fn(&str) -> Cow<str>) -> Self {
+        self.number_sanitizer = sanitizer;
+        self
+    }
+    fn unescaped(&self) -> Result<Cow<[u8]>, DeError> {
+        unescape_if(&self.escaped_value, self.escaped)
+    }
+}
+
+/// Unescapes `value`, if `escaped` is `true`
+fn unescape_if(value: &[u8], escaped: bool) -> Result<Cow<'_, [u8]>, DeError> {
+    if escaped {
+        unescape(value).map_err(|e| DeError::InvalidXml(Error::EscapeError(e)))
+    } else {
+        Ok(Cow::Borrowed(value))
+    }
 }
 
 macro_rules! deserialize_num {
@@ -45,18 +112,29 @@ macro_rules! deserialize_num {
         where
             V: Visitor<'de>,
         {
-            #[cfg(not(feature = "encoding"))]
-            let value = self.decoder.decode(self.escaped_value.as_ref())?.parse()?;
-
-            #[cfg(feature = "encoding")]
-            let value = self.decoder.decode(self.escaped_value.as_ref()).parse()?;
+            let value = self.decoder.decode(&self.escaped_value)?;
+            let value = (self.number_sanitizer)(&value).parse()?;
 
             visitor.$visit(value)
         }
     };
 }
 
-impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'a> {
+macro_rules! deserialize_float {
+    ($method:ident, $visit:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let value = self.decoder.decode(&self.escaped_value)?;
+            let value = (self.number_sanitizer)(&value);
+
+            visitor.$visit(parse_float(&value, self.xsd_floats)?)
+        }
+    };
+}
+
+impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'de, 'a> {
     type Error = DeError;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -70,12 +148,15 @@ impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'a> {
     where
         V: Visitor<'de>,
     {
+        if let Value::Input(value) = self.escaped_value {
+            if let Cow::Borrowed(unescaped) = unescape_if(value, self.escaped)? {
+                if let Cow::Borrowed(value) = self.decoder.decode(unescaped)? {
+                    return visitor.visit_borrowed_str(value);
+                }
+            }
+        }
         let unescaped = self.unescaped()?;
-        #[cfg(not(feature = "encoding"))]
         let value = self.decoder.decode(&unescaped)?;
-
-        #[cfg(feature = "encoding")]
-        let value = self.decoder.decode(&unescaped);
         visitor.visit_str(&value)
     }
 
@@ -105,7 +186,7 @@ impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'a> {
     where
         V: Visitor<'de>,
     {
-        deserialize_bool(self.escaped_value.as_ref(), self.decoder, visitor)
+        deserialize_bool(&self.escaped_value, self.decoder, visitor)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -126,7 +207,7 @@ impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'a> {
     where
         V: Visitor<'de>,
     {
-        if self.escaped_value.as_ref().is_empty() {
+        if self.escaped_value.is_empty() {
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -164,8 +245,8 @@ impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'a> {
     deserialize_num!(deserialize_u32, visit_u32);
     deserialize_num!(deserialize_u16, visit_u16);
     deserialize_num!(deserialize_u8, visit_u8);
-    deserialize_num!(deserialize_f64, visit_f64);
-    deserialize_num!(deserialize_f32, visit_f32);
+    deserialize_float!(deserialize_f64, visit_f64);
+    deserialize_float!(deserialize_f32, visit_f32);
 
     serde_if_integer128! {
         deserialize_num!(deserialize_i128, visit_i128);
@@ -177,7 +258,7 @@ impl<'de, 'a> serde::Deserializer<'de> for EscapedDeserializer<'a> {
     }
 }
 
-impl<'de, 'a> EnumAccess<'de> for EscapedDeserializer<'a> {
+impl<'de, 'a> EnumAccess<'de> for EscapedDeserializer<'de, 'a> {
     type Error = DeError;
     type Variant = Self;
 
@@ -190,7 +271,7 @@ impl<'de, 'a> EnumAccess<'de> for EscapedDeserializer<'a> {
     }
 }
 
-impl<'de, 'a> VariantAccess<'de> for EscapedDeserializer<'a> {
+impl<'de, 'a> VariantAccess<'de> for EscapedDeserializer<'de, 'a> {
     type Error = DeError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
@@ -2,13 +2,17 @@
 
 use crate::{
     de::escape::EscapedDeserializer,
-    de::{deserialize_bool, DeEvent, Deserializer, XmlRead, INNER_VALUE, UNFLATTEN_PREFIX},
+    de::{
+        deserialize_bool, parse_float, DeEvent, Deserializer, NameCollision, XmlRead,
+        ATTRIBUTE_PREFIX, ELEMENT_NAME, INNER_VALUE, UNFLATTEN_PREFIX,
+    },
     errors::serialize::DeError,
-    events::attributes::IterState,
+    events::attributes::{Attributes, IterState},
     events::{BytesCData, BytesStart},
     reader::Decoder,
 };
-use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
 use serde::serde_if_integer128;
 use std::borrow::Cow;
 use std::ops::Range;
@@ -27,6 +31,17 @@ enum ValueSource {
     /// [`next_key_seed()`]: de::MapAccess::next_key_seed
     /// [`next_value_seed()`]: de::MapAccess::next_value_seed
     Unknown,
+    /// Next value should be deserialized from the local name of the element,
+    /// which represents the whole map. Key is always equal to [`ELEMENT_NAME`].
+    /// That state is set only once, before any other keys, when struct has
+    /// a field with the special name [`ELEMENT_NAME`]. No events are consumed
+    /// in that state.
+    ///
+    /// ```xml
+    /// <any-tag attr="...">...</any-tag>
+    /// <!--~~~~~~~ - this data will be used to deserialize a map value -->
+    /// ```
+    Name,
     /// Next value should be deserialized from an attribute value; value is located
     /// at specified span.
     Attribute(Range<usize>),
@@ -133,9 +148,22 @@ enum ValueSource {
     /// [`name()`]: BytesStart::name()
     /// [`Content`]: Self::Content
     Nested,
+    /// Next value should be deserialized from copies of elements, which was
+    /// not matched by any field of a struct with `#[serde(flatten)]` fields.
+    /// The key is always equal to [`INNER_VALUE`].
+    ///
+    /// That state is set once, when the end of the element is reached, if
+    /// unmatched elements were seen. No events are consumed in that state.
+    /// See [`MapAccess::unmatched`] for details.
+    Unmatched,
 }
 
 /// A deserializer for `Attributes`
+///
+/// Keys are returned in the document order: first the [`ELEMENT_NAME`] key
+/// (if requested), then attributes in order of their appearance in the start
+/// tag, then child elements and text content in order of their appearance
+/// inside the element. Order-preserving maps, such as `IndexMap`, rely on that.
 pub(crate) struct MapAccess<'de, 'a, R>
 where
     R: XmlRead<'de>,
@@ -149,8 +177,59 @@ where
     /// Current state of the accessor that determines what next call to API
     /// methods should return.
     source: ValueSource,
+    /// List of fields of the struct, that is deserialized. Used to decide whether
+    /// an element should be routed to the [`INNER_VALUE`] field. Serde includes
+    /// names from `#[serde(alias = "...")]` in that list, so elements named
+    /// after aliases are also matched to their fields.
+    fields: &'static [&'static str],
+    /// `true` if struct has a field with the special name [`ELEMENT_NAME`],
+    /// that is not yet returned by the accessor
+    name_pending: bool,
+    /// `true` if the map represents a struct with `#[serde(flatten)]` fields.
+    /// Serde requests such structs as maps, so their fields are unknown, and
+    /// that is detected only when a key is requested as an identifier.
+    /// See [`KeyDeserializer`]
+    flatten: bool,
+    /// `true` if the text content of the element was returned under
+    /// the [`INNER_VALUE`] key
+    has_text: bool,
+    /// Copies of elements, that was not matched by own fields of a struct with
+    /// `#[serde(flatten)]` fields, with their local names.
+    ///
+    /// Serde buffers values of unknown keys to pass them to the flattened
+    /// fields, but a flattened struct could match only keys with the names
+    /// of its fields. So after all elements are read, copies of unmatched
+    /// elements are returned once again as a sequence under the [`INNER_VALUE`]
+    /// key, which allows to capture them in a flattened `$value` field:
+    ///
+    /// ```ignore
+    /// enum Enum { A, B, С }
+    /// struct Inner {
+    ///     #[serde(rename = "$value", default)]
+    ///     items: Vec<Enum>,
+    /// }
+    /// struct Root {
+    ///     #[serde(flatten)]
+    ///     inner: Inner,
+    /// }
+    /// ```
+    ///
+    /// Each item of that sequence is a map with one entry, the element name
+    /// and its content, which is the representation of an externally tagged
+    /// enum. The sequence is not returned if the element has a text content,
+    /// because it would be a duplicated [`INNER_VALUE`] key, or if
+    /// [`Deserializer::flatten_as_map`] is set.
+    unmatched: Vec<(String, Node)>,
     /// list of fields yet to unflatten (defined as starting with $unflatten=)
     unflatten_fields: Vec<&'static [u8]>,
+    /// A copy of [`Deserializer::map_key_prefix`], that is stripped from
+    /// names of attributes and elements when they are used as map keys
+    key_prefix: Vec<u8>,
+    /// Spans of keys and values of attributes, that are returned after all
+    /// child elements, if they was not overridden by elements with the same
+    /// names. Used when [`Deserializer::name_collision_policy`] is
+    /// [`NameCollision::ElementFirst`]
+    deferred: Vec<AttributeSpan>,
 }
 
 impl<'de, 'a, R> MapAccess<'de, 'a, R>
@@ -161,18 +240,28 @@ where
     pub fn new(
         de: &'a mut Deserializer<'de, R>,
         start: BytesStart<'de>,
-        fields: &[&'static str],
+        fields: &'static [&'static str],
     ) -> Result<Self, DeError> {
+        let key_prefix = de.map_key_prefix.clone();
+        let mut iter = IterState::new(0, false);
+        iter.limits = start.attribute_limits();
         Ok(MapAccess {
             de,
             start,
-            iter: IterState::new(0, false),
+            iter,
             source: ValueSource::Unknown,
+            fields,
+            name_pending: fields.contains(&ELEMENT_NAME),
+            flatten: false,
+            has_text: false,
+            unmatched: Vec::new(),
             unflatten_fields: fields
                 .iter()
                 .filter(|f| f.starts_with(UNFLATTEN_PREFIX))
                 .map(|f| f.as_bytes())
                 .collect(),
+            key_prefix,
+            deferred: Vec::new(),
         })
     }
 }
@@ -191,24 +280,69 @@ where
 
         // FIXME: There error positions counted from end of tag name - need global position
         let slice = self.start.attributes_raw();
-        let decoder = self.de.reader.decoder();
+        let names = Names {
+            decoder: self.de.reader.decoder(),
+            normalizer: self.de.field_name_normalizer,
+        };
         let has_value_field = self.de.has_value_field;
+        let flatten_as_map = self.de.flatten_as_map;
+        // Prefix is stripped only from keys of maps, but not from names of fields
+        let key_prefix: &[u8] = if self.fields.is_empty() {
+            &self.key_prefix
+        } else {
+            &[]
+        };
 
-        if let Some(a) = self.iter.next(slice).transpose()? {
+        if self.name_pending {
+            self.name_pending = false;
+            self.source = ValueSource::Name;
+            seed.deserialize(ELEMENT_NAME.into_deserializer()).map(Some)
+        } else if let Some((key, value)) = next_attribute(
+            &mut self.iter,
+            slice,
+            self.fields,
+            self.de.name_collision,
+            &mut self.deferred,
+            names,
+        )? {
             // try getting map from attributes (key= "value")
-            let (key, value) = a.into();
-            self.source = ValueSource::Attribute(value.unwrap_or_default());
-            seed.deserialize(EscapedDeserializer::new(
-                Cow::Borrowed(&slice[key]),
-                decoder,
-                false,
-            ))
-            .map(Some)
+            self.source = ValueSource::Attribute(value);
+            if let Some(stats) = self.de.stats.as_mut() {
+                stats.attributes += 1;
+            }
+            let name = &slice[key];
+            if let Some(field) = attribute_field(self.fields, &names.normalize(name)?) {
+                seed.deserialize(field.into_deserializer()).map(Some)
+            } else {
+                let field = if flatten_as_map {
+                    names.prefixed_key(ATTRIBUTE_PREFIX, name)?
+                } else {
+                    names.key(name)?
+                };
+                seed.deserialize(KeyDeserializer {
+                    map_key: names.key(strip_key_prefix(key_prefix, name))?,
+                    field,
+                    flatten: flatten_flag(self.fields, &mut self.flatten),
+                })
+                .map(Some)
+            }
         } else {
+            if self.de.name_collision == NameCollision::AttributeFirst {
+                // Skip elements, which values are already taken from attributes
+                while let DeEvent::Start(e) = self.de.peek()? {
+                    if !is_colliding(self.fields, slice, e.local_name(), names)? {
+                        break;
+                    }
+                    let name = e.name().to_vec();
+                    self.de.next()?;
+                    self.de.read_to_end(&name)?;
+                }
+            }
             // try getting from events (<key>value</key>)
             match self.de.peek()? {
                 DeEvent::Text(_) | DeEvent::CData(_) => {
                     self.source = ValueSource::Text;
+                    self.has_text = true;
                     // Deserialize `key` from special attribute name which means
                     // that value should be taken from the text content of the
                     // XML node
@@ -230,12 +364,29 @@ where
                 // }
                 // TODO: This should be handled by #[serde(flatten)]
                 // See https://github.com/serde-rs/serde/issues/1905
-                DeEvent::Start(_) if has_value_field => {
+                //
+                // Elements, named after other fields of the struct, are not
+                // routed to the `$value` field
+                DeEvent::Start(e)
+                    if has_value_field
+                        && !is_known_field(self.fields, &self.unflatten_fields, e, names)? =>
+                {
                     self.source = ValueSource::Content;
                     seed.deserialize(INNER_VALUE.into_deserializer()).map(Some)
                 }
                 DeEvent::Start(e) => {
                     self.source = ValueSource::Nested;
+                    // Elements override deferred attributes with the same name
+                    if !self.deferred.is_empty() {
+                        let name = names.normalize(e.local_name())?;
+                        let mut kept = Vec::with_capacity(self.deferred.len());
+                        for (key, value) in self.deferred.drain(..) {
+                            if names.normalize(&slice[key.clone()])? != name {
+                                kept.push((key, value));
+                            }
+                        }
+                        self.deferred = kept;
+                    }
                     let key = if let Some(p) = self
                         .unflatten_fields
                         .iter()
@@ -254,11 +405,24 @@ where
                         // }
                         seed.deserialize(self.unflatten_fields.remove(p).into_deserializer())
                     } else {
-                        let name = Cow::Borrowed(e.local_name());
-                        seed.deserialize(EscapedDeserializer::new(name, decoder, false))
+                        let name = e.local_name();
+                        seed.deserialize(KeyDeserializer {
+                            map_key: names.key(strip_key_prefix(key_prefix, name))?,
+                            field: names.key(name)?,
+                            flatten: flatten_flag(self.fields, &mut self.flatten),
+                        })
                     };
                     key.map(Some)
                 }
+                _ if !self.deferred.is_empty() => {
+                    let (key, value) = self.deferred.remove(0);
+                    self.source = ValueSource::Attribute(value);
+                    seed.deserialize(names.key(&slice[key])?).map(Some)
+                }
+                _ if !self.unmatched.is_empty() && !self.has_text && !flatten_as_map => {
+                    self.source = ValueSource::Unmatched;
+                    seed.deserialize(INNER_VALUE.into_deserializer()).map(Some)
+                }
                 _ => Ok(None),
             }
         }
@@ -269,15 +433,28 @@ where
         seed: K,
     ) -> Result<K::Value, Self::Error> {
         match std::mem::replace(&mut self.source, ValueSource::Unknown) {
+            ValueSource::Name => {
+                let name = Cow::Borrowed(self.start.local_name());
+                let decoder = self.de.reader.decoder();
+
+                seed.deserialize(EscapedDeserializer::new(name, decoder, false))
+            }
             ValueSource::Attribute(value) => {
-                let slice = self.start.attributes_raw();
                 let decoder = self.de.reader.decoder();
+                // Borrow the value from the input when possible, so it could be
+                // deserialized into a `&str` with `#[serde(borrow)]`
+                let de = match self.start.borrowed_attributes_raw() {
+                    Some(slice) => EscapedDeserializer::from_input(&slice[value], decoder, true),
+                    None => {
+                        let slice = self.start.attributes_raw();
+                        EscapedDeserializer::new(Cow::Borrowed(&slice[value]), decoder, true)
+                    }
+                };
 
-                seed.deserialize(EscapedDeserializer::new(
-                    Cow::Borrowed(&slice[value]),
-                    decoder,
-                    true,
-                ))
+                seed.deserialize(
+                    de.with_xsd_floats(self.de.xsd_floats)
+                        .with_number_sanitizer(self.de.number_sanitizer),
+                )
             }
             // This arm processes the following XML shape:
             // <any-tag>
@@ -303,12 +480,238 @@ where
             // The whole map represented by an `<any-tag>` element, the map key
             // is a `tag`, and the value is a `Start` event (the value deserializer
             // will see that event)
-            ValueSource::Nested => seed.deserialize(&mut *self.de),
+            ValueSource::Nested if self.flatten => {
+                seed.deserialize(FlattenedValueDeserializer { map: self })
+            }
+            ValueSource::Nested => {
+                // Only maps has no fields; they can group repeated elements
+                self.de.seq_group =
+                    self.de.group_repeated && self.fields.is_empty() && !self.flatten;
+                self.de.entry_value = true;
+                seed.deserialize(&mut *self.de)
+            }
+            ValueSource::Unmatched => {
+                let items = std::mem::take(&mut self.unmatched)
+                    .into_iter()
+                    .map(|(name, node)| Node::Map(vec![(name, node.into_variant_content())]));
+                seed.deserialize(SeqDeserializer::new(items))
+            }
             ValueSource::Unknown => Err(DeError::KeyNotRead),
         }
     }
 }
 
+/// Removes [`Deserializer::map_key_prefix`] from the name of an attribute or
+/// an element, if the name starts with it
+fn strip_key_prefix<'k>(prefix: &[u8], name: &'k [u8]) -> &'k [u8] {
+    name.strip_prefix(prefix).unwrap_or(name)
+}
+
+/// Converts names of attributes and elements to the form, in which they are
+/// matched with names of fields. See [`Deserializer::field_name_normalizer`]
+#[derive(Clone, Copy)]
+struct Names {
+    /// Decoder of names, that is used before normalization
+    decoder: Decoder,
+    /// A copy of [`Deserializer::field_name_normalizer`]
+    normalizer: Option<fn(&str) -> Cow<str>>,
+}
+
+impl Names {
+    /// Returns the normalized name. Names are returned as is if normalizer is
+    /// not set, otherwise normalized names are always in UTF-8
+    fn normalize<'n>(&self, name: &'n [u8]) -> Result<Cow<'n, [u8]>, DeError> {
+        match self.normalizer {
+            None => Ok(Cow::Borrowed(name)),
+            Some(normalize) => {
+                let name = self.decoder.decode(name)?;
+                Ok(Cow::Owned(normalize(&name).into_owned().into_bytes()))
+            }
+        }
+    }
+
+    /// Returns a decoder of normalized names
+    fn decoder(&self) -> Decoder {
+        match self.normalizer {
+            None => self.decoder,
+            Some(_) => Decoder::utf8(),
+        }
+    }
+
+    /// Returns a deserializer of a map key from the normalized name
+    fn key<'de, 'n>(&self, name: &'n [u8]) -> Result<EscapedDeserializer<'de, 'n>, DeError> {
+        Ok(EscapedDeserializer::new(
+            self.normalize(name)?,
+            self.decoder(),
+            false,
+        ))
+    }
+
+    /// Returns a deserializer of a map key from the normalized name with the `prefix`
+    fn prefixed_key<'de>(
+        &self,
+        prefix: &str,
+        name: &[u8],
+    ) -> Result<EscapedDeserializer<'de, 'static>, DeError> {
+        let mut key = prefix.as_bytes().to_vec();
+        key.extend_from_slice(&self.normalize(name)?);
+        Ok(EscapedDeserializer::new(
+            Cow::Owned(key),
+            self.decoder(),
+            false,
+        ))
+    }
+}
+
+/// Spans of the key and the value of an attribute
+type AttributeSpan = (Range<usize>, Range<usize>);
+
+/// Returns spans of the key and the value of the next attribute. Attributes,
+/// that can collide with elements, are deferred, if elements have a priority
+fn next_attribute(
+    iter: &mut IterState,
+    slice: &[u8],
+    fields: &[&'static str],
+    policy: NameCollision,
+    deferred: &mut Vec<AttributeSpan>,
+    names: Names,
+) -> Result<Option<AttributeSpan>, DeError> {
+    while let Some(a) = iter.next(slice).transpose()? {
+        let (key, value) = a.into();
+        let value = value.unwrap_or_default();
+        if policy == NameCollision::ElementFirst
+            && can_collide(fields, &names.normalize(&slice[key.clone()])?)
+        {
+            deferred.push((key, value));
+        } else {
+            return Ok(Some((key, value)));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns a field with the `@` prefix, that should be deserialized from
+/// an attribute `name`
+fn attribute_field(fields: &[&'static str], name: &[u8]) -> Option<&'static str> {
+    fields
+        .iter()
+        .find(|f| f.as_bytes().strip_prefix(ATTRIBUTE_PREFIX.as_bytes()) == Some(name))
+        .copied()
+}
+
+/// Returns `true` if a field can be deserialized both from an attribute and
+/// from an element with the `name`
+fn can_collide(fields: &[&'static str], name: &[u8]) -> bool {
+    fields.iter().any(|f| f.as_bytes() == name) && attribute_field(fields, name).is_none()
+}
+
+/// Returns `true` if the element `name` collides with an attribute from the
+/// `attributes` slice
+fn is_colliding(
+    fields: &[&'static str],
+    attributes: &[u8],
+    name: &[u8],
+    names: Names,
+) -> Result<bool, DeError> {
+    let name = names.normalize(name)?;
+    if !can_collide(fields, &name) {
+        return Ok(false);
+    }
+    let mut iter = Attributes::new(attributes, 0);
+    iter.with_checks(false);
+    for a in iter.flatten() {
+        if names.normalize(a.key)? == name {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns `true` if the element `start` should be deserialized into a field
+/// from the `fields` list other than the [`INNER_VALUE`] field
+fn is_known_field(
+    fields: &[&'static str],
+    unflatten_fields: &[&'static [u8]],
+    start: &BytesStart,
+    names: Names,
+) -> Result<bool, DeError> {
+    let name = names.normalize(start.local_name())?;
+    Ok(fields
+        .iter()
+        .any(|f| *f != INNER_VALUE && f.as_bytes() == &*name)
+        || unflatten_fields
+            .iter()
+            .any(|f| start.name() == &f[UNFLATTEN_PREFIX.len()..]))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An accessor to the child elements, that are not matched by other struct fields.
+/// Used when a field with the special name [`INNER_VALUE`] is deserialized as a map.
+///
+/// Each element represents one entry: its local name is a key and the element
+/// itself is a value. The map ends at the first element that belongs to another
+/// field, or at the end of the parent element:
+///
+/// ```xml
+/// <any-tag>
+///     <known>...</known>
+///     <extra1>...</extra1>
+/// <!-- ~~~~~~                  - this data will be used to deserialize a map key -->
+/// <!--^^^^^^^^^^^^^^^^^^^^^^^^ - this data will be used to deserialize a map value -->
+///     <extra2>...</extra2>
+/// </any-tag>
+/// ```
+///
+/// Because elements are read in a streaming fashion, unmatched elements should
+/// be grouped together. If they are interleaved with elements of other fields,
+/// the [`INNER_VALUE`] field will be met twice and deserialization will fail
+/// with a "duplicate field" error.
+struct UnmatchedElements<'de, 'a, 'm, R>
+where
+    R: XmlRead<'de>,
+{
+    map: &'m mut MapAccess<'de, 'a, R>,
+}
+
+impl<'de, 'a, 'm, R> de::MapAccess<'de> for UnmatchedElements<'de, 'a, 'm, R>
+where
+    R: XmlRead<'de>,
+{
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let names = Names {
+            decoder: self.map.de.reader.decoder(),
+            normalizer: self.map.de.field_name_normalizer,
+        };
+        match self.map.de.peek()? {
+            DeEvent::Start(e)
+                if !is_known_field(self.map.fields, &self.map.unflatten_fields, e, names)? =>
+            {
+                let name = strip_key_prefix(&self.map.key_prefix, e.local_name());
+                seed.deserialize(names.key(name)?).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn next_value_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<K::Value, Self::Error> {
+        // Value of each entry is an ordinary element, so sequences should
+        // stop at the first element with a different name
+        let has_value_field = std::mem::replace(&mut self.map.de.has_value_field, false);
+        let value = seed.deserialize(&mut *self.map.de);
+        self.map.de.has_value_field = has_value_field;
+        value
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 macro_rules! forward {
@@ -356,6 +759,27 @@ where
     fn decoder(&self) -> Decoder {
         self.map.de.reader.decoder()
     }
+
+    /// Returns `true` if special float values should be recognized only in
+    /// the XML Schema spelling, used inside [`deserialize_primitives!()`]
+    #[inline]
+    fn is_xsd_floats(&self) -> bool {
+        self.map.de.xsd_floats
+    }
+
+    /// Returns `true` if empty elements should be deserialized as `true`
+    /// booleans, used inside [`deserialize_primitives!()`]
+    #[inline]
+    fn is_presence_bool(&self) -> bool {
+        self.map.de.presence_bool
+    }
+
+    /// Applies a number sanitizer to the text of a number, used inside
+    /// [`deserialize_primitives!()`]
+    #[inline]
+    fn sanitize_number<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        (self.map.de.number_sanitizer)(text)
+    }
 }
 
 impl<'de, 'a, 'm, R> de::Deserializer<'de> for MapValueDeserializer<'de, 'a, 'm, R>
@@ -375,7 +799,18 @@ where
     forward!(deserialize_tuple(len: usize));
     forward!(deserialize_tuple_struct(name: &'static str, len: usize));
 
-    forward!(deserialize_map);
+    /// If the value is an element, collects it and all following elements,
+    /// not matched by other fields, into a map, keyed by the element names.
+    /// If the same name occurs several times, then the value deserializer
+    /// decides what to do: scalar types and structs keep the last value,
+    /// sequences collect all consecutive elements with the same name.
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let DeEvent::Start(_) = self.map.de.peek()? {
+            visitor.visit_map(UnmatchedElements { map: self.map })
+        } else {
+            self.map.de.deserialize_map(visitor)
+        }
+    }
     forward!(deserialize_struct(
         name: &'static str,
         fields: &'static [&'static str]
@@ -394,3 +829,290 @@ where
         self.map.de.is_human_readable()
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A deserializer for a key of a map, named after an attribute or an element.
+///
+/// Structs with `#[serde(flatten)]` fields are requested by serde as maps, but
+/// serde requests their keys by `deserialize_identifier`, as names of fields of
+/// any struct, while keys of maps are requested by other methods. The first key
+/// of a map without known fields, requested as an identifier, marks the map as
+/// a struct with flattened fields.
+/// Names of fields and keys of maps are slightly different, so both are prepared:
+/// the [`Deserializer::map_key_prefix`] is stripped only from keys of maps, and
+/// with [`Deserializer::flatten_as_map`] the `@` prefix is added only to names
+/// of fields
+struct KeyDeserializer<'de, 'k, 'f> {
+    /// A key of a map
+    map_key: EscapedDeserializer<'de, 'k>,
+    /// A name of a field of a struct with flattened fields
+    field: EscapedDeserializer<'de, 'k>,
+    /// Set to `true` when the key is requested as an identifier. `None` for
+    /// structs with known fields
+    flatten: Option<&'f mut bool>,
+}
+
+/// Returns a flag, that should be set when a key is requested as an identifier.
+/// Only maps without known fields can be structs with flattened fields
+fn flatten_flag<'f>(fields: &[&'static str], flatten: &'f mut bool) -> Option<&'f mut bool> {
+    if fields.is_empty() {
+        Some(flatten)
+    } else {
+        None
+    }
+}
+
+macro_rules! forward_key {
+    (
+        $deserialize:ident
+        $(
+            ($($name:ident : $type:ty),*)
+        )?
+    ) => {
+        #[inline]
+        fn $deserialize<V: Visitor<'de>>(
+            self,
+            $($($name: $type,)*)?
+            visitor: V
+        ) -> Result<V::Value, Self::Error> {
+            self.map_key.$deserialize($($($name,)*)? visitor)
+        }
+    };
+}
+
+impl<'de, 'k, 'f> de::Deserializer<'de> for KeyDeserializer<'de, 'k, 'f> {
+    type Error = DeError;
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(flatten) = self.flatten {
+            *flatten = true;
+        }
+        self.field.deserialize_identifier(visitor)
+    }
+
+    forward_key!(deserialize_any);
+    forward_key!(deserialize_bool);
+    forward_key!(deserialize_i8);
+    forward_key!(deserialize_i16);
+    forward_key!(deserialize_i32);
+    forward_key!(deserialize_i64);
+    forward_key!(deserialize_u8);
+    forward_key!(deserialize_u16);
+    forward_key!(deserialize_u32);
+    forward_key!(deserialize_u64);
+    forward_key!(deserialize_i128);
+    forward_key!(deserialize_u128);
+    forward_key!(deserialize_f32);
+    forward_key!(deserialize_f64);
+    forward_key!(deserialize_char);
+    forward_key!(deserialize_str);
+    forward_key!(deserialize_string);
+    forward_key!(deserialize_bytes);
+    forward_key!(deserialize_byte_buf);
+    forward_key!(deserialize_option);
+    forward_key!(deserialize_unit);
+    forward_key!(deserialize_unit_struct(name: &'static str));
+    forward_key!(deserialize_newtype_struct(name: &'static str));
+    forward_key!(deserialize_seq);
+    forward_key!(deserialize_tuple(len: usize));
+    forward_key!(deserialize_tuple_struct(name: &'static str, len: usize));
+    forward_key!(deserialize_map);
+    forward_key!(deserialize_struct(
+        name: &'static str,
+        fields: &'static [&'static str]
+    ));
+    forward_key!(deserialize_enum(
+        name: &'static str,
+        variants: &'static [&'static str]
+    ));
+    forward_key!(deserialize_ignored_any);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A deserializer for a value of a nested element of a struct with
+/// `#[serde(flatten)]` fields.
+///
+/// Own fields of the struct are deserialized as usual. Values of unknown keys
+/// are requested by serde using `deserialize_any`, in that case the element is
+/// copied into the [`MapAccess::unmatched`] list.
+struct FlattenedValueDeserializer<'de, 'a, 'm, R>
+where
+    R: XmlRead<'de>,
+{
+    map: &'m mut MapAccess<'de, 'a, R>,
+}
+
+impl<'de, 'a, 'm, R> de::Deserializer<'de> for FlattenedValueDeserializer<'de, 'a, 'm, R>
+where
+    R: XmlRead<'de>,
+{
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let decoder = self.map.de.reader.decoder();
+        let name = match self.map.de.peek()? {
+            DeEvent::Start(e) => decoder.decode(e.local_name())?.into_owned(),
+            _ => return self.map.de.deserialize_any(visitor),
+        };
+        let node = Node::deserialize(&mut *self.map.de)?;
+        self.map.unmatched.push((name, node.clone()));
+        node.into_text_content().deserialize_any(visitor)
+    }
+
+    forward!(deserialize_bool);
+    forward!(deserialize_i8);
+    forward!(deserialize_i16);
+    forward!(deserialize_i32);
+    forward!(deserialize_i64);
+    forward!(deserialize_u8);
+    forward!(deserialize_u16);
+    forward!(deserialize_u32);
+    forward!(deserialize_u64);
+    serde_if_integer128! {
+        forward!(deserialize_i128);
+        forward!(deserialize_u128);
+    }
+    forward!(deserialize_f32);
+    forward!(deserialize_f64);
+    forward!(deserialize_char);
+    forward!(deserialize_str);
+    forward!(deserialize_string);
+    forward!(deserialize_bytes);
+    forward!(deserialize_byte_buf);
+    forward!(deserialize_identifier);
+
+    forward!(deserialize_option);
+    forward!(deserialize_unit);
+    forward!(deserialize_unit_struct(name: &'static str));
+    forward!(deserialize_newtype_struct(name: &'static str));
+
+    forward!(deserialize_seq);
+    forward!(deserialize_tuple(len: usize));
+    forward!(deserialize_tuple_struct(name: &'static str, len: usize));
+
+    forward!(deserialize_map);
+    forward!(deserialize_struct(
+        name: &'static str,
+        fields: &'static [&'static str]
+    ));
+
+    forward!(deserialize_enum(
+        name: &'static str,
+        variants: &'static [&'static str]
+    ));
+
+    forward!(deserialize_ignored_any);
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.map.de.is_human_readable()
+    }
+}
+
+/// An owned copy of an element, in the same shape as it is returned by
+/// the `deserialize_any` method of a [`Deserializer`]: elements are maps
+/// of attributes and child elements, the text content is stored under
+/// the [`INNER_VALUE`] key.
+#[derive(Clone, Debug)]
+enum Node {
+    /// Empty value
+    Unit,
+    /// Value of an attribute or a text content
+    Text(String),
+    /// Element with attributes and child elements
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Converts an element to the representation of an externally tagged enum
+    /// variant content: empty elements became units, elements with only
+    /// a text content became strings
+    fn into_variant_content(self) -> Self {
+        match self {
+            Node::Map(entries) if entries.is_empty() => Node::Unit,
+            Node::Map(mut entries) if entries.len() == 1 && entries[0].0 == INNER_VALUE => {
+                entries.remove(0).1
+            }
+            node => node,
+        }
+    }
+
+    /// Converts an element with only a text content and without attributes
+    /// to a string, so it can be deserialized into a primitive field of
+    /// a flattened struct. Other elements are returned as is
+    fn into_text_content(self) -> Self {
+        match self {
+            Node::Map(mut entries) if entries.len() == 1 && entries[0].0 == INNER_VALUE => {
+                entries.remove(0).1
+            }
+            node => node,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NodeVisitor;
+
+        impl<'de> Visitor<'de> for NodeVisitor {
+            type Value = Node;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an element, an attribute or a text")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Node, E> {
+                Ok(Node::Unit)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Node, E> {
+                Ok(Node::Text(v.to_owned()))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Node, E> {
+                Ok(Node::Text(v))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Node::Map(entries))
+            }
+        }
+
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Node {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Node {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Node::Unit => visitor.visit_unit(),
+            Node::Text(text) => visitor.visit_string(text),
+            Node::Map(entries) => {
+                MapDeserializer::new(entries.into_iter()).deserialize_any(visitor)
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
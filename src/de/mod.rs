@@ -118,7 +118,22 @@ macro_rules! deserialize_type {
             // No need to unescape because valid integer representations cannot be escaped
             let text = self.next_text(false)?;
             let string = text.decode(self.decoder())?;
-            visitor.$visit(string.parse()?)
+            visitor.$visit(self.sanitize_number(&string).parse()?)
+        }
+    };
+}
+
+macro_rules! deserialize_float {
+    ($deserialize:ident => $visit:ident, $($mut:tt)?) => {
+        fn $deserialize<V>($($mut)? self, visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            // No need to unescape because valid float representations cannot be escaped
+            let text = self.next_text(false)?;
+            let string = text.decode(self.decoder())?;
+            let string = self.sanitize_number(&string);
+            visitor.$visit(parse_float(&string, self.is_xsd_floats())?)
         }
     };
 }
@@ -142,8 +157,8 @@ macro_rules! deserialize_primitives {
             deserialize_type!(deserialize_u128 => visit_u128, $($mut)?);
         }
 
-        deserialize_type!(deserialize_f32 => visit_f32, $($mut)?);
-        deserialize_type!(deserialize_f64 => visit_f64, $($mut)?);
+        deserialize_float!(deserialize_f32 => visit_f32, $($mut)?);
+        deserialize_float!(deserialize_f64 => visit_f64, $($mut)?);
 
         fn deserialize_bool<V>($($mut)? self, visitor: V) -> Result<V::Value, DeError>
         where
@@ -151,6 +166,9 @@ macro_rules! deserialize_primitives {
         {
             // No need to unescape because valid boolean representations cannot be escaped
             let text = self.next_text(false)?;
+            if text.is_empty() && self.is_presence_bool() {
+                return visitor.visit_bool(true);
+            }
 
             deserialize_bool(text.as_ref(), self.decoder(), visitor)
         }
@@ -219,21 +237,71 @@ mod var;
 
 pub use crate::errors::serialize::DeError;
 use crate::{
+    de::escape::EscapedDeserializer,
     errors::Error,
     events::{BytesCData, BytesEnd, BytesStart, BytesText, Event},
-    reader::Decoder,
+    name::QName,
+    reader::{is_whitespace, Decoder},
     Reader,
 };
-use serde::de::{self, Deserialize, DeserializeOwned, Visitor};
+use serde::de::{self, Deserialize, DeserializeOwned, Unexpected, Visitor};
 use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::io::BufRead;
+use std::num::ParseFloatError;
+use std::str::FromStr;
 
 pub(crate) const INNER_VALUE: &str = "$value";
+/// Name of the special field, that receives the local name of the element,
+/// from which the struct is deserialized
+pub(crate) const ELEMENT_NAME: &str = "$name";
 pub(crate) const UNFLATTEN_PREFIX: &str = "$unflatten=";
 pub(crate) const PRIMITIVE_PREFIX: &str = "$primitive=";
+/// Prefix of names of struct fields, that can be deserialized only from attributes
+pub(crate) const ATTRIBUTE_PREFIX: &str = "@";
+
+/// Defines, what happens when a struct field can be deserialized both from
+/// an attribute and from a child element with the same name.
+/// See [`Deserializer::name_collision_policy`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameCollision {
+    /// A value of the attribute is used and the elements are skipped
+    AttributeFirst,
+    /// Values of the elements are used and the attribute is ignored
+    ElementFirst,
+    /// Deserialization fails with a "duplicate field" error. Fields can be
+    /// disambiguated by a `@` prefix: a field renamed to `@name` is deserialized
+    /// only from the `name` attribute
+    Error,
+}
+
+impl Default for NameCollision {
+    #[inline]
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// Counters of XML nodes, processed by the [`Deserializer`].
+/// See [`Deserializer::with_stats`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeStats {
+    /// Number of elements, read from the input. Elements inside skipped
+    /// elements are not read and not counted
+    pub elements: usize,
+    /// Number of attributes, deserialized as keys of maps or fields of structs,
+    /// including attributes that are ignored by the visitor
+    pub attributes: usize,
+    /// Number of text and CDATA nodes, read from the input
+    pub texts: usize,
+    /// Number of elements and texts, that were skipped because the visitor did
+    /// not need them, for example, elements that do not match any field of
+    /// a struct. Unknown attributes are counted only in [`Self::attributes`]
+    pub skipped: usize,
+}
 
 /// Simplified event which contains only these variants that used by deserializer
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DeEvent<'a> {
     /// Start tag (with attributes) `<tag attr="value">`.
     Start(BytesStart<'a>),
@@ -255,6 +323,10 @@ where
 {
     reader: R,
     peek: Option<DeEvent<'de>>,
+    /// Events, that was read ahead from the `reader` and should be returned
+    /// before any other events. Filled by sequences that collect elements with
+    /// the same name, when [`Self::group_repeated_elements`] is enabled
+    replay: VecDeque<DeEvent<'de>>,
     /// Special sing that deserialized struct have a field with the special
     /// name (see constant `INNER_VALUE`). That field should be deserialized
     /// from the text content of the XML node:
@@ -263,6 +335,61 @@ where
     /// <tag>value for INNER_VALUE field<tag>
     /// ```
     has_value_field: bool,
+    /// If `true`, special float values accepted only in the XML Schema spelling.
+    /// See [`Self::xsd_floats`]
+    xsd_floats: bool,
+    /// If `true`, units are deserialized only from empty elements.
+    /// See [`Self::strict_unit`]
+    strict_unit: bool,
+    /// If `true`, structs cannot be deserialized from a text and primitives
+    /// cannot be deserialized from elements with attributes.
+    /// See [`Self::strict_types`]
+    strict_types: bool,
+    /// If `true`, optional values are `None` when an element has no attributes
+    /// and an empty text content. See [`Self::empty_string_as_none`]
+    empty_string_as_none: bool,
+    /// If `true`, unknown attributes and elements are passed to flattened fields
+    /// in a form, suitable for maps. See [`Self::flatten_as_map`]
+    flatten_as_map: bool,
+    /// If `true`, enum variants are selected by the `xsi:type` attribute of
+    /// elements. See [`Self::xsi_type_dispatch`]
+    xsi_type_dispatch: bool,
+    /// A function that is applied to the text of numbers before parsing.
+    /// See [`Self::number_sanitizer`]
+    number_sanitizer: fn(&str) -> Cow<str>,
+    /// A prefix that is stripped from names of attributes and elements, when
+    /// they are deserialized as map keys. See [`Self::map_key_prefix`]
+    map_key_prefix: Vec<u8>,
+    /// A function that is applied to names of attributes and elements before
+    /// matching them with fields. See [`Self::field_name_normalizer`]
+    field_name_normalizer: Option<fn(&str) -> Cow<str>>,
+    /// If `true`, empty elements are deserialized as `true` booleans.
+    /// See [`Self::presence_bool`]
+    presence_bool: bool,
+    /// Defines, which value is used for a field when an attribute and an element
+    /// have the same name. See [`Self::name_collision_policy`]
+    name_collision: NameCollision,
+    /// If `true`, sequences in values of maps collect all elements with the
+    /// same name. See [`Self::group_repeated_elements`]
+    group_repeated: bool,
+    /// If `true`, a sequence that is deserialized now is a value of a map entry
+    /// and should collect all the following elements with the same name.
+    /// Set by a map before deserializing a value and cleared when any event
+    /// is consumed
+    seq_group: bool,
+    /// If `true`, a value that is deserialized now is an element of a sequence.
+    /// Set by a sequence before deserializing each element and cleared when
+    /// any event is consumed
+    seq_item: bool,
+    /// If `true`, a value that is deserialized now is a value of a map entry,
+    /// represented by an element with the name of a key. Such an element cannot
+    /// select an enum variant by its name, so its text is used instead.
+    /// Set by a map before deserializing a value and cleared when any event
+    /// is consumed
+    entry_value: bool,
+    /// Counters of processed nodes, if they are collected.
+    /// See [`Self::with_stats`]
+    stats: Option<DeStats>,
 }
 
 /// Deserialize an instance of type `T` from a string of XML text.
@@ -303,6 +430,523 @@ where
     T::deserialize(&mut de)
 }
 
+/// Deserializes each top-level element of a document, that may have multiple
+/// root elements, into a `T`, collecting successfully deserialized items and
+/// errors separately.
+///
+/// When an element cannot be deserialized, its error is recorded and parsing
+/// continues from the next sibling element, so one malformed item does not
+/// lose the others. This is a best-effort mode intended for importing messy data.
+/// Text, comments and other non-element content between the top-level elements
+/// is ignored.
+///
+/// Parsing stops at the first error that breaks the structure of the document,
+/// such as an unclosed markup, because the next sibling cannot be found after it.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use serde::Deserialize;
+/// use fast_xml::de::from_str_lenient;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Item {
+///     id: u32,
+/// }
+///
+/// let (items, errors) = from_str_lenient::<Item>(
+///     r#"<item id="1"/><item id="two"/><item id="3"/>"#,
+/// );
+/// assert_eq!(items, vec![Item { id: 1 }, Item { id: 3 }]);
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn from_str_lenient<'de, T>(s: &'de str) -> (Vec<T>, Vec<DeError>)
+where
+    T: Deserialize<'de>,
+{
+    let input = s.as_bytes();
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut push = |result: Result<T, DeError>| match result {
+        Ok(item) => items.push(item),
+        Err(e) => errors.push(e),
+    };
+
+    // Find the boundaries of the top-level elements. End names are not checked
+    // here, mismatches are reported when the element itself is deserialized
+    let mut reader = Reader::from_bytes(input);
+    reader.check_end_names(false).with_event_spans(true);
+
+    let mut buf = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    loop {
+        match reader.read_event_with_span(&mut buf) {
+            Ok((Event::Start(_), span)) => {
+                if depth == 0 {
+                    start = span.start;
+                }
+                depth += 1;
+            }
+            Ok((Event::Empty(_), span)) if depth == 0 => push(from_slice(&input[span])),
+            Ok((Event::End(e), span)) => match depth {
+                0 => push(Err(DeError::UnexpectedEnd(e.name().to_owned()))),
+                1 => {
+                    depth = 0;
+                    push(from_slice(&input[start..span.end]));
+                }
+                _ => depth -= 1,
+            },
+            Ok((Event::Eof, _)) => {
+                if depth > 0 {
+                    push(Err(DeError::UnexpectedEof));
+                }
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                push(Err(e.into()));
+                break;
+            }
+        }
+        buf.clear();
+    }
+    (items, errors)
+}
+
+/// Deserializes the first top-level element of a string of XML text into a `T`
+/// and returns it together with the rest of the input after that element.
+///
+/// In contrast to [`from_str`], that deserializes a value from the whole
+/// input, content after the first root element is not read at all. That is
+/// useful for processing of fragments, that contain several elements, or
+/// documents with trailing content. The returned rest of the input can be
+/// passed to this function again to read the next element.
+///
+/// Text, comments and other non-element content before the first element
+/// is skipped.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use serde::Deserialize;
+/// use fast_xml::de::from_str_prefix;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Root {
+///     id: u32,
+/// }
+///
+/// let (root, rest) = from_str_prefix::<Root>("<root><id>1</id></root><trailing/>").unwrap();
+/// assert_eq!(root, Root { id: 1 });
+/// assert_eq!(rest, "<trailing/>");
+/// ```
+pub fn from_str_prefix<'de, T>(s: &'de str) -> Result<(T, &'de str), DeError>
+where
+    T: Deserialize<'de>,
+{
+    let input = s.as_bytes();
+
+    // Find the boundaries of the first top-level element. End names are not
+    // checked here, mismatches are reported when the element is deserialized
+    let mut reader = Reader::from_bytes(input);
+    reader.check_end_names(false).with_event_spans(true);
+
+    let mut buf = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let span = loop {
+        match reader.read_event_with_span(&mut buf)? {
+            (Event::Start(_), span) => {
+                if depth == 0 {
+                    start = span.start;
+                }
+                depth += 1;
+            }
+            (Event::Empty(_), span) if depth == 0 => break span,
+            (Event::End(e), span) => match depth {
+                0 => return Err(DeError::UnexpectedEnd(e.name().to_owned())),
+                1 => break start..span.end,
+                _ => depth -= 1,
+            },
+            (Event::Eof, _) => return Err(DeError::UnexpectedEof),
+            _ => {}
+        }
+        buf.clear();
+    };
+    let value = from_slice(&input[span.clone()])?;
+    Ok((value, &s[span.end..]))
+}
+
+/// Deserializes entries with text values of the current element (attributes and
+/// child elements that contain only a text) into a struct `T`. Intended to be used
+/// together with `#[serde(flatten)]` in a `deserialize_with` attribute to group
+/// related attributes into a nested struct:
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use serde::Deserialize;
+/// use fast_xml::de::{text_entries, from_str};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Rect {
+///     x: i32,
+///     y: i32,
+///     w: u32,
+///     h: u32,
+/// }
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Shape {
+///     name: String,
+///     #[serde(flatten, deserialize_with = "text_entries")]
+///     rect: Rect,
+/// }
+///
+/// let shape: Shape = from_str(r#"<shape name="box" x="-1" y="2"><w>30</w><h>40</h></shape>"#).unwrap();
+/// assert_eq!(shape, Shape {
+///     name: "box".into(),
+///     rect: Rect { x: -1, y: 2, w: 30, h: 40 },
+/// });
+/// ```
+///
+/// Serde passes all entries that were not consumed by other fields of the struct
+/// into a flattened field without telling whether they come from attributes or
+/// from elements, so both kinds are passed to `T`, but only if they have text
+/// values. Values are already decoded by the deserializer and are parsed in
+/// the same way as attribute values, so numbers and booleans are supported in
+/// addition to strings. Entries that `T` does not know about are ignored,
+/// unless `T` is marked with `#[serde(deny_unknown_fields)]`.
+pub fn text_entries<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let entries = deserializer.deserialize_map(Entries)?;
+    T::deserialize(de::value::MapDeserializer::new(entries.into_iter())).map_err(de::Error::custom)
+}
+
+/// A helper function to deserialize a tuple or a tuple struct from the values
+/// of attributes of an element, followed by its text content. Values are taken
+/// by their positions in the document, their names are ignored. Use it with the
+/// `#[serde(deserialize_with = "...")]` attribute on fields:
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use serde::Deserialize;
+/// use fast_xml::de::{attributes_and_text, from_str};
+///
+/// /// A unit, taken from the attribute, and a value, taken from the text
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Measure(String, f64);
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Item {
+///     #[serde(deserialize_with = "attributes_and_text")]
+///     length: Measure,
+/// }
+///
+/// let item: Item = from_str(r#"<item><length unit="cm">12.5</length></item>"#).unwrap();
+/// assert_eq!(item, Item {
+///     length: Measure("cm".into(), 12.5),
+/// });
+/// ```
+///
+/// Values are already decoded by the deserializer and are parsed in the same way
+/// as attribute values, so numbers and booleans are supported in addition to
+/// strings. Each attribute and the text content, if the element has any, occupy
+/// one slot of the tuple, so the number of slots should match exactly. Child elements with a text content, like `<unit>cm</unit>`,
+/// occupy slots in the document order too, other child elements are skipped.
+pub fn attributes_and_text<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let entries = deserializer.deserialize_map(Entries)?;
+    let values = entries.into_iter().map(|(_, value)| value);
+    T::deserialize(de::value::SeqDeserializer::new(values)).map_err(de::Error::custom)
+}
+
+/// Value of a map entry: `Some` for text values, `None` for anything else
+struct Text(Option<String>);
+
+impl<'de> Deserialize<'de> for Text {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TextVisitor;
+
+        impl<'de> Visitor<'de> for TextVisitor {
+            type Value = Text;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("any value")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Text, E> {
+                Ok(Text(Some(v.to_owned())))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Text, E> {
+                Ok(Text(Some(v)))
+            }
+
+            fn visit_bytes<E: de::Error>(self, _v: &[u8]) -> Result<Text, E> {
+                Ok(Text(None))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Text, E> {
+                Ok(Text(None))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Text, A::Error> {
+                while map
+                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
+                    .is_some()
+                {}
+                Ok(Text(None))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Text, A::Error> {
+                while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+                Ok(Text(None))
+            }
+        }
+
+        deserializer.deserialize_any(TextVisitor)
+    }
+}
+
+impl<'de> de::IntoDeserializer<'de, DeError> for Text {
+    type Deserializer = EscapedDeserializer<'de, 'static>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        EscapedDeserializer::from_text(Cow::Owned(self.0.unwrap_or_default()))
+    }
+}
+
+/// Collects entries of a map with text values in the document order
+struct Entries;
+
+impl<'de> Visitor<'de> for Entries {
+    type Value = Vec<(String, Text)>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a map of attributes and elements")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some((key, value)) = map.next_entry::<String, Text>()? {
+            if value.0.is_some() {
+                entries.push((key, value));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// A string with whitespaces collapsed as required by the XML Schema
+/// [`whiteSpace=collapse`](https://www.w3.org/TR/xmlschema-2/#rf-whiteSpace) facet:
+/// each run of XML whitespace characters (spaces, tabs, carriage returns and
+/// line feeds) is replaced by a single space, and leading and trailing whitespaces
+/// are removed. Can be used for any field that is deserialized from a text,
+/// an attribute value or a text content of an element:
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use serde::Deserialize;
+/// use fast_xml::de::{from_str, Collapsed};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Person {
+///     name: Collapsed,
+///     address: Collapsed,
+/// }
+///
+/// let person: Person = from_str(r#"
+///     <person name="  John  Doe ">
+///         <address>
+///             221B  Baker Street,
+///             London
+///         </address>
+///     </person>
+/// "#).unwrap();
+/// assert_eq!(person.name.0, "John Doe");
+/// assert_eq!(person.address.0, "221B Baker Street, London");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Collapsed(pub String);
+
+impl Collapsed {
+    /// Creates a new `Collapsed` string, collapsing whitespaces in `text`
+    pub fn new(text: &str) -> Self {
+        let mut result = String::with_capacity(text.len());
+        for word in text.split(|c: char| c.is_ascii() && is_whitespace(c as u8)) {
+            if !word.is_empty() {
+                if !result.is_empty() {
+                    result.push(' ');
+                }
+                result.push_str(word);
+            }
+        }
+        Self(result)
+    }
+}
+
+impl<'de> Deserialize<'de> for Collapsed {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(Self::new(&text))
+    }
+}
+
+/// A list of values, that can be deserialized both from a sequence and from
+/// a single value. Useful for documents, that are inconsistent about cardinality
+/// of some fields, for example, when a list with only one item is written as
+/// an attribute:
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use serde::Deserialize;
+/// use fast_xml::de::{from_str, OneOrMany};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Post {
+///     #[serde(default)]
+///     tag: OneOrMany<String>,
+/// }
+///
+/// let one: Post = from_str(r#"<post tag="news"/>"#).unwrap();
+/// let many: Post = from_str("<post><tag>news</tag><tag>rust</tag></post>").unwrap();
+/// let none: Post = from_str("<post/>").unwrap();
+///
+/// assert_eq!(one.tag.0, vec!["news"]);
+/// assert_eq!(many.tag.0, vec!["news", "rust"]);
+/// assert_eq!(none.tag.0, Vec::<String>::new());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OneOrMany<T>(pub Vec<T>);
+
+impl<T> Default for OneOrMany<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<'de, T> Deserialize<'de> for OneOrMany<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use de::value::MapAccessDeserializer;
+        use de::IntoDeserializer;
+        use std::marker::PhantomData;
+
+        struct OneOrManyVisitor<T>(PhantomData<T>);
+
+        /// Deserializes a single item from the value
+        macro_rules! one {
+            ($method:ident: $ty:ty) => {
+                fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                    Ok(OneOrMany(vec![T::deserialize(v.into_deserializer())?]))
+                }
+            };
+        }
+
+        impl<'de, T> Visitor<'de> for OneOrManyVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = OneOrMany<T>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("one value or a sequence of values")
+            }
+
+            one!(visit_bool: bool);
+            one!(visit_i64: i64);
+            one!(visit_u64: u64);
+            one!(visit_f64: f64);
+            one!(visit_char: char);
+
+            // Text is parsed in the same way as attribute values, so numbers
+            // and booleans are supported in addition to strings
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let de =
+                    EscapedDeserializer::new(Cow::Borrowed(v.as_bytes()), Decoder::utf8(), false);
+                Ok(OneOrMany(vec![T::deserialize(de).map_err(E::custom)?]))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                let de =
+                    EscapedDeserializer::new(Cow::Owned(v.into_bytes()), Decoder::utf8(), false);
+                Ok(OneOrMany(vec![T::deserialize(de).map_err(E::custom)?]))
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                let de = EscapedDeserializer::from_input(v.as_bytes(), Decoder::utf8(), false);
+                Ok(OneOrMany(vec![T::deserialize(de).map_err(E::custom)?]))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                Ok(OneOrMany(vec![T::deserialize(
+                    MapAccessDeserializer::new(map),
+                )?]))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(OneOrMany(items))
+            }
+        }
+
+        deserializer.deserialize_seq(OneOrManyVisitor(PhantomData))
+    }
+}
+
+/// The default number sanitizer, that returns the text as is.
+/// See [`Deserializer::number_sanitizer`]
+fn keep_number(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
+
+/// Parses a float number from the string. If `xsd` is `true`, infinities and
+/// NaNs are accepted only in the [XML Schema spelling](https://www.w3.org/TR/xmlschema-2/#double),
+/// otherwise the XML Schema spellings of infinities, `INF` and `-INF`, are rejected
+fn parse_float<T>(value: &str, xsd: bool) -> Result<T, DeError>
+where
+    T: FromStr<Err = ParseFloatError>,
+{
+    if xsd {
+        match value {
+            "INF" | "-INF" | "NaN" => {}
+            _ => {
+                let unsigned = value.strip_prefix(&['+', '-'][..]).unwrap_or(value);
+                if unsigned.eq_ignore_ascii_case("inf")
+                    || unsigned.eq_ignore_ascii_case("infinity")
+                    || unsigned.eq_ignore_ascii_case("nan")
+                {
+                    return Err(de::Error::invalid_value(
+                        Unexpected::Str(value),
+                        &"`INF`, `-INF`, `NaN` or a finite number",
+                    ));
+                }
+            }
+        }
+    } else if let "INF" | "-INF" = value {
+        return Err(de::Error::invalid_value(
+            Unexpected::Str(value),
+            &"a float number. Use `Deserializer::xsd_floats` to accept `INF` and `-INF`",
+        ));
+    }
+    Ok(value.parse()?)
+}
+
 // TODO: According to the https://www.w3.org/TR/xmlschema-2/#boolean,
 // valid boolean representations are only "true", "false", "1", and "0"
 fn deserialize_bool<'de, V>(value: &[u8], decoder: Decoder, visitor: V) -> Result<V::Value, DeError>
@@ -311,7 +955,7 @@ where
 {
     #[cfg(feature = "encoding")]
     {
-        let value = decoder.decode(value);
+        let value = decoder.decode(value)?;
         // No need to unescape because valid boolean representations cannot be escaped
         match value.as_ref() {
             "true" | "1" | "True" | "TRUE" | "t" | "Yes" | "YES" | "yes" | "y" => {
@@ -354,10 +998,584 @@ where
         Deserializer {
             reader,
             peek: None,
+            replay: VecDeque::new(),
             has_value_field: false,
+            xsd_floats: false,
+            strict_unit: false,
+            strict_types: false,
+            empty_string_as_none: false,
+            flatten_as_map: false,
+            xsi_type_dispatch: false,
+            number_sanitizer: keep_number,
+            map_key_prefix: Vec::new(),
+            field_name_normalizer: None,
+            presence_bool: false,
+            name_collision: NameCollision::Error,
+            group_repeated: false,
+            seq_group: false,
+            seq_item: false,
+            entry_value: false,
+            stats: None,
         }
     }
 
+    /// Changes how special values of `f32` and `f64` are recognized.
+    ///
+    /// When set to `true`, infinities and NaNs are accepted only in the lexical
+    /// forms, defined by the XML Schema [`double`] type: `INF`, `-INF` and `NaN`
+    /// (case-sensitive). Other spellings, accepted by the Rust [`f64::from_str`],
+    /// such as `inf`, `+infinity` or `nan`, are rejected. When set to `false`,
+    /// all spellings supported by the Rust are accepted, except the XML Schema
+    /// spellings of infinities `INF` and `-INF`.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`double`]: https://www.w3.org/TR/xmlschema-2/#double
+    pub fn xsd_floats(&mut self, val: bool) -> &mut Self {
+        self.xsd_floats = val;
+        self
+    }
+
+    /// Returns `true` if special float values should be recognized only in
+    /// the XML Schema spelling, used inside [`deserialize_primitives!()`]
+    #[inline]
+    fn is_xsd_floats(&self) -> bool {
+        self.xsd_floats
+    }
+
+    /// Sets a function that is applied to the text of integers and floats,
+    /// read from elements and attributes, before parsing.
+    ///
+    /// This crate does not know anything about locales, so numbers are parsed
+    /// in the format of the Rust [`FromStr`] implementations. If your documents
+    /// use grouping separators or another decimal mark, such as `1.234,56`,
+    /// you can convert them to that format using this hook. Errors are reported
+    /// for the sanitized text.
+    ///
+    /// By default the text is passed to the parser unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    /// use std::borrow::Cow;
+    ///
+    /// fn european(text: &str) -> Cow<str> {
+    ///     Cow::Owned(text.replace('.', "").replace(',', "."))
+    /// }
+    ///
+    /// let mut de = Deserializer::from_str("<price>1.234,56</price>");
+    /// de.number_sanitizer(european);
+    ///
+    /// assert_eq!(f64::deserialize(&mut de).unwrap(), 1234.56);
+    /// ```
+    ///
+    /// [`FromStr`]: std::str::FromStr
+    pub fn number_sanitizer(&mut self, sanitizer: fn(&str) -> Cow<str>) -> &mut Self {
+        self.number_sanitizer = sanitizer;
+        self
+    }
+
+    /// Sets a prefix that is removed from names of attributes and elements
+    /// before deserializing them as keys of a map.
+    ///
+    /// Keys of maps are deserialized from names by the `Deserialize` implementation
+    /// of the key type, so they could be numbers or characters. But XML names
+    /// cannot start with a digit, so names like `<5>` are not allowed. Use names
+    /// with a prefix in such documents, such as `<n5>`, and set that prefix here.
+    /// Names that do not start with the prefix are used as is.
+    ///
+    /// The prefix is applied to keys of all maps, including maps nested into
+    /// other types, but not to the names of struct fields.
+    ///
+    /// (empty by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut de = Deserializer::from_str("<root n1='one'><n2>two</n2></root>");
+    /// de.map_key_prefix("n");
+    ///
+    /// let map = BTreeMap::<u32, String>::deserialize(&mut de).unwrap();
+    /// assert_eq!(map[&1], "one");
+    /// assert_eq!(map[&2], "two");
+    /// ```
+    pub fn map_key_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.map_key_prefix = prefix.as_bytes().to_vec();
+        self
+    }
+
+    /// Sets a function that is applied to names of attributes and elements
+    /// before they are matched with names of struct fields or deserialized
+    /// as keys of maps.
+    ///
+    /// That allows to read documents with inconsistently written names, for
+    /// example, in different cases. The function should convert names to the
+    /// form, in which fields are named after all serde renames, for example,
+    /// lowercase them for fields in `snake_case`. The [`map_key_prefix`] is
+    /// stripped before normalization.
+    ///
+    /// By default names are used as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Item {
+    ///     name: String,
+    ///     price: u32,
+    /// }
+    ///
+    /// fn lowercase(name: &str) -> Cow<str> {
+    ///     Cow::Owned(name.to_lowercase())
+    /// }
+    ///
+    /// let mut de = Deserializer::from_str("<Item NAME='apple'><Price>10</Price></Item>");
+    /// de.field_name_normalizer(lowercase);
+    ///
+    /// assert_eq!(
+    ///     Item::deserialize(&mut de).unwrap(),
+    ///     Item { name: "apple".into(), price: 10 }
+    /// );
+    /// ```
+    ///
+    /// [`map_key_prefix`]: Self::map_key_prefix
+    pub fn field_name_normalizer(&mut self, normalizer: fn(&str) -> Cow<str>) -> &mut Self {
+        self.field_name_normalizer = Some(normalizer);
+        self
+    }
+
+    /// Changes how booleans are deserialized from elements without content.
+    ///
+    /// When set to `true`, an empty element, such as `<flag/>` or `<flag></flag>`,
+    /// is deserialized as `true`, so the presence of an element is enough to
+    /// set a flag. Elements with a text content are deserialized as usual,
+    /// that is, `<flag>false</flag>` is still `false`. Because serde reports
+    /// missing fields as errors, mark `bool` fields with `#[serde(default)]`
+    /// to get `false` when an element is absent. When set to `false`, empty
+    /// elements lead to [`DeError::InvalidBoolean`] error.
+    ///
+    /// That setting does not affect attributes.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Options {
+    ///     #[serde(default)]
+    ///     verbose: bool,
+    ///     #[serde(default)]
+    ///     quiet: bool,
+    /// }
+    ///
+    /// let mut de = Deserializer::from_str("<options><verbose/></options>");
+    /// de.presence_bool(true);
+    ///
+    /// let options = Options::deserialize(&mut de).unwrap();
+    /// assert_eq!(options, Options { verbose: true, quiet: false });
+    /// ```
+    pub fn presence_bool(&mut self, val: bool) -> &mut Self {
+        self.presence_bool = val;
+        self
+    }
+
+    /// Returns `true` if empty elements should be deserialized as `true`
+    /// booleans, used inside [`deserialize_primitives!()`]
+    #[inline]
+    fn is_presence_bool(&self) -> bool {
+        self.presence_bool
+    }
+
+    /// Changes which value is used for a struct field, when the element has
+    /// both an attribute and a child element with the name of that field:
+    ///
+    /// ```xml
+    /// <e value="a"><value>b</value></e>
+    /// ```
+    ///
+    /// - [`NameCollision::AttributeFirst`]: the attribute value is used, the
+    ///   elements with that name are skipped
+    /// - [`NameCollision::ElementFirst`]: the elements are used, the attribute is
+    ///   ignored. Fields from attributes with possible collisions are returned
+    ///   after child elements, so keys of maps are not in the document order
+    /// - [`NameCollision::Error`]: a "duplicate field" error is returned
+    ///
+    /// Regardless of that setting, a field renamed to `@name` is deserialized
+    /// only from the `name` attribute, so both values can be captured.
+    ///
+    /// The setting affects only structs, maps always get all the values.
+    ///
+    /// ([`NameCollision::Error`] by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::{Deserializer, NameCollision};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct E {
+    ///     value: String,
+    /// }
+    ///
+    /// let xml = r#"<e value="a"><value>b</value></e>"#;
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.name_collision_policy(NameCollision::AttributeFirst);
+    /// assert_eq!(E::deserialize(&mut de).unwrap().value, "a");
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.name_collision_policy(NameCollision::ElementFirst);
+    /// assert_eq!(E::deserialize(&mut de).unwrap().value, "b");
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Both {
+    ///     #[serde(rename = "@value")]
+    ///     attribute: String,
+    ///     value: String,
+    /// }
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// let both = Both::deserialize(&mut de).unwrap();
+    /// assert_eq!(both.attribute, "a");
+    /// assert_eq!(both.value, "b");
+    /// ```
+    pub fn name_collision_policy(&mut self, policy: NameCollision) -> &mut Self {
+        self.name_collision = policy;
+        self
+    }
+
+    /// Changes how sequences in values of maps are deserialized.
+    ///
+    /// By default, a sequence collects only consecutive elements with the same
+    /// name, so when elements with that name are interleaved with other elements,
+    /// the map gets the same key several times and usually keeps only the last
+    /// value. When set to `true`, a sequence collects all elements with the same
+    /// name up to the end of the parent element, so they are grouped under one
+    /// key. Elements with other names, met in between, are buffered and returned
+    /// as the next entries of the map in the document order.
+    ///
+    /// The setting affects only maps, whose values are sequences. Structs and
+    /// map values of other types are deserialized as usual.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    /// use std::collections::HashMap;
+    ///
+    /// let xml = "<root><color>red</color><size>10</size><color>green</color></root>";
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.group_repeated_elements(true);
+    ///
+    /// let map = HashMap::<String, Vec<String>>::deserialize(&mut de).unwrap();
+    /// assert_eq!(map["color"], vec!["red", "green"]);
+    /// assert_eq!(map["size"], vec!["10"]);
+    /// ```
+    pub fn group_repeated_elements(&mut self, val: bool) -> &mut Self {
+        self.group_repeated = val;
+        self
+    }
+
+    /// Applies [`Self::number_sanitizer`] to the text of a number, used inside
+    /// [`deserialize_primitives!()`]
+    #[inline]
+    fn sanitize_number<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        (self.number_sanitizer)(text)
+    }
+
+    /// Changes which elements are accepted for unit types, such as `()` or
+    /// `struct Unit;`.
+    ///
+    /// When set to `true`, a unit can be deserialized only from an element
+    /// without attributes and content, for example, `<marker/>` or
+    /// `<marker></marker>`. Any other element leads to [`DeError::ExpectedEmpty`]
+    /// error. This is useful for validating marker elements whose presence is
+    /// the only information. When set to `false`, attributes and content of
+    /// the element are ignored.
+    ///
+    /// (`false` by default)
+    pub fn strict_unit(&mut self, val: bool) -> &mut Self {
+        self.strict_unit = val;
+        self
+    }
+
+    /// Changes whether the shape of the XML is checked against the shape of
+    /// the deserialized type.
+    ///
+    /// When set to `true`:
+    /// - a struct cannot be deserialized from a text or an element with only
+    ///   a text content, such as `<field>text</field>`, unless it has a `$value`
+    ///   field. Such inputs lead to [`DeError::UnexpectedText`] error;
+    /// - a primitive value (number, string, boolean) cannot be deserialized from
+    ///   an element with attributes, such as `<field attr="value">text</field>`.
+    ///   Such inputs lead to [`DeError::UnexpectedStart`] error.
+    ///
+    /// When set to `false`, the text is ignored when a struct is deserialized,
+    /// and attributes are ignored when a primitive value is deserialized.
+    /// Primitive values cannot be deserialized from elements with nested elements
+    /// regardless of this setting.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::{DeError, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Inner {
+    ///     value: Option<String>,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Root {
+    ///     inner: Inner,
+    /// }
+    ///
+    /// let xml = "<root><inner>text</inner></root>";
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// let root = Root::deserialize(&mut de).unwrap();
+    /// assert_eq!(root, Root { inner: Inner { value: None } });
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.strict_types(true);
+    /// match Root::deserialize(&mut de) {
+    ///     Err(DeError::UnexpectedText(text)) => assert_eq!(text, b"text"),
+    ///     x => panic!("Expected `Err(UnexpectedText)`, but got `{:?}`", x),
+    /// }
+    /// ```
+    pub fn strict_types(&mut self, val: bool) -> &mut Self {
+        self.strict_types = val;
+        self
+    }
+
+    /// Changes how optional values are deserialized from empty elements.
+    ///
+    /// When set to `true`, an element without attributes and with an empty text
+    /// content, such as `<field/>` or `<field></field>`, is deserialized as `None`
+    /// for `Option` fields. Because text is trimmed, elements with only whitespaces,
+    /// such as `<field> </field>`, are also empty. When set to `false`, such
+    /// elements are deserialized as `Some` with an empty string for `Option<String>`.
+    ///
+    /// That setting does not affect fields that are not `Option`s: a `String`
+    /// field is still deserialized as an empty string. Optional values from
+    /// empty attributes are always `None`.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Person {
+    ///     name: String,
+    ///     email: Option<String>,
+    /// }
+    ///
+    /// let xml = "<person><name/><email></email></person>";
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// let person = Person::deserialize(&mut de).unwrap();
+    /// assert_eq!(person.email, Some("".to_string()));
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.empty_string_as_none(true);
+    /// let person = Person::deserialize(&mut de).unwrap();
+    /// assert_eq!(person, Person { name: "".into(), email: None });
+    /// ```
+    pub fn empty_string_as_none(&mut self, val: bool) -> &mut Self {
+        self.empty_string_as_none = val;
+        self
+    }
+
+    /// Changes how unknown attributes and child elements are passed to
+    /// `#[serde(flatten)]` fields.
+    ///
+    /// Serde requests structs with flattened fields as maps, so the deserializer
+    /// does not know names of their fields, and all unmatched attributes and
+    /// elements are passed to the flattened fields. By default they are passed
+    /// in a form, suitable for flattened structs: attributes are passed under
+    /// their names, and copies of unmatched elements are passed once again
+    /// as a sequence under the `$value` key.
+    ///
+    /// When set to `true`, they are passed in a form, suitable for flattened maps:
+    /// attributes are passed under their names with the `@` prefix, elements are
+    /// passed under their names, and the `$value` sequence is not passed. So one
+    /// flattened map captures unknown attributes as `@name` and unknown elements
+    /// as `name`. Fields of flattened structs, that should be deserialized from
+    /// attributes, should be renamed with the `@` prefix in that case. That also
+    /// allows to deserialize own fields with the `@` prefix of the struct with
+    /// flattened fields, which are not matched by attributes otherwise.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    /// use std::collections::BTreeMap;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Book {
+    ///     title: String,
+    ///     #[serde(flatten)]
+    ///     extra: BTreeMap<String, String>,
+    /// }
+    ///
+    /// let xml = r#"<book lang="en"><title>Dune</title><lang>English</lang></book>"#;
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.flatten_as_map(true);
+    /// let book = Book::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(book.title, "Dune");
+    /// assert_eq!(book.extra["@lang"], "en");
+    /// assert_eq!(book.extra["lang"], "English");
+    /// ```
+    pub fn flatten_as_map(&mut self, val: bool) -> &mut Self {
+        self.flatten_as_map = val;
+        self
+    }
+
+    /// Changes whether variants of enums are selected by the [`xsi:type`] attribute.
+    ///
+    /// By default a variant of an enum is selected by the name of an element.
+    /// When set to `true`, if an element, from which an enum is deserialized,
+    /// has the `xsi:type` attribute, the variant is selected by the value of
+    /// that attribute without the namespace prefix, so `xsi:type="ns:Circle"`
+    /// selects the `Circle` variant. The content of the element is deserialized
+    /// into that variant, and the `xsi:type` attribute itself is passed to it
+    /// as an ordinary attribute. Elements without that attribute are handled
+    /// as usual.
+    ///
+    /// The attribute is matched by its qualified name, so the `xsi` prefix
+    /// should be used for the `http://www.w3.org/2001/XMLSchema-instance`
+    /// namespace, as it is done usually.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// enum Shape {
+    ///     Circle { radius: f64 },
+    ///     Square { side: f64 },
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Drawing {
+    ///     shape: Vec<Shape>,
+    /// }
+    ///
+    /// let xml = r#"
+    ///     <drawing xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    ///         <shape xsi:type="geo:Circle"><radius>1</radius></shape>
+    ///         <shape xsi:type="geo:Square"><side>2</side></shape>
+    ///     </drawing>
+    /// "#;
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.xsi_type_dispatch(true);
+    /// let drawing = Drawing::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(
+    ///     drawing.shape,
+    ///     vec![Shape::Circle { radius: 1.0 }, Shape::Square { side: 2.0 }]
+    /// );
+    /// ```
+    ///
+    /// [`xsi:type`]: https://www.w3.org/TR/xmlschema-1/#xsi_type
+    pub fn xsi_type_dispatch(&mut self, val: bool) -> &mut Self {
+        self.xsi_type_dispatch = val;
+        self
+    }
+
+    /// Changes whether the deserializer counts processed XML nodes. Counters
+    /// are available through [`Self::stats`] and are useful for profiling
+    /// of large documents. They do not affect results of deserialization.
+    ///
+    /// Enabling of this option resets counters.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::{DeStats, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Book {
+    ///     title: String,
+    /// }
+    ///
+    /// let xml = r#"<book id="1"><title>Dune</title><author>Herbert</author></book>"#;
+    ///
+    /// let mut de = Deserializer::from_str(xml);
+    /// de.with_stats(true);
+    /// Book::deserialize(&mut de).unwrap();
+    /// assert_eq!(
+    ///     de.stats(),
+    ///     DeStats {
+    ///         elements: 3,
+    ///         attributes: 1,
+    ///         texts: 1,
+    ///         // The <author> element
+    ///         skipped: 1,
+    ///     }
+    /// );
+    /// ```
+    pub fn with_stats(&mut self, val: bool) -> &mut Self {
+        self.stats = if val { Some(DeStats::default()) } else { None };
+        self
+    }
+
+    /// Returns counters of XML nodes, processed so far. All counters are zero,
+    /// if [`Self::with_stats`] is not enabled
+    pub fn stats(&self) -> DeStats {
+        self.stats.unwrap_or_default()
+    }
+
     /// Get a new deserializer from a regular BufRead
     #[deprecated = "Use `Deserializer::new` instead"]
     pub fn from_borrowing_reader(reader: R) -> Self {
@@ -366,7 +1584,7 @@ where
 
     fn peek(&mut self) -> Result<&DeEvent<'de>, DeError> {
         if self.peek.is_none() {
-            self.peek = Some(self.reader.next()?);
+            self.peek = Some(self.read_next()?);
         }
         match self.peek.as_ref() {
             Some(v) => Ok(v),
@@ -379,10 +1597,44 @@ where
     }
 
     fn next(&mut self) -> Result<DeEvent<'de>, DeError> {
+        self.seq_item = false;
+        self.seq_group = false;
+        self.entry_value = false;
         if let Some(e) = self.peek.take() {
             return Ok(e);
         }
-        self.reader.next()
+        self.read_next()
+    }
+
+    /// Returns the next event from the [`Self::replay`] buffer, or from the reader,
+    /// if the buffer is empty
+    #[inline]
+    fn read_next(&mut self) -> Result<DeEvent<'de>, DeError> {
+        match self.replay.pop_front() {
+            Some(e) => Ok(e),
+            None => {
+                let event = self.reader.next()?;
+                if let Some(stats) = self.stats.as_mut() {
+                    match event {
+                        DeEvent::Start(_) => stats.elements += 1,
+                        DeEvent::Text(_) | DeEvent::CData(_) => stats.texts += 1,
+                        _ => (),
+                    }
+                }
+                Ok(event)
+            }
+        }
+    }
+
+    /// Returns the peeked event and the `events` back, so they will be read
+    /// again before all other events, in the order `events`, peeked event
+    fn unread(&mut self, events: Vec<DeEvent<'de>>) {
+        if let Some(e) = self.peek.take() {
+            self.replay.push_front(e);
+        }
+        for e in events.into_iter().rev() {
+            self.replay.push_front(e);
+        }
     }
 
     fn next_start(&mut self) -> Result<Option<BytesStart<'de>>, DeError> {
@@ -440,14 +1692,23 @@ where
         allow_start: bool,
     ) -> Result<BytesCData<'de>, DeError> {
         match self.next()? {
-            DeEvent::Text(e) if unescape => e.unescape().map_err(|e| DeError::InvalidXml(e.into())),
+            DeEvent::Text(e) if unescape => e
+                .unescape_into_cdata()
+                .map_err(|e| DeError::InvalidXml(e.into())),
             DeEvent::Text(e) => Ok(BytesCData::new(e.into_inner())),
             DeEvent::CData(e) => Ok(e),
+            DeEvent::Start(e)
+                if allow_start
+                    && self.strict_types
+                    && e.attributes_raw().iter().any(|b| !is_whitespace(*b)) =>
+            {
+                Err(DeError::UnexpectedStart(e.name().to_owned()))
+            }
             DeEvent::Start(e) if allow_start => {
                 // allow one nested level
                 let inner = self.next()?;
                 let t = match inner {
-                    DeEvent::Text(t) if unescape => t.unescape()?,
+                    DeEvent::Text(t) if unescape => t.unescape_into_cdata()?,
                     DeEvent::Text(t) => BytesCData::new(t.into_inner()),
                     DeEvent::CData(t) => t,
                     DeEvent::Start(s) => return Err(DeError::UnexpectedStart(s.name().to_owned())),
@@ -459,8 +1720,21 @@ where
                     DeEvent::End(end) => return Err(DeError::UnexpectedEnd(end.name().to_owned())),
                     DeEvent::Eof => return Err(DeError::UnexpectedEof),
                 };
+                // Text can be split into several events by comments, processing
+                // instructions or CDATA sections. Concatenate all of them
+                let mut text = t.into_inner();
+                while let DeEvent::Text(_) | DeEvent::CData(_) = self.peek()? {
+                    let t = match self.next()? {
+                        DeEvent::Text(t) if unescape => t.unescape_into_cdata()?,
+                        DeEvent::Text(t) => BytesCData::new(t.into_inner()),
+                        DeEvent::CData(t) => t,
+                        // SAFETY: only these events are consumed above
+                        _ => unreachable!(),
+                    };
+                    text.to_mut().extend_from_slice(&t);
+                }
                 self.read_to_end(e.name())?;
-                Ok(t)
+                Ok(BytesCData::new(text))
             }
             DeEvent::Start(e) => Err(DeError::UnexpectedStart(e.name().to_owned())),
             DeEvent::End(e) => Err(DeError::UnexpectedEnd(e.name().to_owned())),
@@ -474,13 +1748,141 @@ where
         self.reader.decoder()
     }
 
+    /// Returns a copy of the text of the next element, if it contains only
+    /// a text or a CDATA section. All events remain unconsumed
+    fn element_text(&mut self) -> Result<Option<DeEvent<'de>>, DeError> {
+        if !matches!(self.peek()?, DeEvent::Start(_)) {
+            return Ok(None);
+        }
+        let start = self.next()?;
+        let mut events = vec![start];
+        let text = match self.next()? {
+            e @ DeEvent::Text(_) | e @ DeEvent::CData(_) => {
+                let end = self.next()?;
+                let text = match end {
+                    DeEvent::End(_) => Some(e.clone()),
+                    _ => None,
+                };
+                events.push(e);
+                events.push(end);
+                text
+            }
+            e => {
+                events.push(e);
+                None
+            }
+        };
+        self.unread(events);
+        Ok(text)
+    }
+
+    /// Returns the value of the `xsi:type` attribute of the next element without
+    /// the namespace prefix, if the next event is a start of an element with that
+    /// attribute. Does not consume any events
+    fn xsi_type(&mut self) -> Result<Option<Vec<u8>>, DeError> {
+        if let DeEvent::Start(e) = self.peek()? {
+            if let Some(a) = e.try_get_attribute(b"xsi:type")? {
+                let value = a.unescaped_value()?;
+                return Ok(Some(QName(&value).local_name().into_inner().to_vec()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns `true` if the next event is a start of an element without attributes,
+    /// that contains nothing but empty texts. Does not consume any events
+    fn is_empty_element(&mut self) -> Result<bool, DeError> {
+        match self.peek()? {
+            DeEvent::Start(e) if e.attributes_raw().iter().all(|b| is_whitespace(*b)) => {}
+            _ => return Ok(false),
+        }
+        let mut events = vec![self.next()?];
+        let empty = loop {
+            let event = self.next()?;
+            let empty = match &event {
+                DeEvent::Text(t) if t.is_empty() => None,
+                DeEvent::CData(t) if t.is_empty() => None,
+                DeEvent::End(_) => Some(true),
+                _ => Some(false),
+            };
+            events.push(event);
+            if let Some(empty) = empty {
+                break empty;
+            }
+        };
+        self.unread(events);
+        Ok(empty)
+    }
+
+    /// Checks that a struct with the specified fields is not deserialized from
+    /// a text or from an element with only a text content. Used when
+    /// [`Self::strict_types`] is enabled
+    fn check_struct_text(&mut self, fields: &[&str]) -> Result<(), DeError> {
+        if fields.contains(&INNER_VALUE) {
+            return Ok(());
+        }
+        let text = match self.peek()? {
+            DeEvent::Text(t) => t.to_vec(),
+            DeEvent::CData(t) => t.to_vec(),
+            _ => match self.element_text()? {
+                Some(DeEvent::Text(t)) => t.to_vec(),
+                Some(DeEvent::CData(t)) => t.to_vec(),
+                _ => return Ok(()),
+            },
+        };
+        if text.is_empty() {
+            return Ok(());
+        }
+        Err(DeError::UnexpectedText(text))
+    }
+
+    /// Deserializes a map or a struct from the attributes and the content of
+    /// the already consumed element `start`, then consumes everything up to
+    /// the corresponding end tag.
+    fn visit_element_map<V>(
+        &mut self,
+        start: BytesStart<'de>,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let name = start.name().to_vec();
+        self.has_value_field = fields.contains(&INNER_VALUE);
+        let map = map::MapAccess::new(self, start, fields)?;
+        let value = visitor.visit_map(map)?;
+        self.has_value_field = false;
+        self.read_to_end(&name)?;
+        Ok(value)
+    }
+
     fn read_to_end(&mut self, name: &[u8]) -> Result<(), DeError> {
-        // First one might be in self.peek
-        match self.next()? {
-            DeEvent::Start(e) => self.reader.read_to_end(e.name())?,
-            DeEvent::End(e) if e.name() == name => return Ok(()),
+        // First one might be in self.peek, all other events are skipped
+        // without reading them from the reader
+        match self.peek.take() {
+            Some(DeEvent::Start(e)) => self.skip_to_end(e.name())?,
+            Some(DeEvent::End(e)) if e.name() == name => return Ok(()),
             _ => (),
         }
+        self.skip_to_end(name)
+    }
+
+    /// Skips events up to the end tag `name`, taking into account events
+    /// in the [`Self::replay`] buffer
+    fn skip_to_end(&mut self, name: &[u8]) -> Result<(), DeError> {
+        let mut depth = 0;
+        while !self.replay.is_empty() {
+            match self.read_next()? {
+                DeEvent::Start(_) => depth += 1,
+                DeEvent::End(_) if depth == 0 => return Ok(()),
+                DeEvent::End(_) => depth -= 1,
+                DeEvent::Eof => return Err(DeError::UnexpectedEof),
+                _ => (),
+            }
+        }
+        // Buffered events contains only complete elements, so the rest of
+        // events of the `name` element is in the reader
         self.reader.read_to_end(name)
     }
 }
@@ -498,7 +1900,57 @@ impl<'de> Deserializer<'de, SliceReader<'de>> {
             .expand_empty_elements(true)
             .check_end_names(true)
             .trim_text(true);
-        Self::new(SliceReader { reader })
+        Self::new(SliceReader {
+            reader,
+            validate: false,
+        })
+    }
+
+    /// Enables the check of the balance of tags in the whole input before
+    /// deserialization starts.
+    ///
+    /// Usually malformed input, such as a truncated document, is detected only
+    /// when the deserializer reaches the broken place, after all preceding data
+    /// were deserialized. When set to `true`, the input is scanned once before
+    /// the first event is returned and a missing end tag is reported as
+    /// [`DeError::UnexpectedEof`], and a mismatched end tag as [`DeError::InvalidXml`]
+    /// with [`Error::EndEventMismatch`] inside, without calling any visitors.
+    ///
+    /// The check parses the input twice, so well-formed documents are deserialized
+    /// slower. The scan is much cheaper than deserialization itself: in the
+    /// `validation` benchmark over a list of 10 000 small structs, it adds about
+    /// 15% to the deserialization time, while a document truncated at the end
+    /// is rejected about 7 times faster. Enable it when malformed input is expected
+    /// and deserialization of the valid part is expensive, for example, for big
+    /// documents that could be truncated during transfer.
+    ///
+    /// Only tags are checked. Other errors, such as malformed attributes or
+    /// invalid values, are still reported when the deserializer reaches them.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fast_xml::de::{DeError, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct List {
+    ///     item: Vec<u32>,
+    /// }
+    ///
+    /// let mut de = Deserializer::from_str("<list><item>1</item><item>2</item><item>");
+    /// de.validate_balanced_upfront(true);
+    ///
+    /// match List::deserialize(&mut de) {
+    ///     Err(DeError::UnexpectedEof) => {}
+    ///     x => panic!("Expected `Err(UnexpectedEof)`, but got `{:?}`", x),
+    /// }
+    /// ```
+    pub fn validate_balanced_upfront(&mut self, val: bool) -> &mut Self {
+        self.reader.validate = val;
+        self
     }
 }
 
@@ -541,15 +1993,12 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.strict_types {
+            self.check_struct_text(fields)?;
+        }
         // Try to go to the next `<tag ...>...</tag>` or `<tag .../>`
         if let Some(e) = self.next_start()? {
-            let name = e.name().to_vec();
-            self.has_value_field = fields.contains(&INNER_VALUE);
-            let map = map::MapAccess::new(self, e, fields)?;
-            let value = visitor.visit_map(map)?;
-            self.has_value_field = false;
-            self.read_to_end(&name)?;
-            Ok(value)
+            self.visit_element_map(e, fields, visitor)
         } else {
             Err(DeError::ExpectedStart)
         }
@@ -564,11 +2013,14 @@ where
     /// - any text content
     /// - any CDATA content
     ///
+    /// If [`strict_unit`](Deserializer::strict_unit) is enabled, only elements
+    /// without attributes and content are accepted.
+    ///
     /// # Events handling
     ///
     /// |Event             |XML                        |Handling
     /// |------------------|---------------------------|-------------------------------------------
-    /// |[`DeEvent::Start`]|`<tag>...</tag>`           |Calls `visitor.visit_unit()`, consumes all events up to corresponding `End` event
+    /// |[`DeEvent::Start`]|`<tag>...</tag>`           |Calls `visitor.visit_unit()`, consumes all events up to corresponding `End` event. In strict mode emits [`ExpectedEmpty("tag")`](DeError::ExpectedEmpty) if the element has attributes or content
     /// |[`DeEvent::End`]  |`</tag>`                   |Emits [`UnexpectedEnd("tag")`](DeError::UnexpectedEnd)
     /// |[`DeEvent::Text`] |`text content`             |Calls `visitor.visit_unit()`. Text content is ignored
     /// |[`DeEvent::CData`]|`<![CDATA[cdata content]]>`|Calls `visitor.visit_unit()`. CDATA content is ignored
@@ -578,6 +2030,13 @@ where
         V: Visitor<'de>,
     {
         match self.next()? {
+            DeEvent::Start(s) if self.strict_unit => {
+                let has_attributes = s.attributes_raw().iter().any(|b| !is_whitespace(*b));
+                match self.next()? {
+                    DeEvent::End(_) if !has_attributes => visitor.visit_unit(),
+                    _ => Err(DeError::ExpectedEmpty(s.name().to_owned())),
+                }
+            }
             DeEvent::Start(s) => {
                 self.read_to_end(s.name())?;
                 visitor.visit_unit()
@@ -600,6 +2059,34 @@ where
         self.deserialize_unit(visitor)
     }
 
+    /// Newtype structs are represented the same as their inner values, read
+    /// from the next element. A newtype over a sequence, such as `Vec<T>`, is
+    /// represented by one element, which children are items of the sequence.
+    /// The name of the element is not checked, and names of children are
+    /// interpreted by the item type, so items could be variants of an enum:
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Deserialize;
+    /// # use fast_xml::de::from_str;
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// enum Shape {
+    ///     Circle(u32),
+    ///     Square(u32),
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Shapes(Vec<Shape>);
+    ///
+    /// let shapes: Shapes = from_str(
+    ///     "<shapes><Circle>1</Circle><Square>2</Square></shapes>"
+    /// ).unwrap();
+    /// assert_eq!(shapes, Shapes(vec![Shape::Circle(1), Shape::Square(2)]));
+    /// ```
+    ///
+    /// Note, that a struct field of such type is represented by one element
+    /// with the field name, unlike a `Vec<T>` field, represented by repeated
+    /// elements with the field name.
     fn deserialize_newtype_struct<V>(
         self,
         _name: &'static str,
@@ -612,11 +2099,46 @@ where
     }
 
     /// Representation of tuples the same as [sequences](#method.deserialize_seq).
+    ///
+    /// A tuple consumes as many consecutive elements as it has fields, regardless
+    /// of their names. Therefore, a `$value` sequence of tuples can pair elements
+    /// with alternating names, such as terms and definitions of an HTML `<dl>`
+    /// list. Use `#[serde(from = "(...)")]` to get named fields in that case:
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::de::from_str;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// #[serde(from = "(String, String)")]
+    /// struct Entry {
+    ///     term: String,
+    ///     definition: String,
+    /// }
+    /// impl From<(String, String)> for Entry {
+    ///     fn from((term, definition): (String, String)) -> Self {
+    ///         Entry { term, definition }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct List {
+    ///     #[serde(rename = "$value")]
+    ///     entries: Vec<Entry>,
+    /// }
+    ///
+    /// let list: List = from_str("<dl><dt>a</dt><dd>1</dd><dt>b</dt><dd>2</dd></dl>").unwrap();
+    /// assert_eq!(list.entries, vec![
+    ///     Entry { term: "a".into(), definition: "1".into() },
+    ///     Entry { term: "b".into(), definition: "2".into() },
+    /// ]);
+    /// ```
     fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        visitor.visit_seq(seq::SeqAccess::new(self)?)
     }
 
     /// Representation of named tuples the same as [unnamed tuples](#method.deserialize_tuple).
@@ -632,6 +2154,86 @@ where
         self.deserialize_tuple(len, visitor)
     }
 
+    /// Variants of externally tagged enums are represented by elements with
+    /// the variant names or by a text with the variant name for unit variants.
+    ///
+    /// An element with an unknown name is deserialized into a `#[serde(other)]`
+    /// variant, if the enum has one. The whole subtree of such element is
+    /// skipped, regardless of its content:
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Deserialize;
+    /// # use fast_xml::de::from_str;
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// enum Shape {
+    ///     Circle { radius: u32 },
+    ///     #[serde(other)]
+    ///     Unknown,
+    /// }
+    ///
+    /// let shape: Shape = from_str("<Triangle side='3'><point/></Triangle>").unwrap();
+    /// assert_eq!(shape, Shape::Unknown);
+    /// ```
+    ///
+    /// When a variant is selected by the name of the single child of a wrapper
+    /// element, for example, in RPC responses that contain either a result or
+    /// a fault, the wrapper is represented by a struct with a `$value` field
+    /// of the enum type. `Result<T, E>` can be used directly, if the children
+    /// are named `Ok` and `Err`:
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Deserialize;
+    /// # use fast_xml::de::from_str;
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// enum Body {
+    ///     #[serde(rename = "result")]
+    ///     Success { value: u32 },
+    ///     #[serde(rename = "fault")]
+    ///     Fault { message: String },
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Response {
+    ///     #[serde(rename = "$value")]
+    ///     body: Body,
+    /// }
+    ///
+    /// let response: Response = from_str(
+    ///     "<response><fault><message>Access denied</message></fault></response>"
+    /// ).unwrap();
+    /// assert_eq!(response.body, Body::Fault { message: "Access denied".into() });
+    /// ```
+    ///
+    /// An element, that is a value of a struct field or a map entry, is named
+    /// after the field, so when it contains only a text or a CDATA section,
+    /// a unit variant is selected by that text:
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Deserialize;
+    /// # use fast_xml::de::from_str;
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Status {
+    ///     Active,
+    ///     Inactive,
+    ///     #[serde(other)]
+    ///     Unknown,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Account {
+    ///     status: Status,
+    /// }
+    ///
+    /// let account: Account = from_str("<account><status>active</status></account>").unwrap();
+    /// assert_eq!(account.status, Status::Active);
+    ///
+    /// let account: Account = from_str("<account><status>deleted</status></account>").unwrap();
+    /// assert_eq!(account.status, Status::Unknown);
+    /// ```
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
@@ -645,24 +2247,69 @@ where
         Ok(value)
     }
 
+    /// Sequences are represented by repeating elements. A sequence, that is
+    /// an element of another sequence, is represented by one element with
+    /// the repeating children:
+    ///
+    /// ```xml
+    /// <group><item>1</item><item>2</item></group><group><item>3</item></group>
+    /// ```
+    ///
+    /// That is the format, produced by the [`Serializer`] for `Vec<Vec<T>>`.
+    /// See [`Serializer::nested_seq_tags`]
+    ///
+    /// [`Serializer`]: crate::se::Serializer
+    /// [`Serializer::nested_seq_tags`]: crate::se::Serializer::nested_seq_tags
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
+        if self.seq_item {
+            if let DeEvent::Start(e) = self.peek()? {
+                let name = e.name().to_vec();
+                // Consume the group element, its children are elements of the sequence
+                self.next()?;
+                let value = visitor.visit_seq(seq::SeqAccess::children(self))?;
+                self.read_to_end(&name)?;
+                return Ok(value);
+            }
+        }
         visitor.visit_seq(seq::SeqAccess::new(self)?)
     }
 
+    /// Attributes are keyed by their qualified names, as they are written in
+    /// the document, so namespaced attributes like `xlink:href` and attributes
+    /// without a prefix, like `href`, become different entries of a map.
+    ///
+    /// Structs with `#[serde(flatten)]` fields are also deserialized as maps,
+    /// because serde does not know their full list of fields. In that case
+    /// child elements, not matched by own fields of the struct, are additionally
+    /// passed as a sequence under the `$value` key, so they can be captured
+    /// by a `$value` field of a flattened struct. Child elements
+    /// without attributes, that contain only a text, are passed to flattened
+    /// structs as strings, so they can fill primitive fields
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_struct("", &[], visitor)
+        if let Some(e) = self.next_start()? {
+            self.visit_element_map(e, &[], visitor)
+        } else {
+            Err(DeError::ExpectedStart)
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, DeError>
     where
         V: Visitor<'de>,
     {
+        if self.empty_string_as_none && self.is_empty_element()? {
+            // Consume the element
+            if let DeEvent::Start(e) = self.next()? {
+                self.read_to_end(e.name())?;
+            }
+            return visitor.visit_none();
+        }
         match self.peek()? {
             DeEvent::Text(t) if t.is_empty() => visitor.visit_none(),
             DeEvent::CData(t) if t.is_empty() => visitor.visit_none(),
@@ -688,6 +2335,9 @@ where
             DeEvent::Eof => return Err(DeError::UnexpectedEof),
             _ => (),
         }
+        if let Some(stats) = self.stats.as_mut() {
+            stats.skipped += 1;
+        }
         visitor.visit_unit()
     }
 
@@ -695,6 +2345,16 @@ where
     where
         V: Visitor<'de>,
     {
+        // An element, that is a value of a map entry and contains only a text,
+        // is a string, so internally tagged enums can find their tag and fields
+        // in elements, such as `<tag>Variant</tag><field>value</field>`
+        if std::mem::replace(&mut self.entry_value, false) && self.element_text()?.is_some() {
+            if let DeEvent::Start(e) = self.peek()? {
+                if e.attributes_raw().iter().all(|b| is_whitespace(*b)) {
+                    return self.deserialize_string(visitor);
+                }
+            }
+        }
         match self.peek()? {
             DeEvent::Start(_) => self.deserialize_map(visitor),
             // Redirect to deserialize_unit in order to consume an event and return an appropriate error
@@ -720,6 +2380,16 @@ pub trait XmlRead<'i> {
 
     /// A copy of the reader's decoder used to decode strings.
     fn decoder(&self) -> Decoder;
+
+    /// Counts items of a sequence of elements with the specified name, which
+    /// starts from the just read `Start` event with that name. Text and CDATA
+    /// events between elements are also counted, because they are items of
+    /// the sequence too. Used to provide a size hint for sequences.
+    ///
+    /// Returns `None` if the count cannot be obtained without consuming events.
+    fn count_siblings(&self, _name: &[u8]) -> Option<usize> {
+        None
+    }
 }
 
 /// XML input source that reads from a std::io input stream.
@@ -769,10 +2439,35 @@ impl<'i, R: BufRead> XmlRead<'i> for IoReader<R> {
 /// [`Deserializer::from_str`] or [`Deserializer::from_slice`]
 pub struct SliceReader<'de> {
     reader: Reader<&'de [u8]>,
+    /// If `true`, the balance of tags is checked before the first event is
+    /// returned. See [`Deserializer::validate_balanced_upfront`]
+    validate: bool,
+}
+
+impl<'de> SliceReader<'de> {
+    /// Scans the rest of the input with a copy of the reader and checks, that
+    /// each start tag has a matching end tag
+    fn check_balance(&self) -> Result<(), DeError> {
+        let mut reader = self.reader.clone();
+        let mut depth = 0usize;
+        loop {
+            match reader.read_event_unbuffered()? {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth = depth.saturating_sub(1),
+                Event::Eof if depth > 0 => return Err(DeError::UnexpectedEof),
+                Event::Eof => return Ok(()),
+                _ => (),
+            }
+        }
+    }
 }
 
 impl<'de> XmlRead<'de> for SliceReader<'de> {
     fn next(&mut self) -> Result<DeEvent<'de>, DeError> {
+        if self.validate {
+            self.validate = false;
+            self.check_balance()?;
+        }
         loop {
             let e = self.reader.read_event_unbuffered()?;
             match e {
@@ -797,6 +2492,12 @@ impl<'de> XmlRead<'de> for SliceReader<'de> {
     fn decoder(&self) -> Decoder {
         self.reader.decoder()
     }
+
+    /// Scans the rest of the input, which is cheap because the whole input
+    /// is already in memory
+    fn count_siblings(&self, name: &[u8]) -> Option<usize> {
+        Some(self.reader.count_siblings(name))
+    }
 }
 
 #[cfg(test)]
@@ -864,6 +2565,7 @@ mod tests {
         };
         let mut reader2 = SliceReader {
             reader: Reader::from_bytes(s),
+            validate: false,
         };
 
         loop {
@@ -890,6 +2592,7 @@ mod tests {
 
         let mut reader = SliceReader {
             reader: Reader::from_bytes(s),
+            validate: false,
         };
 
         reader
@@ -934,6 +2637,7 @@ mod tests {
         let s = " <item /> ";
         let mut reader = SliceReader {
             reader: Reader::from_str(s),
+            validate: false,
         };
 
         reader
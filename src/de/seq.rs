@@ -24,6 +24,15 @@ where
 {
     de: &'a mut Deserializer<'de, R>,
     names: Names,
+    /// Number of remaining items, if known. See [`XmlRead::count_siblings`]
+    remaining: Option<usize>,
+    /// If `true`, the sequence collects all elements with the same name up to
+    /// the end of the parent element. See [`Deserializer::group_repeated_elements`]
+    group: bool,
+    /// Events of elements with other names, that was skipped while searching
+    /// for the next item. Returned back to the deserializer when the sequence
+    /// is dropped
+    skipped: Vec<DeEvent<'de>>,
 }
 
 impl<'a, 'de, R> SeqAccess<'de, 'a, R>
@@ -32,16 +41,90 @@ where
 {
     /// Get a new SeqAccess
     pub fn new(de: &'a mut Deserializer<'de, R>) -> Result<Self, DeError> {
-        let names = if de.has_value_field {
-            Names::Unknown
+        let group = std::mem::replace(&mut de.seq_group, false);
+        let (names, remaining) = if de.has_value_field {
+            (Names::Unknown, None)
         } else {
             if let DeEvent::Start(e) = de.peek()? {
-                Names::Peek(e.name().to_vec())
+                let name = e.name().to_vec();
+                // Siblings cannot be counted when some events are buffered
+                // or when elements will be searched up to the end of the parent
+                let remaining = if group || !de.replay.is_empty() {
+                    None
+                } else {
+                    de.reader.count_siblings(&name)
+                };
+                (Names::Peek(name), remaining)
             } else {
-                Names::Unknown
+                (Names::Unknown, None)
             }
         };
-        Ok(SeqAccess { de, names })
+        let group = group && matches!(names, Names::Peek(_));
+        Ok(SeqAccess {
+            de,
+            names,
+            remaining,
+            group,
+            skipped: Vec::new(),
+        })
+    }
+
+    /// Get a new SeqAccess for a sequence, represented by the children of
+    /// an already consumed element. All children are items of the sequence,
+    /// regardless of their names, so, for example, each child can represent
+    /// a different variant of an enum
+    pub fn children(de: &'a mut Deserializer<'de, R>) -> Self {
+        de.seq_group = false;
+        SeqAccess {
+            de,
+            names: Names::Unknown,
+            remaining: None,
+            group: false,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Moves events of nodes, that are not items of the sequence, to the
+    /// [`Self::skipped`] list until the next item or the end of the parent
+    /// element is found
+    fn skip_others(&mut self) -> Result<(), DeError> {
+        loop {
+            match self.de.peek()? {
+                DeEvent::Eof | DeEvent::End(_) => return Ok(()),
+                DeEvent::Start(e) if self.names.is_valid(e) => return Ok(()),
+                DeEvent::Start(_) => {
+                    let mut depth = 0;
+                    loop {
+                        let event = self.de.next()?;
+                        match event {
+                            DeEvent::Start(_) => depth += 1,
+                            DeEvent::End(_) => depth -= 1,
+                            DeEvent::Eof => return Err(DeError::UnexpectedEof),
+                            _ => (),
+                        }
+                        self.skipped.push(event);
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                _ => {
+                    let event = self.de.next()?;
+                    self.skipped.push(event);
+                }
+            }
+        }
+    }
+}
+
+impl<'de, 'a, R> Drop for SeqAccess<'de, 'a, R>
+where
+    R: XmlRead<'de>,
+{
+    fn drop(&mut self) {
+        if !self.skipped.is_empty() {
+            self.de.unread(std::mem::take(&mut self.skipped));
+        }
     }
 }
 
@@ -55,10 +138,25 @@ where
     where
         T: DeserializeSeed<'de>,
     {
+        if self.group {
+            self.skip_others()?;
+        }
         match self.de.peek()? {
             DeEvent::Eof | DeEvent::End(_) => Ok(None),
             DeEvent::Start(e) if !self.names.is_valid(e) => Ok(None),
-            _ => seed.deserialize(&mut *self.de).map(Some),
+            _ => {
+                if let Some(remaining) = self.remaining.as_mut() {
+                    *remaining = remaining.saturating_sub(1);
+                }
+                self.de.seq_item = true;
+                let result = seed.deserialize(&mut *self.de).map(Some);
+                self.de.seq_item = false;
+                result
+            }
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
+    }
 }
@@ -33,8 +33,30 @@ where
     where
         V: DeserializeSeed<'de>,
     {
+        if self.de.xsi_type_dispatch {
+            if let Some(name) = self.de.xsi_type()? {
+                // The variant is selected by the `xsi:type` attribute, so the
+                // element name, which could be the name of a map key, is not used
+                self.de.entry_value = false;
+                let decoder = self.de.reader.decoder();
+                let de = EscapedDeserializer::new(Cow::Owned(name), decoder, false);
+                let name = seed.deserialize(de)?;
+                return Ok((name, VariantAccess { de: self.de }));
+            }
+        }
+        // An element, that is a value of a map entry, is named after the key, so
+        // a variant is selected by its text, for example, `<status>active</status>`
+        let text = if std::mem::replace(&mut self.de.entry_value, false) {
+            self.de.element_text()?
+        } else {
+            None
+        };
         let decoder = self.de.reader.decoder();
-        let de = match self.de.peek()? {
+        let event = match text {
+            Some(ref e) => e,
+            None => self.de.peek()?,
+        };
+        let de = match event {
             DeEvent::Text(t) => EscapedDeserializer::new(Cow::Borrowed(t), decoder, true),
             // Escape sequences does not processed inside CDATA section
             DeEvent::CData(t) => EscapedDeserializer::new(Cow::Borrowed(t), decoder, false),
@@ -63,6 +85,9 @@ where
 {
     type Error = DeError;
 
+    /// Consumes the whole element of a variant, so its attributes and content
+    /// are ignored. That also allows to map elements with unknown names and
+    /// any content to a `#[serde(other)]` variant
     fn unit_variant(self) -> Result<(), DeError> {
         match self.de.next()? {
             DeEvent::Start(e) => self.de.read_to_end(e.name()),
@@ -2,6 +2,7 @@
 
 use crate::escape::EscapeError;
 use crate::events::attributes::AttrError;
+use std::io::ErrorKind;
 use std::str::Utf8Error;
 
 /// The error type used by this crate.
@@ -20,18 +21,132 @@ pub enum Error {
         /// Found end event
         found: String,
     },
+    /// Name of the root element does not match the name from the DOCTYPE
+    /// declaration. Reported only when [`Reader::validate_doctype_root`] is enabled
+    ///
+    /// [`Reader::validate_doctype_root`]: crate::Reader::validate_doctype_root
+    DocTypeRootMismatch {
+        /// Name of the root element declared in the DOCTYPE
+        expected: String,
+        /// Actual name of the root element
+        found: String,
+    },
+    /// Content of a start tag does not begin with an element name. Reported by
+    /// [`BytesStart::parse`]
+    ///
+    /// [`BytesStart::parse`]: crate::events::BytesStart::parse
+    EmptyElementName,
     /// Unexpected token
     UnexpectedToken(String),
     /// Unexpected <!>
     UnexpectedBang(u8),
     /// Text not found, expected `Event::Text`
     TextNotFound,
+    /// An element with the expected name was not found by [`Reader::expect_start`]
+    ///
+    /// [`Reader::expect_start`]: crate::Reader::expect_start
+    StartNotFound {
+        /// Name of the expected element
+        expected: String,
+        /// Description of the found event, for example, ``` `Start` event of element <b> ```
+        found: String,
+    },
     /// `Event::XmlDecl` must start with *version* attribute
     XmlDeclWithoutVersion(Option<String>),
     /// Attribute parsing error
     InvalidAttr(AttrError),
     /// Escape error
     EscapeError(EscapeError),
+    /// Content of the `<!DOCTYPE ...>` declaration does not match the [grammar].
+    /// Reported by the [`BytesDocType`] accessors
+    ///
+    /// [grammar]: https://www.w3.org/TR/xml11/#NT-doctypedecl
+    /// [`BytesDocType`]: crate::events::BytesDocType
+    InvalidDocType(String),
+    /// I/O error while writing an event by the [`Writer`]
+    ///
+    /// [`Writer`]: crate::Writer
+    WriteEvent {
+        /// Kind of the event, for example, `Start` or `Text`
+        event: &'static str,
+        /// Name of the element, if the event is `Start`, `End` or `Empty`
+        name: Option<String>,
+        /// The underlying I/O error
+        source: ::std::io::Error,
+    },
+    /// A single text node or markup is larger than allowed by
+    /// [`Reader::max_token_size`]
+    ///
+    /// [`Reader::max_token_size`]: crate::Reader::max_token_size
+    TokenTooLarge {
+        /// The maximum allowed size of a token in bytes
+        limit: usize,
+    },
+    /// The document is larger than allowed by [`Reader::max_document_size`]
+    ///
+    /// [`Reader::max_document_size`]: crate::Reader::max_document_size
+    SizeLimitExceeded {
+        /// The maximum allowed size of a document in bytes
+        limit: usize,
+    },
+    /// Name of an element or an attribute does not match the XML [`Name`]
+    /// production. Reported only when [`Reader::check_names`] is enabled
+    ///
+    /// [`Name`]: https://www.w3.org/TR/xml/#NT-Name
+    /// [`Reader::check_names`]: crate::Reader::check_names
+    IllFormedName {
+        /// Position of the first invalid character of the name in the input
+        offset: usize,
+    },
+    /// Content of an event contains a character that is not allowed in XML 1.0
+    /// documents by the [`Char`] production. Reported only by the [`Writer`]
+    /// when [`Writer::check_output`] is enabled
+    ///
+    /// [`Char`]: https://www.w3.org/TR/xml/#NT-Char
+    /// [`Writer`]: crate::Writer
+    /// [`Writer::check_output`]: crate::Writer::check_output
+    InvalidCharacter {
+        /// The invalid character
+        ch: char,
+        /// Position of the character in the raw content of the event
+        offset: usize,
+    },
+    /// The document was finished while an element was not closed. Reported only
+    /// by the [`Writer::finish`] when [`Writer::check_nesting`] is enabled
+    ///
+    /// [`Writer::finish`]: crate::Writer::finish
+    /// [`Writer::check_nesting`]: crate::Writer::check_nesting
+    UnclosedElement {
+        /// Name of the last opened element, that was not closed
+        name: String,
+    },
+}
+
+impl Error {
+    /// Returns `true` if the error is caused by the end of input in the middle
+    /// of a markup or by an I/O error of the [`ErrorKind::UnexpectedEof`] kind
+    pub fn is_eof(&self) -> bool {
+        match self {
+            Error::UnexpectedEof(_) => true,
+            _ => self.io_error_kind() == Some(ErrorKind::UnexpectedEof),
+        }
+    }
+
+    /// Returns `true` if the error is caused by an I/O error while reading
+    /// or writing. The kind of the error is returned by [`Self::io_error_kind`]
+    pub fn is_io(&self) -> bool {
+        self.io_error_kind().is_some()
+    }
+
+    /// Returns the kind of the underlying I/O error, if the error is caused by
+    /// an I/O error while reading or writing
+    pub fn io_error_kind(&self) -> Option<ErrorKind> {
+        match self {
+            Error::Io(e) => Some(e.kind()),
+            Error::WriteEvent { source, .. } => Some(source.kind()),
+            _ => None,
+        }
+    }
 }
 
 impl From<::std::io::Error> for Error {
@@ -79,6 +194,12 @@ impl std::fmt::Display for Error {
             Error::EndEventMismatch { expected, found } => {
                 write!(f, "Expecting </{}> found </{}>", expected, found)
             }
+            Error::DocTypeRootMismatch { expected, found } => write!(
+                f,
+                "DOCTYPE declares root element <{}> but found <{}>",
+                expected, found
+            ),
+            Error::EmptyElementName => write!(f, "Element name is empty"),
             Error::UnexpectedToken(e) => write!(f, "Unexpected token '{}'", e),
             Error::UnexpectedBang(b) => write!(
                 f,
@@ -86,6 +207,9 @@ impl std::fmt::Display for Error {
                 *b as char
             ),
             Error::TextNotFound => write!(f, "Cannot read text, expecting Event::Text"),
+            Error::StartNotFound { expected, found } => {
+                write!(f, "Expecting start tag <{}>, but found {}", expected, found)
+            }
             Error::XmlDeclWithoutVersion(e) => write!(
                 f,
                 "XmlDecl must start with 'version' attribute, found {:?}",
@@ -93,6 +217,36 @@ impl std::fmt::Display for Error {
             ),
             Error::InvalidAttr(e) => write!(f, "error while parsing attribute: {}", e),
             Error::EscapeError(e) => write!(f, "{}", e),
+            Error::InvalidDocType(e) => write!(f, "Invalid DOCTYPE declaration: {}", e),
+            Error::WriteEvent {
+                event,
+                name: Some(name),
+                source,
+            } => write!(
+                f,
+                "I/O error while writing `{}` event of element <{}>: {}",
+                event, name, source
+            ),
+            Error::WriteEvent {
+                event,
+                name: None,
+                source,
+            } => write!(f, "I/O error while writing `{}` event: {}", event, source),
+            Error::TokenTooLarge { limit } => {
+                write!(f, "Token exceeds the maximum allowed size of {} bytes", limit)
+            }
+            Error::SizeLimitExceeded { limit } => {
+                write!(f, "Document exceeds the maximum allowed size of {} bytes", limit)
+            }
+            Error::IllFormedName { offset } => {
+                write!(f, "Ill-formed name at position {}", offset)
+            }
+            Error::InvalidCharacter { ch, offset } => write!(
+                f,
+                "Invalid character {:?} (U+{:04X}) at position {}",
+                ch, *ch as u32, offset
+            ),
+            Error::UnclosedElement { name } => write!(f, "Element <{}> is not closed", name),
         }
     }
 }
@@ -101,6 +255,7 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::Io(e) => Some(e),
+            Error::WriteEvent { source, .. } => Some(source),
             Error::Utf8(e) => Some(e),
             Error::InvalidAttr(e) => Some(e),
             Error::EscapeError(e) => Some(e),
@@ -139,7 +294,11 @@ pub mod serialize {
         KeyNotRead,
         /// Deserializer encounter a start tag with a specified name when it is
         /// not expecting. This happens when you try to deserialize a primitive
-        /// value (numbers, strings, booleans) from an XML element.
+        /// value (numbers, strings, booleans) from an XML element, that has
+        /// nested elements, or, when [`Deserializer::strict_types`] is enabled,
+        /// attributes.
+        ///
+        /// [`Deserializer::strict_types`]: crate::de::Deserializer::strict_types
         UnexpectedStart(Vec<u8>),
         /// Deserializer encounter an end tag with a specified name when it is
         /// not expecting. Usually that should not be possible, because XML reader
@@ -157,10 +316,67 @@ pub mod serialize {
         ///
         /// [`deserialize_struct`]: serde::de::Deserializer::deserialize_struct
         ExpectedStart,
+        /// Deserializer encounter an element with a specified name that has
+        /// attributes or content when a unit is deserialized and
+        /// [`Deserializer::strict_unit`] is enabled.
+        ///
+        /// [`Deserializer::strict_unit`]: crate::de::Deserializer::strict_unit
+        ExpectedEmpty(Vec<u8>),
+        /// Deserializer encounter a text content when it expects an XML element
+        /// for a struct and [`Deserializer::strict_types`] is enabled. Contains
+        /// the raw text.
+        ///
+        /// [`Deserializer::strict_types`]: crate::de::Deserializer::strict_types
+        UnexpectedText(Vec<u8>),
+        /// A required field of a struct or a struct variant with a specified
+        /// name is missing in the input
+        MissingField(String),
+        /// A field of a struct or a struct variant with a specified name is
+        /// present in the input more than once, for example, when it can be
+        /// deserialized both from an attribute and from an element.
+        /// See [`Deserializer::name_collision_policy`]
+        ///
+        /// [`Deserializer::name_collision_policy`]: crate::de::Deserializer::name_collision_policy
+        DuplicateField(String),
+        /// A sequence, a tuple or a tuple struct has a wrong number of elements
+        InvalidLength {
+            /// The number of elements that were found in the input
+            got: usize,
+            /// Description of the expected length
+            expected: String,
+        },
         /// Unsupported operation
         Unsupported(&'static str),
     }
 
+    impl DeError {
+        /// Returns `true` if the error is caused by the end of input, either
+        /// when the deserializer expected more events, or in the middle of
+        /// a markup. See [`Error::is_eof`]
+        pub fn is_eof(&self) -> bool {
+            match self {
+                DeError::UnexpectedEof => true,
+                DeError::InvalidXml(e) => e.is_eof(),
+                _ => false,
+            }
+        }
+
+        /// Returns `true` if the error is caused by an I/O error.
+        /// See [`Error::is_io`]
+        pub fn is_io(&self) -> bool {
+            self.io_error_kind().is_some()
+        }
+
+        /// Returns the kind of the underlying I/O error, if the error is caused
+        /// by an I/O error. See [`Error::io_error_kind`]
+        pub fn io_error_kind(&self) -> Option<ErrorKind> {
+            match self {
+                DeError::InvalidXml(e) => e.io_error_kind(),
+                _ => None,
+            }
+        }
+    }
+
     impl fmt::Display for DeError {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             match self {
@@ -182,6 +398,21 @@ pub mod serialize {
                 }
                 DeError::UnexpectedEof => write!(f, "Unexpected `Event::Eof`"),
                 DeError::ExpectedStart => write!(f, "Expecting `Event::Start`"),
+                DeError::ExpectedEmpty(e) => {
+                    f.write_str("Expecting an empty element `")?;
+                    write_byte_string(f, e)?;
+                    f.write_str("`")
+                }
+                DeError::UnexpectedText(t) => {
+                    f.write_str("Unexpected text `")?;
+                    write_byte_string(f, t)?;
+                    f.write_str("` when expecting an element")
+                }
+                DeError::MissingField(name) => write!(f, "missing field `{}`", name),
+                DeError::DuplicateField(name) => write!(f, "duplicate field `{}`", name),
+                DeError::InvalidLength { got, expected } => {
+                    write!(f, "invalid length {}, expected {}", got, expected)
+                }
                 DeError::Unsupported(s) => write!(f, "Unsupported operation {}", s),
             }
         }
@@ -202,6 +433,21 @@ pub mod serialize {
         fn custom<T: fmt::Display>(msg: T) -> Self {
             DeError::Custom(msg.to_string())
         }
+
+        fn missing_field(field: &'static str) -> Self {
+            DeError::MissingField(field.to_string())
+        }
+
+        fn duplicate_field(field: &'static str) -> Self {
+            DeError::DuplicateField(field.to_string())
+        }
+
+        fn invalid_length(len: usize, exp: &dyn serde::de::Expected) -> Self {
+            DeError::InvalidLength {
+                got: len,
+                expected: exp.to_string(),
+            }
+        }
     }
 
     impl serde::ser::Error for DeError {
@@ -242,3 +488,116 @@ pub mod serialize {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io;
+
+    #[test]
+    fn eof() {
+        let error = Error::UnexpectedEof("Element".to_string());
+        assert!(error.is_eof());
+        assert!(!error.is_io());
+        assert_eq!(error.io_error_kind(), None);
+
+        let error = Error::Io(io::Error::new(ErrorKind::UnexpectedEof, "eof"));
+        assert!(error.is_eof());
+        assert!(error.is_io());
+    }
+
+    #[test]
+    fn io() {
+        let error = Error::Io(io::Error::new(ErrorKind::WouldBlock, "blocked"));
+        assert!(!error.is_eof());
+        assert!(error.is_io());
+        assert_eq!(error.io_error_kind(), Some(ErrorKind::WouldBlock));
+
+        let error = Error::WriteEvent {
+            event: "Start",
+            name: None,
+            source: io::Error::new(ErrorKind::BrokenPipe, "closed"),
+        };
+        assert!(!error.is_eof());
+        assert_eq!(error.io_error_kind(), Some(ErrorKind::BrokenPipe));
+    }
+
+    #[test]
+    fn mismatch() {
+        let error = Error::EndEventMismatch {
+            expected: "a".to_string(),
+            found: "b".to_string(),
+        };
+        assert!(!error.is_eof());
+        assert!(!error.is_io());
+        assert_eq!(error.io_error_kind(), None);
+    }
+
+    #[cfg(feature = "serialize")]
+    mod serialize {
+        use super::*;
+        use crate::errors::serialize::DeError;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn eof() {
+            assert!(DeError::UnexpectedEof.is_eof());
+            assert!(!DeError::UnexpectedEof.is_io());
+
+            let error = DeError::InvalidXml(Error::UnexpectedEof("Element".to_string()));
+            assert!(error.is_eof());
+            assert!(!error.is_io());
+        }
+
+        #[test]
+        fn io() {
+            let error = DeError::InvalidXml(Error::Io(io::Error::new(
+                ErrorKind::PermissionDenied,
+                "denied",
+            )));
+            assert!(!error.is_eof());
+            assert!(error.is_io());
+            assert_eq!(error.io_error_kind(), Some(ErrorKind::PermissionDenied));
+        }
+
+        #[test]
+        fn mismatch() {
+            let error = DeError::InvalidXml(Error::EndEventMismatch {
+                expected: "a".to_string(),
+                found: "b".to_string(),
+            });
+            assert!(!error.is_eof());
+            assert!(!error.is_io());
+
+            let error = DeError::UnexpectedStart(b"a".to_vec());
+            assert!(!error.is_eof());
+            assert_eq!(error.io_error_kind(), None);
+        }
+
+        /// Structured errors are produced by serde and have the same messages
+        /// as the default serde errors
+        #[test]
+        fn structured() {
+            use serde::de::Error as _;
+
+            let error = DeError::missing_field("field");
+            assert!(matches!(&error, DeError::MissingField(f) if f == "field"));
+            assert_eq!(error.to_string(), "missing field `field`");
+
+            let error = DeError::duplicate_field("field");
+            assert!(matches!(&error, DeError::DuplicateField(f) if f == "field"));
+            assert_eq!(error.to_string(), "duplicate field `field`");
+
+            let error = DeError::invalid_length(2, &"a tuple of size 3");
+            assert!(matches!(
+                &error,
+                DeError::InvalidLength { got: 2, expected } if expected == "a tuple of size 3"
+            ));
+            assert_eq!(
+                error.to_string(),
+                "invalid length 2, expected a tuple of size 3"
+            );
+        }
+    }
+}
@@ -74,7 +74,21 @@ pub fn escape(raw: &[u8]) -> Cow<[u8]> {
         }
     }
 
-    _escape(raw, to_escape)
+    _escape(raw, to_escape, false)
+}
+
+/// Escapes a `&[u8]` and replaces all xml special characters (<, >, &, ', ") with their
+/// corresponding xml escaped value, like [`escape`], but uses numeric character
+/// references `&#39;` and `&#34;` for quotes instead of `&apos;` and `&quot;`.
+///
+/// The `&apos;` entity is not defined in HTML 4, so the result of this function
+/// is understood by both XML and HTML parsers.
+pub fn numeric_escape(raw: &[u8]) -> Cow<'_, [u8]> {
+    _escape(
+        raw,
+        |b| matches!(b, b'<' | b'>' | b'\'' | b'&' | b'"'),
+        true,
+    )
 }
 
 /// Should only be used for escaping text content. In xml text content, it is allowed
@@ -90,12 +104,19 @@ pub fn partial_escape(raw: &[u8]) -> Cow<[u8]> {
         }
     }
 
-    _escape(raw, to_escape)
+    _escape(raw, to_escape, false)
+}
+
+/// Escapes only double quotes (`"`) in a `&[u8]`. Used to make already escaped
+/// text content, where quotes are not escaped, suitable for an attribute value.
+pub(crate) fn escape_quotes(raw: &[u8]) -> Cow<'_, [u8]> {
+    _escape(raw, |b| b == b'"', false)
 }
 
 /// Escapes a `&[u8]` and replaces a subset of xml special characters (<, >, &, ', ") with their
-/// corresponding xml escaped value.
-fn _escape<F: Fn(u8) -> bool>(raw: &[u8], escape_chars: F) -> Cow<[u8]> {
+/// corresponding xml escaped value. If `numeric_quotes` is `true`, quotes are replaced
+/// with numeric character references instead of named entities.
+fn _escape<F: Fn(u8) -> bool>(raw: &[u8], escape_chars: F, numeric_quotes: bool) -> Cow<[u8]> {
     let mut escaped = None;
     let mut bytes = raw.iter();
     let mut pos = 0;
@@ -109,8 +130,10 @@ fn _escape<F: Fn(u8) -> bool>(raw: &[u8], escape_chars: F) -> Cow<[u8]> {
         match raw[new_pos] {
             b'<' => escaped.extend_from_slice(b"&lt;"),
             b'>' => escaped.extend_from_slice(b"&gt;"),
+            b'\'' if numeric_quotes => escaped.extend_from_slice(b"&#39;"),
             b'\'' => escaped.extend_from_slice(b"&apos;"),
             b'&' => escaped.extend_from_slice(b"&amp;"),
+            b'"' if numeric_quotes => escaped.extend_from_slice(b"&#34;"),
             b'"' => escaped.extend_from_slice(b"&quot;"),
             _ => unreachable!("Only '<', '>','\', '&' and '\"' are escaped"),
         }
@@ -1696,6 +1719,7 @@ fn parse_number(bytes: &[u8], range: Range<usize>) -> Result<char, EscapeError>
 }
 
 fn parse_hexadecimal(bytes: &[u8]) -> Result<u32, EscapeError> {
+    let bytes = skip_leading_zeros(bytes);
     // maximum code is 0x10FFFF => 6 characters
     if bytes.len() > 6 {
         return Err(EscapeError::TooLongHexadecimal);
@@ -1714,6 +1738,7 @@ fn parse_hexadecimal(bytes: &[u8]) -> Result<u32, EscapeError> {
 }
 
 fn parse_decimal(bytes: &[u8]) -> Result<u32, EscapeError> {
+    let bytes = skip_leading_zeros(bytes);
     // maximum code is 0x10FFFF = 1114111 => 7 characters
     if bytes.len() > 7 {
         return Err(EscapeError::TooLongDecimal);
@@ -1729,6 +1754,13 @@ fn parse_decimal(bytes: &[u8]) -> Result<u32, EscapeError> {
     Ok(code)
 }
 
+/// Leading zeros are allowed in character references, but do not count
+/// in the length limit of a number
+fn skip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let zeros = bytes.iter().take_while(|&&b| b == b'0').count();
+    &bytes[zeros..]
+}
+
 #[test]
 fn test_unescape() {
     assert_eq!(&*unescape(b"test").unwrap(), b"test");
@@ -1738,6 +1770,52 @@ fn test_unescape() {
     assert!(unescape(b"&foo;").is_err());
 }
 
+#[test]
+fn test_unescape_leading_zeros() {
+    assert_eq!(&*unescape(b"&#x0000000041;").unwrap(), b"A");
+    assert_eq!(&*unescape(b"&#0000000065;").unwrap(), b"A");
+    assert_eq!(
+        &*unescape(b"&#x0010FFFF;").unwrap(),
+        "\u{10FFFF}".as_bytes()
+    );
+    assert!(matches!(
+        unescape(b"&#x0000;"),
+        Err(EscapeError::EntityWithNull(_))
+    ));
+    assert!(matches!(
+        unescape(b"&#x0110000;"),
+        Err(EscapeError::InvalidCodepoint(0x110000))
+    ));
+    assert!(matches!(
+        unescape(b"&#x1000000;"),
+        Err(EscapeError::TooLongHexadecimal)
+    ));
+    assert!(matches!(
+        unescape(b"&#012345678;"),
+        Err(EscapeError::TooLongDecimal)
+    ));
+}
+
+#[test]
+fn test_unescape_surrogates() {
+    assert!(matches!(
+        unescape(b"&#xD800;"),
+        Err(EscapeError::InvalidCodepoint(0xD800))
+    ));
+    assert!(matches!(
+        unescape(b"&#57343;"),
+        Err(EscapeError::InvalidCodepoint(0xDFFF))
+    ));
+}
+
+#[test]
+fn test_escape_roundtrip() {
+    let raw = b"&amp; &#x41; <tag attr='\"'> & ; &";
+    assert_eq!(&*unescape(&escape(raw)).unwrap(), raw);
+    assert_eq!(&*unescape(&partial_escape(raw)).unwrap(), raw);
+    assert_eq!(&*unescape(&numeric_escape(raw)).unwrap(), raw);
+}
+
 #[test]
 fn test_unescape_with() {
     let custom_entities = vec![(b"foo".to_vec(), b"BAR".to_vec())]
@@ -1766,6 +1844,22 @@ fn test_escape() {
     );
 }
 
+#[test]
+fn test_numeric_escape() {
+    assert_eq!(&*numeric_escape(b"test"), b"test");
+    assert_eq!(&*numeric_escape(b"<test>"), b"&lt;test&gt;");
+    assert_eq!(&*numeric_escape(b"\"a\"b'c'"), b"&#34;a&#34;b&#39;c&#39;");
+    assert_eq!(
+        &*numeric_escape(b"prefix_\"a\"b&<>c"),
+        "prefix_&#34;a&#34;b&amp;&lt;&gt;c".as_bytes()
+    );
+    // All forms of quotes are unescaped
+    assert_eq!(
+        &*unescape(b"&#39;&apos;&#x27;&#34;&quot;&#x22;").unwrap(),
+        b"'''\"\"\""
+    );
+}
+
 #[test]
 fn test_partial_escape() {
     assert_eq!(&*partial_escape(b"test"), b"test");
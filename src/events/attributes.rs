@@ -107,27 +107,13 @@ impl<'a> Attribute<'a> {
     }
 
     /// The keys and values of `custom_entities`, if any, must be valid UTF-8.
-    #[cfg(feature = "encoding")]
-    fn do_unescape_and_decode_value<B: BufRead>(
-        &self,
-        reader: &Reader<B>,
-        custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
-    ) -> XmlResult<String> {
-        let decoded = reader.decode(&*self.value);
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
-        String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
-    }
-
-    #[cfg(not(feature = "encoding"))]
     fn do_unescape_and_decode_value<B: BufRead>(
         &self,
         reader: &Reader<B>,
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> XmlResult<String> {
         let decoded = reader.decode(&*self.value)?;
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -208,8 +194,7 @@ impl<'a> Attribute<'a> {
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> XmlResult<String> {
         let decoded = reader.decode_without_bom(&*self.value);
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -220,8 +205,7 @@ impl<'a> Attribute<'a> {
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> XmlResult<String> {
         let decoded = reader.decode_without_bom(&*self.value)?;
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 }
@@ -331,6 +315,61 @@ impl<'a> Attributes<'a> {
         self.state.check_duplicates = val;
         self
     }
+
+    /// Changes limits on the number of attributes and on the length of their
+    /// values. Limits are checked lazily, when the attributes are iterated.
+    ///
+    /// ([`AttrLimits::UNLIMITED`] by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::attributes::{AttrError, AttrLimits, Attributes};
+    ///
+    /// let mut iter = Attributes::new(br#"tag a="1" b="2" c="3""#, 3);
+    /// iter.with_limits(AttrLimits {
+    ///     max_attributes: 2,
+    ///     ..AttrLimits::UNLIMITED
+    /// });
+    ///
+    /// assert!(iter.next().unwrap().is_ok());
+    /// assert!(iter.next().unwrap().is_ok());
+    /// assert_eq!(iter.next(), Some(Err(AttrError::TooMany(16))));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn with_limits(&mut self, limits: AttrLimits) -> &mut Attributes<'a> {
+        self.state.limits = limits;
+        self
+    }
+
+    /// Converts this iterator into an iterator that yields only well-formed
+    /// attributes. Malformed attributes are skipped and parsing continues from
+    /// the [recovery position] of the error. Skipped errors are collected and
+    /// can be retrieved with [`SkipErrors::errors()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use std::borrow::Cow;
+    /// use fast_xml::events::attributes::{Attribute, Attributes, AttrError};
+    ///
+    /// let mut iter = Attributes::new(br#"tag first='1' broken second="2""#, 3).skip_errors();
+    ///
+    /// assert_eq!(iter.next(), Some(Attribute { key: b"first", value: Cow::Borrowed(b"1") }));
+    /// assert_eq!(iter.next(), Some(Attribute { key: b"second", value: Cow::Borrowed(b"2") }));
+    /// assert_eq!(iter.next(), None);
+    /// assert_eq!(iter.errors(), &[AttrError::ExpectedEq(21)]);
+    /// ```
+    ///
+    /// [recovery position]: AttrError
+    pub fn skip_errors(self) -> SkipErrors<'a> {
+        SkipErrors {
+            iter: self,
+            errors: Vec::new(),
+        }
+    }
 }
 
 impl<'a> Iterator for Attributes<'a> {
@@ -348,13 +387,51 @@ impl<'a> Iterator for Attributes<'a> {
 
 impl<'a> FusedIterator for Attributes<'a> {}
 
+/// Iterator over well-formed attributes, that skips malformed ones and
+/// remembers errors that were encountered.
+///
+/// Created by the [`Attributes::skip_errors()`] method.
+#[derive(Clone, Debug)]
+pub struct SkipErrors<'a> {
+    iter: Attributes<'a>,
+    /// Errors, skipped so far
+    errors: Vec<AttrError>,
+}
+
+impl<'a> SkipErrors<'a> {
+    /// Returns errors, that were skipped so far, in the order of their appearance
+    pub fn errors(&self) -> &[AttrError] {
+        &self.errors
+    }
+
+    /// Consumes the iterator and returns errors, that were skipped so far
+    pub fn into_errors(self) -> Vec<AttrError> {
+        self.errors
+    }
+}
+
+impl<'a> Iterator for SkipErrors<'a> {
+    type Item = Attribute<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next()? {
+                Ok(attr) => return Some(attr),
+                Err(e) => self.errors.push(e),
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for SkipErrors<'a> {}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 /// Errors that can be raised during parsing attributes.
 ///
 /// Recovery position in examples shows the position from which parsing of the
 /// next attribute will be attempted.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AttrError {
     /// Attribute key was not followed by `=`, position relative to the start of
     /// the owning tag is provided.
@@ -437,6 +514,30 @@ pub enum AttrError {
     /// This error is returned only when [`Attributes::with_checks()`] is set
     /// to `true` (that is default behavior).
     Duplicated(usize, usize),
+    /// An element has more attributes than allowed by
+    /// [`AttrLimits::max_attributes`], position relative to the start of
+    /// the owning tag is provided. That position is the start of the first
+    /// attribute that exceeds the limit. Iteration is stopped after this error.
+    ///
+    /// Example of input that raises this error when only two attributes are allowed:
+    ///
+    /// ```xml
+    /// <tag a='1' b='2' c='3'/>
+    /// <!--             ^~~ error position (16) -->
+    /// ```
+    TooMany(usize),
+    /// An attribute value is longer than allowed by [`AttrLimits::max_value_len`],
+    /// position relative to the start of the owning tag is provided. That position
+    /// is the start of the value.
+    ///
+    /// Example of input that raises this error when values are limited to 3 bytes:
+    ///
+    /// ```xml
+    /// <tag key='value' another='1'/>
+    /// <!--      ^     ^~~ recovery position (15) -->
+    /// <!--      '~~ error position (9) -->
+    /// ```
+    TooLong(usize),
 }
 
 impl Display for AttrError {
@@ -467,6 +568,16 @@ impl Display for AttrError {
                 r#"position {}: duplicated attribute, previous declaration at position {}"#,
                 pos1, pos2
             ),
+            Self::TooMany(pos) => write!(
+                f,
+                r#"position {}: number of attributes exceeds the limit"#,
+                pos
+            ),
+            Self::TooLong(pos) => write!(
+                f,
+                r#"position {}: attribute value length exceeds the limit"#,
+                pos
+            ),
         }
     }
 }
@@ -591,6 +702,57 @@ impl<T> From<Attr<T>> for (T, Option<T>) {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Limits on the attributes of one element, that protect from elements with
+/// a huge number of attributes or with huge attribute values.
+///
+/// Limits are checked by the [`Attributes`] iterator, when attributes are
+/// iterated. They can be set by [`Attributes::with_limits`] or, for all
+/// elements read by a [`Reader`], by [`Reader::attribute_limits`].
+///
+/// # Example
+///
+/// ```
+/// use fast_xml::events::attributes::AttrLimits;
+///
+/// // Restrict only the number of attributes
+/// let limits = AttrLimits {
+///     max_attributes: 16,
+///     ..AttrLimits::UNLIMITED
+/// };
+/// # assert_eq!(limits.max_value_len, usize::MAX);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AttrLimits {
+    /// Maximum number of attributes in an element. An attribute that exceeds
+    /// that limit is reported as [`AttrError::TooMany`]
+    pub max_attributes: usize,
+    /// Maximum length of an attribute value in bytes, before unescaping.
+    /// A value that exceeds that limit is reported as [`AttrError::TooLong`]
+    pub max_value_len: usize,
+}
+
+impl AttrLimits {
+    /// No limits. That is the default
+    pub const UNLIMITED: AttrLimits = AttrLimits {
+        max_attributes: usize::MAX,
+        max_value_len: usize::MAX,
+    };
+
+    /// Limits, that are enough for the most documents: 256 attributes in
+    /// an element and 64 KiB in a value
+    pub const STRICT: AttrLimits = AttrLimits {
+        max_attributes: 256,
+        max_value_len: 64 * 1024,
+    };
+}
+
+impl Default for AttrLimits {
+    #[inline]
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
 type AttrResult = Result<Attr<Range<usize>>, AttrError>;
 
 #[derive(Clone, Copy, Debug)]
@@ -624,6 +786,10 @@ pub(crate) struct IterState {
     /// names. We store a ranges instead of slices to able to report a previous
     /// attribute position
     keys: Vec<Range<usize>>,
+    /// Limits on the number of attributes and on the length of values
+    pub limits: AttrLimits,
+    /// Number of attributes returned so far, used to check `limits`
+    count: usize,
 }
 
 impl IterState {
@@ -633,6 +799,8 @@ impl IterState {
             html,
             check_duplicates: true,
             keys: Vec::new(),
+            limits: AttrLimits::UNLIMITED,
+            count: 0,
         }
     }
 
@@ -671,15 +839,15 @@ impl IterState {
         let mut iter = (offset..).zip(slice[offset..].iter());
 
         // Skip all up to the quote and get the quote type
-        let quote = match iter.find(|(_, &b)| !is_whitespace(b)) {
+        let (start, quote) = match iter.find(|(_, &b)| !is_whitespace(b)) {
             // Input: `    key  =  "`
             //                  |  ^
             //             offset
-            Some((_, b'"')) => b'"',
+            Some((s, b'"')) => (s + 1, b'"'),
             // Input: `    key  =  '`
             //                  |  ^
             //             offset
-            Some((_, b'\'')) => b'\'',
+            Some((s, b'\'')) => (s + 1, b'\''),
 
             // Input: `    key  =  x`
             //                  |  ^
@@ -691,20 +859,14 @@ impl IterState {
             None => return None,
         };
 
-        match iter.find(|(_, &b)| b == quote) {
-            // Input: `    key  =  "   "`
-            //                         ^
-            Some((e, b'"')) => Some(e),
-            // Input: `    key  =  '   '`
-            //                         ^
-            Some((e, _)) => Some(e),
-
-            // Input: `    key  =  "   `
-            // Input: `    key  =  '   `
-            //                         ^
-            // Closing quote not found
-            None => None,
-        }
+        // Input: `    key  =  "   "`
+        // Input: `    key  =  '   '`
+        //                         ^
+        // If closing quote not found, returns `None`:
+        // Input: `    key  =  "   `
+        // Input: `    key  =  '   `
+        //                         ^
+        memchr::memchr(quote, &slice[start..]).map(|e| start + e)
     }
 
     #[inline]
@@ -754,7 +916,28 @@ impl IterState {
         Some(Ok(Attr::SingleQ(key, value)))
     }
 
+    /// Returns the next attribute and checks it against the limits
     pub fn next(&mut self, slice: &[u8]) -> Option<AttrResult> {
+        let attr = match self.next_attr(slice)? {
+            Ok(attr) => attr,
+            Err(e) => return Some(Err(e)),
+        };
+        self.count += 1;
+        let (key, value) = attr.clone().into();
+        if self.count > self.limits.max_attributes {
+            // Because all following attributes also exceeds the limit, stop iteration
+            self.state = State::Done;
+            return Some(Err(AttrError::TooMany(key.start)));
+        }
+        match value {
+            Some(value) if value.len() > self.limits.max_value_len => {
+                Some(Err(AttrError::TooLong(value.start)))
+            }
+            _ => Some(Ok(attr)),
+        }
+    }
+
+    fn next_attr(&mut self, slice: &[u8]) -> Option<AttrResult> {
         let mut iter = match self.recover(slice) {
             Some(offset) => (offset..).zip(slice[offset..].iter()),
             None => return None,
@@ -874,13 +1057,14 @@ impl IterState {
             }
         };
 
-        match iter.find(|(_, &b)| b == quote) {
+        // Values usually are much longer than keys, so use a fast search here
+        match memchr::memchr(quote, &slice[start_value..]).map(|e| start_value + e) {
             // Input: `    key  =  "   "`
             //                         ^
-            Some((e, b'"')) => self.double_q(key, start_value..e),
+            Some(e) if quote == b'"' => self.double_q(key, start_value..e),
             // Input: `    key  =  '   '`
             //                         ^
-            Some((e, _)) => self.single_q(key, start_value..e),
+            Some(e) => self.single_q(key, start_value..e),
 
             // Input: `    key  =  "   `
             // Input: `    key  =  '   `
@@ -1579,6 +1763,142 @@ mod xml {
         assert_eq!(iter.next(), None);
         assert_eq!(iter.next(), None);
     }
+
+    /// Checks, that malformed attributes are skipped by the [`SkipErrors`] iterator
+    mod skip_errors {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn malformed_in_the_middle() {
+            let mut iter =
+                Attributes::new(br#"tag first='1' second = value third="3""#, 3).skip_errors();
+            //                      0             ^ = 14  ^ = 23
+
+            assert_eq!(
+                iter.next(),
+                Some(Attribute {
+                    key: b"first",
+                    value: Cow::Borrowed(b"1"),
+                })
+            );
+            assert_eq!(iter.errors(), &[]);
+            assert_eq!(
+                iter.next(),
+                Some(Attribute {
+                    key: b"third",
+                    value: Cow::Borrowed(b"3"),
+                })
+            );
+            assert_eq!(iter.errors(), &[AttrError::UnquotedValue(23)]);
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.into_errors(), vec![AttrError::UnquotedValue(23)]);
+        }
+
+        #[test]
+        fn duplicated() {
+            let mut iter =
+                Attributes::new(br#"tag key='value' key="dup" another=''"#, 3).skip_errors();
+            //                      0   ^ = 4       ^ = 16
+
+            let attrs: Vec<_> = iter.by_ref().collect();
+            assert_eq!(
+                attrs,
+                vec![
+                    Attribute {
+                        key: b"key",
+                        value: Cow::Borrowed(b"value"),
+                    },
+                    Attribute {
+                        key: b"another",
+                        value: Cow::Borrowed(b""),
+                    },
+                ]
+            );
+            assert_eq!(iter.errors(), &[AttrError::Duplicated(16, 4)]);
+        }
+    }
+
+    mod limits {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn too_many() {
+            let mut iter = Attributes::new(br#"tag a='1' b='2' c='3' d='4'"#, 3);
+            //                                0               ^ = 16
+            iter.with_limits(AttrLimits {
+                max_attributes: 2,
+                ..AttrLimits::UNLIMITED
+            });
+
+            assert_eq!(
+                iter.next(),
+                Some(Ok(Attribute {
+                    key: b"a",
+                    value: Cow::Borrowed(b"1"),
+                }))
+            );
+            assert_eq!(
+                iter.next(),
+                Some(Ok(Attribute {
+                    key: b"b",
+                    value: Cow::Borrowed(b"2"),
+                }))
+            );
+            assert_eq!(iter.next(), Some(Err(AttrError::TooMany(16))));
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next(), None);
+        }
+
+        #[test]
+        fn too_long() {
+            let mut iter = Attributes::new(br#"tag key='value' short='abc'"#, 3);
+            //                                0        ^ = 9
+            iter.with_limits(AttrLimits {
+                max_value_len: 3,
+                ..AttrLimits::UNLIMITED
+            });
+
+            assert_eq!(iter.next(), Some(Err(AttrError::TooLong(9))));
+            assert_eq!(
+                iter.next(),
+                Some(Ok(Attribute {
+                    key: b"short",
+                    value: Cow::Borrowed(b"abc"),
+                }))
+            );
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next(), None);
+        }
+
+        /// Elements within the limits are not affected
+        #[test]
+        fn within() {
+            let mut iter = Attributes::new(br#"tag a='1' b='22'"#, 3);
+            iter.with_limits(AttrLimits {
+                max_attributes: 2,
+                max_value_len: 2,
+            });
+
+            assert_eq!(
+                iter.next(),
+                Some(Ok(Attribute {
+                    key: b"a",
+                    value: Cow::Borrowed(b"1"),
+                }))
+            );
+            assert_eq!(
+                iter.next(),
+                Some(Ok(Attribute {
+                    key: b"b",
+                    value: Cow::Borrowed(b"22"),
+                }))
+            );
+            assert_eq!(iter.next(), None);
+        }
+    }
 }
 
 /// Checks, how parsing of HTML-style attributes works. Each attribute can be
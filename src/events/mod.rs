@@ -38,12 +38,21 @@ pub mod attributes;
 
 #[cfg(feature = "encoding_rs")]
 use encoding_rs::Encoding;
-use std::{borrow::Cow, collections::HashMap, io::BufRead, ops::Deref, str::from_utf8};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    convert::TryFrom,
+    io::BufRead,
+    ops::Deref,
+    str::{from_utf8, FromStr},
+};
 
 use crate::escape::{do_unescape, escape, partial_escape};
+use crate::escapei::escape_quotes;
+use crate::name::QName;
 use crate::utils::write_cow_string;
-use crate::{errors::Error, errors::Result, reader::Reader};
-use attributes::{Attribute, Attributes};
+use crate::{errors::Error, errors::Result, reader::is_whitespace, reader::Reader};
+use attributes::{Attr, AttrLimits, Attribute, Attributes, IterState};
 
 #[cfg(feature = "serialize")]
 use crate::escape::EscapeError;
@@ -61,12 +70,15 @@ use memchr;
 /// [`local_name`]: #method.local_name
 /// [`unescaped`]: #method.unescaped
 /// [`attributes`]: #method.attributes
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq)]
 pub struct BytesStart<'a> {
     /// content of the element, before any utf8 conversion
     buf: Cow<'a, [u8]>,
     /// end of the element name, the name starts at that the start of `buf`
     name_len: usize,
+    /// Limits, applied to the iterators over attributes. Set by the reader,
+    /// see [`Reader::attribute_limits`]
+    limits: AttrLimits,
 }
 
 impl<'a> BytesStart<'a> {
@@ -80,6 +92,7 @@ impl<'a> BytesStart<'a> {
         BytesStart {
             buf: Cow::Borrowed(content),
             name_len,
+            limits: AttrLimits::UNLIMITED,
         }
     }
 
@@ -101,6 +114,7 @@ impl<'a> BytesStart<'a> {
         BytesStart {
             buf: Cow::Owned(content.into()),
             name_len,
+            limits: AttrLimits::UNLIMITED,
         }
     }
 
@@ -113,17 +127,70 @@ impl<'a> BytesStart<'a> {
         BytesStart {
             name_len: content.len(),
             buf: Cow::Owned(content),
+            limits: AttrLimits::UNLIMITED,
         }
     }
 
+    /// Creates a new `BytesStart` from the given content (name + attributes).
+    /// A string version of [`Self::borrowed`].
+    ///
+    /// # Warning
+    ///
+    /// `&content[..name_len]` is not checked to be a valid name
+    #[inline]
+    pub fn from_content(content: &'a str, name_len: usize) -> Self {
+        Self::borrowed(content.as_bytes(), name_len)
+    }
+
+    /// Creates a new `BytesStart` from the content of a start tag without
+    /// angle brackets, such as `item id="5"`. The name ends at the first
+    /// whitespace character, the rest is treated as attributes.
+    ///
+    /// Unlike other constructors, this one checks that the name is not empty
+    /// and that attributes are well-formed, so it is convenient for building
+    /// events by hand, for example, in tests or for the [`Writer`]. Returns
+    /// [`Error::EmptyElementName`] if the content does not start with a name
+    /// and [`Error::InvalidAttr`] if attributes are malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::BytesStart;
+    ///
+    /// let start = BytesStart::parse(r#"item id="5" class='new'"#).unwrap();
+    /// assert_eq!(start.name(), b"item");
+    /// assert_eq!(start.try_get_attribute("id").unwrap().unwrap().value, &b"5"[..]);
+    /// ```
+    ///
+    /// [`Writer`]: crate::Writer
+    pub fn parse(content: &'a str) -> Result<Self> {
+        Self::parse_bytes(content.as_bytes())
+    }
+
+    fn parse_bytes(content: &'a [u8]) -> Result<Self> {
+        let name_len = content
+            .iter()
+            .position(|&b| is_whitespace(b))
+            .unwrap_or(content.len());
+        if name_len == 0 {
+            return Err(Error::EmptyElementName);
+        }
+        let start = Self::borrowed(content, name_len);
+        for attr in start.attributes() {
+            attr?;
+        }
+        Ok(start)
+    }
+
     /// Converts the event into an owned event.
     pub fn into_owned(self) -> BytesStart<'static> {
-        Self::owned(self.buf.into_owned(), self.name_len)
+        Self::owned(self.buf.into_owned(), self.name_len).with_attribute_limits(self.limits)
     }
 
     /// Converts the event into an owned event without taking ownership of Event
     pub fn to_owned(&self) -> BytesStart<'static> {
-        Self::owned(self.buf.to_owned(), self.name_len)
+        Self::owned(self.buf.to_owned(), self.name_len).with_attribute_limits(self.limits)
     }
 
     /// Converts the event into a borrowed event. Most useful when paired with [`to_end`].
@@ -151,7 +218,32 @@ impl<'a> BytesStart<'a> {
     ///
     /// [`to_end`]: #method.to_end
     pub fn to_borrowed(&self) -> BytesStart {
-        BytesStart::borrowed(&self.buf, self.name_len)
+        BytesStart::borrowed(&self.buf, self.name_len).with_attribute_limits(self.limits)
+    }
+
+    /// Sets limits, that are applied to the iterators over attributes
+    #[inline]
+    pub(crate) fn with_attribute_limits(mut self, limits: AttrLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns limits, that are applied to the iterators over attributes
+    #[cfg(feature = "serialize")]
+    #[inline]
+    pub(crate) fn attribute_limits(&self) -> AttrLimits {
+        self.limits
+    }
+
+    /// Returns the same as [`Self::attributes_raw`], if the content of the
+    /// element is borrowed, so the returned slice can outlive the event
+    #[cfg(feature = "serialize")]
+    #[inline]
+    pub(crate) fn borrowed_attributes_raw(&self) -> Option<&'a [u8]> {
+        match self.buf {
+            Cow::Borrowed(buf) => Some(&buf[self.name_len..]),
+            Cow::Owned(_) => None,
+        }
     }
 
     /// Creates new paired close tag
@@ -174,6 +266,21 @@ impl<'a> BytesStart<'a> {
         memchr::memchr(b':', name).map_or(name, |i| &name[i + 1..])
     }
 
+    /// Gets the undecoded raw tag name as a [`QName`], which can be compared
+    /// with string and byte string literals.
+    ///
+    /// ```
+    /// use fast_xml::events::BytesStart;
+    ///
+    /// let start = BytesStart::borrowed(b"item id='1'", 4);
+    /// assert!(start.qname() == "item");
+    /// assert!(start.qname() == b"item");
+    /// ```
+    #[inline]
+    pub fn qname(&self) -> QName<'_> {
+        QName(self.name())
+    }
+
     /// Gets the unescaped tag name.
     ///
     /// XML escape sequences like "`&lt;`" will be replaced by their unescaped characters like
@@ -250,20 +357,6 @@ impl<'a> BytesStart<'a> {
         self.do_unescape_and_decode_with_custom_entities(reader, Some(custom_entities))
     }
 
-    #[cfg(feature = "encoding")]
-    #[inline]
-    fn do_unescape_and_decode_with_custom_entities<B: BufRead>(
-        &self,
-        reader: &Reader<B>,
-        custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
-    ) -> Result<String> {
-        let decoded = reader.decode(&*self);
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
-        String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
-    }
-
-    #[cfg(not(feature = "encoding"))]
     #[inline]
     fn do_unescape_and_decode_with_custom_entities<B: BufRead>(
         &self,
@@ -271,8 +364,7 @@ impl<'a> BytesStart<'a> {
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> Result<String> {
         let decoded = reader.decode(&*self)?;
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -318,17 +410,87 @@ impl<'a> BytesStart<'a> {
     }
 
     /// Adds an attribute to this element.
+    ///
+    /// The value is written as is, so it should be already escaped. Value is
+    /// enclosed in double quotes, unless it contains a double quote character,
+    /// in which case single quotes are used. If the value contains both quote
+    /// characters, it is enclosed in double quotes and double quotes inside
+    /// it are escaped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fast_xml::events::BytesStart;
+    ///
+    /// let mut start = BytesStart::borrowed_name(b"tag");
+    /// // Values from byte slices are not escaped
+    /// start.push_attribute((&b"a"[..], &b"1"[..]));
+    /// start.push_attribute((&b"b"[..], &br#"say "hi""#[..]));
+    /// start.push_attribute((&b"c"[..], &br#"it's "hi""#[..]));
+    /// assert_eq!(
+    ///     start.attributes_raw(),
+    ///     br#" a="1" b='say "hi"' c="it's &quot;hi&quot;""#
+    /// );
+    /// ```
     pub fn push_attribute<'b, A>(&mut self, attr: A)
     where
         A: Into<Attribute<'b>>,
     {
         let a = attr.into();
+        let (quote, value) = if memchr::memchr(b'"', &a.value).is_none() {
+            (b'"', Cow::Borrowed(&*a.value))
+        } else if memchr::memchr(b'\'', &a.value).is_none() {
+            (b'\'', Cow::Borrowed(&*a.value))
+        } else {
+            (b'"', escape_quotes(&a.value))
+        };
         let bytes = self.buf.to_mut();
         bytes.push(b' ');
         bytes.extend_from_slice(a.key);
-        bytes.extend_from_slice(b"=\"");
-        bytes.extend_from_slice(&*a.value);
-        bytes.push(b'"');
+        bytes.push(b'=');
+        bytes.push(quote);
+        bytes.extend_from_slice(&value);
+        bytes.push(quote);
+    }
+
+    /// Removes the first attribute with the specified name from this element.
+    ///
+    /// Other attributes and whitespaces between them are kept as is. Returns
+    /// `true` if attribute was found and removed, and `false` if element does
+    /// not have such attribute.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fast_xml::events::BytesStart;
+    ///
+    /// let mut start = BytesStart::borrowed(br#"tag a="1" b="2"  c="3""#, 3);
+    /// assert_eq!(start.remove_attribute("b").unwrap(), true);
+    /// assert_eq!(start.remove_attribute("b").unwrap(), false);
+    /// assert_eq!(start.attributes_raw(), br#" a="1"  c="3""#);
+    /// ```
+    pub fn remove_attribute<N: AsRef<[u8]>>(&mut self, attr_name: N) -> Result<bool> {
+        let name = attr_name.as_ref();
+        let mut iter = IterState::new(self.name_len, false);
+        // End of the previous attribute or of the tag name. Whitespaces before
+        // the removed attribute are removed together with it
+        let mut prev_end = self.name_len;
+        while let Some(attr) = iter.next(&self.buf) {
+            let (key, end) = match attr? {
+                Attr::DoubleQ(key, value) | Attr::SingleQ(key, value) => (key, value.end + 1),
+                Attr::Unquoted(key, value) => (key, value.end),
+                Attr::Empty(key) => {
+                    let end = key.end;
+                    (key, end)
+                }
+            };
+            if &self.buf[key] == name {
+                self.buf.to_mut().drain(prev_end..end);
+                return Ok(true);
+            }
+            prev_end = end;
+        }
+        Ok(false)
     }
 
     /// Remove all attributes from the ByteStart
@@ -337,14 +499,22 @@ impl<'a> BytesStart<'a> {
         self
     }
 
-    /// Returns an iterator over the attributes of this tag.
+    /// Returns an iterator over the attributes of this tag. Attributes are parsed
+    /// lazily, as the iterator advances.
+    ///
+    /// If the tag was read by a [`Reader`], the iterator checks the limits
+    /// set by [`Reader::attribute_limits`].
     pub fn attributes(&self) -> Attributes {
-        Attributes::new(&self.buf, self.name_len)
+        let mut iter = Attributes::new(&self.buf, self.name_len);
+        iter.with_limits(self.limits);
+        iter
     }
 
     /// Returns an iterator over the HTML-like attributes of this tag (no mandatory quotes or `=`).
     pub fn html_attributes(&self) -> Attributes {
-        Attributes::html(self, self.name_len)
+        let mut iter = Attributes::html(self, self.name_len);
+        iter.with_limits(self.limits);
+        iter
     }
 
     /// Gets the undecoded raw string with the attributes of this tag as a `&[u8]`,
@@ -367,6 +537,82 @@ impl<'a> BytesStart<'a> {
         }
         Ok(None)
     }
+
+    /// Calls `f` for each attribute of this tag, stopping at the first malformed
+    /// attribute. The error for it is returned.
+    ///
+    /// Attributes are parsed one by one, so the memory used does not depend on
+    /// the number of attributes. For that reason attributes are not checked for
+    /// duplicates (because that requires remembering names of all seen attributes).
+    /// Use [`attributes()`] if you need that check.
+    ///
+    /// If the tag was read by a [`Reader`], the limits set by [`Reader::attribute_limits`]
+    /// are checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::BytesStart;
+    ///
+    /// let start = BytesStart::borrowed(br#"tag a="1" b='2' c="3""#, 3);
+    /// let mut sum = 0;
+    /// start.for_each_attribute(|attr| {
+    ///     sum += std::str::from_utf8(&attr.value).unwrap().parse::<u32>().unwrap();
+    /// }).unwrap();
+    ///
+    /// assert_eq!(sum, 6);
+    /// ```
+    ///
+    /// [`attributes()`]: Self::attributes
+    pub fn for_each_attribute<'b, F>(&'b self, mut f: F) -> Result<()>
+    where
+        F: FnMut(Attribute<'b>),
+    {
+        let mut iter = self.attributes();
+        iter.with_checks(false);
+        for a in iter {
+            f(a?);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for BytesStart<'a> {
+    type Error = Error;
+
+    /// Creates a `BytesStart` from the content of a start tag. See [`BytesStart::parse`]
+    #[inline]
+    fn try_from(content: &'a str) -> Result<Self> {
+        Self::parse(content)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for BytesStart<'a> {
+    type Error = Error;
+
+    /// Creates a `BytesStart` from the content of a start tag. See [`BytesStart::parse`]
+    #[inline]
+    fn try_from(content: &'a [u8]) -> Result<Self> {
+        Self::parse_bytes(content)
+    }
+}
+
+impl FromStr for BytesStart<'static> {
+    type Err = Error;
+
+    /// Creates an owned `BytesStart` from the content of a start tag.
+    /// See [`BytesStart::parse`]
+    fn from_str(content: &str) -> Result<Self> {
+        Ok(BytesStart::parse(content)?.into_owned())
+    }
+}
+
+/// Limits for attributes are not a part of the content, so they are not compared
+impl<'a> PartialEq for BytesStart<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.buf == other.buf && self.name_len == other.name_len
+    }
 }
 
 impl<'a> std::fmt::Debug for BytesStart<'a> {
@@ -658,6 +904,13 @@ impl<'a> BytesEnd<'a> {
             self.name()
         }
     }
+
+    /// Gets `BytesEnd` event name as a [`QName`], which can be compared
+    /// with string and byte string literals
+    #[inline]
+    pub fn qname(&self) -> QName<'_> {
+        QName(self.name())
+    }
 }
 
 impl<'a> std::fmt::Debug for BytesEnd<'a> {
@@ -672,10 +925,56 @@ impl<'a> std::fmt::Debug for BytesEnd<'a> {
 
 /// Data from various events (most notably, `Event::Text`) that stored in XML
 /// in escaped form. Internally data is stored in escaped form
-#[derive(Clone, Eq, PartialEq)]
+///
+/// # Raw and unescaped content
+///
+/// A `BytesText` read by a [`Reader`] holds exactly the bytes of the document
+/// between markup, without any changes: entity and character references, such
+/// as `&amp;`, are kept as is. Those raw bytes are available via [`escaped()`],
+/// [`into_inner()`] and [`Deref`], which is useful when the original
+/// representation matters, for example, to verify signatures over XML.
+/// Use [`unescape()`] or [`unescaped()`] to get the content with all references
+/// replaced.
+///
+/// The only exception is whitespaces, removed by [`Reader::trim_text`] and
+/// [`Reader::trim_text_end`]. They are not stored in the event, so if you need
+/// them, either disable trimming or use [`Reader::read_event_with_span`], which
+/// reports the position of the text in the input including skipped whitespaces.
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use fast_xml::events::Event;
+/// use fast_xml::Reader;
+///
+/// let mut reader = Reader::from_str("<tag>fish &amp; chips</tag>");
+/// reader.trim_text(true);
+/// let mut buf = Vec::new();
+///
+/// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::Start(_)));
+/// match reader.read_event(&mut buf).unwrap() {
+///     Event::Text(e) => {
+///         assert_eq!(e.escaped(), b"fish &amp; chips");
+///         assert_eq!(e.unescape().unwrap(), "fish & chips");
+///     }
+///     e => panic!("Expected Text event, got {:?}", e),
+/// }
+/// ```
+///
+/// [`Reader`]: crate::Reader
+/// [`escaped()`]: Self::escaped
+/// [`into_inner()`]: Self::into_inner
+/// [`unescape()`]: Self::unescape
+/// [`unescaped()`]: Self::unescaped
+/// [`Reader::trim_text`]: crate::Reader::trim_text
+/// [`Reader::trim_text_end`]: crate::Reader::trim_text_end
+/// [`Reader::read_event_with_span`]: crate::Reader::read_event_with_span
+#[derive(Clone)]
 pub struct BytesText<'a> {
     // Invariant: The content is always escaped.
     content: Cow<'a, [u8]>,
+    /// `true` if the reader marked this text as a whitespace-only run.
+    /// See [`Reader::emit_whitespace`](crate::Reader::emit_whitespace)
+    whitespace: bool,
 }
 
 impl<'a> BytesText<'a> {
@@ -684,6 +983,7 @@ impl<'a> BytesText<'a> {
     pub fn from_escaped<C: Into<Cow<'a, [u8]>>>(content: C) -> Self {
         Self {
             content: content.into(),
+            whitespace: false,
         }
     }
 
@@ -693,6 +993,7 @@ impl<'a> BytesText<'a> {
     pub fn from_plain(content: &'a [u8]) -> Self {
         Self {
             content: escape(content),
+            whitespace: false,
         }
     }
 
@@ -718,10 +1019,35 @@ impl<'a> BytesText<'a> {
     pub fn into_owned(self) -> BytesText<'static> {
         BytesText {
             content: self.content.into_owned().into(),
+            whitespace: self.whitespace,
+        }
+    }
+
+    /// Returns `true` if this text was read as a run of whitespace characters
+    /// only, for example, indentation between elements.
+    ///
+    /// Texts are marked only by a [`Reader`] with enabled [`emit_whitespace`]
+    /// option, so this method always returns `false` for texts created manually
+    /// or read without that option.
+    ///
+    /// [`Reader`]: crate::Reader
+    /// [`emit_whitespace`]: crate::Reader::emit_whitespace
+    #[inline]
+    pub fn is_whitespace(&self) -> bool {
+        self.whitespace
+    }
+
+    /// Marks this text as a whitespace-only run
+    #[inline]
+    pub(crate) fn into_whitespace(self) -> Self {
+        Self {
+            whitespace: true,
+            ..self
         }
     }
 
     /// Extracts the inner `Cow` from the `BytesText` event container.
+    /// The content is in escaped form, see [raw and unescaped content](#raw-and-unescaped-content).
     #[inline]
     pub fn into_inner(self) -> Cow<'a, [u8]> {
         self.content
@@ -730,7 +1056,7 @@ impl<'a> BytesText<'a> {
     /// Returns unescaped version of the text content, that can be written
     /// as CDATA in XML
     #[cfg(feature = "serialize")]
-    pub(crate) fn unescape(self) -> std::result::Result<BytesCData<'a>, EscapeError> {
+    pub(crate) fn unescape_into_cdata(self) -> std::result::Result<BytesCData<'a>, EscapeError> {
         //TODO: need to think about better API instead of dozens similar functions
         // Maybe use builder pattern. After that expose function as public API
         //FIXME: need to take into account entities defined in the document
@@ -740,6 +1066,35 @@ impl<'a> BytesText<'a> {
         }))
     }
 
+    /// Returns the unescaped text content as an UTF-8 string.
+    ///
+    /// If content does not contain any entity or character references, it is
+    /// returned borrowed without any allocations. A new string is allocated only
+    /// if some references was replaced.
+    ///
+    /// Content is expected to be in the UTF-8 encoding, otherwise [`Error::Utf8`]
+    /// is returned. If you read a document in other encoding, decode it with
+    /// [`Reader::decode()`] and use
+    /// [`unescape()`](../escape/fn.unescape.html) function instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use fast_xml::events::BytesText;
+    ///
+    /// let text = BytesText::from_escaped_str("plain text");
+    /// assert_eq!(text.unescape().unwrap(), Cow::Borrowed("plain text"));
+    ///
+    /// let text = BytesText::from_escaped_str("&lt;text&gt;");
+    /// assert_eq!(text.unescape().unwrap(), Cow::<str>::Owned("<text>".into()));
+    /// ```
+    ///
+    /// [`Reader::decode()`]: ../reader/struct.Reader.html#method.decode
+    pub fn unescape(&self) -> Result<Cow<'_, str>> {
+        unescape_str(&self.content)
+    }
+
     /// gets escaped content
     ///
     /// Searches for '&' into content and try to escape the coded character if possible
@@ -852,8 +1207,7 @@ impl<'a> BytesText<'a> {
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> Result<String> {
         let decoded = reader.decode_without_bom(&*self);
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -864,8 +1218,7 @@ impl<'a> BytesText<'a> {
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> Result<String> {
         let decoded = reader.decode_without_bom(&*self)?;
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -897,34 +1250,102 @@ impl<'a> BytesText<'a> {
         self.do_unescape_and_decode_with_custom_entities(reader, Some(custom_entities))
     }
 
-    #[cfg(feature = "encoding")]
-    fn do_unescape_and_decode_with_custom_entities<B: BufRead>(
-        &self,
-        reader: &Reader<B>,
-        custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
-    ) -> Result<String> {
-        let decoded = reader.decode(&*self);
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
-        String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
-    }
-
-    #[cfg(not(feature = "encoding"))]
     fn do_unescape_and_decode_with_custom_entities<B: BufRead>(
         &self,
         reader: &Reader<B>,
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
     ) -> Result<String> {
         let decoded = reader.decode(&*self)?;
-        let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+        let unescaped = reader.unescape(decoded.as_bytes(), custom_entities)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
-    /// Gets escaped content.
+    /// Gets escaped content, as it is written in the document, without replacing
+    /// entity and character references. See [raw and unescaped content](#raw-and-unescaped-content).
     pub fn escaped(&self) -> &[u8] {
         self.content.as_ref()
     }
+
+    /// Returns a new `BytesText` with leading and trailing XML whitespaces
+    /// (spaces, tabs, carriage returns and line feeds) removed from the escaped
+    /// content.
+    ///
+    /// Only raw whitespaces are removed. Entity and character references are
+    /// never split, and whitespaces written as references, such as `&#x20;`,
+    /// are kept, because they are part of the content and not a formatting.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::BytesText;
+    ///
+    /// let text = BytesText::from_escaped_str("  &amp; text&#x20;\n");
+    /// assert_eq!(text.trim().escaped(), b"&amp; text&#x20;");
+    /// ```
+    pub fn trim(self) -> BytesText<'a> {
+        let (start, end) = trimmed_range(&self.content);
+        let content = match self.content {
+            Cow::Borrowed(c) => Cow::Borrowed(&c[start..end]),
+            Cow::Owned(mut c) => {
+                c.truncate(end);
+                c.drain(..start);
+                Cow::Owned(c)
+            }
+        };
+        BytesText {
+            content,
+            whitespace: self.whitespace,
+        }
+    }
+
+    /// Returns the unescaped text content as an UTF-8 string with leading and
+    /// trailing XML whitespaces removed.
+    ///
+    /// Whitespaces are removed before unescaping, as in [`trim()`], so
+    /// whitespaces written as character references, such as `&#x20;`,
+    /// are kept in the result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::BytesText;
+    ///
+    /// let text = BytesText::from_escaped_str("  &amp; text&#x20;\n");
+    /// assert_eq!(text.unescape_and_trim().unwrap(), "& text ");
+    /// ```
+    ///
+    /// [`trim()`]: Self::trim
+    pub fn unescape_and_trim(&self) -> Result<Cow<'_, str>> {
+        let (start, end) = trimmed_range(&self.content);
+        unescape_str(&self.content[start..end])
+    }
+}
+
+/// Returns the start and the end of `content` without leading and trailing
+/// XML whitespaces
+fn trimmed_range(content: &[u8]) -> (usize, usize) {
+    let start = content
+        .iter()
+        .position(|&b| !is_whitespace(b))
+        .unwrap_or(content.len());
+    let end = content
+        .iter()
+        .rposition(|&b| !is_whitespace(b))
+        .map_or(start, |i| i + 1);
+    (start, end)
+}
+
+/// Unescapes UTF-8 `content`, borrowing it if it contains no references
+fn unescape_str(content: &[u8]) -> Result<Cow<'_, str>> {
+    let text = from_utf8(content)?;
+    match do_unescape(content, None)? {
+        Cow::Borrowed(_) => Ok(Cow::Borrowed(text)),
+        Cow::Owned(unescaped) => String::from_utf8(unescaped)
+            .map(Cow::Owned)
+            .map_err(|e| Error::Utf8(e.utf8_error())),
+    }
 }
 
 impl<'a> std::fmt::Debug for BytesText<'a> {
@@ -935,6 +1356,270 @@ impl<'a> std::fmt::Debug for BytesText<'a> {
     }
 }
 
+/// Texts are compared only by their content. Whether the reader marked a text
+/// as a whitespace-only run, is not taken into account
+impl<'a> PartialEq for BytesText<'a> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
+}
+
+impl<'a> Eq for BytesText<'a> {}
+
+impl<'a> std::hash::Hash for BytesText<'a> {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.content.hash(state);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Content of the document type declaration `<!DOCTYPE ...>` with access to
+/// its parts. [`Event::DocType`] contains the content after the `DOCTYPE`
+/// keyword, use [`From`] implementation to convert it to `BytesDocType`.
+///
+/// Content is not parsed until one of the accessors is called, and each of them
+/// returns [`Error::InvalidDocType`] if the content does not match the [grammar]:
+///
+/// ```text
+/// <!DOCTYPE name (SYSTEM "system" | PUBLIC "public" "system")? ([ internal subset ])?>
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use fast_xml::events::{BytesDocType, Event};
+/// use fast_xml::Reader;
+///
+/// let mut reader = Reader::from_str(r#"<!DOCTYPE note SYSTEM "note.dtd" [<!ENTITY a "b">]>"#);
+/// reader.trim_text(true);
+/// let mut buf = Vec::new();
+/// match reader.read_event(&mut buf).unwrap() {
+///     Event::DocType(e) => {
+///         let doctype = BytesDocType::from(e);
+///         assert_eq!(doctype.name().unwrap(), b"note");
+///         assert_eq!(doctype.public_id().unwrap(), None);
+///         assert_eq!(doctype.system_id().unwrap(), Some(&b"note.dtd"[..]));
+///         assert_eq!(doctype.internal_subset().unwrap(), Some(&br#"<!ENTITY a "b">"#[..]));
+///     }
+///     e => panic!("Expected DocType event, got {:?}", e),
+/// }
+/// ```
+///
+/// [grammar]: https://www.w3.org/TR/xml11/#NT-doctypedecl
+#[derive(Clone, Eq, PartialEq)]
+pub struct BytesDocType<'a> {
+    content: Cow<'a, [u8]>,
+}
+
+/// Parts of the document type declaration
+struct DocTypeParts<'a> {
+    name: &'a [u8],
+    public_id: Option<&'a [u8]>,
+    system_id: Option<&'a [u8]>,
+    internal_subset: Option<&'a [u8]>,
+}
+
+impl<'a> BytesDocType<'a> {
+    /// Creates a new `BytesDocType` from the content of declaration after
+    /// the `DOCTYPE` keyword, for example, `html PUBLIC "public" "system"`.
+    #[inline]
+    pub fn new<C: Into<Cow<'a, [u8]>>>(content: C) -> Self {
+        Self {
+            content: content.into(),
+        }
+    }
+
+    /// Ensures that all data is owned to extend the object's lifetime if
+    /// necessary.
+    #[inline]
+    pub fn into_owned(self) -> BytesDocType<'static> {
+        BytesDocType {
+            content: self.content.into_owned().into(),
+        }
+    }
+
+    /// Gets the name of the root element, declared by the DOCTYPE.
+    pub fn name(&self) -> Result<&[u8]> {
+        Ok(self.parse()?.name)
+    }
+
+    /// Gets the public identifier of the external DTD subset, excluding quotes
+    /// (`'` or `"`). Returns `None` if the declaration does not have the
+    /// `PUBLIC` external identifier.
+    pub fn public_id(&self) -> Result<Option<&[u8]>> {
+        Ok(self.parse()?.public_id)
+    }
+
+    /// Gets the system identifier (usually, an URI) of the external DTD subset,
+    /// excluding quotes (`'` or `"`). Returns `None` if the declaration does not
+    /// have an external identifier.
+    pub fn system_id(&self) -> Result<Option<&[u8]>> {
+        Ok(self.parse()?.system_id)
+    }
+
+    /// Gets the raw content of the internal DTD subset, excluding square brackets.
+    /// Returns `None` if the declaration does not have an internal subset.
+    pub fn internal_subset(&self) -> Result<Option<&[u8]>> {
+        Ok(self.parse()?.internal_subset)
+    }
+
+    /// Parses declarations of internal general entities `<!ENTITY name "value">`
+    /// in the internal subset and returns their names and raw replacement texts
+    /// in the order of declaration. Declarations of parameter entities and of
+    /// external entities, as well as malformed declarations, are skipped
+    #[cfg(feature = "custom-entities")]
+    pub(crate) fn internal_entities(&self) -> Result<Vec<(&[u8], &[u8])>> {
+        /// Splits the input after the first `>`, that is not inside a quoted literal
+        fn split_declaration(input: &[u8]) -> (&[u8], &[u8]) {
+            let mut quote = None;
+            for (i, &b) in input.iter().enumerate() {
+                match (quote, b) {
+                    (None, b'"') | (None, b'\'') => quote = Some(b),
+                    (Some(q), b) if q == b => quote = None,
+                    (None, b'>') => return (&input[..i], &input[i + 1..]),
+                    _ => (),
+                }
+            }
+            (input, &[])
+        }
+        /// Parses the content of a general entity declaration after `<!ENTITY`
+        fn entity(decl: &[u8]) -> Option<(&[u8], &[u8])> {
+            let start = decl.iter().position(|&b| !is_whitespace(b))?;
+            if start == 0 || decl[start] == b'%' {
+                return None;
+            }
+            let decl = &decl[start..];
+            let name_end = decl.iter().position(|&b| is_whitespace(b))?;
+            let (name, rest) = decl.split_at(name_end);
+            let rest = &rest[rest.iter().position(|&b| !is_whitespace(b))?..];
+            match rest[0] {
+                quote @ b'"' | quote @ b'\'' => {
+                    let end = memchr::memchr(quote, &rest[1..])?;
+                    Some((name, &rest[1..end + 1]))
+                }
+                // External entities: `SYSTEM "uri"` or `PUBLIC "id" "uri"`
+                _ => None,
+            }
+        }
+
+        let mut subset = match self.internal_subset()? {
+            Some(subset) => subset,
+            None => return Ok(Vec::new()),
+        };
+        let mut entities = Vec::new();
+        while let Some(start) = memchr::memchr(b'<', subset) {
+            let rest = &subset[start..];
+            subset = if let Some(rest) = rest.strip_prefix(b"<!--") {
+                match memchr::memmem::find(rest, b"-->") {
+                    Some(end) => &rest[end + 3..],
+                    None => break,
+                }
+            } else if let Some(rest) = rest.strip_prefix(b"<?") {
+                match memchr::memmem::find(rest, b"?>") {
+                    Some(end) => &rest[end + 2..],
+                    None => break,
+                }
+            } else if let Some(rest) = rest.strip_prefix(b"<!ENTITY") {
+                let (decl, rest) = split_declaration(rest);
+                entities.extend(entity(decl));
+                rest
+            } else {
+                split_declaration(&rest[1..]).1
+            };
+        }
+        Ok(entities)
+    }
+
+    fn parse(&self) -> Result<DocTypeParts<'_>> {
+        fn invalid(msg: &str) -> Error {
+            Error::InvalidDocType(msg.to_string())
+        }
+        fn skip_whitespace(input: &[u8]) -> &[u8] {
+            let start = input
+                .iter()
+                .position(|&b| !is_whitespace(b))
+                .unwrap_or(input.len());
+            &input[start..]
+        }
+        /// Reads a literal in single or double quotes, that should be preceded
+        /// by a whitespace. Returns literal content and the rest of input
+        fn literal<'i>(input: &'i [u8], what: &str) -> Result<(&'i [u8], &'i [u8])> {
+            let rest = skip_whitespace(input);
+            if rest.len() == input.len() {
+                return Err(invalid(&format!("expected whitespace before {}", what)));
+            }
+            match rest.first() {
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    match memchr::memchr(quote, &rest[1..]) {
+                        Some(end) => Ok((&rest[1..end + 1], &rest[end + 2..])),
+                        None => Err(invalid(&format!("unclosed {}", what))),
+                    }
+                }
+                _ => Err(invalid(&format!("expected quoted {}", what))),
+            }
+        }
+
+        let content = skip_whitespace(&self.content);
+        let name_end = content
+            .iter()
+            .position(|&b| is_whitespace(b) || b == b'[')
+            .unwrap_or(content.len());
+        let (name, rest) = content.split_at(name_end);
+        if name.is_empty() {
+            return Err(invalid("missing name"));
+        }
+
+        let rest = skip_whitespace(rest);
+        let (public_id, system_id, rest) = if let Some(rest) = rest.strip_prefix(b"SYSTEM") {
+            let (system, rest) = literal(rest, "system identifier")?;
+            (None, Some(system), rest)
+        } else if let Some(rest) = rest.strip_prefix(b"PUBLIC") {
+            let (public, rest) = literal(rest, "public identifier")?;
+            let (system, rest) = literal(rest, "system identifier")?;
+            (Some(public), Some(system), rest)
+        } else {
+            (None, None, rest)
+        };
+
+        let rest = skip_whitespace(rest);
+        let internal_subset = match rest.first() {
+            None => None,
+            Some(b'[') => match rest.iter().rposition(|&b| b == b']') {
+                Some(end) if skip_whitespace(&rest[end + 1..]).is_empty() => Some(&rest[1..end]),
+                Some(_) => return Err(invalid("unexpected content after internal subset")),
+                None => return Err(invalid("unclosed internal subset")),
+            },
+            Some(_) => return Err(invalid("unexpected content after name")),
+        };
+
+        Ok(DocTypeParts {
+            name,
+            public_id,
+            system_id,
+            internal_subset,
+        })
+    }
+}
+
+impl<'a> From<BytesText<'a>> for BytesDocType<'a> {
+    /// Creates a `BytesDocType` from the content of [`Event::DocType`]
+    #[inline]
+    fn from(text: BytesText<'a>) -> Self {
+        Self::new(text.into_inner())
+    }
+}
+
+impl<'a> std::fmt::Debug for BytesDocType<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BytesDocType {{ content: ")?;
+        write_cow_string(f, &self.content)?;
+        write!(f, " }}")
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 /// CDATA content contains unescaped data from the reader. If you want to write them as a text,
@@ -1017,25 +1702,8 @@ impl<'a> BytesCData<'a> {
     #[cfg(feature = "serialize")]
     pub(crate) fn decode(&self, decoder: crate::reader::Decoder) -> Result<Cow<'a, str>> {
         Ok(match &self.content {
-            Cow::Borrowed(bytes) => {
-                #[cfg(feature = "encoding")]
-                {
-                    decoder.decode(bytes)
-                }
-                #[cfg(not(feature = "encoding"))]
-                {
-                    decoder.decode(bytes)?.into()
-                }
-            }
-            Cow::Owned(bytes) => {
-                #[cfg(feature = "encoding")]
-                let decoded = decoder.decode(bytes).into_owned();
-
-                #[cfg(not(feature = "encoding"))]
-                let decoded = decoder.decode(bytes)?.to_string();
-
-                decoded.into()
-            }
+            Cow::Borrowed(bytes) => decoder.decode(bytes)?,
+            Cow::Owned(bytes) => decoder.decode(bytes)?.into_owned().into(),
         })
     }
 }
@@ -1050,6 +1718,57 @@ impl<'a> std::fmt::Debug for BytesCData<'a> {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// A reference to a general entity `&name;` in a text, which replacement text
+/// is unknown to the reader. Emitted instead of the reference only when
+/// [`Reader::emit_general_refs`] is enabled.
+///
+/// [`Reader::emit_general_refs`]: crate::Reader::emit_general_refs
+#[derive(Clone, Eq, PartialEq)]
+pub struct BytesRef<'a> {
+    name: Cow<'a, [u8]>,
+}
+
+impl<'a> BytesRef<'a> {
+    /// Creates a new `BytesRef` from the name of the entity, without the
+    /// surrounding `&` and `;`.
+    #[inline]
+    pub fn new<C: Into<Cow<'a, [u8]>>>(name: C) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// Ensures that all data is owned to extend the object's lifetime if
+    /// necessary.
+    #[inline]
+    pub fn into_owned(self) -> BytesRef<'static> {
+        BytesRef {
+            name: self.name.into_owned().into(),
+        }
+    }
+
+    /// Extracts the inner `Cow` with the name of the entity.
+    #[inline]
+    pub fn into_inner(self) -> Cow<'a, [u8]> {
+        self.name
+    }
+
+    /// Returns the name of the referenced entity, without the surrounding
+    /// `&` and `;`.
+    #[inline]
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+}
+
+impl<'a> std::fmt::Debug for BytesRef<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BytesRef {{ name: ")?;
+        write_cow_string(f, &self.name)?;
+        write!(f, " }}")
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
 /// Event emitted by [`Reader::read_event`].
 ///
 /// [`Reader::read_event`]: ../reader/struct.Reader.html#method.read_event
@@ -1071,8 +1790,14 @@ pub enum Event<'a> {
     Decl(BytesDecl<'a>),
     /// Processing instruction `<?...?>`.
     PI(BytesText<'a>),
-    /// Doctype `<!DOCTYPE ...>`.
+    /// Doctype `<!DOCTYPE ...>`. Convert content to [`BytesDocType`] to get
+    /// access to the parts of the declaration.
     DocType(BytesText<'a>),
+    /// Reference to an unknown general entity `&name;` in a text. Emitted only
+    /// when [`Reader::emit_general_refs`] is enabled.
+    ///
+    /// [`Reader::emit_general_refs`]: crate::Reader::emit_general_refs
+    GeneralRef(BytesRef<'a>),
     /// End of XML document.
     Eof,
 }
@@ -1091,6 +1816,7 @@ impl<'a> Event<'a> {
             Event::Decl(e) => Event::Decl(e.into_owned()),
             Event::PI(e) => Event::PI(e.into_owned()),
             Event::DocType(e) => Event::DocType(e.into_owned()),
+            Event::GeneralRef(e) => Event::GeneralRef(e.into_owned()),
             Event::Eof => Event::Eof,
         }
     }
@@ -1126,6 +1852,13 @@ impl<'a> Deref for BytesText<'a> {
     }
 }
 
+impl<'a> Deref for BytesDocType<'a> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        &self.content
+    }
+}
+
 impl<'a> Deref for BytesCData<'a> {
     type Target = [u8];
 
@@ -1134,6 +1867,13 @@ impl<'a> Deref for BytesCData<'a> {
     }
 }
 
+impl<'a> Deref for BytesRef<'a> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        &self.name
+    }
+}
+
 impl<'a> Deref for Event<'a> {
     type Target = [u8];
     fn deref(&self) -> &[u8] {
@@ -1146,6 +1886,7 @@ impl<'a> Deref for Event<'a> {
             Event::CData(ref e) => &*e,
             Event::Comment(ref e) => &*e,
             Event::DocType(ref e) => &*e,
+            Event::GeneralRef(ref e) => e,
             Event::Eof => &[],
         }
     }
@@ -1233,4 +1974,346 @@ mod test {
         assert_eq!(b.len(), 4);
         assert_eq!(b.name(), b"test");
     }
+
+    #[test]
+    fn bytestext_unescape() {
+        let text = BytesText::from_escaped(b"no entities; just text".as_ref());
+        assert!(matches!(
+            text.unescape().unwrap(),
+            Cow::Borrowed("no entities; just text")
+        ));
+
+        let text = BytesText::from_escaped(b"with &lt;entities&gt; &#x20;".as_ref());
+        match text.unescape().unwrap() {
+            Cow::Owned(s) => assert_eq!(s, "with <entities>  "),
+            Cow::Borrowed(s) => panic!("Expected `Cow::Owned`, found `Cow::Borrowed({:?})`", s),
+        }
+
+        let text = BytesText::from_escaped(b"unknown &entity;".as_ref());
+        assert!(matches!(text.unescape(), Err(Error::EscapeError(_))));
+
+        let text = BytesText::from_escaped(b"invalid \xFF utf-8".as_ref());
+        assert!(matches!(text.unescape(), Err(Error::Utf8(_))));
+    }
+
+    #[test]
+    fn bytestext_trim() {
+        let text = BytesText::from_escaped(b"  &amp; text  ".as_ref());
+        assert_eq!(text.unescape_and_trim().unwrap(), "& text");
+        assert_eq!(text.trim().escaped(), b"&amp; text");
+
+        let text = BytesText::from_escaped(b"\t\r\n&#x20;&lt;text&gt;&#32;\n".to_vec());
+        assert_eq!(text.unescape_and_trim().unwrap(), " <text> ");
+        assert_eq!(text.trim().escaped(), b"&#x20;&lt;text&gt;&#32;");
+
+        let text = BytesText::from_escaped(b"no whitespaces".as_ref());
+        assert!(matches!(
+            text.unescape_and_trim().unwrap(),
+            Cow::Borrowed("no whitespaces")
+        ));
+        assert_eq!(text.trim().escaped(), b"no whitespaces");
+
+        let text = BytesText::from_escaped(b" \t\r\n ".as_ref());
+        assert_eq!(text.unescape_and_trim().unwrap(), "");
+        assert_eq!(text.trim().escaped(), b"");
+    }
+
+    #[test]
+    fn bytestart_push_attribute_with_quote() {
+        let mut b = BytesStart::owned_name("test");
+        b.push_attribute(Attribute {
+            key: b"x",
+            value: Cow::Borrowed(b"y\"z"),
+        });
+        assert_eq!(b.attributes_raw(), br#" x='y"z'"#);
+    }
+
+    #[test]
+    fn bytestart_remove_attribute() {
+        let mut b = BytesStart::borrowed(b"test first='1'  middle=\"2\"\nlast='3'", 4);
+
+        assert_eq!(b.remove_attribute("middle").unwrap(), true);
+        assert_eq!(b.attributes_raw(), b" first='1'\nlast='3'");
+
+        assert_eq!(b.remove_attribute("middle").unwrap(), false);
+        assert_eq!(b.attributes_raw(), b" first='1'\nlast='3'");
+
+        assert_eq!(b.remove_attribute("first").unwrap(), true);
+        assert_eq!(b.attributes_raw(), b"\nlast='3'");
+
+        assert_eq!(b.remove_attribute("last").unwrap(), true);
+        assert_eq!(b.attributes_raw(), b"");
+        assert_eq!(b.name(), b"test");
+    }
+
+    #[test]
+    fn bytestart_for_each_attribute() {
+        use crate::events::attributes::AttrError;
+
+        let b = BytesStart::borrowed(b"test a='1' b=\"2\" a='3'", 4);
+        let mut attrs = Vec::new();
+        b.for_each_attribute(|a| attrs.push((a.key, a.value.into_owned())))
+            .unwrap();
+        // Duplicates are not checked
+        assert_eq!(
+            attrs,
+            vec![
+                (&b"a"[..], b"1".to_vec()),
+                (&b"b"[..], b"2".to_vec()),
+                (&b"a"[..], b"3".to_vec()),
+            ]
+        );
+
+        // Iteration stops at the first error
+        let b = BytesStart::borrowed(b"test a='1' broken b='2'", 4);
+        let mut keys = Vec::new();
+        match b.for_each_attribute(|a| keys.push(a.key)) {
+            Err(Error::InvalidAttr(AttrError::ExpectedEq(18))) => {}
+            x => panic!(
+                "Expected `Err(InvalidAttr(ExpectedEq(18)))`, but got `{:?}`",
+                x
+            ),
+        }
+        assert_eq!(keys, vec![&b"a"[..]]);
+    }
+
+    mod bytestart_parse {
+        use super::*;
+        use crate::events::attributes::AttrError;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn name_only() {
+            let b = BytesStart::parse("item").unwrap();
+            assert_eq!(b.name(), b"item");
+            assert_eq!(b.attributes_raw(), b"");
+        }
+
+        #[test]
+        fn with_attributes() {
+            let b = BytesStart::parse("ns:item id=\"5\"\n  class='new' ").unwrap();
+            assert_eq!(b.name(), b"ns:item");
+            assert_eq!(b.local_name(), b"item");
+            assert_eq!(b.attributes_raw(), b" id=\"5\"\n  class='new' ");
+            assert_eq!(
+                b.attributes().map(|a| a.unwrap().key).collect::<Vec<_>>(),
+                vec![&b"id"[..], b"class"]
+            );
+        }
+
+        #[test]
+        fn try_from() {
+            assert_eq!(
+                BytesStart::try_from("item id='5'").unwrap(),
+                BytesStart::borrowed(b"item id='5'", 4)
+            );
+            assert_eq!(
+                BytesStart::try_from(b"item id='5'".as_ref()).unwrap(),
+                BytesStart::borrowed(b"item id='5'", 4)
+            );
+        }
+
+        #[test]
+        fn from_str() {
+            let b: BytesStart<'static> = "item id='5'".parse().unwrap();
+            assert_eq!(b, BytesStart::owned(b"item id='5'".to_vec(), 4));
+        }
+
+        #[test]
+        fn from_content() {
+            let b = BytesStart::from_content("item id='5'", 4);
+            assert_eq!(b.name(), b"item");
+            assert_eq!(b.attributes_raw(), b" id='5'");
+        }
+
+        #[test]
+        fn empty() {
+            match BytesStart::parse("") {
+                Err(Error::EmptyElementName) => {}
+                x => panic!("Expected `Err(EmptyElementName)`, but got `{:?}`", x),
+            }
+        }
+
+        #[test]
+        fn leading_whitespace() {
+            match BytesStart::parse(" item") {
+                Err(Error::EmptyElementName) => {}
+                x => panic!("Expected `Err(EmptyElementName)`, but got `{:?}`", x),
+            }
+        }
+
+        #[test]
+        fn malformed_attribute() {
+            match BytesStart::parse("item id") {
+                Err(Error::InvalidAttr(AttrError::ExpectedEq(7))) => {}
+                x => panic!(
+                    "Expected `Err(InvalidAttr(ExpectedEq(7)))`, but got `{:?}`",
+                    x
+                ),
+            }
+        }
+
+        #[test]
+        fn duplicated_attribute() {
+            match BytesStart::parse("item id='1' id='2'") {
+                Err(Error::InvalidAttr(AttrError::Duplicated(12, 5))) => {}
+                x => panic!(
+                    "Expected `Err(InvalidAttr(Duplicated(12, 5)))`, but got `{:?}`",
+                    x
+                ),
+            }
+        }
+    }
+
+    mod doctype {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[cfg(feature = "custom-entities")]
+        #[test]
+        fn internal_entities() {
+            let d = BytesDocType::new(
+                br#"root [
+                    <!-- <!ENTITY commented "no"> -->
+                    <?pi <!ENTITY in_pi "no"> ?>
+                    <!ELEMENT root (#PCDATA)>
+                    <!ATTLIST root attr CDATA "<!ENTITY in_attlist 'no'>">
+                    <!ENTITY double "a 'quoted' &amp; value">
+                    <!ENTITY  single
+                        '"&#x41;"' >
+                    <!ENTITY % parameter "no">
+                    <!ENTITY external SYSTEM "external.xml">
+                    <!ENTITY public PUBLIC "-//public" "public.xml">
+                    <!ENTITY empty "">
+                    <!ENTITYmalformed "no">
+                ]"#
+                .as_ref(),
+            );
+            assert_eq!(
+                d.internal_entities().unwrap(),
+                vec![
+                    (&b"double"[..], &b"a 'quoted' &amp; value"[..]),
+                    (&b"single"[..], &b"\"&#x41;\""[..]),
+                    (&b"empty"[..], &b""[..]),
+                ]
+            );
+
+            let d = BytesDocType::new(b"root".as_ref());
+            assert_eq!(d.internal_entities().unwrap(), vec![]);
+
+            let d = BytesDocType::new(b"root [".as_ref());
+            assert!(d.internal_entities().is_err());
+        }
+
+        #[test]
+        fn name_only() {
+            let d = BytesDocType::new(b"html".as_ref());
+            assert_eq!(d.name().unwrap(), b"html");
+            assert_eq!(d.public_id().unwrap(), None);
+            assert_eq!(d.system_id().unwrap(), None);
+            assert_eq!(d.internal_subset().unwrap(), None);
+
+            let d = BytesDocType::new(b" html \n".as_ref());
+            assert_eq!(d.name().unwrap(), b"html");
+        }
+
+        #[test]
+        fn system() {
+            let d = BytesDocType::new(br#"note SYSTEM "note.dtd""#.as_ref());
+            assert_eq!(d.name().unwrap(), b"note");
+            assert_eq!(d.public_id().unwrap(), None);
+            assert_eq!(d.system_id().unwrap(), Some(&b"note.dtd"[..]));
+            assert_eq!(d.internal_subset().unwrap(), None);
+
+            let d = BytesDocType::new(b"note SYSTEM 'it\"s.dtd'".as_ref());
+            assert_eq!(d.system_id().unwrap(), Some(&b"it\"s.dtd"[..]));
+        }
+
+        #[test]
+        fn public() {
+            let d = BytesDocType::new(
+                br#"html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN"
+                'http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd'"#
+                    .as_ref(),
+            );
+            assert_eq!(d.name().unwrap(), b"html");
+            assert_eq!(
+                d.public_id().unwrap(),
+                Some(&b"-//W3C//DTD XHTML 1.0 Strict//EN"[..])
+            );
+            assert_eq!(
+                d.system_id().unwrap(),
+                Some(&b"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd"[..])
+            );
+            assert_eq!(d.internal_subset().unwrap(), None);
+        }
+
+        #[test]
+        fn internal_subset() {
+            let d = BytesDocType::new(br#"root[<!ENTITY e "[value]">]"#.as_ref());
+            assert_eq!(d.name().unwrap(), b"root");
+            assert_eq!(d.system_id().unwrap(), None);
+            assert_eq!(
+                d.internal_subset().unwrap(),
+                Some(&br#"<!ENTITY e "[value]">"#[..])
+            );
+
+            let d =
+                BytesDocType::new(br#"root PUBLIC "pub" "sys" [ <!ELEMENT root ANY> ] "#.as_ref());
+            assert_eq!(d.public_id().unwrap(), Some(&b"pub"[..]));
+            assert_eq!(d.system_id().unwrap(), Some(&b"sys"[..]));
+            assert_eq!(
+                d.internal_subset().unwrap(),
+                Some(&b" <!ELEMENT root ANY> "[..])
+            );
+
+            let d = BytesDocType::new(br#"root SYSTEM "sys"[]"#.as_ref());
+            assert_eq!(d.internal_subset().unwrap(), Some(&b""[..]));
+        }
+
+        #[test]
+        fn from_reader() {
+            let mut reader =
+                Reader::from_str(r#"<!DOCTYPE root SYSTEM "sys" [<!ENTITY e "v">]><root/>"#);
+            reader.trim_text(true);
+            let mut buf = Vec::new();
+            match reader.read_event(&mut buf).unwrap() {
+                Event::DocType(e) => {
+                    let d = BytesDocType::from(e);
+                    assert_eq!(d.name().unwrap(), b"root");
+                    assert_eq!(d.system_id().unwrap(), Some(&b"sys"[..]));
+                    assert_eq!(
+                        d.internal_subset().unwrap(),
+                        Some(&br#"<!ENTITY e "v">"#[..])
+                    );
+                }
+                e => panic!("Expected DocType event, got {:?}", e),
+            }
+        }
+
+        macro_rules! malformed {
+            ($name:ident: $content:literal => $message:literal) => {
+                #[test]
+                fn $name() {
+                    let d = BytesDocType::new($content.as_ref());
+                    match d.name() {
+                        Err(Error::InvalidDocType(e)) => assert_eq!(e, $message),
+                        x => panic!("Expected `Err(InvalidDocType(_))`, but got `{:?}`", x),
+                    }
+                }
+            };
+        }
+
+        malformed!(empty: b"" => "missing name");
+        malformed!(only_subset: b"[]" => "missing name");
+        malformed!(unknown_keyword: br#"html OTHER "id""# => "unexpected content after name");
+        malformed!(system_without_id: b"html SYSTEM" => "expected whitespace before system identifier");
+        malformed!(system_unquoted: b"html SYSTEM id" => "expected quoted system identifier");
+        malformed!(system_unclosed: br#"html SYSTEM "id"# => "unclosed system identifier");
+        malformed!(public_without_system: br#"html PUBLIC "pub""# => "expected whitespace before system identifier");
+        malformed!(public_no_space: br#"html PUBLIC "pub""sys""# => "expected whitespace before system identifier");
+        malformed!(public_unclosed: br#"html PUBLIC 'pub"# => "unclosed public identifier");
+        malformed!(subset_unclosed: b"html [<!ENTITY e 'v'>" => "unclosed internal subset");
+        malformed!(after_subset: b"html [] extra" => "unexpected content after internal subset");
+    }
 }
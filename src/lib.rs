@@ -144,9 +144,10 @@ mod escapei;
 pub mod escape {
     //! Manage xml character escapes
     pub(crate) use crate::escapei::{do_unescape, EscapeError};
-    pub use crate::escapei::{escape, partial_escape, unescape, unescape_with};
+    pub use crate::escapei::{escape, numeric_escape, partial_escape, unescape, unescape_with};
 }
 pub mod events;
+pub mod name;
 mod reader;
 #[cfg(feature = "serialize")]
 pub mod se;
@@ -159,5 +160,5 @@ mod writer;
 #[cfg(feature = "serialize")]
 pub use crate::errors::serialize::DeError;
 pub use crate::errors::{Error, Result};
-pub use crate::reader::Reader;
-pub use crate::writer::{ElementWriter, Writer};
+pub use crate::reader::{resolve_namespace, Decoder, NamespaceBindings, PushReader, Reader};
+pub use crate::writer::{ElementWriter, LineEnding, Writer};
@@ -0,0 +1,144 @@
+//! Wrappers over raw names of elements, that can be compared with string and
+//! byte string literals.
+//!
+//! Names are compared as they are written in the document, without resolving
+//! namespaces: a qualified name `x:item` is equal to `"x:item"`, but not to
+//! `"item"`. To match elements by namespace, use [`Reader::read_namespaced_event`],
+//! which resolves a namespace of each element, and compare the namespace and
+//! the [`QName::local_name`] separately.
+//!
+//! [`Reader::read_namespaced_event`]: crate::Reader::read_namespaced_event
+
+use crate::utils::write_byte_string;
+use std::fmt;
+
+/// A qualified name of an element, as it is written in the document, including
+/// the namespace prefix, if any.
+///
+/// # Example
+///
+/// ```
+/// use fast_xml::events::BytesStart;
+///
+/// let start = BytesStart::borrowed_name(b"x:item");
+/// let name = start.qname();
+///
+/// assert!(name == "x:item");
+/// assert!(name == b"x:item");
+/// assert!(name != "item");
+/// assert!(name.local_name() == "item");
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct QName<'a>(pub &'a [u8]);
+
+impl<'a> QName<'a> {
+    /// Returns the raw bytes of the name
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Returns the name without the namespace prefix. All content up to and
+    /// including the first `:` character is removed from the name
+    #[inline]
+    pub fn local_name(&self) -> LocalName<'a> {
+        LocalName(memchr::memchr(b':', self.0).map_or(self.0, |i| &self.0[i + 1..]))
+    }
+
+    /// Returns the namespace prefix of the name, if any. The prefix is a part
+    /// of the name before the first `:` character
+    #[inline]
+    pub fn prefix(&self) -> Option<&'a [u8]> {
+        memchr::memchr(b':', self.0).map(|i| &self.0[..i])
+    }
+}
+
+/// A local name of an element, that is, the qualified name without the
+/// namespace prefix. Returned by [`QName::local_name`].
+#[derive(Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct LocalName<'a>(&'a [u8]);
+
+impl<'a> LocalName<'a> {
+    /// Returns the raw bytes of the name
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+macro_rules! impl_name {
+    ($name:ident) => {
+        impl<'a> AsRef<[u8]> for $name<'a> {
+            #[inline]
+            fn as_ref(&self) -> &[u8] {
+                self.0
+            }
+        }
+
+        impl<'a> fmt::Debug for $name<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}(", stringify!($name))?;
+                write_byte_string(f, self.0)?;
+                write!(f, ")")
+            }
+        }
+
+        impl_eq!($name, str, |s| s.as_bytes());
+        impl_eq!($name, &str, |s| s.as_bytes());
+        impl_eq!($name, [u8], |s| s);
+        impl_eq!($name, &[u8], |s| *s);
+        impl_eq!($name, [u8; N], |s| &s[..], const N);
+        impl_eq!($name, &[u8; N], |s| &s[..], const N);
+    };
+}
+
+macro_rules! impl_eq {
+    ($name:ident, $other:ty, |$s:ident| $bytes:expr $(, const $n:ident)?) => {
+        impl<'a $(, const $n: usize)?> PartialEq<$other> for $name<'a> {
+            #[inline]
+            fn eq(&self, $s: &$other) -> bool {
+                self.0 == $bytes
+            }
+        }
+
+        impl<'a $(, const $n: usize)?> PartialEq<$name<'a>> for $other {
+            #[inline]
+            fn eq(&self, other: &$name<'a>) -> bool {
+                let $s = self;
+                other.0 == $bytes
+            }
+        }
+    };
+}
+
+impl_name!(QName);
+impl_name!(LocalName);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn prefix() {
+        assert_eq!(QName(b"x:item").prefix(), Some(b"x".as_ref()));
+        assert_eq!(QName(b"item").prefix(), None);
+        assert_eq!(QName(b":item").prefix(), Some(b"".as_ref()));
+    }
+
+    #[test]
+    fn local_name() {
+        assert_eq!(QName(b"x:item").local_name(), "item");
+        assert_eq!(QName(b"item").local_name(), "item");
+        assert_eq!(QName(b"x:y:item").local_name(), "y:item");
+    }
+
+    #[test]
+    fn debug() {
+        assert_eq!(format!("{:?}", QName(b"x:item")), r#"QName("x:item")"#);
+        assert_eq!(
+            format!("{:?}", QName(b"x:item").local_name()),
+            r#"LocalName("item")"#
+        );
+    }
+}
@@ -1,16 +1,21 @@
 //! A module to handle `Reader`
 
-#[cfg(feature = "encoding")]
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
 use std::{fs::File, path::Path, str::from_utf8};
 
 #[cfg(feature = "encoding")]
 use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
 
 use crate::errors::{Error, Result};
-use crate::events::attributes::Attribute;
-use crate::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use crate::escapei::do_unescape;
+use crate::events::attributes::{Attr, AttrLimits, Attribute, IterState};
+use crate::events::{BytesCData, BytesDecl, BytesEnd, BytesRef, BytesStart, BytesText, Event};
+
+#[cfg(feature = "custom-entities")]
+use crate::events::BytesDocType;
 
 use memchr;
 
@@ -19,6 +24,8 @@ enum TagState {
     Opened,
     Closed,
     Empty,
+    /// Inside a text node, that is returned in chunks. See [`Reader::text_chunk_size`]
+    Text,
     /// Either Eof or Errored
     Exit,
 }
@@ -75,12 +82,56 @@ pub struct Reader<R: BufRead> {
     trim_text_start: bool,
     /// trims trailing whitespace in Text events.
     trim_text_end: bool,
+    /// characters, trimmed from Text events in addition to the XML whitespaces
+    extra_whitespace: Vec<u8>,
+    /// mark whitespace-only Text events, see [`BytesText::is_whitespace`]
+    emit_whitespace: bool,
+    /// replace `\r\n` and lone `\r` with `\n` in texts, CDATA sections and attributes
+    normalize_line_endings: bool,
     /// trims trailing whitespaces from markup names in closing tags `</a >`
     trim_markup_names_in_closing_tags: bool,
     /// check if End nodes match last Start node
     check_end_names: bool,
     /// check if comments contains `--` (false per default)
     check_comments: bool,
+    /// check that element and attribute names match the XML `Name` production
+    check_names: bool,
+    /// reject non-ASCII bytes when decoding, see [`Self::assume_ascii`]
+    assume_ascii: bool,
+    /// return content of CDATA sections as escaped `Text` events
+    merge_cdata_as_text: bool,
+    /// report byte ranges of events in [`Self::read_event_with_span`]
+    event_spans: bool,
+    /// check that the root element has the name declared in the DOCTYPE
+    validate_doctype_root: bool,
+    /// The name declared in the DOCTYPE that the root element should have.
+    /// Set only if [`Self::validate_doctype_root`] is `true` and the root
+    /// element is not read yet
+    doctype_name: Option<Vec<u8>>,
+    /// internal general entities, declared in the DOCTYPE, see [`Self::doctype_entities`]
+    #[cfg(feature = "custom-entities")]
+    doctype_entities: HashMap<Vec<u8>, Vec<u8>>,
+    /// unescape entity and character references in the `unescape_and_decode*` helpers
+    expand_entities: bool,
+    /// emit references to unknown entities in texts as separate `GeneralRef` events
+    emit_general_refs: bool,
+    /// the rest of a text after a reference to an unknown entity, that should
+    /// be returned before reading the next event. Used only if
+    /// [`Self::emit_general_refs`] is `true`
+    pending_text: Option<Vec<u8>>,
+    /// the event, returned by [`Self::peek_event`], that should be returned
+    /// before reading the next event, and the position before that event
+    peeked: Option<(Event<'static>, usize)>,
+    /// capacity that is reserved in the user-given buffer before reading an event
+    buffer_capacity: usize,
+    /// maximum size of a single markup or text token, unlimited if `None`
+    max_token_size: Option<usize>,
+    /// maximum number of bytes read from the source, unlimited if `None`
+    max_document_size: Option<usize>,
+    /// size of chunks in which long text nodes are returned, unlimited if `None`
+    text_chunk_size: Option<usize>,
+    /// limits on attributes, checked by iterators over attributes of read elements
+    attribute_limits: AttrLimits,
     /// All currently Started elements which didn't have a matching
     /// End element yet.
     ///
@@ -123,10 +174,30 @@ impl<R: BufRead> Reader<R> {
             expand_empty_elements: false,
             trim_text_start: false,
             trim_text_end: false,
+            extra_whitespace: Vec::new(),
+            emit_whitespace: false,
+            normalize_line_endings: true,
             trim_markup_names_in_closing_tags: true,
             check_end_names: true,
             buf_position: 0,
             check_comments: false,
+            check_names: false,
+            assume_ascii: false,
+            merge_cdata_as_text: false,
+            event_spans: false,
+            validate_doctype_root: false,
+            doctype_name: None,
+            #[cfg(feature = "custom-entities")]
+            doctype_entities: HashMap::new(),
+            expand_entities: true,
+            emit_general_refs: false,
+            pending_text: None,
+            peeked: None,
+            buffer_capacity: 0,
+            max_token_size: None,
+            max_document_size: None,
+            text_chunk_size: None,
+            attribute_limits: AttrLimits::UNLIMITED,
             ns_resolver: NamespaceResolver::default(),
             #[cfg(feature = "encoding")]
             encoding: ::encoding_rs::UTF_8,
@@ -181,6 +252,121 @@ impl<R: BufRead> Reader<R> {
         self
     }
 
+    /// Changes whether whitespace-only [`Text`] events are marked as such.
+    ///
+    /// When set to `true`, [`Text`] events that consist only of whitespace
+    /// characters, for example, indentation of a pretty-printed document, return
+    /// `true` from [`BytesText::is_whitespace`]. Their content is preserved as is,
+    /// so tools that reformat documents can decide whether to drop or to keep
+    /// such texts without confusing them with significant text.
+    ///
+    /// This option has effect only if [`trim_text`] and [`trim_text_end`] are
+    /// off, because otherwise whitespace-only texts are trimmed or not emitted
+    /// at all.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`trim_text`]: Self::trim_text
+    /// [`trim_text_end`]: Self::trim_text_end
+    pub fn emit_whitespace(&mut self, val: bool) -> &mut Reader<R> {
+        self.emit_whitespace = val;
+        self
+    }
+
+    /// Sets characters, that are trimmed from [`Text`] events in addition to
+    /// the XML whitespaces (space, tab, carriage return and line feed), when
+    /// [`trim_text`] or [`trim_text_end`] is enabled.
+    ///
+    /// That is a lenient mode for non-conformant documents, that use, for example,
+    /// form feeds (`\x0C`) or vertical tabs (`\x0B`) as separators. Only trimming
+    /// of texts is affected, names and attributes are parsed according to
+    /// the specification.
+    ///
+    /// (empty by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesText, Event};
+    /// use fast_xml::Reader;
+    ///
+    /// let xml = "<root>\x0C text\x0C</root>";
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.trim_text(true).extra_whitespace(b"\x0C");
+    /// let mut buf = Vec::new();
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::Start(_)));
+    /// assert_eq!(
+    ///     reader.read_event(&mut buf).unwrap(),
+    ///     Event::Text(BytesText::from_escaped_str("text"))
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `chars` contains non-ASCII bytes. Such bytes are parts of
+    /// multi-byte characters in UTF-8 and in other ASCII-compatible encodings,
+    /// so trimming them would break characters at the edges of texts.
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`trim_text`]: Self::trim_text
+    /// [`trim_text_end`]: Self::trim_text_end
+    pub fn extra_whitespace(&mut self, chars: &[u8]) -> &mut Reader<R> {
+        assert!(
+            chars.is_ascii(),
+            "extra whitespace characters must be ASCII, but got {:?}",
+            chars
+        );
+        self.extra_whitespace = chars.to_vec();
+        self
+    }
+
+    /// Changes whether line endings are normalized, as required by the [XML specification].
+    ///
+    /// When set to `true`, each `\r\n` pair and each `\r`, that is not followed
+    /// by `\n`, are replaced with a single `\n` in [`Text`], [`CData`], [`Comment`],
+    /// [`PI`] and [`DocType`] events and in the content of [`Start`] and [`Empty`]
+    /// events, including attribute values. Content without `\r` is still borrowed
+    /// from the input, otherwise the event owns a normalized copy of it.
+    ///
+    /// Set it to `false` to get content exactly as it is written in the document.
+    ///
+    /// (`true` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesText, Event};
+    /// use fast_xml::Reader;
+    ///
+    /// let xml = "<root>line 1\r\nline 2\rline 3</root>";
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::Start(_)));
+    /// assert_eq!(
+    ///     reader.read_event(&mut buf).unwrap(),
+    ///     Event::Text(BytesText::from_escaped_str("line 1\nline 2\nline 3"))
+    /// );
+    /// ```
+    ///
+    /// [XML specification]: https://www.w3.org/TR/xml/#sec-line-ends
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`CData`]: events/enum.Event.html#variant.CData
+    /// [`Comment`]: events/enum.Event.html#variant.Comment
+    /// [`PI`]: events/enum.Event.html#variant.PI
+    /// [`DocType`]: events/enum.Event.html#variant.DocType
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    pub fn normalize_line_endings(&mut self, val: bool) -> &mut Reader<R> {
+        self.normalize_line_endings = val;
+        self
+    }
+
     /// Changes whether trailing whitespaces after the markup name are trimmed in closing tags
     /// `</a >`.
     ///
@@ -234,10 +420,418 @@ impl<R: BufRead> Reader<R> {
         self
     }
 
+    /// Changes whether the document is expected to contain only ASCII characters.
+    ///
+    /// When set to `true`, [`decode`] and the [`Decoder`], returned by [`decoder`],
+    /// check that the decoded bytes are ASCII, and return [`Error::Utf8`], that
+    /// points to the first non-ASCII byte, otherwise. That check is cheap and
+    /// is done before the conversion, so non-ASCII data is never decoded. With
+    /// the `encoding` feature ASCII data is returned as is, without a call to
+    /// the decoder of the document encoding.
+    ///
+    /// Note, that the conversion of checked bytes to a string still validates
+    /// them as UTF-8, because skipping validation would require `unsafe` code,
+    /// which is forbidden in this crate. Validation of ASCII data is cheap, so
+    /// without the `encoding` feature this option does not make decoding faster,
+    /// it only rejects non-ASCII input, that was not expected.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`decode`]: Self::decode
+    /// [`decoder`]: Self::decoder
+    pub fn assume_ascii(&mut self, val: bool) -> &mut Reader<R> {
+        self.assume_ascii = val;
+        self
+    }
+
+    /// Changes whether names of elements and attributes should be validated.
+    ///
+    /// When set to `true`, names of [`Start`], [`Empty`] and [`End`] events and
+    /// names of attributes of elements are checked to match the XML [`Name`]
+    /// production: a name should start with a letter, `_` or `:` and should
+    /// contain only letters, digits, `-`, `.`, `_`, `:` and a few other Unicode
+    /// characters. Reading an element with an ill-formed name returns
+    /// [`Error::IllFormedName`] with the position of the first invalid character.
+    /// Names are expected to be encoded in UTF-8.
+    ///
+    /// Attributes that cannot be parsed are not reported by this check, they are
+    /// still reported lazily by the [`Attributes`] iterator. The check requires
+    /// an additional pass over the content of each tag, so the default value is
+    /// `false` to improve performance.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::{Error, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<1tag/>");
+    /// reader.trim_text(true).check_names(true);
+    ///
+    /// match reader.read_event(&mut Vec::new()) {
+    ///     Err(Error::IllFormedName { offset }) => assert_eq!(offset, 1),
+    ///     e => panic!("Expected `IllFormedName`, but got `{:?}`", e),
+    /// }
+    /// ```
+    ///
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`End`]: events/enum.Event.html#variant.End
+    /// [`Name`]: https://www.w3.org/TR/xml/#NT-Name
+    /// [`Error::IllFormedName`]: crate::Error::IllFormedName
+    /// [`Attributes`]: crate::events::attributes::Attributes
+    pub fn check_names(&mut self, val: bool) -> &mut Reader<R> {
+        self.check_names = val;
+        self
+    }
+
+    /// Changes whether CDATA sections should be returned as [`Text`] events.
+    ///
+    /// When set to `true`, a `<![CDATA[...]]>` section is returned as a [`Text`]
+    /// event instead of a [`CData`] event, so code that does not care about
+    /// the difference handles only one kind of events. Because the raw content
+    /// of [`Text`] events is escaped, special characters of the CDATA content
+    /// are escaped (see [`BytesCData::escape`]), so unescaping the event gives
+    /// the content of the section verbatim, and writing the event produces
+    /// well-formed XML. Adjacent text and CDATA sections are still returned as
+    /// separate events.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::Event;
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<tag>1 &lt; 2<![CDATA[ & 3 > 2]]></tag>");
+    /// reader.trim_text(true).merge_cdata_as_text(true);
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut text = String::new();
+    /// loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         Event::Text(e) => text += &e.unescape_and_decode(&reader).unwrap(),
+    ///         Event::Eof => break,
+    ///         _ => {}
+    ///     }
+    ///     buf.clear();
+    /// }
+    /// assert_eq!(text, "1 < 2 & 3 > 2");
+    /// ```
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`CData`]: events/enum.Event.html#variant.CData
+    /// [`BytesCData::escape`]: crate::events::BytesCData::escape
+    pub fn merge_cdata_as_text(&mut self, val: bool) -> &mut Reader<R> {
+        self.merge_cdata_as_text = val;
+        self
+    }
+
+    /// Changes whether [`Self::read_event_with_span`] should report byte ranges
+    /// of the events in the input.
+    ///
+    /// When set to `true`, each returned range covers all bytes consumed while
+    /// reading the event, including whitespaces skipped due to [`Self::trim_text()`].
+    /// Ranges of consecutive events are contiguous, so concatenation of all of them
+    /// gives the original input. When set to `false`, returned ranges are always
+    /// empty and point to the position where event starts.
+    ///
+    /// (`false` by default)
+    pub fn with_event_spans(&mut self, val: bool) -> &mut Reader<R> {
+        self.event_spans = val;
+        self
+    }
+
+    /// Changes whether the name of the root element should be checked against
+    /// the name declared in the DOCTYPE.
+    ///
+    /// When set to `true`, a [`DocType`] event remembers the declared name and
+    /// the first [`Start`] or [`Empty`] event after it returns
+    /// [`Error::DocTypeRootMismatch`] if it has a different name. Documents
+    /// without DOCTYPE are not checked.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`DocType`]: events/enum.Event.html#variant.DocType
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    pub fn validate_doctype_root(&mut self, val: bool) -> &mut Reader<R> {
+        self.validate_doctype_root = val;
+        self
+    }
+
+    /// Changes whether entity and character references should be expanded by
+    /// the helper methods, that accept a reader, such as
+    /// [`BytesText::unescape_and_decode`] or [`Attribute::unescape_and_decode_value`].
+    ///
+    /// The reader itself never expands references: [`Text`] events and attribute
+    /// values always contain the content as it is written in the document, so
+    /// writing them back with a [`Writer`] preserves references verbatim. When
+    /// set to `false`, the helper methods only decode the content and return
+    /// references, including the predefined ones, such as `&amp;` or `&lt;`,
+    /// unchanged. This is useful for tools that should preserve the document
+    /// as it was written, for example, editors.
+    ///
+    /// (`true` by default)
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`Writer`]: crate::Writer
+    pub fn expand_entities(&mut self, val: bool) -> &mut Reader<R> {
+        self.expand_entities = val;
+        self
+    }
+
+    /// Changes how references to unknown entities in texts are reported.
+    ///
+    /// When set to `true`, a reference to a general entity, which is not one of
+    /// the predefined entities (`&lt;`, `&gt;`, `&amp;`, `&apos;` and `&quot;`),
+    /// is returned as a separate [`GeneralRef`] event, and the text around it
+    /// is returned in separate [`Text`] events. That lets tools, which should
+    /// tolerate references to entities defined elsewhere, for example, in an
+    /// external DTD, to decide how to handle them. The reader does not process
+    /// DTDs, so references to entities declared in the DOCTYPE are reported too.
+    ///
+    /// When set to `false`, such references are kept in [`Text`] events, and
+    /// unescaping of that texts fails with an [`Error::EscapeError`] about
+    /// an unrecognized entity.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesRef, BytesText, Event};
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<p>&copy; 2022 &amp; later</p>");
+    /// reader.trim_text(true).emit_general_refs(true);
+    /// let mut buf = Vec::new();
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::Start(_)));
+    /// assert_eq!(
+    ///     reader.read_event(&mut buf).unwrap(),
+    ///     Event::GeneralRef(BytesRef::new(b"copy".as_ref()))
+    /// );
+    /// assert_eq!(
+    ///     reader.read_event(&mut buf).unwrap(),
+    ///     Event::Text(BytesText::from_escaped(b" 2022 &amp; later".as_ref()))
+    /// );
+    /// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::End(_)));
+    /// ```
+    ///
+    /// [`GeneralRef`]: Event::GeneralRef
+    /// [`Text`]: Event::Text
+    pub fn emit_general_refs(&mut self, val: bool) -> &mut Reader<R> {
+        self.emit_general_refs = val;
+        self
+    }
+
+    /// Sets the number of bytes that is reserved in the buffer passed to
+    /// [`read_event`] and similar methods before an event is read.
+    ///
+    /// The buffer grows geometrically when the data does not fit into it, so
+    /// this setting only helps to avoid several small reallocations at the start
+    /// when documents are known to contain large text nodes or tags. Capacity
+    /// is never reserved beyond the [`max_token_size`], if it is set.
+    ///
+    /// (`0` by default)
+    ///
+    /// [`read_event`]: Self::read_event
+    /// [`max_token_size`]: Self::max_token_size
+    pub fn set_buffer_capacity(&mut self, capacity: usize) -> &mut Reader<R> {
+        self.buffer_capacity = capacity;
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of a single text node or markup
+    /// (a tag, comment, CDATA section, processing instruction or DOCTYPE).
+    ///
+    /// When a token exceeds that size, reading stops with
+    /// [`Error::TokenTooLarge`] as soon as the limit is reached, so malformed
+    /// or malicious input, such as an unclosed comment, cannot make the buffer
+    /// grow without bound. Because of geometric growth the buffer may still
+    /// allocate up to twice the limit.
+    ///
+    /// (unlimited by default)
+    ///
+    /// [`Error::TokenTooLarge`]: crate::Error::TokenTooLarge
+    pub fn max_token_size(&mut self, size: usize) -> &mut Reader<R> {
+        self.max_token_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum number of bytes, that could be read from the source.
+    ///
+    /// When the document is larger, reading stops with [`Error::SizeLimitExceeded`]
+    /// as soon as the limit is passed, regardless of the document structure.
+    /// That protects from untrusted streaming input of an unbounded size,
+    /// for example, an endless sequence of small elements, that [`max_token_size`]
+    /// does not prevent. A token, that does not fit into the rest of the limit,
+    /// is not read into the buffer completely.
+    ///
+    /// The limit is compared with the [`buffer_position`], that is, with the
+    /// number of bytes actually consumed from the source.
+    ///
+    /// (unlimited by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::Event;
+    /// use fast_xml::{Error, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<root><item/><item/><item/></root>");
+    /// reader.trim_text(true).max_document_size(20);
+    ///
+    /// let mut buf = Vec::new();
+    /// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Start(_))));
+    /// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Empty(_))));
+    /// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Empty(_))));
+    /// match reader.read_event(&mut buf) {
+    ///     Err(Error::SizeLimitExceeded { limit }) => assert_eq!(limit, 20),
+    ///     x => panic!("Expected `Err(SizeLimitExceeded)`, but got `{:?}`", x),
+    /// }
+    /// ```
+    ///
+    /// [`Error::SizeLimitExceeded`]: crate::Error::SizeLimitExceeded
+    /// [`max_token_size`]: Self::max_token_size
+    /// [`buffer_position`]: Self::buffer_position
+    pub fn max_document_size(&mut self, size: usize) -> &mut Reader<R> {
+        self.max_document_size = Some(size);
+        self
+    }
+
+    /// Sets the size, in bytes, of chunks in which long text nodes are returned.
+    ///
+    /// When a text node is longer than that size, it is returned as several
+    /// consecutive [`Text`] events, so huge text content, such as base64-encoded
+    /// data, can be processed incrementally without holding the whole node in
+    /// the buffer. Concatenation of the chunks gives the original text.
+    ///
+    /// A chunk never ends inside a multi-byte UTF-8 character or inside an entity
+    /// or character reference like `&amp;` or `&#x20;`, so each chunk can be
+    /// decoded and unescaped independently. To achieve that, a chunk can be
+    /// a few bytes longer than the size. Boundaries of characters are detected
+    /// for UTF-8 and other ASCII-compatible encodings only.
+    ///
+    /// Leading whitespaces are trimmed only from the first chunk and trailing
+    /// whitespaces only from the last chunk of a text node (see [`trim_text`]),
+    /// so whitespaces that span several chunks are trimmed only partially and
+    /// the last chunk could be empty.
+    ///
+    /// Note, that [`read_text`] and the serde deserializer expect the whole
+    /// text in one event, so they should not be used with that setting.
+    ///
+    /// (unlimited by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::Event;
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<data>01&amp;23456</data>");
+    /// reader.trim_text(true).text_chunk_size(4);
+    ///
+    /// let mut chunks = Vec::new();
+    /// loop {
+    ///     match reader.read_event(&mut Vec::new()).unwrap() {
+    ///         Event::Text(e) => chunks.push(e.unescape_and_decode(&reader).unwrap()),
+    ///         Event::Eof => break,
+    ///         _ => (),
+    ///     }
+    /// }
+    /// // The first chunk is extended up to the end of the `&amp;` reference
+    /// assert_eq!(chunks, ["01&", "2345", "6"]);
+    /// ```
+    ///
+    /// [`Text`]: Event::Text
+    /// [`trim_text`]: Self::trim_text
+    /// [`read_text`]: Self::read_text
+    pub fn text_chunk_size(&mut self, size: usize) -> &mut Reader<R> {
+        self.text_chunk_size = Some(size.max(1));
+        self
+    }
+
+    /// Sets limits on the number of attributes in an element and on the length
+    /// of their values, that are checked when attributes of elements, read by
+    /// this reader, are iterated.
+    ///
+    /// Limits do not affect reading of events, they are checked lazily by the
+    /// [`Attributes`] iterator, so elements with a few short attributes pay
+    /// nothing for that. When a limit is exceeded, the iterator returns
+    /// [`AttrError::TooMany`] or [`AttrError::TooLong`]. Use [`AttrLimits::STRICT`]
+    /// for reasonable limits.
+    ///
+    /// ([`AttrLimits::UNLIMITED`] by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::attributes::{AttrError, AttrLimits};
+    /// use fast_xml::events::Event;
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<tag a='1' b='2' c='3'/>");
+    /// reader.trim_text(true);
+    /// reader.attribute_limits(AttrLimits::STRICT).max_attributes(2);
+    ///
+    /// match reader.read_event(&mut Vec::new()).unwrap() {
+    ///     Event::Empty(e) => {
+    ///         let mut attrs = e.attributes();
+    ///         assert!(attrs.next().unwrap().is_ok());
+    ///         assert!(attrs.next().unwrap().is_ok());
+    ///         assert_eq!(attrs.next(), Some(Err(AttrError::TooMany(16))));
+    ///     }
+    ///     e => panic!("Expected `Empty`, but got `{:?}`", e),
+    /// }
+    /// ```
+    ///
+    /// [`Attributes`]: crate::events::attributes::Attributes
+    /// [`AttrError::TooMany`]: crate::events::attributes::AttrError::TooMany
+    /// [`AttrError::TooLong`]: crate::events::attributes::AttrError::TooLong
+    pub fn attribute_limits(&mut self, limits: AttrLimits) -> &mut Reader<R> {
+        self.attribute_limits = limits;
+        self
+    }
+
+    /// Sets the maximum number of attributes in an element. See [`attribute_limits`]
+    ///
+    /// (unlimited by default)
+    ///
+    /// [`attribute_limits`]: Self::attribute_limits
+    pub fn max_attributes(&mut self, max: usize) -> &mut Reader<R> {
+        self.attribute_limits.max_attributes = max;
+        self
+    }
+
+    /// Sets the maximum length of an attribute value in bytes, before unescaping.
+    /// See [`attribute_limits`]
+    ///
+    /// (unlimited by default)
+    ///
+    /// [`attribute_limits`]: Self::attribute_limits
+    pub fn max_attribute_value_len(&mut self, max: usize) -> &mut Reader<R> {
+        self.attribute_limits.max_value_len = max;
+        self
+    }
+
     /// Gets the current byte position in the input data.
     ///
     /// Useful when debugging errors.
     pub fn buffer_position(&self) -> usize {
+        // the peeked event is not consumed yet
+        if let Some((_, position)) = self.peeked {
+            return position;
+        }
         // when internal state is Opened, we have actually read until '<',
         // which we don't want to show
         if let TagState::Opened = self.tag_state {
@@ -247,34 +841,87 @@ impl<R: BufRead> Reader<R> {
         }
     }
 
+    /// Sets the current byte position in the input data, that is returned by
+    /// [`buffer_position`] and used to calculate positions of next events.
+    ///
+    /// The reader counts only bytes that it has read itself. Use that method
+    /// to resynchronize the position after reading bytes directly from the
+    /// underlying reader, obtained by [`get_mut`].
+    ///
+    /// [`buffer_position`]: Self::buffer_position
+    /// [`get_mut`]: Self::get_mut
+    pub fn set_buffer_position(&mut self, position: usize) -> &mut Reader<R> {
+        // when internal state is Opened, the '<' is already read
+        self.buf_position = match self.tag_state {
+            TagState::Opened => position + 1,
+            _ => position,
+        };
+        self
+    }
+
     /// private function to read until '<' is found
     /// return a `Text` event
     fn read_until_open<'i, B>(&mut self, buf: B) -> Result<Event<'i>>
     where
         R: XmlSource<'i, B>,
     {
+        // Continuation of a text, returned in chunks, should not be trimmed
+        let continued = matches!(self.tag_state, TagState::Text);
         self.tag_state = TagState::Opened;
 
-        if self.trim_text_start {
-            self.reader.skip_whitespace(&mut self.buf_position)?;
+        if self.trim_text_start && !continued {
+            self.reader
+                .skip_whitespace(&self.extra_whitespace, &mut self.buf_position)?;
             if self.reader.skip_one(b'<', &mut self.buf_position)? {
                 return self.read_event_buffered(buf);
             }
         }
 
-        match self
-            .reader
-            .read_bytes_until(b'<', buf, &mut self.buf_position)
-        {
-            Ok(Some(bytes)) if self.trim_text_end => {
+        let limit = self.token_limit();
+        let result = match self.text_chunk_size {
+            Some(chunk) => self
+                .reader
+                .read_text(buf, &mut self.buf_position, limit, chunk),
+            None => self
+                .reader
+                .read_bytes_until(b'<', buf, &mut self.buf_position, limit)
+                .map(|bytes| bytes.map(|bytes| (bytes, true))),
+        };
+        let result = match result {
+            Ok(Some((bytes, false))) => {
+                self.tag_state = TagState::Text;
+                Ok(Some(bytes))
+            }
+            Ok(bytes) => Ok(bytes.map(|(bytes, _)| bytes)),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(Some(bytes)) if self.trim_text_end && matches!(self.tag_state, TagState::Opened) => {
                 // Skip the ending '<
+                // The last chunk of a text could consist only of whitespaces,
+                // leading whitespaces of other texts already skipped
+                let extra = &self.extra_whitespace;
                 let len = bytes
                     .iter()
-                    .rposition(|&b| !is_whitespace(b))
-                    .map_or_else(|| bytes.len(), |p| p + 1);
-                Ok(Event::Text(BytesText::from_escaped(&bytes[..len])))
+                    .rposition(|&b| !is_whitespace(b) && !extra.contains(&b))
+                    .map_or_else(|| if continued { 0 } else { bytes.len() }, |p| p + 1);
+                Ok(Event::Text(BytesText::from_escaped(
+                    self.normalize_text(&bytes[..len]),
+                )))
             }
-            Ok(Some(bytes)) => Ok(Event::Text(BytesText::from_escaped(bytes))),
+            Ok(Some(bytes))
+                if self.emit_whitespace
+                    && !bytes.is_empty()
+                    && bytes.iter().all(|&b| is_whitespace(b)) =>
+            {
+                Ok(Event::Text(
+                    BytesText::from_escaped(self.normalize(bytes)).into_whitespace(),
+                ))
+            }
+            Ok(Some(bytes)) => Ok(Event::Text(BytesText::from_escaped(
+                self.normalize_text(bytes),
+            ))),
             Ok(None) => Ok(Event::Eof),
             Err(e) => Err(e),
         }
@@ -287,34 +934,44 @@ impl<R: BufRead> Reader<R> {
         R: XmlSource<'i, B>,
     {
         self.tag_state = TagState::Closed;
+        let limit = self.token_limit();
 
         match self.reader.peek_one() {
             // `<!` - comment, CDATA or DOCTYPE declaration
-            Ok(Some(b'!')) => match self.reader.read_bang_element(buf, &mut self.buf_position) {
-                Ok(None) => Ok(Event::Eof),
-                Ok(Some((bang_type, bytes))) => self.read_bang(bang_type, bytes),
-                Err(e) => Err(e),
-            },
+            Ok(Some(b'!')) => {
+                match self
+                    .reader
+                    .read_bang_element(buf, &mut self.buf_position, limit)
+                {
+                    Ok(None) => Ok(Event::Eof),
+                    Ok(Some((bang_type, bytes))) => self.read_bang(bang_type, bytes),
+                    Err(e) => Err(e),
+                }
+            }
             // `</` - closing tag
-            Ok(Some(b'/')) => match self
-                .reader
-                .read_bytes_until(b'>', buf, &mut self.buf_position)
-            {
-                Ok(None) => Ok(Event::Eof),
-                Ok(Some(bytes)) => self.read_end(bytes),
-                Err(e) => Err(e),
-            },
+            Ok(Some(b'/')) => {
+                match self
+                    .reader
+                    .read_bytes_until(b'>', buf, &mut self.buf_position, limit)
+                {
+                    Ok(None) => Ok(Event::Eof),
+                    Ok(Some(bytes)) => self.read_end(bytes),
+                    Err(e) => Err(e),
+                }
+            }
             // `<?` - processing instruction
-            Ok(Some(b'?')) => match self
-                .reader
-                .read_bytes_until(b'>', buf, &mut self.buf_position)
-            {
-                Ok(None) => Ok(Event::Eof),
-                Ok(Some(bytes)) => self.read_question_mark(bytes),
-                Err(e) => Err(e),
-            },
+            Ok(Some(b'?')) => {
+                match self
+                    .reader
+                    .read_bytes_until(b'>', buf, &mut self.buf_position, limit)
+                {
+                    Ok(None) => Ok(Event::Eof),
+                    Ok(Some(bytes)) => self.read_question_mark(bytes),
+                    Err(e) => Err(e),
+                }
+            }
             // `<...` - opening or self-closed tag
-            Ok(Some(_)) => match self.reader.read_element(buf, &mut self.buf_position) {
+            Ok(Some(_)) => match self.reader.read_element(buf, &mut self.buf_position, limit) {
                 Ok(None) => Ok(Event::Eof),
                 Ok(Some(bytes)) => self.read_start(bytes),
                 Err(e) => Err(e),
@@ -340,6 +997,15 @@ impl<R: BufRead> Reader<R> {
         } else {
             &buf[1..]
         };
+        if self.check_names {
+            // Whitespaces after the name are allowed even if they are not trimmed
+            let end = name
+                .iter()
+                .rposition(|&b| !is_whitespace(b))
+                .map_or(0, |p| p + 1);
+            // `buf` starts with `/` just after the `<`
+            self.check_name(&name[..end], self.buf_position - buf.len())?;
+        }
         if self.check_end_names {
             let mismatch_err = |expected: &[u8], found: &[u8], buf_position: &mut usize| {
                 *buf_position -= buf.len();
@@ -384,10 +1050,17 @@ impl<R: BufRead> Reader<R> {
                         return Err(Error::UnexpectedToken("--".to_string()));
                     }
                 }
-                Ok(Event::Comment(BytesText::from_escaped(&buf[3..len - 2])))
+                Ok(Event::Comment(BytesText::from_escaped(
+                    self.normalize(&buf[3..len - 2]),
+                )))
             }
             BangType::CData if uncased_starts_with(buf, b"![CDATA[") => {
-                Ok(Event::CData(BytesCData::new(&buf[8..])))
+                let cdata = BytesCData::new(self.normalize(&buf[8..]));
+                if self.merge_cdata_as_text {
+                    Ok(Event::Text(cdata.escape()))
+                } else {
+                    Ok(Event::CData(cdata))
+                }
             }
             BangType::DocType if uncased_starts_with(buf, b"!DOCTYPE") => {
                 let start = buf[8..]
@@ -395,7 +1068,28 @@ impl<R: BufRead> Reader<R> {
                     .position(|b| !is_whitespace(*b))
                     .unwrap_or_else(|| len - 8);
                 debug_assert!(start < len - 8, "DocType must have a name");
-                Ok(Event::DocType(BytesText::from_escaped(&buf[8 + start..])))
+                let content = &buf[8 + start..];
+                if self.validate_doctype_root {
+                    // Name ends before the external ID or the internal subset
+                    let name_end = content
+                        .iter()
+                        .position(|&b| is_whitespace(b) || b == b'[')
+                        .unwrap_or(content.len());
+                    self.doctype_name = Some(content[..name_end].to_vec());
+                }
+                // Malformed declarations are not reported by the reader
+                #[cfg(feature = "custom-entities")]
+                if let Ok(entities) = BytesDocType::new(content).internal_entities() {
+                    for (name, value) in entities {
+                        // The first declaration of an entity is binding
+                        self.doctype_entities
+                            .entry(name.to_vec())
+                            .or_insert_with(|| value.to_vec());
+                    }
+                }
+                Ok(Event::DocType(BytesText::from_escaped(
+                    self.normalize(content),
+                )))
             }
             _ => Err(bang_type.to_err()),
         }
@@ -418,7 +1112,9 @@ impl<R: BufRead> Reader<R> {
 
                 Ok(Event::Decl(event))
             } else {
-                Ok(Event::PI(BytesText::from_escaped(&buf[1..len - 1])))
+                Ok(Event::PI(BytesText::from_escaped(
+                    self.normalize(&buf[1..len - 1]),
+                )))
             }
         } else {
             self.buf_position -= len;
@@ -435,28 +1131,116 @@ impl<R: BufRead> Reader<R> {
         Ok(Event::End(BytesEnd::owned(name)))
     }
 
+    /// Checks that the root element has the name declared in the DOCTYPE.
+    /// `len` is the length of the element content between `<` and `>`, used
+    /// to report error position at the start of the element
+    fn check_doctype_root(&mut self, name: &[u8], len: usize) -> Result<()> {
+        match self.doctype_name.take() {
+            Some(expected) if expected != name => {
+                self.buf_position -= len + 2;
+                Err(Error::DocTypeRootMismatch {
+                    expected: from_utf8(&expected).unwrap_or("").to_owned(),
+                    found: from_utf8(name).unwrap_or("").to_owned(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that `name` is a well-formed XML name. `offset` is the position
+    /// of the name in the input, used to report the error position
+    fn check_name(&self, name: &[u8], offset: usize) -> Result<()> {
+        match check_name(name) {
+            Ok(()) => Ok(()),
+            Err(pos) => Err(Error::IllFormedName {
+                offset: offset + pos,
+            }),
+        }
+    }
+
+    /// Checks that the element name and names of all parseable attributes
+    /// of the element content `buf` are well-formed XML names. `offset` is
+    /// the position of `buf` in the input
+    fn check_element_names(&self, buf: &[u8], name_end: usize, offset: usize) -> Result<()> {
+        self.check_name(&buf[..name_end], offset)?;
+
+        let mut attrs = IterState::new(name_end, false);
+        while let Some(attr) = attrs.next(buf) {
+            if let Ok(
+                Attr::DoubleQ(key, _)
+                | Attr::SingleQ(key, _)
+                | Attr::Unquoted(key, _)
+                | Attr::Empty(key),
+            ) = attr
+            {
+                self.check_name(&buf[key.clone()], offset + key.start)?;
+            }
+        }
+        Ok(())
+    }
+
     /// reads `BytesElement` starting with any character except `/`, `!` or ``?`
     /// return `Start` or `Empty` event
     fn read_start<'a, 'b>(&'a mut self, buf: &'b [u8]) -> Result<Event<'b>> {
         // TODO: do this directly when reading bufreader ...
         let len = buf.len();
         let name_end = buf.iter().position(|&b| is_whitespace(b)).unwrap_or(len);
+        // `buf` starts just after the `<` and ends just before the `>`
+        let offset = self.buf_position - len - 1;
         if let Some(&b'/') = buf.last() {
             let end = if name_end < len { name_end } else { len - 1 };
+            if self.check_names {
+                self.check_element_names(&buf[..len - 1], end, offset)?;
+            }
+            self.check_doctype_root(&buf[..end], len)?;
             if self.expand_empty_elements {
                 self.tag_state = TagState::Empty;
                 self.opened_starts.push(self.opened_buffer.len());
                 self.opened_buffer.extend(&buf[..end]);
-                Ok(Event::Start(BytesStart::borrowed(&buf[..len - 1], end)))
+                Ok(Event::Start(self.start(&buf[..len - 1], end)))
             } else {
-                Ok(Event::Empty(BytesStart::borrowed(&buf[..len - 1], end)))
+                Ok(Event::Empty(self.start(&buf[..len - 1], end)))
+            }
+        } else {
+            if self.check_names {
+                self.check_element_names(buf, name_end, offset)?;
             }
+            self.check_doctype_root(&buf[..name_end], len)?;
+            if self.check_end_names {
+                self.opened_starts.push(self.opened_buffer.len());
+                self.opened_buffer.extend(&buf[..name_end]);
+            }
+            Ok(Event::Start(self.start(buf, name_end)))
+        }
+    }
+
+    /// Creates a `Start` or `Empty` event content from the element content `buf`
+    /// with normalized line endings in attribute values
+    fn start<'b>(&self, buf: &'b [u8], name_end: usize) -> BytesStart<'b> {
+        let start = match self.normalize(buf) {
+            Cow::Borrowed(buf) => BytesStart::borrowed(buf, name_end),
+            Cow::Owned(buf) => BytesStart::owned(buf, name_end),
+        };
+        start.with_attribute_limits(self.attribute_limits)
+    }
+
+    /// Normalizes line endings in `bytes`, if [`Self::normalize_line_endings`] is `true`
+    fn normalize<'b>(&self, bytes: &'b [u8]) -> Cow<'b, [u8]> {
+        if self.normalize_line_endings {
+            normalize_line_endings(bytes)
+        } else {
+            Cow::Borrowed(bytes)
+        }
+    }
+
+    /// Normalizes line endings in the text content. If [`Self::emit_general_refs`]
+    /// is `true`, the text is returned as is and normalized in [`Self::split_general_ref`]
+    /// after splitting, so the position is advanced by lengths of the original parts
+    fn normalize_text<'b>(&self, bytes: &'b [u8]) -> Cow<'b, [u8]> {
+        if self.emit_general_refs {
+            Cow::Borrowed(bytes)
         } else {
-            if self.check_end_names {
-                self.opened_starts.push(self.opened_buffer.len());
-                self.opened_buffer.extend(&buf[..name_end]);
-            }
-            Ok(Event::Start(BytesStart::borrowed(buf, name_end)))
+            self.normalize(bytes)
         }
     }
 
@@ -505,9 +1289,133 @@ impl<R: BufRead> Reader<R> {
     /// ```
     #[inline]
     pub fn read_event<'a, 'b>(&'a mut self, buf: &'b mut Vec<u8>) -> Result<Event<'b>> {
+        self.reserve(buf);
         self.read_event_buffered(buf)
     }
 
+    /// Reads the next `Event` without consuming it. The next call of [`read_event`]
+    /// or of any other method, that reads events, returns that event again,
+    /// and subsequent calls of `peek_event` return the same event.
+    ///
+    /// The peeked event owns its data, because it should outlive the `buf`,
+    /// so peeking of each event allocates. [`buffer_position`] returns the
+    /// position before the peeked event until the event is consumed.
+    ///
+    /// Errors are not cached: if reading of the next event fails, the error
+    /// is returned from `peek_event` and the event is not consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesStart, BytesText, Event};
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<tag>text</tag>");
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    ///
+    /// assert_eq!(
+    ///     reader.peek_event(&mut buf).unwrap(),
+    ///     &Event::Start(BytesStart::borrowed_name(b"tag"))
+    /// );
+    /// assert_eq!(reader.buffer_position(), 0);
+    ///
+    /// assert_eq!(
+    ///     reader.read_event(&mut buf).unwrap(),
+    ///     Event::Start(BytesStart::borrowed_name(b"tag"))
+    /// );
+    /// assert_eq!(reader.buffer_position(), 5);
+    ///
+    /// assert_eq!(
+    ///     reader.read_event(&mut buf).unwrap(),
+    ///     Event::Text(BytesText::from_escaped_str("text"))
+    /// );
+    /// ```
+    ///
+    /// [`read_event`]: Self::read_event
+    /// [`buffer_position`]: Self::buffer_position
+    pub fn peek_event(&mut self, buf: &mut Vec<u8>) -> Result<&Event<'static>> {
+        let peeked = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => {
+                let position = self.buffer_position();
+                self.reserve(buf);
+                let event = self.read_event_buffered(buf)?.into_owned();
+                (event, position)
+            }
+        };
+        Ok(&self.peeked.get_or_insert(peeked).0)
+    }
+
+    /// Reads the next `Event` together with the `[start, end)` byte range that
+    /// it occupies in the input.
+    ///
+    /// Ranges are reported only if enabled by [`Self::with_event_spans()`].
+    /// Events that does not consume any input, such as an [`End`] event produced
+    /// by [`Self::expand_empty_elements()`] or an [`Eof`], have empty ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fast_xml::Reader;
+    /// use fast_xml::events::Event;
+    ///
+    /// let xml = "<tag>text</tag>";
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.with_event_spans(true);
+    /// let mut buf = Vec::new();
+    /// loop {
+    ///     match reader.read_event_with_span(&mut buf) {
+    ///         Ok((Event::Eof, _)) => break,
+    ///         Ok((_, span)) => println!("{}", &xml[span]),
+    ///         Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+    ///     }
+    ///     buf.clear();
+    /// }
+    /// ```
+    ///
+    /// [`End`]: events/enum.Event.html#variant.End
+    /// [`Eof`]: events/enum.Event.html#variant.Eof
+    pub fn read_event_with_span<'b>(
+        &mut self,
+        buf: &'b mut Vec<u8>,
+    ) -> Result<(Event<'b>, Range<usize>)> {
+        let start = self.buffer_position();
+        self.reserve(buf);
+        let event = self.read_event_buffered(buf)?;
+        let end = if self.event_spans {
+            self.buffer_position()
+        } else {
+            start
+        };
+        Ok((event, start..end))
+    }
+
+    /// Returns the maximum size of the next token: the [`Self::max_token_size`],
+    /// but no more than the rest of the [`Self::max_document_size`]
+    #[inline]
+    fn token_limit(&self) -> Option<usize> {
+        match self.max_document_size {
+            Some(size) => {
+                let rest = size.saturating_sub(self.buf_position);
+                Some(self.max_token_size.map_or(rest, |limit| limit.min(rest)))
+            }
+            None => self.max_token_size,
+        }
+    }
+
+    /// Reserves the [`Self::set_buffer_capacity`] bytes in the user-given buffer,
+    /// but no more than the [`Self::max_token_size`]
+    #[inline]
+    fn reserve(&self, buf: &mut Vec<u8>) {
+        let capacity = match self.max_token_size {
+            Some(limit) => self.buffer_capacity.min(limit),
+            None => self.buffer_capacity,
+        };
+        buf.reserve(capacity);
+    }
+
     /// Read text into the given buffer, and return an event that borrows from
     /// either that buffer or from the input itself, based on the type of the
     /// reader.
@@ -515,19 +1423,94 @@ impl<R: BufRead> Reader<R> {
     where
         R: XmlSource<'i, B>,
     {
+        if let Some((event, _)) = self.peeked.take() {
+            return Ok(event);
+        }
+        if let Some(text) = self.pending_text.take() {
+            self.buf_position += text.len();
+            return Ok(self.split_general_ref(BytesText::from_escaped(text)));
+        }
         let event = match self.tag_state {
             TagState::Opened => self.read_until_close(buf),
-            TagState::Closed => self.read_until_open(buf),
+            TagState::Closed | TagState::Text => self.read_until_open(buf),
             TagState::Empty => self.close_expanded_empty(),
             TagState::Exit => return Ok(Event::Eof),
         };
+        let event = match (event, self.max_document_size) {
+            // The source reports a token, that does not fit into the rest of
+            // the document, as too large, unless the token limit is reached first
+            (Err(Error::TokenTooLarge { limit: rest }), Some(limit))
+                if self.max_token_size.filter(|&max| max <= rest).is_none() =>
+            {
+                Err(Error::SizeLimitExceeded { limit })
+            }
+            (Ok(_), Some(limit)) if self.buf_position > limit => {
+                Err(Error::SizeLimitExceeded { limit })
+            }
+            (event, _) => event,
+        };
         match event {
             Err(_) | Ok(Event::Eof) => self.tag_state = TagState::Exit,
+            Ok(Event::Text(e)) if self.emit_general_refs => {
+                return Ok(self.split_general_ref(e));
+            }
             _ => {}
         }
         event
     }
 
+    /// Returns the part of the `text` before the first reference to an unknown
+    /// entity as a `Text` event, or the reference itself as a `GeneralRef` event,
+    /// if the text starts with it. The rest of the text is stored in the
+    /// [`Self::pending_text`] and its length is excluded from the position
+    /// until it is returned.
+    ///
+    /// The `text` should not be normalized yet, so the length of the rest is
+    /// the number of bytes it occupies in the input. Line endings are normalized
+    /// in the returned `Text` event
+    fn split_general_ref<'i>(&mut self, text: BytesText<'i>) -> Event<'i> {
+        let (start, end) = match find_general_ref(&text) {
+            Some(range) => range,
+            // Whitespace-only texts are already normalized
+            None if text.is_whitespace() => return Event::Text(text),
+            None => return Event::Text(self.normalize_text_event(text.into_inner())),
+        };
+        let split = if start == 0 { end } else { start };
+        let (head, rest) = match text.into_inner() {
+            Cow::Borrowed(t) => (Cow::Borrowed(&t[..split]), t[split..].to_vec()),
+            Cow::Owned(mut t) => {
+                let rest = t.split_off(split);
+                (Cow::Owned(t), rest)
+            }
+        };
+        if !rest.is_empty() {
+            self.buf_position -= rest.len();
+            self.pending_text = Some(rest);
+        }
+        if start > 0 {
+            return Event::Text(self.normalize_text_event(head));
+        }
+        // Strip the `&` and `;` around the name
+        let name = match head {
+            Cow::Borrowed(r) => Cow::Borrowed(&r[1..r.len() - 1]),
+            Cow::Owned(r) => Cow::Owned(r[1..r.len() - 1].to_vec()),
+        };
+        Event::GeneralRef(BytesRef::new(name))
+    }
+
+    /// Creates a `Text` event from the text, split by [`Self::split_general_ref`],
+    /// normalizing its line endings
+    fn normalize_text_event<'i>(&self, text: Cow<'i, [u8]>) -> BytesText<'i> {
+        let text = match text {
+            Cow::Borrowed(t) => self.normalize(t),
+            Cow::Owned(t) => match self.normalize(&t) {
+                Cow::Owned(normalized) => Cow::Owned(normalized),
+                Cow::Borrowed(_) => Cow::Owned(t),
+            },
+        };
+        BytesText::from_escaped(text)
+    }
+
     /// Resolves a potentially qualified **event name** into (namespace name, local name).
     ///
     /// *Qualified* attribute names have the form `prefix:local-name` where the`prefix` is defined
@@ -560,6 +1543,56 @@ impl<R: BufRead> Reader<R> {
         self.ns_resolver.resolve(qname, namespace_buffer, false)
     }
 
+    /// Returns all namespace bindings that are in scope at the current position
+    /// of the reader in the namespaced mode, as `(prefix, namespace name)` pairs.
+    ///
+    /// The prefix is `None` for the default namespace. Bindings are returned in
+    /// order of their declaration, bindings shadowed by a redeclaration of the same
+    /// prefix are not returned. Bindings removed by `xmlns=""` or `xmlns:prefix=""`
+    /// are not in scope, so they and the bindings they shadow are not returned too.
+    ///
+    /// `namespace_buffer` should be the same buffer that is passed to the
+    /// [`Self::read_namespaced_event`]. After a [`Start`] or [`Empty`] event
+    /// the declarations of that element are included. After an [`End`] event
+    /// the declarations of the closed element are still included, they are
+    /// removed by the next call of [`Self::read_namespaced_event`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str(r#"
+    ///     <root xmlns="urn:default" xmlns:p="urn:p">
+    ///         <p:child xmlns:p="urn:other" xmlns:q="urn:q"/>
+    ///     </root>
+    /// "#);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    /// let mut ns_buf = Vec::new();
+    ///
+    /// reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap(); // <root>
+    /// reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap(); // <p:child/>
+    ///
+    /// let bindings: Vec<_> = reader.namespaces(&ns_buf).collect();
+    /// assert_eq!(bindings, vec![
+    ///     (None, b"urn:default".as_ref()),
+    ///     (Some(b"p".as_ref()), b"urn:other".as_ref()),
+    ///     (Some(b"q".as_ref()), b"urn:q".as_ref()),
+    /// ]);
+    /// ```
+    ///
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`End`]: events/enum.Event.html#variant.End
+    pub fn namespaces<'a>(
+        &'a self,
+        namespace_buffer: &'a [u8],
+    ) -> impl Iterator<Item = (Option<&'a [u8]>, &'a [u8])> + 'a {
+        self.ns_resolver.bindings(namespace_buffer)
+    }
+
     /// Reads the next event and resolves its namespace (if applicable).
     ///
     /// # Examples
@@ -660,14 +1693,11 @@ impl<R: BufRead> Reader<R> {
 
     /// Decodes a slice using the encoding specified in the XML declaration.
     ///
-    /// Decode `bytes` with BOM sniffing and with malformed sequences replaced with the
-    /// `U+FFFD REPLACEMENT CHARACTER`.
-    ///
-    /// If no encoding is specified, defaults to UTF-8.
+    /// This is a shortcut for [`self.decoder().decode(bytes)`](Decoder::decode),
+    /// see [`Decoder::decode`] for details.
     #[inline]
-    #[cfg(feature = "encoding")]
-    pub fn decode<'b, 'c>(&'b self, bytes: &'c [u8]) -> Cow<'c, str> {
-        self.encoding.decode(bytes).0
+    pub fn decode<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        self.decoder().decode(bytes)
     }
 
     /// Decodes a UTF8 slice without BOM (Byte order mark) regardless of XML declaration.
@@ -715,32 +1745,87 @@ impl<R: BufRead> Reader<R> {
         self.encoding.decode_without_bom_handling(bytes).0
     }
 
-    /// Decodes a UTF8 slice regardless of XML declaration.
+    /// Unescapes `bytes` if [`Self::expand_entities`] is enabled, otherwise
+    /// returns them unchanged
+    pub(crate) fn unescape<'c>(
+        &self,
+        bytes: &'c [u8],
+        custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
+    ) -> Result<Cow<'c, [u8]>> {
+        if self.expand_entities {
+            do_unescape(bytes, custom_entities).map_err(Error::EscapeError)
+        } else {
+            Ok(Cow::Borrowed(bytes))
+        }
+    }
+
+    /// Returns internal general entities, declared in the internal subset of
+    /// the DOCTYPE, that was read by this reader, as a map from names of entities
+    /// to their raw replacement texts. The map is empty until the DOCTYPE is read.
     ///
-    /// Decode `bytes` with BOM sniffing and with malformed sequences replaced with the
-    /// `U+FFFD REPLACEMENT CHARACTER`.
+    /// Only entities with a literal value, like `<!ENTITY name "value">`, are
+    /// collected. Parameter entities (`<!ENTITY % name "value">`) and external
+    /// entities (`<!ENTITY name SYSTEM "uri">`) are excluded, because their
+    /// replacement texts are not available without loading external resources.
     ///
-    /// # Note
+    /// The map can be passed to the helpers, that accept custom entities, such
+    /// as [`BytesText::unescape_and_decode_with_custom_entities`].
     ///
-    /// If you instead want to use XML declared encoding, use the `encoding` feature
-    #[inline]
-    #[cfg(not(feature = "encoding"))]
-    pub fn decode<'c>(&self, bytes: &'c [u8]) -> Result<&'c str> {
-        from_utf8(bytes).map_err(Error::Utf8)
+    /// Available only with the `custom-entities` feature, so the DOCTYPE is not
+    /// parsed by readers, that do not need its entities.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::Event;
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str(r#"
+    ///     <!DOCTYPE note [<!ENTITY writer "Donald Duck."><!ENTITY % param "no">]>
+    ///     <note>&writer;</note>
+    /// "#);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::DocType(_)));
+    /// assert_eq!(reader.doctype_entities().len(), 1);
+    /// assert_eq!(reader.doctype_entities()[&b"writer"[..]], b"Donald Duck.");
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf).unwrap(), Event::Start(_)));
+    /// match reader.read_event(&mut buf).unwrap() {
+    ///     Event::Text(e) => assert_eq!(
+    ///         e.unescape_and_decode_with_custom_entities(&reader, reader.doctype_entities())
+    ///             .unwrap(),
+    ///         "Donald Duck.",
+    ///     ),
+    ///     e => panic!("Expected Text event, got {:?}", e),
+    /// }
+    /// ```
+    #[cfg(feature = "custom-entities")]
+    pub fn doctype_entities(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+        &self.doctype_entities
     }
 
-    /// Get utf8 decoder
+    /// Returns a [`Decoder`] that converts bytes to strings in the same way as
+    /// events produced by this reader do. Decoder captures the encoding, known at
+    /// the moment of the call, so get it after the XML declaration was read.
     #[cfg(feature = "encoding")]
     pub fn decoder(&self) -> Decoder {
         Decoder {
             encoding: self.encoding,
+            assume_ascii: self.assume_ascii,
         }
     }
 
-    /// Get utf8 decoder
+    /// Returns a [`Decoder`] that converts bytes to strings in the same way as
+    /// events produced by this reader do. Without the `encoding` feature
+    /// it always decodes UTF-8.
     #[cfg(not(feature = "encoding"))]
     pub fn decoder(&self) -> Decoder {
-        Decoder
+        Decoder {
+            assume_ascii: self.assume_ascii,
+        }
     }
 
     /// Reads until end element is found
@@ -815,6 +1900,70 @@ impl<R: BufRead> Reader<R> {
         s
     }
 
+    /// Reads the next significant event and checks that it is a start tag
+    /// (of a [`Start`] or an [`Empty`] event) with the specified name.
+    ///
+    /// Whitespace-only [`Text`] events, comments, processing instructions, XML
+    /// declaration and DOCTYPE are skipped. If the found event is not a start
+    /// tag or has a different name, [`Error::StartNotFound`] is returned.
+    /// Names are compared as written in the document, including namespace prefixes.
+    ///
+    /// `buf` is used only as a buffer for reading events, the start tag is
+    /// returned as an owned value, so it can be used together with `buf` in
+    /// the subsequent calls, such as [`read_text`] or [`read_to_end`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::{Error, Reader};
+    ///
+    /// let mut reader = Reader::from_str(r#"
+    ///     <!-- a list of users -->
+    ///     <users><user name="Alice"/></users>
+    /// "#);
+    /// let mut buf = Vec::new();
+    ///
+    /// reader.expect_start("users", &mut buf).unwrap();
+    /// let user = reader.expect_start("user", &mut buf).unwrap();
+    /// assert_eq!(user.try_get_attribute("name").unwrap().unwrap().value, &b"Alice"[..]);
+    ///
+    /// match reader.expect_start("user", &mut buf) {
+    ///     Err(Error::StartNotFound { expected, found }) => {
+    ///         assert_eq!(expected, "user");
+    ///         assert_eq!(found, "`End` event of element <users>");
+    ///     }
+    ///     x => panic!("Expected `Err(StartNotFound)`, but got `{:?}`", x),
+    /// }
+    /// ```
+    ///
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`read_text`]: Self::read_text
+    /// [`read_to_end`]: Self::read_to_end
+    pub fn expect_start(&mut self, name: &str, buf: &mut Vec<u8>) -> Result<BytesStart<'static>> {
+        loop {
+            buf.clear();
+            let found = match self.read_event(buf)? {
+                Event::Start(e) | Event::Empty(e) if e.name() == name.as_bytes() => {
+                    return Ok(e.into_owned())
+                }
+                Event::Text(e) if e.iter().all(|&b| is_whitespace(b)) => continue,
+                Event::Comment(_) | Event::PI(_) | Event::Decl(_) | Event::DocType(_) => continue,
+                Event::Eof => return Err(Error::UnexpectedEof(format!("<{}>", name))),
+                e => match crate::writer::describe(&e) {
+                    (kind, Some(name)) => format!("`{}` event of element <{}>", kind, name),
+                    (kind, None) => format!("`{}` event", kind),
+                },
+            };
+            return Err(Error::StartNotFound {
+                expected: name.to_string(),
+                found,
+            });
+        }
+    }
+
     /// Consumes `Reader` returning the underlying reader
     ///
     /// Can be used to compute line and column of a parsing error position
@@ -878,6 +2027,47 @@ impl<R: BufRead> Reader<R> {
     }
 
     /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Data can be read directly from the underlying reader between events,
+    /// for example, to read binary frames in protocols that interleave XML and
+    /// binary data. The next event is read from the position, where such raw
+    /// reading has stopped.
+    ///
+    /// Bytes, read directly, are not counted in the [`buffer_position`], so it
+    /// is behind by their number. Call [`set_buffer_position`] to resynchronize it.
+    ///
+    /// Raw data should be read only after markup events, such as [`Start`] or
+    /// [`End`]. When a [`Text`] event is read, the `<` character of the next
+    /// markup is already consumed from the underlying reader.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::Event;
+    /// use fast_xml::Reader;
+    /// use std::io::Read;
+    ///
+    /// let mut reader = Reader::from_reader(&b"<frame len='4'/>\x00\x01\x02\x03<next/>"[..]);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Empty(_))));
+    /// let position = reader.buffer_position();
+    ///
+    /// let mut frame = [0; 4];
+    /// reader.get_mut().read_exact(&mut frame).unwrap();
+    /// reader.set_buffer_position(position + frame.len());
+    ///
+    /// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Empty(_))));
+    /// assert_eq!(reader.buffer_position(), 27);
+    /// ```
+    ///
+    /// [`buffer_position`]: Self::buffer_position
+    /// [`set_buffer_position`]: Self::set_buffer_position
+    /// [`Start`]: Event::Start
+    /// [`End`]: Event::End
+    /// [`Text`]: Event::Text
     pub fn get_mut(&mut self) -> &mut R {
         &mut self.reader
     }
@@ -892,6 +2082,43 @@ impl Reader<BufReader<File>> {
     }
 }
 
+impl<'a> Reader<&'a mut dyn BufRead> {
+    /// Creates an XML reader from a trait object. All such readers share one
+    /// instance of the parsing code, which helps to reduce the code size of
+    /// libraries that accept arbitrary readers.
+    ///
+    /// `Box<dyn BufRead>` can be used with [`Reader::from_reader`] too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use std::io::BufRead;
+    /// use fast_xml::events::Event;
+    /// use fast_xml::Reader;
+    ///
+    /// fn count_elements(input: &mut dyn BufRead) -> fast_xml::Result<usize> {
+    ///     let mut reader = Reader::from_dyn_reader(input);
+    ///     let mut buf = Vec::new();
+    ///     let mut count = 0;
+    ///     loop {
+    ///         match reader.read_event(&mut buf)? {
+    ///             Event::Start(_) | Event::Empty(_) => count += 1,
+    ///             Event::Eof => return Ok(count),
+    ///             _ => {}
+    ///         }
+    ///         buf.clear();
+    ///     }
+    /// }
+    ///
+    /// let mut input = "<root><a/><b></b></root>".as_bytes();
+    /// assert_eq!(count_elements(&mut input).unwrap(), 3);
+    /// ```
+    pub fn from_dyn_reader(reader: &'a mut dyn BufRead) -> Self {
+        Reader::from_reader(reader)
+    }
+}
+
 impl<'a> Reader<&'a [u8]> {
     /// Creates an XML reader from a string slice.
     pub fn from_str(s: &'a str) -> Reader<&'a [u8]> {
@@ -909,6 +2136,97 @@ impl<'a> Reader<&'a [u8]> {
         self.read_event_buffered(())
     }
 
+    /// Reads all remaining events into a list. Events borrow from the input,
+    /// so the list can be iterated many times without parsing the document
+    /// again. The final [`Event::Eof`] is not included in the list.
+    ///
+    /// The events are the same as returned by [`read_event_unbuffered`], so
+    /// all settings of the reader are applied to them. With the default settings
+    /// the list represents the whole document, and writing all events with
+    /// a [`Writer`] gives the original input back.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesStart, BytesText, Event};
+    /// use fast_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<tag>text</tag>");
+    /// reader.trim_text(true);
+    /// let events = reader.parse_all().unwrap();
+    ///
+    /// for _ in 0..2 {
+    ///     let names: Vec<_> = events
+    ///         .iter()
+    ///         .filter_map(|e| match e {
+    ///             Event::Start(e) => Some(e.name()),
+    ///             _ => None,
+    ///         })
+    ///         .collect();
+    ///     assert_eq!(names, vec![b"tag"]);
+    /// }
+    /// assert_eq!(events[1], Event::Text(BytesText::from_escaped_str("text")));
+    /// ```
+    ///
+    /// [`read_event_unbuffered`]: Self::read_event_unbuffered
+    /// [`Writer`]: crate::Writer
+    pub fn parse_all(&mut self) -> Result<Vec<Event<'a>>> {
+        let mut events = Vec::new();
+        loop {
+            match self.read_event_unbuffered()? {
+                Event::Eof => return Ok(events),
+                e => events.push(e),
+            }
+        }
+    }
+
+    /// Counts elements with the specified name, that follow each other, starting
+    /// from the just read `Start` event with that name. Text and CDATA events
+    /// between elements are also counted. Comments, processing instructions,
+    /// declarations and DOCTYPEs are skipped.
+    ///
+    /// The rest of the input is scanned by a separate reader, that shares only
+    /// the settings affecting texts with this one, so the state of this reader
+    /// is not changed and, unlike a copy of it, the scan does not allocate
+    #[cfg(feature = "serialize")]
+    pub(crate) fn count_siblings(&self, name: &[u8]) -> usize {
+        let mut reader = Reader::from_reader(self.reader);
+        reader.trim_text_start = self.trim_text_start;
+        reader.trim_text_end = self.trim_text_end;
+        reader.extra_whitespace.clone_from(&self.extra_whitespace);
+        reader.check_end_names = false;
+
+        let mut count = 1;
+        // Content of the expanded empty element was already read
+        let mut is_start = !matches!(self.tag_state, TagState::Empty);
+        loop {
+            if is_start && reader.read_to_end_unbuffered(name).is_err() {
+                break;
+            }
+            is_start = match reader.read_event_unbuffered() {
+                Ok(Event::Start(e)) if e.name() == name => {
+                    count += 1;
+                    true
+                }
+                Ok(Event::Empty(e)) if e.name() == name => {
+                    count += 1;
+                    false
+                }
+                Ok(Event::Text(_)) | Ok(Event::CData(_)) => {
+                    count += 1;
+                    false
+                }
+                Ok(Event::Comment(_))
+                | Ok(Event::Decl(_))
+                | Ok(Event::PI(_))
+                | Ok(Event::DocType(_)) => false,
+                _ => break,
+            };
+        }
+        count
+    }
+
     /// Reads until end element is found
     ///
     /// Manages nested cases where parent and child elements have the same name
@@ -973,6 +2291,9 @@ trait XmlSource<'r, B> {
     /// - `buf`: Buffer that could be filled from an input (`Self`) and
     ///   from which [events] could borrow their data
     /// - `position`: Will be increased by amount of bytes consumed
+    /// - `limit`: Maximum size of the returned slice. If the data does not fit,
+    ///   [`Error::TokenTooLarge`] is returned and `position` is not changed, so
+    ///   it points to the start of the token
     ///
     /// [events]: crate::events::Event
     fn read_bytes_until(
@@ -980,8 +2301,37 @@ trait XmlSource<'r, B> {
         byte: u8,
         buf: B,
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<&'r [u8]>>;
 
+    /// Read a text until `<` is found, like [`read_bytes_until`], but return
+    /// a chunk of the text, when it is longer than `chunk` bytes. The chunk
+    /// ends at the first position after `chunk` bytes, that does not split
+    /// a UTF-8 character, an entity reference or a `\r\n` pair (see [`text_chunk_end`]).
+    ///
+    /// Returns the read slice and `true`, if the text is finished, or `false`,
+    /// if it is a chunk and more text follows. In the latter case `<` is not
+    /// consumed. If input (`Self`) is exhausted, returns `None`.
+    ///
+    /// # Parameters
+    /// - `buf`: Buffer that could be filled from an input (`Self`) and
+    ///   from which [events] could borrow their data
+    /// - `position`: Will be increased by amount of bytes consumed
+    /// - `limit`: Maximum size of the returned slice. If the data does not fit,
+    ///   [`Error::TokenTooLarge`] is returned and `position` is not changed, so
+    ///   it points to the start of the token
+    /// - `chunk`: Size of a chunk
+    ///
+    /// [`read_bytes_until`]: Self::read_bytes_until
+    /// [events]: crate::events::Event
+    fn read_text(
+        &mut self,
+        buf: B,
+        position: &mut usize,
+        limit: Option<usize>,
+        chunk: usize,
+    ) -> Result<Option<(&'r [u8], bool)>>;
+
     /// Read input until comment, CDATA or processing instruction is finished.
     ///
     /// This method expect that `<` already was read.
@@ -995,12 +2345,16 @@ trait XmlSource<'r, B> {
     /// - `buf`: Buffer that could be filled from an input (`Self`) and
     ///   from which [events] could borrow their data
     /// - `position`: Will be increased by amount of bytes consumed
+    /// - `limit`: Maximum size of the returned slice. If the data does not fit,
+    ///   [`Error::TokenTooLarge`] is returned and `position` is not changed, so
+    ///   it points to the start of the token
     ///
     /// [events]: crate::events::Event
     fn read_bang_element(
         &mut self,
         buf: B,
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<(BangType, &'r [u8])>>;
 
     /// Read input until XML element is closed by approaching a `>` symbol.
@@ -1022,12 +2376,22 @@ trait XmlSource<'r, B> {
     /// - `buf`: Buffer that could be filled from an input (`Self`) and
     ///   from which [events] could borrow their data
     /// - `position`: Will be increased by amount of bytes consumed
+    /// - `limit`: Maximum size of the returned slice. If the data does not fit,
+    ///   [`Error::TokenTooLarge`] is returned and `position` is not changed, so
+    ///   it points to the start of the token
     ///
     /// [defined]: https://www.w3.org/TR/xml11/#NT-AttValue
     /// [events]: crate::events::Event
-    fn read_element(&mut self, buf: B, position: &mut usize) -> Result<Option<&'r [u8]>>;
+    fn read_element(
+        &mut self,
+        buf: B,
+        position: &mut usize,
+        limit: Option<usize>,
+    ) -> Result<Option<&'r [u8]>>;
 
-    fn skip_whitespace(&mut self, position: &mut usize) -> Result<()>;
+    /// Consumes XML whitespaces and `extra` characters until the next other
+    /// character or EOF
+    fn skip_whitespace(&mut self, extra: &[u8], position: &mut usize) -> Result<()>;
 
     fn skip_one(&mut self, byte: u8, position: &mut usize) -> Result<bool>;
 
@@ -1043,6 +2407,7 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
         byte: u8,
         buf: &'b mut Vec<u8>,
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<&'b [u8]>> {
         let mut read = 0;
         let mut done = false;
@@ -1059,27 +2424,80 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
                     }
                 };
 
-                match memchr::memchr(byte, available) {
-                    Some(i) => {
-                        buf.extend_from_slice(&available[..i]);
-                        done = true;
-                        i + 1
-                    }
-                    None => {
-                        buf.extend_from_slice(available);
-                        available.len()
-                    }
+                let (consumed, used) = match memchr::memchr(byte, available) {
+                    Some(i) => {
+                        done = true;
+                        (&available[..i], i + 1)
+                    }
+                    None => (available, available.len()),
+                };
+                check_token_size(buf.len() - start + consumed.len(), limit)?;
+                buf.extend_from_slice(consumed);
+                used
+            };
+            self.consume(used);
+            read += used;
+        }
+        *position += read;
+
+        if read == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(&buf[start..]))
+        }
+    }
+
+    fn read_text(
+        &mut self,
+        buf: &'b mut Vec<u8>,
+        position: &mut usize,
+        limit: Option<usize>,
+        chunk: usize,
+    ) -> Result<Option<(&'b [u8], bool)>> {
+        let mut read = 0;
+        let start = buf.len();
+        loop {
+            let available = match self.fill_buf() {
+                Ok([]) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    *position += read;
+                    return Err(Error::Io(e));
                 }
             };
+
+            let (text, found) = match memchr::memchr(b'<', available) {
+                Some(i) => (&available[..i], true),
+                None => (available, false),
+            };
+            let len = buf.len() - start;
+            buf.extend_from_slice(text);
+            // All bytes before `len` was checked in the previous iterations,
+            // so the end of the chunk is always in the current `text`
+            if let Some(end) = text_chunk_end(&buf[start..], chunk) {
+                buf.truncate(start + end);
+                check_token_size(end, limit)?;
+                let used = end - len;
+                self.consume(used);
+                *position += read + used;
+                return Ok(Some((&buf[start..], false)));
+            }
+            check_token_size(buf.len() - start, limit)?;
+            // Consume also the `<`
+            let used = if found { text.len() + 1 } else { text.len() };
             self.consume(used);
             read += used;
+            if found {
+                break;
+            }
         }
         *position += read;
 
         if read == 0 {
             Ok(None)
         } else {
-            Ok(Some(&buf[start..]))
+            Ok(Some((&buf[start..], true)))
         }
     }
 
@@ -1087,6 +2505,7 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
         &mut self,
         buf: &'b mut Vec<u8>,
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<(BangType, &'b [u8])>> {
         // Peeked one bang ('!') before being called, so it's guaranteed to
         // start with it.
@@ -1104,6 +2523,7 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
                 Ok(n) if n.is_empty() => return Err(bang_type.to_err()),
                 Ok(available) => {
                     if let Some((consumed, used)) = bang_type.parse(available, read) {
+                        check_token_size(buf.len() - start + consumed.len(), limit)?;
                         buf.extend_from_slice(consumed);
 
                         self.consume(used);
@@ -1112,6 +2532,7 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
                         *position += read;
                         break;
                     } else {
+                        check_token_size(buf.len() - start + available.len(), limit)?;
                         buf.extend_from_slice(available);
 
                         let used = available.len();
@@ -1139,6 +2560,7 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
         &mut self,
         buf: &'b mut Vec<u8>,
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<&'b [u8]>> {
         let mut state = ReadElementState::Elem;
         let mut read = 0;
@@ -1146,9 +2568,17 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
         let start = buf.len();
         loop {
             match self.fill_buf() {
-                Ok(n) if n.is_empty() => break,
+                Ok(n) if n.is_empty() => {
+                    if read == 0 {
+                        return Ok(None);
+                    }
+                    // Note: Do not update position, so the error points to the same
+                    // place as in the borrowed reader rather than at the EOF.
+                    return Err(Error::UnexpectedEof("Element".to_string()));
+                }
                 Ok(available) => {
                     if let Some((consumed, used)) = state.change(available) {
+                        check_token_size(buf.len() - start + consumed.len(), limit)?;
                         buf.extend_from_slice(consumed);
 
                         self.consume(used);
@@ -1157,6 +2587,7 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
                         *position += read;
                         break;
                     } else {
+                        check_token_size(buf.len() - start + available.len(), limit)?;
                         buf.extend_from_slice(available);
 
                         let used = available.len();
@@ -1172,20 +2603,19 @@ impl<'b, R: BufRead> XmlSource<'b, &'b mut Vec<u8>> for R {
             };
         }
 
-        if read == 0 {
-            Ok(None)
-        } else {
-            Ok(Some(&buf[start..]))
-        }
+        Ok(Some(&buf[start..]))
     }
 
     /// Consume and discard all the whitespace until the next non-whitespace
     /// character or EOF.
-    fn skip_whitespace(&mut self, position: &mut usize) -> Result<()> {
+    fn skip_whitespace(&mut self, extra: &[u8], position: &mut usize) -> Result<()> {
         loop {
             break match self.fill_buf() {
                 Ok(n) => {
-                    let count = n.iter().position(|b| !is_whitespace(*b)).unwrap_or(n.len());
+                    let count = n
+                        .iter()
+                        .position(|b| !is_whitespace(*b) && !extra.contains(b))
+                        .unwrap_or(n.len());
                     if count > 0 {
                         self.consume(count);
                         *position += count;
@@ -1235,17 +2665,22 @@ impl<'a> XmlSource<'a, ()> for &'a [u8] {
         byte: u8,
         _buf: (),
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<&'a [u8]>> {
         if self.is_empty() {
             return Ok(None);
         }
 
+        // Note: Do not update position when the token is too large, so the
+        // error points to the start of that token
         Ok(Some(if let Some(i) = memchr::memchr(byte, self) {
+            check_token_size(i, limit)?;
             *position += i + 1;
             let bytes = &self[..i];
             *self = &self[i + 1..];
             bytes
         } else {
+            check_token_size(self.len(), limit)?;
             *position += self.len();
             let bytes = &self[..];
             *self = &[];
@@ -1253,10 +2688,37 @@ impl<'a> XmlSource<'a, ()> for &'a [u8] {
         }))
     }
 
+    fn read_text(
+        &mut self,
+        _buf: (),
+        position: &mut usize,
+        limit: Option<usize>,
+        chunk: usize,
+    ) -> Result<Option<(&'a [u8], bool)>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+
+        let text = match memchr::memchr(b'<', self) {
+            Some(i) => &self[..i],
+            None => self,
+        };
+        if let Some(end) = text_chunk_end(text, chunk) {
+            check_token_size(end, limit)?;
+            *position += end;
+            let bytes = &self[..end];
+            *self = &self[end..];
+            return Ok(Some((bytes, false)));
+        }
+        self.read_bytes_until(b'<', (), position, limit)
+            .map(|bytes| bytes.map(|bytes| (bytes, true)))
+    }
+
     fn read_bang_element(
         &mut self,
         _buf: (),
         position: &mut usize,
+        limit: Option<usize>,
     ) -> Result<Option<(BangType, &'a [u8])>> {
         // Peeked one bang ('!') before being called, so it's guaranteed to
         // start with it.
@@ -1265,6 +2727,7 @@ impl<'a> XmlSource<'a, ()> for &'a [u8] {
         let bang_type = BangType::new(self[1..].first().copied())?;
 
         if let Some((bytes, i)) = bang_type.parse(self, 0) {
+            check_token_size(bytes.len(), limit)?;
             *position += i;
             *self = &self[i..];
             return Ok(Some((bang_type, bytes)));
@@ -1275,7 +2738,12 @@ impl<'a> XmlSource<'a, ()> for &'a [u8] {
         Err(bang_type.to_err())
     }
 
-    fn read_element(&mut self, _buf: (), position: &mut usize) -> Result<Option<&'a [u8]>> {
+    fn read_element(
+        &mut self,
+        _buf: (),
+        position: &mut usize,
+        limit: Option<usize>,
+    ) -> Result<Option<&'a [u8]>> {
         if self.is_empty() {
             return Ok(None);
         }
@@ -1283,6 +2751,7 @@ impl<'a> XmlSource<'a, ()> for &'a [u8] {
         let mut state = ReadElementState::Elem;
 
         if let Some((bytes, i)) = state.change(self) {
+            check_token_size(bytes.len(), limit)?;
             *position += i;
             *self = &self[i..];
             return Ok(Some(bytes));
@@ -1295,10 +2764,10 @@ impl<'a> XmlSource<'a, ()> for &'a [u8] {
         // FIXME: Figure out why the other one works without UnexpectedEof
     }
 
-    fn skip_whitespace(&mut self, position: &mut usize) -> Result<()> {
+    fn skip_whitespace(&mut self, extra: &[u8], position: &mut usize) -> Result<()> {
         let whitespaces = self
             .iter()
-            .position(|b| !is_whitespace(*b))
+            .position(|b| !is_whitespace(*b) && !extra.contains(b))
             .unwrap_or(self.len());
         *position += whitespaces;
         *self = &self[whitespaces..];
@@ -1423,6 +2892,156 @@ impl ReadElementState {
     }
 }
 
+/// Returns [`Error::TokenTooLarge`] if a token of `len` bytes exceeds the `limit`
+#[inline]
+fn check_token_size(len: usize, limit: Option<usize>) -> Result<()> {
+    match limit {
+        Some(limit) if len > limit => Err(Error::TokenTooLarge { limit }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the end of the first chunk of the `text`, that is at least `chunk`
+/// bytes long and does not end inside a UTF-8 character, an entity or character
+/// reference or between `\r` and `\n` (which are normalized together to one
+/// line feed). Returns `None` if the `text` is not longer than `chunk` or such
+/// position was not found in the `text`.
+///
+/// The decision is based only on the bytes up to the returned position, so
+/// the result does not change when more text is appended.
+fn text_chunk_end(text: &[u8], chunk: usize) -> Option<usize> {
+    if text.len() <= chunk {
+        return None;
+    }
+    // A reference is started, if the last `&` is not followed by `;` or by
+    // a character, that cannot appear in a reference
+    let mut in_reference = match memchr::memrchr(b'&', &text[..chunk]) {
+        Some(i) => !text[i + 1..chunk]
+            .iter()
+            .any(|&b| b == b';' || is_whitespace(b)),
+        None => false,
+    };
+    for (i, &b) in text[chunk..].iter().enumerate() {
+        let crlf = b == b'\n' && text[chunk + i - 1] == b'\r';
+        // UTF-8 continuation bytes have the form 0b10xx_xxxx
+        if !in_reference && !crlf && b & 0xC0 != 0x80 {
+            return Some(chunk + i);
+        }
+        match b {
+            b'&' => in_reference = true,
+            b';' => in_reference = false,
+            b if is_whitespace(b) => in_reference = false,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Returns the range of the first reference `&name;` in the `text` to a general
+/// entity, which is not predefined. Character references and malformed references
+/// are skipped
+fn find_general_ref(text: &[u8]) -> Option<(usize, usize)> {
+    let mut from = 0;
+    while let Some(i) = memchr::memchr(b'&', &text[from..]) {
+        let start = from + i;
+        let end = start + memchr::memchr(b';', &text[start..])?;
+        let name = &text[start + 1..end];
+        // Not a reference, continue search from the next `&` or whitespace
+        if let Some(i) = name.iter().position(|&b| b == b'&' || is_whitespace(b)) {
+            from = start + 1 + i;
+            continue;
+        }
+        match name {
+            [] | [b'#', ..] | b"lt" | b"gt" | b"amp" | b"apos" | b"quot" => from = end + 1,
+            _ => return Some((start, end + 1)),
+        }
+    }
+    None
+}
+
+/// Checks whether a character matches the XML [`NameStartChar`] production
+///
+/// [`NameStartChar`]: https://www.w3.org/TR/xml/#NT-NameStartChar
+#[inline]
+fn is_name_start_char(ch: char) -> bool {
+    matches!(ch,
+        ':' | 'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+/// Checks whether a character matches the XML [`NameChar`] production
+///
+/// [`NameChar`]: https://www.w3.org/TR/xml/#NT-NameChar
+#[inline]
+fn is_name_char(ch: char) -> bool {
+    is_name_start_char(ch)
+        || matches!(ch,
+            '-' | '.' | '0'..='9'
+            | '\u{B7}'
+            | '\u{0300}'..='\u{036F}'
+            | '\u{203F}'..='\u{2040}'
+        )
+}
+
+/// Checks that `name` matches the XML [`Name`] production. Returns the position
+/// of the first invalid character in case of error. An empty name is invalid
+/// at position 0, the name with invalid UTF-8 sequence is invalid at the position
+/// of that sequence.
+///
+/// [`Name`]: https://www.w3.org/TR/xml/#NT-Name
+fn check_name(name: &[u8]) -> std::result::Result<(), usize> {
+    let (valid, invalid_at) = match from_utf8(name) {
+        Ok(valid) => (valid, None),
+        Err(e) => {
+            let end = e.valid_up_to();
+            // Prefix before the invalid sequence is always valid UTF-8
+            (from_utf8(&name[..end]).unwrap_or_default(), Some(end))
+        }
+    };
+    let mut chars = valid.char_indices();
+    match chars.next() {
+        Some((_, ch)) if is_name_start_char(ch) => {}
+        Some((i, _)) => return Err(i),
+        None => return Err(0),
+    }
+    if let Some((i, _)) = chars.find(|&(_, ch)| !is_name_char(ch)) {
+        return Err(i);
+    }
+    invalid_at.map_or(Ok(()), Err)
+}
+
+/// Replaces each `\r\n` pair and each lone `\r` with `\n`. Borrows `bytes`,
+/// if there is nothing to replace
+fn normalize_line_endings(bytes: &[u8]) -> Cow<'_, [u8]> {
+    let mut rest = match memchr::memchr(b'\r', bytes) {
+        Some(_) => bytes,
+        None => return Cow::Borrowed(bytes),
+    };
+    let mut normalized = Vec::with_capacity(bytes.len());
+    while let Some(i) = memchr::memchr(b'\r', rest) {
+        normalized.extend_from_slice(&rest[..i]);
+        normalized.push(b'\n');
+        rest = match rest.get(i + 1) {
+            Some(b'\n') => &rest[i + 2..],
+            _ => &rest[i + 1..],
+        };
+    }
+    normalized.extend_from_slice(rest);
+    Cow::Owned(normalized)
+}
+
 /// A function to check whether the byte is a whitespace (blank, new line, carriage return or tab)
 #[inline]
 pub(crate) fn is_whitespace(b: u8) -> bool {
@@ -1482,6 +3101,18 @@ impl NamespaceEntry {
         }
     }
 
+    /// Gets the namespace prefix slice out of namespace buffer
+    ///
+    /// Returns `None` if this entry defines a default namespace
+    #[inline]
+    fn prefix<'b>(&self, buffer: &'b [u8]) -> Option<&'b [u8]> {
+        if self.prefix_len == 0 {
+            None
+        } else {
+            Some(&buffer[self.start..self.start + self.prefix_len])
+        }
+    }
+
     /// Check if the namespace matches the potentially qualified name
     #[inline]
     fn is_match(&self, buffer: &[u8], qname: &[u8]) -> bool {
@@ -1534,6 +3165,28 @@ impl NamespaceResolver {
             .and_then(|n| n.namespace(buffer))
     }
 
+    /// Returns all [namespace bindings] that are in scope, in order of their
+    /// declaration. Bindings that are shadowed by later declarations of the same
+    /// prefix, and bindings that remove a namespace using `xmlns[:prefix]=""`
+    /// are not returned.
+    ///
+    /// [namespace bindings]: https://www.w3.org/TR/xml-names11/#dt-NSDecl
+    fn bindings<'b>(
+        &'b self,
+        buffer: &'b [u8],
+    ) -> impl Iterator<Item = (Option<&'b [u8]>, &'b [u8])> + 'b {
+        self.bindings
+            .iter()
+            .enumerate()
+            .filter(move |(i, n)| {
+                let prefix = n.prefix(buffer);
+                !self.bindings[i + 1..]
+                    .iter()
+                    .any(|m| m.prefix(buffer) == prefix)
+            })
+            .filter_map(move |(_, n)| Some((n.prefix(buffer), n.namespace(buffer)?)))
+    }
+
     /// Ends a top-most scope by popping all [namespace binding], that was added by
     /// last call to [`Self::push()`].
     ///
@@ -1640,32 +3293,387 @@ impl NamespaceResolver {
     }
 }
 
-/// Utf8 Decoder
+/// A stack of [namespace bindings] (prefix to namespace name mappings) that
+/// are in scope at some point of a document.
+///
+/// This is the same structure that [`Reader::read_namespaced_event`] maintains
+/// internally, but it can be used standalone together with [`resolve_namespace`]
+/// to resolve names of selected elements without reading the whole document in
+/// the namespaced mode. Call [`push`] for each start element and [`pop`] for
+/// each end element to keep bindings in sync with the document.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use fast_xml::{resolve_namespace, NamespaceBindings};
+/// use fast_xml::events::BytesStart;
+///
+/// let mut bindings = NamespaceBindings::new();
+/// bindings.push(&BytesStart::borrowed(br#"root xmlns="urn:default" xmlns:p="urn:p""#, 4));
+///
+/// assert_eq!(resolve_namespace(b"p:child", &bindings), Some(b"urn:p".as_ref()));
+/// assert_eq!(resolve_namespace(b"child", &bindings), Some(b"urn:default".as_ref()));
+///
+/// bindings.pop();
+/// assert_eq!(resolve_namespace(b"p:child", &bindings), None);
+/// ```
+///
+/// [namespace bindings]: https://www.w3.org/TR/xml-names11/#dt-NSDecl
+/// [`push`]: Self::push
+/// [`pop`]: Self::pop
+#[derive(Debug, Default, Clone)]
+pub struct NamespaceBindings {
+    resolver: NamespaceResolver,
+    /// Buffer with prefixes and namespace names of all bindings in scope
+    buffer: Vec<u8>,
+}
+
+impl NamespaceBindings {
+    /// Creates an empty stack of bindings, where no prefixes are declared
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begins a new scope and adds to it all namespace declarations (`xmlns`
+    /// and `xmlns:prefix` attributes) found in the specified start element.
+    pub fn push(&mut self, start: &BytesStart) {
+        self.resolver.push(start, &mut self.buffer);
+    }
+
+    /// Ends a top-most scope, removing all bindings added by the last call to
+    /// [`Self::push()`]. Does nothing if there are no scopes.
+    pub fn pop(&mut self) {
+        if self.resolver.nesting_level > 0 {
+            self.resolver.pending_pop = true;
+            self.resolver.pop(&mut self.buffer);
+        }
+    }
+}
+
+/// Resolves a potentially qualified **element name** into a [namespace name]
+/// using the specified bindings.
+///
+/// Unprefixed names are resolved into the current *default namespace*.
+///
+/// Returns `None`, if:
+/// - prefix is not declared in the bindings
+/// - name is unprefixed and there is no default namespace
+/// - namespace was [unbound] using `xmlns[:prefix]=""`
+///
+/// [namespace name]: https://www.w3.org/TR/xml-names11/#dt-NSName
+/// [unbound]: https://www.w3.org/TR/xml-names11/#scoping
+#[inline]
+pub fn resolve_namespace<'b>(qname: &[u8], bindings: &'b NamespaceBindings) -> Option<&'b [u8]> {
+    bindings.resolver.find(qname, &bindings.buffer)
+}
+
+/// Input of the [`PushReader`]: all fed bytes, some of which may be already
+/// consumed by the reader.
+///
+/// When all fed bytes are consumed, but the end of input is not signalled yet,
+/// reading fails with an [`io::ErrorKind::WouldBlock`] error, so the reader can
+/// distinguish an incomplete token from the end of the document.
+#[derive(Clone, Debug, Default)]
+struct PushSource {
+    /// Fed bytes. Consumed bytes are dropped lazily, when new bytes are fed
+    data: Vec<u8>,
+    /// Position of the first not consumed byte in `data`
+    pos: usize,
+    /// `true`, if no more bytes will be fed
+    finished: bool,
+}
+
+impl io::Read for PushSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for PushSource {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.data.len() && !self.finished {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "more input is needed",
+            ));
+        }
+        Ok(&self.data[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+/// A push-style XML reader, that parses a document from chunks of input, which
+/// are fed to it as they arrive, for example, from a network socket.
+///
+/// In contrast to the [`Reader`], that pulls data from the underlying reader
+/// itself, this reader never blocks: [`next_event`] returns `Ok(None)` when
+/// the fed input ends in the middle of an event, and the event is returned
+/// when the rest of it is fed with [`feed`]. Because the end of a text cannot
+/// be found until the next markup starts, the last text of the document is
+/// returned only after [`finish`] is called. After that, [`next_event`] behaves
+/// as [`Reader::read_event`] at the end of input and eventually returns [`Eof`].
+///
+/// Partially read events are buffered inside, so the memory usage is limited
+/// by the size of the largest event. Use [`Reader::max_token_size`] via
+/// [`reader_mut`] to limit it.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use fast_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+/// use fast_xml::PushReader;
+///
+/// let mut reader = PushReader::new();
+/// reader.reader_mut().trim_text(true);
+///
+/// reader.feed(b"<greeting>Hel");
+/// assert_eq!(
+///     reader.next_event().unwrap(),
+///     Some(Event::Start(BytesStart::borrowed_name(b"greeting")))
+/// );
+/// // The text is not finished yet
+/// assert_eq!(reader.next_event().unwrap(), None);
+///
+/// reader.feed(b"lo</greet");
+/// assert_eq!(
+///     reader.next_event().unwrap(),
+///     Some(Event::Text(BytesText::from_escaped_str("Hello")))
+/// );
+/// assert_eq!(reader.next_event().unwrap(), None);
+///
+/// reader.feed(b"ing>");
+/// reader.finish();
+/// assert_eq!(
+///     reader.next_event().unwrap(),
+///     Some(Event::End(BytesEnd::borrowed(b"greeting")))
+/// );
+/// assert_eq!(reader.next_event().unwrap(), Some(Event::Eof));
+/// ```
+///
+/// [`next_event`]: Self::next_event
+/// [`feed`]: Self::feed
+/// [`finish`]: Self::finish
+/// [`reader_mut`]: Self::reader_mut
+/// [`Eof`]: crate::events::Event::Eof
+#[derive(Clone)]
+pub struct PushReader {
+    /// A reader that holds the parser state and all fed, but not yet consumed input
+    reader: Reader<PushSource>,
+    /// A buffer, from which returned events borrow their data
+    buf: Vec<u8>,
+}
+
+impl PushReader {
+    /// Creates a reader without any input
+    pub fn new() -> Self {
+        Self {
+            reader: Reader::from_reader(PushSource::default()),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying [`Reader`] to change its settings, such as
+    /// [`Reader::trim_text`] or [`Reader::check_end_names`].
+    ///
+    /// Events should be read only with [`Self::next_event`], reading them
+    /// from the returned reader directly leads to errors when the input ends
+    /// in the middle of an event.
+    pub fn reader_mut(&mut self) -> &mut Reader<impl BufRead> {
+        &mut self.reader
+    }
+
+    /// Appends a chunk of input. Chunks may end at any byte, including in the
+    /// middle of a markup or of a multi-byte character.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if called after [`Self::finish`].
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let source = &mut self.reader.reader;
+        assert!(!source.finished, "input is already finished");
+        // Drop consumed bytes to not accumulate the whole document
+        source.data.drain(..source.pos);
+        source.pos = 0;
+        source.data.extend_from_slice(bytes);
+    }
+
+    /// Signals that there is no more input. After that call the last text of
+    /// the document becomes available, and incomplete events are reported as
+    /// errors by [`Self::next_event`].
+    pub fn finish(&mut self) {
+        self.reader.reader.finished = true;
+    }
+
+    /// Reads the next event from the fed input.
+    ///
+    /// Returns `Ok(None)` if the fed input does not contain the whole event
+    /// and more input is needed. Such partially fed event is not consumed, so
+    /// it is returned by the next call after more input is fed.
+    pub fn next_event(&mut self) -> Result<Option<Event<'_>>> {
+        // Saves the state that is changed even when reading of an event fails
+        let tag_state = self.reader.tag_state.clone();
+        let buf_position = self.reader.buf_position;
+        let pos = self.reader.reader.pos;
+
+        self.buf.clear();
+        match self.reader.read_event(&mut self.buf) {
+            Ok(event) => Ok(Some(event)),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.reader.tag_state = tag_state;
+                self.reader.buf_position = buf_position;
+                self.reader.reader.pos = pos;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the current byte position in the whole fed input, the same as
+    /// [`Reader::buffer_position`]
+    pub fn buffer_position(&self) -> usize {
+        self.reader.buffer_position()
+    }
+}
+
+impl Default for PushReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decoder of byte slices into strings.
+///
+/// Returned by [`Reader::decoder()`] and remembers the encoding of the document
+/// at the moment of creation. Decoder is a lightweight `Copy` handle, so it can
+/// be stored or passed around freely, for example, when you need to convert
+/// event content outside of the scope where the reader is borrowed.
+///
+/// If the `encoding` feature is enabled, the decoder uses the encoding, that
+/// was declared in the XML declaration or detected from the BOM, and malformed
+/// sequences are replaced with the `U+FFFD REPLACEMENT CHARACTER`. Otherwise,
+/// the input is always expected to be in UTF-8.
 #[cfg(not(feature = "encoding"))]
 #[derive(Clone, Copy, Debug)]
-pub struct Decoder;
+pub struct Decoder {
+    /// If `true`, only ASCII data can be decoded, see [`Reader::assume_ascii`]
+    assume_ascii: bool,
+}
 
-/// Utf8 Decoder
+/// Decoder of byte slices into strings.
+///
+/// Returned by [`Reader::decoder()`] and remembers the encoding of the document
+/// at the moment of creation. Decoder is a lightweight `Copy` handle, so it can
+/// be stored or passed around freely, for example, when you need to convert
+/// event content outside of the scope where the reader is borrowed.
+///
+/// If the `encoding` feature is enabled, the decoder uses the encoding, that
+/// was declared in the XML declaration or detected from the BOM, and malformed
+/// sequences are replaced with the `U+FFFD REPLACEMENT CHARACTER`. Otherwise,
+/// the input is always expected to be in UTF-8.
 #[cfg(feature = "encoding")]
 #[derive(Clone, Copy, Debug)]
 pub struct Decoder {
     encoding: &'static Encoding,
+    /// If `true`, only ASCII data can be decoded, see [`Reader::assume_ascii`]
+    assume_ascii: bool,
 }
 
 impl Decoder {
+    /// Creates a decoder for UTF-8 data
+    #[cfg(all(feature = "serialize", not(feature = "encoding")))]
+    pub(crate) fn utf8() -> Self {
+        Decoder {
+            assume_ascii: false,
+        }
+    }
+
+    /// Creates a decoder for UTF-8 data
+    #[cfg(all(feature = "serialize", feature = "encoding"))]
+    pub(crate) fn utf8() -> Self {
+        Decoder {
+            encoding: ::encoding_rs::UTF_8,
+            assume_ascii: false,
+        }
+    }
+
+    /// Decodes a UTF-8 slice.
+    ///
+    /// Returns an error if `bytes` is not a valid UTF-8 sequence or, if
+    /// [`Reader::assume_ascii`] is enabled, contains non-ASCII bytes. The result
+    /// always borrows from the input.
+    ///
+    /// # Performance
+    ///
+    /// The reader itself works with bytes and never validates UTF-8 while parsing,
+    /// so validation happens only here, for the data that you want to decode.
+    /// Validation of ASCII-only data is cheap, because [`from_utf8`] checks ASCII
+    /// in word-sized chunks.
+    ///
+    /// [`from_utf8`]: std::str::from_utf8
     #[cfg(not(feature = "encoding"))]
-    pub fn decode<'c>(&self, bytes: &'c [u8]) -> Result<&'c str> {
-        from_utf8(bytes).map_err(Error::Utf8)
+    pub fn decode<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        if self.assume_ascii {
+            check_ascii(bytes)?;
+        }
+        Ok(Cow::Borrowed(from_utf8(bytes).map_err(Error::Utf8)?))
     }
 
+    /// Decodes an owned UTF-8 buffer without copying it.
     #[cfg(not(feature = "encoding"))]
-    pub fn decode_owned<'c>(&self, bytes: Vec<u8>) -> Result<String> {
+    pub fn decode_owned(&self, bytes: Vec<u8>) -> Result<String> {
+        if self.assume_ascii {
+            check_ascii(&bytes)?;
+        }
         String::from_utf8(bytes).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
+    /// Decodes a slice using the encoding of the document.
+    ///
+    /// Malformed sequences are replaced with the `U+FFFD REPLACEMENT CHARACTER`,
+    /// so an error is returned only if [`Reader::assume_ascii`] is enabled and
+    /// `bytes` contains non-ASCII bytes. ASCII data is the same in all supported
+    /// encodings, so in that case it is returned as is. The result borrows from
+    /// the input if no conversion was required.
+    #[cfg(feature = "encoding")]
+    pub fn decode<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        if self.assume_ascii {
+            check_ascii(bytes)?;
+            return Ok(Cow::Borrowed(from_utf8(bytes).map_err(Error::Utf8)?));
+        }
+        Ok(self.encoding.decode(bytes).0)
+    }
+
+    /// Returns the encoding that is used to decode data.
     #[cfg(feature = "encoding")]
-    pub fn decode<'c>(&self, bytes: &'c [u8]) -> Cow<'c, str> {
-        self.encoding.decode(bytes).0
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+}
+
+/// Checks that `bytes` contains only ASCII characters. The first non-ASCII
+/// byte is reported as an invalid UTF-8 sequence at its position, because
+/// a byte with the high bit set cannot be a complete UTF-8 sequence on its own
+fn check_ascii(bytes: &[u8]) -> Result<()> {
+    // Checks ASCII in word-sized chunks, unlike the search of the position below
+    if bytes.is_ascii() {
+        return Ok(());
+    }
+    let end = bytes
+        .iter()
+        .position(|b| !b.is_ascii())
+        .map_or(0, |i| i + 1);
+    match from_utf8(&bytes[..end]) {
+        Err(e) => Err(Error::Utf8(e)),
+        Ok(_) => unreachable!("non-ASCII byte at the end of a slice is not valid UTF-8"),
     }
 }
 
@@ -1689,7 +3697,7 @@ mod test {
 
                     assert_eq!(
                         input
-                            .read_bytes_until(b'*', buf, &mut position)
+                            .read_bytes_until(b'*', buf, &mut position, None)
                             .unwrap()
                             .map(Bytes),
                         None
@@ -1708,7 +3716,7 @@ mod test {
 
                     assert_eq!(
                         input
-                            .read_bytes_until(b'*', buf, &mut position)
+                            .read_bytes_until(b'*', buf, &mut position, None)
                             .unwrap()
                             .map(Bytes),
                         Some(Bytes(b"abcdef"))
@@ -1728,7 +3736,7 @@ mod test {
 
                     assert_eq!(
                         input
-                            .read_bytes_until(b'*', buf, &mut position)
+                            .read_bytes_until(b'*', buf, &mut position, None)
                             .unwrap()
                             .map(Bytes),
                         Some(Bytes(b""))
@@ -1748,7 +3756,7 @@ mod test {
 
                     assert_eq!(
                         input
-                            .read_bytes_until(b'*', buf, &mut position)
+                            .read_bytes_until(b'*', buf, &mut position, None)
                             .unwrap()
                             .map(Bytes),
                         Some(Bytes(b"abc"))
@@ -1768,7 +3776,7 @@ mod test {
 
                     assert_eq!(
                         input
-                            .read_bytes_until(b'*', buf, &mut position)
+                            .read_bytes_until(b'*', buf, &mut position, None)
                             .unwrap()
                             .map(Bytes),
                         Some(Bytes(b"abcdef"))
@@ -1795,7 +3803,7 @@ mod test {
                         let mut input = b"![]]>other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "CData" => {}
                             x => assert!(
                                 false,
@@ -1815,7 +3823,7 @@ mod test {
                         let mut input = b"![CDATA[other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "CData" => {}
                             x => assert!(
                                 false,
@@ -1836,7 +3844,7 @@ mod test {
 
                         assert_eq!(
                             input
-                                .read_bang_element(buf, &mut position)
+                                .read_bang_element(buf, &mut position, None)
                                 .unwrap()
                                 .map(|(ty, data)| (ty, Bytes(data))),
                             Some((BangType::CData, Bytes(b"![CDATA[")))
@@ -1856,7 +3864,7 @@ mod test {
 
                         assert_eq!(
                             input
-                                .read_bang_element(buf, &mut position)
+                                .read_bang_element(buf, &mut position, None)
                                 .unwrap()
                                 .map(|(ty, data)| (ty, Bytes(data))),
                             Some((BangType::CData, Bytes(b"![CDATA[cdata]] ]>content")))
@@ -1895,7 +3903,7 @@ mod test {
                         let mut input = b"!- -->other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "Comment" => {}
                             x => assert!(
                                 false,
@@ -1913,7 +3921,7 @@ mod test {
                         let mut input = b"!->other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "Comment" => {}
                             x => assert!(
                                 false,
@@ -1931,7 +3939,7 @@ mod test {
                         let mut input = b"!--other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "Comment" => {}
                             x => assert!(
                                 false,
@@ -1949,7 +3957,7 @@ mod test {
                         let mut input = b"!-->other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "Comment" => {}
                             x => assert!(
                                 false,
@@ -1967,7 +3975,7 @@ mod test {
                         let mut input = b"!--->other content".as_ref();
                         //                ^= 0
 
-                        match input.read_bang_element(buf, &mut position) {
+                        match input.read_bang_element(buf, &mut position, None) {
                             Err(Error::UnexpectedEof(s)) if s == "Comment" => {}
                             x => assert!(
                                 false,
@@ -1987,7 +3995,7 @@ mod test {
 
                         assert_eq!(
                             input
-                                .read_bang_element(buf, &mut position)
+                                .read_bang_element(buf, &mut position, None)
                                 .unwrap()
                                 .map(|(ty, data)| (ty, Bytes(data))),
                             Some((BangType::Comment, Bytes(b"!----")))
@@ -2004,7 +4012,7 @@ mod test {
 
                         assert_eq!(
                             input
-                                .read_bang_element(buf, &mut position)
+                                .read_bang_element(buf, &mut position, None)
                                 .unwrap()
                                 .map(|(ty, data)| (ty, Bytes(data))),
                             Some((BangType::Comment, Bytes(b"!--->comment<---")))
@@ -2028,7 +4036,7 @@ mod test {
                             let mut input = b"!D other content".as_ref();
                             //                ^= 0
 
-                            match input.read_bang_element(buf, &mut position) {
+                            match input.read_bang_element(buf, &mut position, None) {
                                 Err(Error::UnexpectedEof(s)) if s == "DOCTYPE" => {}
                                 x => assert!(
                                     false,
@@ -2046,7 +4054,7 @@ mod test {
                             let mut input = b"!DOCTYPEother content".as_ref();
                             //                ^= 0
 
-                            match input.read_bang_element(buf, &mut position) {
+                            match input.read_bang_element(buf, &mut position, None) {
                                 Err(Error::UnexpectedEof(s)) if s == "DOCTYPE" => {}
                                 x => assert!(
                                     false,
@@ -2066,7 +4074,7 @@ mod test {
 
                             assert_eq!(
                                 input
-                                    .read_bang_element(buf, &mut position)
+                                    .read_bang_element(buf, &mut position, None)
                                     .unwrap()
                                     .map(|(ty, data)| (ty, Bytes(data))),
                                 Some((BangType::DocType, Bytes(b"!DOCTYPE")))
@@ -2081,7 +4089,7 @@ mod test {
                             let mut input = b"!DOCTYPE other content".as_ref();
                             //                ^= 0
 
-                            match input.read_bang_element(buf, &mut position) {
+                            match input.read_bang_element(buf, &mut position, None) {
                                 Err(Error::UnexpectedEof(s)) if s == "DOCTYPE" => {}
                                 x => assert!(
                                     false,
@@ -2106,7 +4114,7 @@ mod test {
                             let mut input = b"!d other content".as_ref();
                             //                ^= 0
 
-                            match input.read_bang_element(buf, &mut position) {
+                            match input.read_bang_element(buf, &mut position, None) {
                                 Err(Error::UnexpectedEof(s)) if s == "DOCTYPE" => {}
                                 x => assert!(
                                     false,
@@ -2124,7 +4132,7 @@ mod test {
                             let mut input = b"!doctypeother content".as_ref();
                             //                ^= 0
 
-                            match input.read_bang_element(buf, &mut position) {
+                            match input.read_bang_element(buf, &mut position, None) {
                                 Err(Error::UnexpectedEof(s)) if s == "DOCTYPE" => {}
                                 x => assert!(
                                     false,
@@ -2144,7 +4152,7 @@ mod test {
 
                             assert_eq!(
                                 input
-                                    .read_bang_element(buf, &mut position)
+                                    .read_bang_element(buf, &mut position, None)
                                     .unwrap()
                                     .map(|(ty, data)| (ty, Bytes(data))),
                                 Some((BangType::DocType, Bytes(b"!doctype")))
@@ -2159,7 +4167,7 @@ mod test {
                             let mut input = b"!doctype other content".as_ref();
                             //                ^= 0
 
-                            match input.read_bang_element(buf, &mut position) {
+                            match input.read_bang_element(buf, &mut position, None) {
                                 Err(Error::UnexpectedEof(s)) if s == "DOCTYPE" => {}
                                 x => assert!(
                                     false,
@@ -2186,7 +4194,7 @@ mod test {
                     let mut input = b"".as_ref();
                     //                ^= 0
 
-                    assert_eq!(input.read_element(buf, &mut position).unwrap().map(Bytes), None);
+                    assert_eq!(input.read_element(buf, &mut position, None).unwrap().map(Bytes), None);
                     assert_eq!(position, 0);
                 }
 
@@ -2203,7 +4211,7 @@ mod test {
                         //                 ^= 1
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b""))
                         );
                         assert_eq!(position, 1);
@@ -2217,7 +4225,7 @@ mod test {
                         //                    ^= 4
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b"tag"))
                         );
                         assert_eq!(position, 4);
@@ -2231,7 +4239,7 @@ mod test {
                         //                  ^= 2
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b":"))
                         );
                         assert_eq!(position, 2);
@@ -2245,7 +4253,7 @@ mod test {
                         //                     ^= 5
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b":tag"))
                         );
                         assert_eq!(position, 5);
@@ -2259,7 +4267,7 @@ mod test {
                         //                                                        ^= 38
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(br#"tag  attr-1=">"  attr2  =  '>'  3attr"#))
                         );
                         assert_eq!(position, 38);
@@ -2279,7 +4287,7 @@ mod test {
                         //                  ^= 2
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b"/"))
                         );
                         assert_eq!(position, 2);
@@ -2293,7 +4301,7 @@ mod test {
                         //                     ^= 5
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b"tag/"))
                         );
                         assert_eq!(position, 5);
@@ -2307,7 +4315,7 @@ mod test {
                         //                   ^= 3
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b":/"))
                         );
                         assert_eq!(position, 3);
@@ -2321,7 +4329,7 @@ mod test {
                         //                      ^= 6
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(b":tag/"))
                         );
                         assert_eq!(position, 6);
@@ -2335,7 +4343,7 @@ mod test {
                         //                                                           ^= 41
 
                         assert_eq!(
-                            input.read_element(buf, &mut position).unwrap().map(Bytes),
+                            input.read_element(buf, &mut position, None).unwrap().map(Bytes),
                             Some(Bytes(br#"tag  attr-1="/>"  attr2  =  '/>'  3attr/"#))
                         );
                         assert_eq!(position, 41);
@@ -2406,6 +4414,102 @@ mod test {
                     }
                 }
             }
+
+            /// Tests for [`Reader::validate_doctype_root`]
+            mod validate_doctype_root {
+                use crate::errors::Error;
+                use crate::events::Event;
+                use pretty_assertions::assert_eq;
+
+                /// Reads events from the document until the first `Start` or `Empty`
+                /// event or an error, and returns the last result
+                fn read_root(doc: &str, validate: bool) -> Result<(), String> {
+                    let mut reader = crate::Reader::from_str(doc);
+                    reader.validate_doctype_root(validate);
+
+                    loop {
+                        match reader.read_event_buffered($buf) {
+                            Ok(Event::Start(_)) | Ok(Event::Empty(_)) => return Ok(()),
+                            Ok(Event::Eof) => panic!("Root element not found"),
+                            Ok(_) => {}
+                            Err(Error::DocTypeRootMismatch { expected, found }) => {
+                                assert_eq!(reader.buffer_position(), doc.rfind('<').unwrap());
+                                return Err(format!("{} != {}", expected, found));
+                            }
+                            Err(e) => panic!("Unexpected error: {:?}", e),
+                        }
+                    }
+                }
+
+                #[test]
+                fn without_doctype() {
+                    assert_eq!(read_root("<root/>", true), Ok(()));
+                }
+
+                #[test]
+                fn matched() {
+                    assert_eq!(read_root("<!DOCTYPE root><root/>", true), Ok(()));
+                    assert_eq!(read_root("<!DOCTYPE root>\n<root></root>", true), Ok(()));
+                }
+
+                #[test]
+                fn mismatched() {
+                    assert_eq!(
+                        read_root("<!DOCTYPE html><root/>", true),
+                        Err("html != root".into())
+                    );
+                    assert_eq!(
+                        read_root("<!DOCTYPE html>\n<root>", true),
+                        Err("html != root".into())
+                    );
+                }
+
+                #[test]
+                fn external_id() {
+                    let doctype = r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" "xhtml1-strict.dtd">"#;
+                    assert_eq!(read_root(&format!("{}<html>", doctype), true), Ok(()));
+                    assert_eq!(
+                        read_root(&format!("{}<body>", doctype), true),
+                        Err("html != body".into())
+                    );
+                }
+
+                #[test]
+                fn internal_subset() {
+                    assert_eq!(
+                        read_root("<!DOCTYPE root[<!ENTITY e 'value'>]><root/>", true),
+                        Ok(())
+                    );
+                    assert_eq!(
+                        read_root("<!DOCTYPE root [<!ENTITY e 'value'>]><root/>", true),
+                        Ok(())
+                    );
+                    assert_eq!(
+                        read_root("<!DOCTYPE root [<!ENTITY e 'value'>]><other/>", true),
+                        Err("root != other".into())
+                    );
+                }
+
+                /// Only the root element is checked
+                #[test]
+                fn nested() {
+                    let mut reader = crate::Reader::from_str("<!DOCTYPE root><root><inner/></root>");
+                    reader.validate_doctype_root(true);
+
+                    loop {
+                        match reader.read_event_buffered($buf) {
+                            Ok(Event::Eof) => break,
+                            Ok(_) => {}
+                            Err(e) => panic!("Unexpected error: {:?}", e),
+                        }
+                    }
+                }
+
+                #[test]
+                fn disabled() {
+                    assert_eq!(read_root("<!DOCTYPE html><root/>", false), Ok(()));
+                }
+            }
         };
     }
 
@@ -4,14 +4,18 @@ mod var;
 
 use self::var::{Map, Seq, Struct, Tuple};
 use crate::{
-    de::PRIMITIVE_PREFIX,
+    de::{INNER_VALUE, PRIMITIVE_PREFIX},
     errors::serialize::DeError,
+    escape::{numeric_escape, partial_escape},
     events::{BytesEnd, BytesStart, BytesText, Event},
-    writer::Writer,
+    reader::Reader,
+    writer::{LineEnding, Writer},
 };
 use serde::ser::{self, Serialize};
 use serde::serde_if_integer128;
-use std::io::Write;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::Utf8Error;
 
 /// Serialize struct into a `Write`r
 pub fn to_writer<W: Write, S: Serialize>(writer: W, value: &S) -> Result<(), DeError> {
@@ -19,19 +23,206 @@ pub fn to_writer<W: Write, S: Serialize>(writer: W, value: &S) -> Result<(), DeE
     value.serialize(&mut serializer)
 }
 
+/// Serialize struct into a [`fmt::Write`]r, for example, a `String` or
+/// a [`fmt::Formatter`]
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use fast_xml::se::to_fmt;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Item {
+///     name: String,
+/// }
+///
+/// let mut xml = String::from("<?xml version='1.0'?>");
+/// to_fmt(&mut xml, &Item { name: "a".into() }).unwrap();
+///
+/// assert_eq!(xml, r#"<?xml version='1.0'?><Item name="a"/>"#);
+/// ```
+pub fn to_fmt<W: fmt::Write, S: Serialize>(writer: W, value: &S) -> Result<(), DeError> {
+    let mut writer = FmtWriter {
+        writer,
+        utf8_error: None,
+    };
+    to_writer(&mut writer, value).map_err(|e| match writer.utf8_error.take() {
+        Some(e) => crate::errors::Error::Utf8(e).into(),
+        None => e,
+    })
+}
+
 /// Serialize struct into a `String`
 pub fn to_string<S: Serialize>(value: &S) -> Result<String, DeError> {
-    let mut writer = Vec::new();
-    to_writer(&mut writer, value)?;
-    let s = String::from_utf8(writer).map_err(|e| crate::errors::Error::Utf8(e.utf8_error()))?;
+    let mut s = String::new();
+    to_fmt(&mut s, value)?;
+    Ok(s)
+}
+
+/// An adapter, that passes the output of a [`Serializer`] to a [`fmt::Write`]r.
+///
+/// The serializer writes only content of strings and names, each with one
+/// `write_all` call, so every written slice is valid UTF-8 on its own.
+/// The serializer works with bytes, so each slice is still checked before
+/// it is passed to the inner writer
+struct FmtWriter<W: fmt::Write> {
+    writer: W,
+    /// The error, if a written slice was not a valid UTF-8 string
+    utf8_error: Option<Utf8Error>,
+}
+
+impl<W: fmt::Write> Write for FmtWriter<W> {
+    // `io::Error::other` is available only since Rust 1.74
+    #[allow(clippy::io_other_error)]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let s = std::str::from_utf8(buf).map_err(|e| {
+            self.utf8_error = Some(e);
+            io::Error::new(io::ErrorKind::InvalidData, e)
+        })?;
+        self.writer
+            .write_str(s)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize struct into a `String` with indentation. Each nested element is
+/// written on a new line, that ends with the `line_ending` and is indented by
+/// `indent_size` repetitions of the `indent_char` for each nesting level.
+///
+/// Text content is written as is: elements with text are not broken into lines
+/// and line endings inside the text are not changed.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use fast_xml::se::to_string_pretty;
+/// use fast_xml::LineEnding;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Item {
+///     name: String,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Project {
+///     #[serde(rename = "item")]
+///     items: Vec<Item>,
+/// }
+///
+/// let project = Project {
+///     items: vec![Item { name: "a".into() }, Item { name: "b".into() }],
+/// };
+///
+/// assert_eq!(
+///     to_string_pretty(&project, b'\t', 1, LineEnding::CrLf).unwrap(),
+///     "<Project>\r\n\t<item name=\"a\"/>\r\n\t<item name=\"b\"/>\r\n</Project>"
+/// );
+/// ```
+pub fn to_string_pretty<S: Serialize>(
+    value: &S,
+    indent_char: u8,
+    indent_size: usize,
+    line_ending: LineEnding,
+) -> Result<String, DeError> {
+    // Nested elements are serialized into intermediate buffers without indentation,
+    // so the compact document is re-written by the indenting writer
+    let compact = to_string(value)?;
+    let mut reader = Reader::from_str(&compact);
+    // Line endings in the text content should be kept as is
+    reader.normalize_line_endings(false);
+    let mut writer = Writer::new_with_indent(Vec::new(), indent_char, indent_size);
+    writer.line_ending(line_ending);
+
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf)? {
+            Event::Eof => break,
+            Event::Text(e) if e.is_empty() => {}
+            e => writer.write_event(e)?,
+        }
+        buf.clear();
+    }
+    let s = String::from_utf8(writer.into_inner())
+        .map_err(|e| crate::errors::Error::Utf8(e.utf8_error()))?;
     Ok(s)
 }
 
+/// Defines, which characters are escaped in text content and attribute values,
+/// written by the [`Serializer`]. See [`Serializer::escape`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscapeMode {
+    /// All XML special characters (`<`, `>`, `&`, `'` and `"`) are escaped.
+    /// Such content can be safely placed anywhere in the document
+    Full,
+    /// Only characters, that would break the markup, are escaped: `<`, `>`
+    /// and `&` in text content, and in addition `"` in attribute values,
+    /// because they are enclosed in double quotes
+    Minimal,
+    /// All XML special characters are escaped, like in the [`Full`] mode, but
+    /// quotes are written as numeric character references `&#39;` and `&#34;`
+    /// instead of the `&apos;` and `&quot;` entities. The `&apos;` entity is
+    /// not defined in HTML 4, so use this mode for output intended for HTML
+    /// consumers
+    ///
+    /// [`Full`]: Self::Full
+    Numeric,
+}
+
+impl Default for EscapeMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
 /// A Serializer
 pub struct Serializer<'r, W: Write> {
     writer: Writer<W>,
     /// Name of the root tag. If not specified, deduced from the structure name
     root_tag: Option<&'r str>,
+    /// If `true`, empty sequences and maps are written as self-closed elements,
+    /// otherwise nothing is written for them
+    write_empty_collections: bool,
+    /// If `true`, the serializer writes a top-level value, otherwise a value of
+    /// a struct field or a map entry. Empty maps are always written at the top
+    /// level, so the document gets a root element, like for empty structs
+    top_level: bool,
+    /// Name of the element that wraps each item of a nested sequence. If not
+    /// specified, a root tag name is used. See [`Self::nested_seq_tags`]
+    group_tag: Option<&'r str>,
+    /// Name of the element that wraps each element of a nested sequence.
+    /// See [`Self::nested_seq_tags`]
+    item_tag: &'r str,
+    /// If `true`, a value that is serialized now is an element of a sequence.
+    /// Set by a sequence serializer before serializing each element and
+    /// cleared when nested elements are written
+    seq_item: bool,
+    /// Defines which characters are escaped in strings. See [`Self::escape`]
+    escape: EscapeMode,
+    /// If `true`, attributes of structs are written sorted by name.
+    /// See [`Self::sort_attributes`]
+    sort_attributes: bool,
+    /// If `true`, primitive fields of structs are written as attributes,
+    /// otherwise as elements. See [`Self::scalars_as_attributes`]
+    scalars_as_attributes: bool,
+    /// If `true`, entries of maps with keys, that start with `@`, are written
+    /// as attributes. See [`Self::map_attributes`]
+    map_attributes: bool,
+    /// If `true`, a value that is serialized now is a value of an attribute.
+    /// Newtype structs are serialized as their inner values in that case, so
+    /// wrappers over strings (like dates) become attribute values instead of
+    /// nested elements. Set when serializing a value of an `@`-prefixed field
+    attribute_value: bool,
 }
 
 impl<'r, W: Write> Serializer<'r, W> {
@@ -93,7 +284,320 @@ impl<'r, W: Write> Serializer<'r, W> {
     /// );
     /// ```
     pub fn with_root(writer: Writer<W>, root_tag: Option<&'r str>) -> Self {
-        Self { writer, root_tag }
+        Self {
+            writer,
+            root_tag,
+            write_empty_collections: false,
+            top_level: true,
+            group_tag: None,
+            item_tag: "item",
+            seq_item: false,
+            escape: EscapeMode::Full,
+            sort_attributes: false,
+            scalars_as_attributes: true,
+            map_attributes: false,
+            attribute_value: false,
+        }
+    }
+
+    /// Changes whether empty sequences and maps should be written.
+    ///
+    /// When set to `true`, an empty sequence or map that has a tag name (for
+    /// example, because it is a struct field) is written as a self-closed element
+    /// `<field/>`. When set to `false`, nothing is written for it, the same as for
+    /// `None` values. An empty top-level map with a root tag name is always
+    /// written as a self-closed root element, like an empty struct.
+    ///
+    /// Note, that sequences are represented by repeating elements, so `<field/>`
+    /// will be read back as a sequence with one element, if its type can be
+    /// deserialized from an empty element.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Serialize;
+    /// use fast_xml::Writer;
+    /// use fast_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Struct {
+    ///     list: Vec<u32>,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// Struct { list: vec![] }.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), "<Struct/>");
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.write_empty_collections(true);
+    /// Struct { list: vec![] }.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), "<Struct><list/></Struct>");
+    /// ```
+    pub fn write_empty_collections(&mut self, val: bool) -> &mut Self {
+        self.write_empty_collections = val;
+        self
+    }
+
+    /// Changes names of elements, used to represent sequences nested in other
+    /// sequences, such as `Vec<Vec<T>>`.
+    ///
+    /// Sequences are represented by repeating elements, so the boundaries of
+    /// inner sequences would be lost if their elements were written one after
+    /// another. Instead of that, each inner sequence is wrapped into a `group`
+    /// element, and each of its elements is wrapped into an `item` element.
+    /// When `group` is `None`, the root tag name is used for it, which is a
+    /// field name for struct fields. An empty inner sequence is written as
+    /// a self-closed `group` element.
+    ///
+    /// Such XML is read back by [`Deserializer`] without any configuration:
+    /// each element of an outer sequence, that is deserialized as a sequence,
+    /// takes the content of one element as a sequence of its children. Note,
+    /// that struct fields are matched by element names, so for round-trip of
+    /// a `Vec<Vec<T>>` field `group` should be `None`.
+    ///
+    /// (`None` and `"item"` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Serialize;
+    /// use fast_xml::se::Serializer;
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.nested_seq_tags(Some("group"), "item");
+    /// vec![vec![1, 2], vec![3]].serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     "<group><item>1</item><item>2</item></group><group><item>3</item></group>"
+    /// );
+    /// ```
+    ///
+    /// [`Deserializer`]: crate::de::Deserializer
+    pub fn nested_seq_tags(&mut self, group: Option<&'r str>, item: &'r str) -> &mut Self {
+        self.group_tag = group;
+        self.item_tag = item;
+        self
+    }
+
+    /// Changes which characters are escaped in strings, written as text content
+    /// and attribute values.
+    ///
+    /// Only strings and characters are affected, because other primitives never
+    /// contain special characters. With [`EscapeMode::Minimal`] quotes in text
+    /// content and `'` in attribute values are written as is, which gives
+    /// a cleaner output for files that are edited by humans. [`EscapeMode::Numeric`]
+    /// writes quotes as numeric character references, that are understood by
+    /// HTML parsers too. All modes produce well-formed XML, that is read back
+    /// by [`Deserializer`] to the same values.
+    ///
+    /// ([`EscapeMode::Full`] by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Serialize;
+    /// use fast_xml::se::{EscapeMode, Serializer};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Quote {
+    ///     author: String,
+    ///     #[serde(rename = "$value")]
+    ///     text: String,
+    /// }
+    ///
+    /// let quote = Quote {
+    ///     author: "O'Brien".into(),
+    ///     text: r#"Say "cheese" & smile"#.into(),
+    /// };
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// quote.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Quote author="O&apos;Brien">Say &quot;cheese&quot; &amp; smile</Quote>"#
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.escape(EscapeMode::Minimal);
+    /// quote.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Quote author="O'Brien">Say "cheese" &amp; smile</Quote>"#
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.escape(EscapeMode::Numeric);
+    /// quote.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Quote author="O&#39;Brien">Say &#34;cheese&#34; &amp; smile</Quote>"#
+    /// );
+    /// ```
+    ///
+    /// [`Deserializer`]: crate::de::Deserializer
+    pub fn escape(&mut self, mode: EscapeMode) -> &mut Self {
+        self.escape = mode;
+        self
+    }
+
+    /// Changes whether attributes of structs are written in the order of fields
+    /// or sorted by name.
+    ///
+    /// Sorted attributes give a stable output, that does not depend on the order
+    /// of fields, which is useful for comparing documents with diff tools.
+    /// Namespace declarations (`xmlns` and `xmlns:*` attributes) are written
+    /// before all other attributes, sorted among themselves. Elements are
+    /// always written in the order of fields.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Serialize;
+    /// use fast_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Link {
+    ///     href: &'static str,
+    ///     #[serde(rename = "xmlns:xlink")]
+    ///     xlink: &'static str,
+    ///     rel: &'static str,
+    /// }
+    ///
+    /// let link = Link {
+    ///     href: "/",
+    ///     xlink: "http://www.w3.org/1999/xlink",
+    ///     rel: "home",
+    /// };
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.sort_attributes(true);
+    /// link.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Link xmlns:xlink="http://www.w3.org/1999/xlink" href="/" rel="home"/>"#
+    /// );
+    /// ```
+    pub fn sort_attributes(&mut self, val: bool) -> &mut Self {
+        self.sort_attributes = val;
+        self
+    }
+
+    /// Changes how fields of structs with primitive values (numbers, strings,
+    /// booleans) are written.
+    ///
+    /// When set to `true`, such fields are written as attributes of the struct
+    /// element. When set to `false`, they are written as child elements named
+    /// after the field, like fields with other values. Fields, renamed with
+    /// the `@` prefix, are always written as attributes, so both forms can be
+    /// read back by the [`Deserializer`]. Nested structs, sequences, maps and
+    /// unit variants are always written as elements.
+    ///
+    /// (`true` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Serialize;
+    /// use fast_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Book {
+    ///     #[serde(rename = "@id")]
+    ///     id: u32,
+    ///     title: &'static str,
+    /// }
+    ///
+    /// let book = Book { id: 42, title: "Dune" };
+    ///
+    /// let mut buffer = Vec::new();
+    /// book.serialize(&mut Serializer::new(&mut buffer)).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Book id="42" title="Dune"/>"#
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.scalars_as_attributes(false);
+    /// book.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Book id="42"><title>Dune</title></Book>"#
+    /// );
+    /// ```
+    ///
+    /// [`Deserializer`]: crate::de::Deserializer
+    pub fn scalars_as_attributes(&mut self, val: bool) -> &mut Self {
+        self.scalars_as_attributes = val;
+        self
+    }
+
+    /// Changes whether entries of maps with keys, that start with `@`, are
+    /// written as attributes of the map element.
+    ///
+    /// Structs with fields, marked with `#[serde(flatten)]`, are serialized
+    /// as maps, so names of their fields become map keys. Enable this option
+    /// to write fields of such structs, renamed with the `@` prefix, as
+    /// attributes, the same as fields of ordinary structs. When set to `false`,
+    /// keys of maps are always written as element names. Attributes can be
+    /// written only for maps with a known tag name, so serializing such entry
+    /// of a map without a tag name is an error.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pretty_assertions::assert_eq;
+    /// # use serde::Serialize;
+    /// use fast_xml::Writer;
+    /// use fast_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Point {
+    ///     #[serde(rename = "@x")]
+    ///     x: u32,
+    ///     #[serde(rename = "@y")]
+    ///     y: u32,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Label {
+    ///     #[serde(flatten)]
+    ///     point: Point,
+    ///     text: &'static str,
+    /// }
+    ///
+    /// let label = Label { point: Point { x: 1, y: 2 }, text: "origin" };
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("label"));
+    /// ser.map_attributes(true);
+    /// label.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<label x="1" y="2"><text>origin</text></label>"#
+    /// );
+    /// ```
+    pub fn map_attributes(&mut self, val: bool) -> &mut Self {
+        self.map_attributes = val;
+        self
     }
 
     fn write_primitive<P: std::fmt::Display>(
@@ -102,12 +606,25 @@ impl<'r, W: Write> Serializer<'r, W> {
         escaped: bool,
     ) -> Result<(), DeError> {
         let value = value.to_string().into_bytes();
-        let event = if escaped {
-            BytesText::from_escaped(value)
-        } else {
-            BytesText::from_plain(&value)
+        let event = match self.escape {
+            _ if escaped => BytesText::from_escaped(value),
+            EscapeMode::Full => BytesText::from_plain(&value),
+            EscapeMode::Minimal => BytesText::from_escaped(partial_escape(&value)),
+            EscapeMode::Numeric => BytesText::from_escaped(numeric_escape(&value)),
         };
-        self.writer.write_event(Event::Text(event))?;
+        // Items of a sequence in a struct field are written as repeated elements
+        // named after the field, like structs, so they can be read back
+        match self.root_tag {
+            Some(tag) if self.seq_item && tag != INNER_VALUE => {
+                let tag = tag.as_bytes();
+                self.writer
+                    .write_event(Event::Start(BytesStart::borrowed_name(tag)))?;
+                self.writer.write_event(Event::Text(event))?;
+                self.writer
+                    .write_event(Event::End(BytesEnd::borrowed(tag)))?;
+            }
+            _ => self.writer.write_event(Event::Text(event))?,
+        }
         Ok(())
     }
 
@@ -124,6 +641,7 @@ impl<'r, W: Write> Serializer<'r, W> {
         tag_name: &str,
         value: &T,
     ) -> Result<(), DeError> {
+        self.seq_item = false;
         self.writer
             .write_event(Event::Start(BytesStart::borrowed_name(tag_name.as_bytes())))?;
         value.serialize(&mut *self)?;
@@ -238,8 +756,25 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
         if variant.starts_with(PRIMITIVE_PREFIX) {
             let variant = variant.split_at(PRIMITIVE_PREFIX.len()).1;
             self.write_primitive(variant, false)
+        } else if self.attribute_value {
+            self.write_primitive(variant, false)
         } else {
-            self.write_self_closed(variant)
+            match self.root_tag {
+                // Unit variants in struct fields and map entries are written as
+                // `<field>Variant</field>`, so they can be read back. In particular,
+                // that is how tags of adjacently tagged enums are written
+                Some(tag) if !self.top_level && tag != INNER_VALUE => {
+                    let tag = tag.as_bytes();
+                    self.writer
+                        .write_event(Event::Start(BytesStart::borrowed_name(tag)))?;
+                    self.writer
+                        .write_event(Event::Text(BytesText::from_plain_str(variant)))?;
+                    self.writer
+                        .write_event(Event::End(BytesEnd::borrowed(tag)))?;
+                    Ok(())
+                }
+                _ => self.write_self_closed(variant),
+            }
         }
     }
 
@@ -248,7 +783,11 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
         name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, DeError> {
-        self.write_paired(self.root_tag.unwrap_or(name), value)
+        if self.attribute_value {
+            value.serialize(self)
+        } else {
+            self.write_paired(self.root_tag.unwrap_or(name), value)
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized + Serialize>(
@@ -270,7 +809,19 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, DeError> {
-        Ok(Seq::new(self))
+        if self.seq_item {
+            let tag = match self.group_tag.or(self.root_tag) {
+                Some(tag) => tag,
+                None => {
+                    return Err(DeError::Custom(
+                        "root tag name must be specified when serialize nested sequence".into(),
+                    ))
+                }
+            };
+            Ok(Seq::grouped(self, tag))
+        } else {
+            Ok(Seq::new(self))
+        }
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, DeError> {
@@ -304,11 +855,6 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, DeError> {
-        if let Some(tag) = self.root_tag {
-            // TODO: Write self-closed tag if map is empty
-            self.writer
-                .write_event(Event::Start(BytesStart::borrowed_name(tag.as_bytes())))?;
-        }
         Ok(Map::new(self))
     }
 
@@ -629,6 +1175,140 @@ mod tests {
         assert_eq!(got, should_be);
     }
 
+    mod to_fmt {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Serialize)]
+        struct Item {
+            name: String,
+            #[serde(rename = "$value")]
+            text: String,
+        }
+
+        fn item() -> Item {
+            Item {
+                name: "\"café\"".into(),
+                text: "<ünïcode> & text".into(),
+            }
+        }
+
+        #[test]
+        fn string() {
+            let mut xml = String::from("prefix:");
+            to_fmt(&mut xml, &item()).unwrap();
+
+            assert_eq!(xml, format!("prefix:{}", to_string(&item()).unwrap()));
+
+            let mut buffer = Vec::new();
+            to_writer(&mut buffer, &item()).unwrap();
+            assert_eq!(xml.as_bytes()[7..], buffer[..]);
+            assert_eq!(
+                xml,
+                "prefix:<Item name=\"&quot;café&quot;\">&lt;ünïcode&gt; &amp; text</Item>"
+            );
+        }
+
+        /// Serialization into a formatter, for example, in a `Display` implementation
+        #[test]
+        fn formatter() {
+            struct Xml(Item);
+
+            impl fmt::Display for Xml {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    to_fmt(f, &self.0).map_err(|_| fmt::Error)
+                }
+            }
+
+            assert_eq!(Xml(item()).to_string(), to_string(&item()).unwrap());
+        }
+
+        /// Errors of the `fmt::Write`r are reported
+        #[test]
+        fn error() {
+            struct Failing;
+
+            impl fmt::Write for Failing {
+                fn write_str(&mut self, _s: &str) -> fmt::Result {
+                    Err(fmt::Error)
+                }
+            }
+
+            match to_fmt(Failing, &item()) {
+                Err(DeError::InvalidXml(crate::errors::Error::WriteEvent { .. })) => {}
+                x => panic!("Expected `Err(InvalidXml(WriteEvent))`, but got `{:?}`", x),
+            }
+        }
+    }
+
+    mod pretty {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Serialize)]
+        struct Inner {
+            id: u32,
+            #[serde(rename = "line")]
+            lines: Vec<String>,
+        }
+
+        #[derive(Serialize)]
+        struct Outer {
+            name: String,
+            inner: Inner,
+        }
+
+        fn outer() -> Outer {
+            Outer {
+                name: "outer".into(),
+                inner: Inner {
+                    id: 1,
+                    lines: vec!["1\n2".into(), "3 & 4".into()],
+                },
+            }
+        }
+
+        #[test]
+        fn lf() {
+            assert_eq!(
+                to_string_pretty(&outer(), b'\t', 1, LineEnding::Lf).unwrap(),
+                "<Outer name=\"outer\">\n\t<inner id=\"1\">\n\t\t<line>1\n2</line>\n\t\t<line>3 &amp; 4</line>\n\t</inner>\n</Outer>"
+            );
+        }
+
+        #[test]
+        fn crlf() {
+            assert_eq!(
+                to_string_pretty(&outer(), b'\t', 1, LineEnding::CrLf).unwrap(),
+                "<Outer name=\"outer\">\r\n\t<inner id=\"1\">\r\n\t\t<line>1\n2</line>\r\n\t\t<line>3 &amp; 4</line>\r\n\t</inner>\r\n</Outer>"
+            );
+        }
+
+        #[test]
+        fn spaces() {
+            assert_eq!(
+                to_string_pretty(&outer(), b' ', 2, LineEnding::Lf).unwrap(),
+                "<Outer name=\"outer\">\n  <inner id=\"1\">\n    <line>1\n2</line>\n    <line>3 &amp; 4</line>\n  </inner>\n</Outer>"
+            );
+        }
+
+        /// Line endings in the text content and attribute values are not changed
+        #[test]
+        fn carriage_returns() {
+            let outer = Outer {
+                name: "a\r\nb\rc".into(),
+                inner: Inner {
+                    id: 1,
+                    lines: vec!["1\r\n2\r3".into()],
+                },
+            };
+            assert_eq!(
+                to_string_pretty(&outer, b'\t', 1, LineEnding::Lf).unwrap(),
+                "<Outer name=\"a\r\nb\rc\">\n\t<inner id=\"1\">\n\t\t<line>1\r\n2\r3</line>\n\t</inner>\n</Outer>"
+            );
+        }
+    }
+
     mod enum_ {
         use super::*;
 
@@ -946,7 +1626,7 @@ mod tests {
             #[test]
             fn unit() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Unit"/>"#;
+                let should_be = "<root><tag>Unit</tag></root>";
 
                 {
                     let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
@@ -961,7 +1641,7 @@ mod tests {
             #[test]
             fn newtype() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Newtype" content="true"/>"#;
+                let should_be = r#"<root content="true"><tag>Newtype</tag></root>"#;
 
                 {
                     let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
@@ -976,7 +1656,7 @@ mod tests {
             #[test]
             fn tuple_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Tuple"><content>42</content><content>answer</content>
+                let should_be = r#"<root><tag>Tuple</tag><content>42</content><content>answer</content>
 </root>"#;
 
                 {
@@ -995,7 +1675,7 @@ mod tests {
             #[test]
             fn struct_() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Struct"><content float="42" string="answer"/>
+                let should_be = r#"<root><tag>Struct</tag><content float="42" string="answer"/>
 </root>"#;
 
                 {
@@ -1017,7 +1697,7 @@ mod tests {
             #[test]
             fn nested_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Holder"><content string="answer"><nested float="42"/></content>
+                let should_be = r#"<root><tag>Holder</tag><content string="answer"><nested float="42"/></content>
 </root>"#;
 
                 {
@@ -1039,7 +1719,7 @@ mod tests {
             #[test]
             fn flatten_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Flatten"><content><float>42</float><string>answer</string></content>
+                let should_be = r#"<root><tag>Flatten</tag><content><float>42</float><string>answer</string></content>
 </root>"#;
 
                 {
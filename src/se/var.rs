@@ -1,8 +1,9 @@
 use crate::{
-    de::{INNER_VALUE, UNFLATTEN_PREFIX},
+    de::{ATTRIBUTE_PREFIX, INNER_VALUE, UNFLATTEN_PREFIX},
     errors::{serialize::DeError, Error},
-    events::{BytesEnd, BytesStart, Event},
-    se::Serializer,
+    escapei::escape_quotes,
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    se::{EscapeMode, Serializer},
     writer::Writer,
 };
 use serde::ser::{self, Serialize};
@@ -10,11 +11,24 @@ use serde::Serializer as _;
 use std::io::Write;
 
 /// An implementation of `SerializeMap` for serializing to XML.
+///
+/// If [`Serializer::map_attributes`] is enabled, entries with keys that start
+/// with `@` (for example, fields of structs, flattened with `#[serde(flatten)]`)
+/// are written as attributes of the map element, all other entries are written
+/// as nested elements after them
 pub struct Map<'r, 'w, W>
 where
     W: 'w + Write,
 {
     parent: &'w mut Serializer<'r, W>,
+    /// `true` if at least one entry was serialized. Used to skip empty maps
+    started: bool,
+    /// Entries, serialized as attributes, in order of their appearance
+    attrs: Vec<(String, Vec<u8>)>,
+    /// Buffer for holding entries, serialized as elements, until all attributes
+    /// are known. `None` if the map cannot have attributes, in that case entries
+    /// are written directly to the parent writer
+    children: Option<Vec<u8>>,
 }
 
 impl<'r, 'w, W> Map<'r, 'w, W>
@@ -23,10 +37,60 @@ where
 {
     /// Create a new Map
     pub fn new(parent: &'w mut Serializer<'r, W>) -> Self {
-        Map { parent }
+        parent.seq_item = false;
+        let buffered = parent.map_attributes && parent.root_tag.is_some();
+        Map {
+            parent,
+            started: false,
+            attrs: Vec::new(),
+            children: if buffered { Some(Vec::new()) } else { None },
+        }
+    }
+
+    /// Writes a serialized entry of the map. The opening tag of the map is
+    /// written lazily before the first entry to be able to skip empty maps
+    fn write_child(&mut self, child: &[u8]) -> Result<(), DeError> {
+        match self.children {
+            Some(ref mut children) => children.extend_from_slice(child),
+            None => {
+                if !self.started {
+                    if let Some(tag) = self.parent.root_tag {
+                        self.parent
+                            .writer
+                            .write_event(Event::Start(BytesStart::borrowed_name(tag.as_bytes())))?;
+                    }
+                }
+                // Children are already escaped. Writing them as a text keeps the writer
+                // from inserting indentation before the closing tag
+                self.parent
+                    .writer
+                    .write_event(Event::Text(BytesText::from_escaped(child)))?;
+            }
+        }
+        self.started = true;
+        Ok(())
     }
 }
 
+/// Creates a serializer that writes to the `buffer` with the same settings
+/// as the `parent` serializer
+fn nested<'n, 'r: 'n, 'b, W: Write>(
+    parent: &Serializer<'r, W>,
+    buffer: &'b mut Vec<u8>,
+    root_tag: Option<&'n str>,
+) -> Serializer<'n, &'b mut Vec<u8>> {
+    // TODO: Inherit indentation state from parent.writer
+    let mut serializer = Serializer::with_root(Writer::new(buffer), root_tag);
+    serializer.write_empty_collections(parent.write_empty_collections);
+    serializer.nested_seq_tags(parent.group_tag, parent.item_tag);
+    serializer.escape(parent.escape);
+    serializer.sort_attributes(parent.sort_attributes);
+    serializer.scalars_as_attributes(parent.scalars_as_attributes);
+    serializer.map_attributes(parent.map_attributes);
+    serializer.top_level = false;
+    serializer
+}
+
 impl<'r, 'w, W> ser::SerializeMap for Map<'r, 'w, W>
 where
     W: 'w + Write,
@@ -40,21 +104,66 @@ where
             "impossible to serialize the key on its own, please use serialize_entry()",
         ))
         */
-        write!(self.parent.writer.inner(), "<enum key=\"").map_err(Error::Io)?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer.inner(), "\"/>").map_err(Error::Io)?;
-        Ok(())
+        let mut buffer = b"<enum key=\"".to_vec();
+        // Key is written as an attribute value, so quotes should be escaped
+        let mut serializer = nested(self.parent, &mut buffer, self.parent.root_tag);
+        if self.parent.escape == EscapeMode::Minimal {
+            serializer.escape(EscapeMode::Full);
+        }
+        key.serialize(&mut serializer)?;
+        buffer.extend_from_slice(b"\"/>");
+        self.write_child(&buffer)
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
-        value.serialize(&mut *self.parent)
+        let mut buffer = Vec::new();
+        value.serialize(&mut nested(self.parent, &mut buffer, self.parent.root_tag))?;
+        self.write_child(&buffer)
     }
 
     fn end(self) -> Result<Self::Ok, DeError> {
-        if let Some(tag) = self.parent.root_tag {
+        let tag = match self.parent.root_tag {
+            Some(tag) => tag,
+            None => return Ok(()),
+        };
+        let children = match self.children {
+            Some(children) => children,
+            None => {
+                if self.started {
+                    self.parent
+                        .writer
+                        .write_event(Event::End(BytesEnd::borrowed(tag.as_bytes())))?;
+                } else if self.parent.write_empty_collections || self.parent.top_level {
+                    self.parent
+                        .writer
+                        .write_event(Event::Empty(BytesStart::borrowed_name(tag.as_bytes())))?;
+                }
+                return Ok(());
+            }
+        };
+
+        let mut attrs = self.attrs;
+        if self.parent.sort_attributes {
+            // Namespace declarations go first, because they are applied to the other attributes
+            attrs.sort_by(|(a, _), (b, _)| {
+                (!is_namespace_declaration(a), a).cmp(&(!is_namespace_declaration(b), b))
+            });
+        }
+        let mut start = BytesStart::borrowed_name(tag.as_bytes());
+        for (key, value) in &attrs {
+            start.push_attribute((key.as_bytes(), value.as_slice()));
+        }
+
+        if !children.is_empty() {
             self.parent
                 .writer
-                .write_event(Event::End(BytesEnd::borrowed(tag.as_bytes())))?;
+                .write_event(Event::Start(start.to_borrowed()))?;
+            self.parent
+                .writer
+                .write_event(Event::Text(BytesText::from_escaped(children)))?;
+            self.parent.writer.write_event(Event::End(start.to_end()))?;
+        } else if self.started || self.parent.write_empty_collections || self.parent.top_level {
+            self.parent.writer.write_event(Event::Empty(start))?;
         }
         Ok(())
     }
@@ -66,15 +175,50 @@ where
     ) -> Result<(), DeError> {
         // TODO: Is it possible to ensure our key is never a composite type?
         // Anything which isn't a "primitive" would lead to malformed XML here...
-        write!(self.parent.writer.inner(), "<").map_err(Error::Io)?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer.inner(), ">").map_err(Error::Io)?;
-
-        value.serialize(&mut *self.parent)?;
-
-        write!(self.parent.writer.inner(), "</").map_err(Error::Io)?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer.inner(), ">").map_err(Error::Io)?;
+        let mut name = Vec::new();
+        key.serialize(&mut nested(self.parent, &mut name, None))?;
+        let name = String::from_utf8(name).map_err(|e| Error::Utf8(e.utf8_error()))?;
+
+        let mut buffer = Vec::new();
+        // Entries with the `@` prefix are written as attributes, if requested
+        let attribute = match name.strip_prefix(ATTRIBUTE_PREFIX) {
+            Some(name) if self.parent.map_attributes => Some(name),
+            _ => None,
+        };
+        if let Some(name) = attribute {
+            if self.children.is_none() {
+                return Err(DeError::Unsupported(
+                    "attributes of a map without a root tag name",
+                ));
+            }
+            let mut serializer = nested(self.parent, &mut buffer, Some(name));
+            serializer.attribute_value = true;
+            value.serialize(&mut serializer)?;
+            // Text content can contain unescaped quotes, that are not
+            // allowed in attribute values
+            let value = match self.parent.escape {
+                EscapeMode::Full | EscapeMode::Numeric => buffer,
+                EscapeMode::Minimal => escape_quotes(&buffer).into_owned(),
+            };
+            self.attrs.push((name.to_string(), value));
+            self.started = true;
+        } else {
+            value.serialize(&mut nested(self.parent, &mut buffer, Some(&name)))?;
+
+            if buffer.first() == Some(&b'<') || name == INNER_VALUE {
+                self.write_child(&buffer)?;
+            } else {
+                let mut child = Vec::with_capacity(buffer.len() + 2 * name.len() + 5);
+                child.push(b'<');
+                child.extend_from_slice(name.as_bytes());
+                child.push(b'>');
+                child.append(&mut buffer);
+                child.extend_from_slice(b"</");
+                child.extend_from_slice(name.as_bytes());
+                child.push(b'>');
+                self.write_child(&child)?;
+            }
+        }
         Ok(())
     }
 }
@@ -88,6 +232,9 @@ where
     /// Buffer for holding fields, serialized as attributes. Doesn't allocate
     /// if there are no fields represented as attributes
     attrs: BytesStart<'w>,
+    /// Fields, serialized as attributes, that are written to `attrs` after
+    /// sorting by name. Used only when [`Serializer::sort_attributes`] is enabled
+    sorted_attrs: Vec<(&'static str, Vec<u8>)>,
     /// Buffer for holding fields, serialized as elements
     children: Vec<u8>,
     /// Buffer for serializing one field. Cleared after serialize each field
@@ -104,6 +251,7 @@ where
         Struct {
             parent,
             attrs: BytesStart::borrowed_name(name),
+            sorted_attrs: Vec::new(),
             children: Vec::new(),
             buffer: Vec::new(),
         }
@@ -122,24 +270,44 @@ where
         key: &'static str,
         value: &T,
     ) -> Result<(), DeError> {
-        // TODO: Inherit indentation state from self.parent.writer
-        let writer = Writer::new(&mut self.buffer);
         if key.starts_with(UNFLATTEN_PREFIX) {
             let key = &key[UNFLATTEN_PREFIX.len()..];
-            let mut serializer = Serializer::with_root(writer, Some(key));
+            let mut serializer = nested(self.parent, &mut self.buffer, Some(key));
             serializer.serialize_newtype_struct(key, value)?;
             self.children.append(&mut self.buffer);
         } else {
-            let mut serializer = Serializer::with_root(writer, Some(key));
+            // Fields with the `@` prefix are always written as attributes
+            let explicit = key.strip_prefix(ATTRIBUTE_PREFIX);
+            let attribute = explicit.is_some() || self.parent.scalars_as_attributes;
+            let key = explicit.unwrap_or(key);
+            let mut serializer = nested(self.parent, &mut self.buffer, Some(key));
+            serializer.attribute_value = explicit.is_some();
             value.serialize(&mut serializer)?;
 
             if !self.buffer.is_empty() {
                 if self.buffer[0] == b'<' || key == INNER_VALUE {
                     // Drains buffer, moves it to children
                     self.children.append(&mut self.buffer);
+                } else if !attribute {
+                    self.children.push(b'<');
+                    self.children.extend_from_slice(key.as_bytes());
+                    self.children.push(b'>');
+                    self.children.append(&mut self.buffer);
+                    self.children.extend_from_slice(b"</");
+                    self.children.extend_from_slice(key.as_bytes());
+                    self.children.push(b'>');
                 } else {
-                    self.attrs
-                        .push_attribute((key.as_bytes(), self.buffer.as_ref()));
+                    // Text content can contain unescaped quotes, that are not
+                    // allowed in attribute values
+                    let value = match self.parent.escape {
+                        EscapeMode::Full | EscapeMode::Numeric => self.buffer.as_slice().into(),
+                        EscapeMode::Minimal => escape_quotes(&self.buffer),
+                    };
+                    if self.parent.sort_attributes {
+                        self.sorted_attrs.push((key, value.into_owned()));
+                    } else {
+                        self.attrs.push_attribute((key.as_bytes(), value.as_ref()));
+                    }
                     self.buffer.clear();
                 }
             }
@@ -148,7 +316,15 @@ where
         Ok(())
     }
 
-    fn end(self) -> Result<Self::Ok, DeError> {
+    fn end(mut self) -> Result<Self::Ok, DeError> {
+        // Namespace declarations go first, because they are applied to the other attributes
+        self.sorted_attrs
+            .sort_by_key(|(key, _)| (!is_namespace_declaration(key), *key));
+        for (key, value) in &self.sorted_attrs {
+            self.attrs
+                .push_attribute((key.as_bytes(), value.as_slice()));
+        }
+
         if self.children.is_empty() {
             self.parent.writer.write_event(Event::Empty(self.attrs))?;
         } else {
@@ -186,12 +362,27 @@ where
     }
 }
 
+/// Returns `true` if an attribute with the specified name is a namespace
+/// declaration, that is, `xmlns` or `xmlns:prefix`
+fn is_namespace_declaration(name: &str) -> bool {
+    match name.strip_prefix("xmlns") {
+        Some(rest) => rest.is_empty() || rest.starts_with(':'),
+        None => false,
+    }
+}
+
 /// An implementation of `SerializeSeq' for serializing to XML.
 pub struct Seq<'r, 'w, W>
 where
     W: 'w + Write,
 {
     parent: &'w mut Serializer<'r, W>,
+    /// `true` if no elements was serialized yet
+    empty: bool,
+    /// Name of the element surrounding the whole sequence. Specified only for
+    /// sequences nested in other sequences, elements of such sequences are
+    /// wrapped into item elements. See [`Serializer::nested_seq_tags`]
+    group: Option<&'r str>,
 }
 
 impl<'r, 'w, W> Seq<'r, 'w, W>
@@ -200,7 +391,20 @@ where
 {
     /// Create a new `Seq`
     pub fn new(parent: &'w mut Serializer<'r, W>) -> Self {
-        Seq { parent }
+        parent.seq_item = false;
+        Seq {
+            parent,
+            empty: true,
+            group: None,
+        }
+    }
+
+    /// Create a new `Seq` for a sequence, nested in another sequence. Such
+    /// sequence is surrounded by `<group>...</group>`
+    pub fn grouped(parent: &'w mut Serializer<'r, W>, group: &'r str) -> Self {
+        let mut seq = Self::new(parent);
+        seq.group = Some(group);
+        seq
     }
 }
 
@@ -215,11 +419,42 @@ where
     where
         T: Serialize,
     {
-        value.serialize(&mut *self.parent)?;
-        Ok(())
+        if let Some(group) = self.group {
+            if self.empty {
+                self.parent
+                    .writer
+                    .write_event(Event::Start(BytesStart::borrowed_name(group.as_bytes())))?;
+            }
+            self.empty = false;
+            // Item tag is written here, so structs should not write the root tag
+            let root = self.parent.root_tag.take();
+            let result = self.parent.write_paired(self.parent.item_tag, value);
+            self.parent.root_tag = root;
+            return result;
+        }
+        self.empty = false;
+        self.parent.seq_item = true;
+        let result = value.serialize(&mut *self.parent);
+        self.parent.seq_item = false;
+        result
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        if let Some(group) = self.group {
+            let group = group.as_bytes();
+            let event = if self.empty {
+                Event::Empty(BytesStart::borrowed_name(group))
+            } else {
+                Event::End(BytesEnd::borrowed(group))
+            };
+            self.parent.writer.write_event(event)?;
+        } else if self.empty && self.parent.write_empty_collections {
+            if let Some(tag) = self.parent.root_tag {
+                self.parent
+                    .writer
+                    .write_event(Event::Empty(BytesStart::borrowed_name(tag.as_bytes())))?;
+            }
+        }
         Ok(())
     }
 }
@@ -241,6 +476,7 @@ where
 {
     /// Create a new `Tuple`
     pub fn new(parent: &'w mut Serializer<'r, W>, name: &'r str) -> Self {
+        parent.seq_item = false;
         Tuple { parent, name }
     }
 }
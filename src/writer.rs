@@ -1,7 +1,9 @@
 //! A module to handle `Writer`
 
 use crate::errors::{Error, Result};
+use crate::escapei::escape;
 use crate::events::{attributes::Attribute, BytesCData, BytesStart, BytesText, Event};
+use crate::reader::{resolve_namespace, NamespaceBindings};
 use std::io::Write;
 
 /// XML writer.
@@ -58,6 +60,20 @@ pub struct Writer<W: Write> {
     /// underlying writer
     writer: W,
     indent: Option<Indentation>,
+    /// Namespace bindings, tracked only after the first call to
+    /// [`Self::bind_namespace`]
+    namespaces: Option<Namespaces>,
+    /// If `true`, content of events is checked for characters that are not
+    /// allowed in XML. See [`Self::check_output`]
+    check_output: bool,
+    /// If `true`, end tags are checked against names of opened elements.
+    /// See [`Self::check_nesting`]
+    check_nesting: bool,
+    /// All currently opened elements names, stored one after another,
+    /// tracked only when [`Self::check_nesting`] is enabled
+    opened_buffer: Vec<u8>,
+    /// Start positions of names of opened elements in the `opened_buffer`
+    opened_starts: Vec<usize>,
 }
 
 impl<W: Write> Writer<W> {
@@ -66,6 +82,11 @@ impl<W: Write> Writer<W> {
         Writer {
             writer: inner,
             indent: None,
+            namespaces: None,
+            check_output: false,
+            check_nesting: false,
+            opened_buffer: Vec::new(),
+            opened_starts: Vec::new(),
         }
     }
 
@@ -74,6 +95,11 @@ impl<W: Write> Writer<W> {
         Writer {
             writer: inner,
             indent: Some(Indentation::new(indent_char, indent_size)),
+            namespaces: None,
+            check_output: false,
+            check_nesting: false,
+            opened_buffer: Vec::new(),
+            opened_starts: Vec::new(),
         }
     }
 
@@ -87,8 +113,103 @@ impl<W: Write> Writer<W> {
         &mut self.writer
     }
 
+    /// Changes whether content of events is checked for characters that are
+    /// not allowed in XML 1.0 documents, such as `\0` or other control characters
+    /// except tab, carriage return and line feed.
+    ///
+    /// When set to `true`, [`write_event`] returns [`Error::InvalidCharacter`]
+    /// for events with such characters in a text, in attribute values or in any
+    /// other part of an event and nothing is written. Use it to catch bugs
+    /// when building XML from arbitrary data.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesText, Event};
+    /// use fast_xml::{Error, Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.check_output(true);
+    ///
+    /// match writer.write_event(Event::Text(BytesText::from_plain_str("a\0b"))) {
+    ///     Err(Error::InvalidCharacter { ch, offset }) => {
+    ///         assert_eq!(ch, '\0');
+    ///         assert_eq!(offset, 1);
+    ///     }
+    ///     x => panic!("Expected `Err(InvalidCharacter)`, but got `{:?}`", x),
+    /// }
+    /// assert_eq!(writer.into_inner(), b"");
+    /// ```
+    ///
+    /// [`write_event`]: Self::write_event
+    pub fn check_output(&mut self, val: bool) -> &mut Self {
+        self.check_output = val;
+        self
+    }
+
+    /// Changes whether end tags are checked against names of opened elements.
+    ///
+    /// When set to `true`, the writer keeps a stack of names of opened elements,
+    /// [`write_event`] returns [`Error::EndEventMismatch`] for an `End` event
+    /// which name does not match the name of the last opened element and nothing
+    /// is written, and [`finish`] returns [`Error::UnclosedElement`] if some
+    /// elements were not closed. Use it to catch bugs when writing events manually.
+    /// Content written by [`write_raw`] is not checked.
+    ///
+    /// This option should be enabled before the first event is written.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::{BytesEnd, BytesStart, Event};
+    /// use fast_xml::{Error, Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.check_nesting(true);
+    ///
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"root")))?;
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"child")))?;
+    /// match writer.write_event(Event::End(BytesEnd::borrowed(b"root"))) {
+    ///     Err(Error::EndEventMismatch { expected, found }) => {
+    ///         assert_eq!(expected, "child");
+    ///         assert_eq!(found, "root");
+    ///     }
+    ///     x => panic!("Expected `Err(EndEventMismatch)`, but got `{:?}`", x),
+    /// }
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"child")))?;
+    /// match writer.finish() {
+    ///     Err(Error::UnclosedElement { name }) => assert_eq!(name, "root"),
+    ///     x => panic!("Expected `Err(UnclosedElement)`, but got `{:?}`", x),
+    /// }
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"root")))?;
+    /// writer.finish()?;
+    ///
+    /// assert_eq!(writer.into_inner(), b"<root><child></child></root>");
+    /// # Ok::<(), fast_xml::Error>(())
+    /// ```
+    ///
+    /// [`write_event`]: Self::write_event
+    /// [`finish`]: Self::finish
+    /// [`write_raw`]: Self::write_raw
+    pub fn check_nesting(&mut self, val: bool) -> &mut Self {
+        self.check_nesting = val;
+        self
+    }
+
     /// Writes the given event to the underlying writer.
     pub fn write_event<'a, E: AsRef<Event<'a>>>(&mut self, event: E) -> Result<()> {
+        if self.check_output {
+            check_chars(event.as_ref())?;
+        }
+        if self.check_nesting {
+            self.track_nesting(event.as_ref())?;
+        }
         let mut next_should_line_break = true;
         let result = match *event.as_ref() {
             Event::Start(ref e) => {
@@ -96,15 +217,24 @@ impl<W: Write> Writer<W> {
                 if let Some(i) = self.indent.as_mut() {
                     i.grow();
                 }
+                if let Some(ns) = self.namespaces.as_mut() {
+                    ns.scope.push(e);
+                }
                 result
             }
             Event::End(ref e) => {
                 if let Some(i) = self.indent.as_mut() {
                     i.shrink();
                 }
+                if let Some(ns) = self.namespaces.as_mut() {
+                    ns.scope.pop();
+                }
                 self.write_wrapped(b"</", e, b">")
             }
-            Event::Empty(ref e) => self.write_wrapped(b"<", e, b"/>"),
+            Event::Empty(ref e) => {
+                // Declarations on an empty element are never in scope of other elements
+                self.write_wrapped(b"<", e, b"/>")
+            }
             Event::Text(ref e) => {
                 next_should_line_break = false;
                 self.write(&e.escaped())
@@ -119,12 +249,79 @@ impl<W: Write> Writer<W> {
             Event::Decl(ref e) => self.write_wrapped(b"<?", e, b"?>"),
             Event::PI(ref e) => self.write_wrapped(b"<?", e, b"?>"),
             Event::DocType(ref e) => self.write_wrapped(b"<!DOCTYPE ", e, b">"),
+            Event::GeneralRef(ref e) => {
+                next_should_line_break = false;
+                self.write(b"&")?;
+                self.write(e)?;
+                self.write(b";")
+            }
             Event::Eof => Ok(()),
         };
         if let Some(i) = self.indent.as_mut() {
             i.should_line_break = next_should_line_break;
         }
-        result
+        result.map_err(|e| match e {
+            Error::Io(source) => {
+                let (event, name) = describe(event.as_ref());
+                Error::WriteEvent {
+                    event,
+                    name,
+                    source,
+                }
+            }
+            e => e,
+        })
+    }
+
+    /// Flushes the underlying writer, ensuring that all written data reached
+    /// its destination.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(Error::Io)
+    }
+
+    /// Finishes the document: checks that all elements are closed, if
+    /// [`check_nesting`] is enabled, and flushes the underlying writer.
+    ///
+    /// Returns [`Error::UnclosedElement`] with the name of the last opened
+    /// element, if some elements were not closed. The writer can still be used
+    /// after that, for example, to write the missing end tags.
+    ///
+    /// [`check_nesting`]: Self::check_nesting
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(&start) = self.opened_starts.last() {
+            return Err(Error::UnclosedElement {
+                name: String::from_utf8_lossy(&self.opened_buffer[start..]).into_owned(),
+            });
+        }
+        self.flush()
+    }
+
+    /// Updates the stack of opened elements. Returns an error if the `End`
+    /// event does not close the last opened element
+    fn track_nesting(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::Start(e) => {
+                self.opened_starts.push(self.opened_buffer.len());
+                self.opened_buffer.extend_from_slice(e.name());
+            }
+            Event::End(e) => {
+                let expected: &[u8] = match self.opened_starts.last() {
+                    Some(&start) => &self.opened_buffer[start..],
+                    None => b"",
+                };
+                if e.name() != expected {
+                    return Err(Error::EndEventMismatch {
+                        expected: String::from_utf8_lossy(expected).into_owned(),
+                        found: String::from_utf8_lossy(e.name()).into_owned(),
+                    });
+                }
+                if let Some(start) = self.opened_starts.pop() {
+                    self.opened_buffer.truncate(start);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
     }
 
     /// Writes bytes
@@ -133,14 +330,47 @@ impl<W: Write> Writer<W> {
         self.writer.write_all(value).map_err(Error::Io)
     }
 
+    /// Changes the line ending, that is written before indentation. Text content
+    /// is written as is, line endings inside it are not changed.
+    ///
+    /// This method does nothing if `Writer` was not constructed with `new_with_indent`.
+    ///
+    /// ([`LineEnding::Lf`] by default)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::events::BytesText;
+    /// use fast_xml::{LineEnding, Writer};
+    ///
+    /// let mut writer = Writer::new_with_indent(Vec::new(), b'\t', 1);
+    /// writer.line_ending(LineEnding::CrLf);
+    /// writer.create_element("root").write_inner_content(|writer| {
+    ///     writer
+    ///         .create_element("item")
+    ///         .write_text_content(BytesText::from_plain_str("1\n2"))?;
+    ///     Ok(())
+    /// })?;
+    ///
+    /// assert_eq!(
+    ///     std::str::from_utf8(&writer.into_inner()).unwrap(),
+    ///     "<root>\r\n\t<item>1\n2</item>\r\n</root>"
+    /// );
+    /// # Ok::<(), fast_xml::Error>(())
+    /// ```
+    pub fn line_ending(&mut self, ending: LineEnding) -> &mut Self {
+        if let Some(i) = self.indent.as_mut() {
+            i.line_ending = ending;
+        }
+        self
+    }
+
     #[inline]
     fn write_wrapped(&mut self, before: &[u8], value: &[u8], after: &[u8]) -> Result<()> {
         if let Some(ref i) = self.indent {
             if i.should_line_break {
-                self.writer.write_all(b"\n").map_err(Error::Io)?;
-                self.writer
-                    .write_all(&i.indents[..i.indents_len])
-                    .map_err(Error::Io)?;
+                self.write_line_break()?;
             }
         }
         self.write(before)?;
@@ -159,8 +389,18 @@ impl<W: Write> Writer<W> {
     /// [Text]: events/enum.Event.html#variant.Text
     /// [Start]: events/enum.Event.html#variant.Start
     pub fn write_indent(&mut self) -> Result<()> {
+        if self.indent.is_some() {
+            self.write_line_break()?;
+        }
+        Ok(())
+    }
+
+    /// Writes a line ending and indentation at the current level
+    fn write_line_break(&mut self) -> Result<()> {
         if let Some(ref i) = self.indent {
-            self.writer.write_all(b"\n").map_err(Error::Io)?;
+            self.writer
+                .write_all(i.line_ending.as_bytes())
+                .map_err(Error::Io)?;
             self.writer
                 .write_all(&i.indents[..i.indents_len])
                 .map_err(Error::Io)?;
@@ -168,6 +408,51 @@ impl<W: Write> Writer<W> {
         Ok(())
     }
 
+    /// Writes a pre-formatted XML fragment verbatim, without parsing or escaping it.
+    ///
+    /// This is useful to splice trusted markup, for example a cached rendered
+    /// fragment, into a generated document.
+    ///
+    /// The caller is responsible for the well-formedness of the fragment. Unbalanced
+    /// tags, unescaped `<` and `&` characters or a wrong encoding will produce a broken
+    /// document and are not reported.
+    ///
+    /// When indentation is enabled, the fragment is handled as a single element:
+    /// it is written on its own line at the current level and the next element
+    /// starts on a new line. The structure inside the fragment is not tracked, so
+    /// its own lines are not re-indented and it should not leave any elements open.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pretty_assertions::assert_eq;
+    /// use fast_xml::Writer;
+    /// use fast_xml::events::BytesText;
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.create_element("root").write_inner_content(|writer| {
+    ///     writer
+    ///         .create_element("before")
+    ///         .write_text_content(BytesText::from_plain_str("1 < 2"))?;
+    ///     writer.write_raw(r#"<cached attr="&amp;">fragment</cached>"#)?;
+    ///     writer.create_element("after").write_empty()?;
+    ///     Ok(())
+    /// })?;
+    ///
+    /// assert_eq!(
+    ///     std::str::from_utf8(&writer.into_inner()).unwrap(),
+    ///     r#"<root><before>1 &lt; 2</before><cached attr="&amp;">fragment</cached><after/></root>"#
+    /// );
+    /// # Ok::<(), fast_xml::Error>(())
+    /// ```
+    pub fn write_raw(&mut self, xml: &str) -> Result<()> {
+        self.write_wrapped(b"", xml.as_bytes(), b"")?;
+        if let Some(i) = self.indent.as_mut() {
+            i.should_line_break = true;
+        }
+        Ok(())
+    }
+
     /// Provides a simple, high-level API for writing XML elements.
     ///
     /// Returns an [ElementWriter] that simplifies setting attributes and writing content inside the element.
@@ -218,6 +503,88 @@ impl<W: Write> Writer<W> {
             start_tag: BytesStart::borrowed_name(name.as_ref()),
         }
     }
+
+    /// Binds a namespace prefix to a namespace name (URI). An empty prefix binds
+    /// the default namespace.
+    ///
+    /// The binding is not written immediately. Instead, elements and attributes
+    /// created with [`create_ns_element`] and [`ElementWriter::with_ns_attribute`]
+    /// get an `xmlns:prefix="uri"` (or `xmlns="uri"`) declaration when they use
+    /// the prefix and the same binding is not already in scope. Binding a prefix
+    /// again to another namespace makes subsequent elements declare the new one.
+    ///
+    /// Scopes of declarations are tracked over all written events, starting
+    /// from the first call of this method.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pretty_assertions::assert_eq;
+    /// # use fast_xml::Result;
+    /// # fn main() -> Result<()> {
+    /// use fast_xml::Writer;
+    /// use fast_xml::events::BytesText;
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()));
+    /// writer
+    ///     .bind_namespace("", "http://www.w3.org/2005/Atom")
+    ///     .bind_namespace("media", "http://search.yahoo.com/mrss/");
+    ///
+    /// writer
+    ///     .create_ns_element(("", "feed"))
+    ///     .write_inner_content(|writer| {
+    ///         writer
+    ///             .create_ns_element(("media", "title"))
+    ///             .write_text_content(BytesText::from_plain_str("one"))?;
+    ///         writer
+    ///             .create_ns_element(("media", "title"))
+    ///             .write_text_content(BytesText::from_plain_str("two"))?;
+    ///         Ok(())
+    ///     })?;
+    ///
+    /// assert_eq!(
+    ///     std::str::from_utf8(&writer.into_inner().into_inner()).unwrap(),
+    ///     r#"<feed xmlns="http://www.w3.org/2005/Atom">"#.to_owned()
+    ///         + r#"<media:title xmlns:media="http://search.yahoo.com/mrss/">one</media:title>"#
+    ///         + r#"<media:title xmlns:media="http://search.yahoo.com/mrss/">two</media:title>"#
+    ///         + r#"</feed>"#
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`create_ns_element`]: Self::create_ns_element
+    pub fn bind_namespace(&mut self, prefix: &str, namespace: &str) -> &mut Self {
+        self.namespaces
+            .get_or_insert_with(Namespaces::default)
+            .known
+            .push((
+                prefix.as_bytes().to_vec(),
+                escape(namespace.as_bytes()).into_owned(),
+            ));
+        self
+    }
+
+    /// Creates an [`ElementWriter`] for an element with a name, given as
+    /// a `(prefix, local name)` pair. An empty prefix means an unprefixed name.
+    ///
+    /// If the prefix was bound with [`bind_namespace`] and that binding is not
+    /// in scope, a namespace declaration is added to the element. Prefixes that
+    /// were not bound are written as is, without declarations.
+    ///
+    /// [`bind_namespace`]: Self::bind_namespace
+    #[must_use]
+    pub fn create_ns_element(&mut self, name: (&str, &str)) -> ElementWriter<'_, W> {
+        let mut start_tag = BytesStart::owned_name(qualified_name(name));
+        if let Some(ns) = self.namespaces.as_ref() {
+            ns.declare(&mut start_tag, name.0.as_bytes());
+        }
+        ElementWriter {
+            writer: self,
+            start_tag,
+        }
+    }
 }
 
 /// A struct to write an element. Contains methods to add attributes and inner
@@ -237,6 +604,23 @@ impl<'a, W: Write> ElementWriter<'a, W> {
         self
     }
 
+    /// Adds an attribute with a name, given as a `(prefix, local name)` pair.
+    ///
+    /// If the prefix was bound with [`Writer::bind_namespace`] and that binding
+    /// is not in scope, a namespace declaration is added to this element.
+    /// Unprefixed attributes are never in a namespace, so an empty prefix
+    /// does not declare the default namespace.
+    pub fn with_ns_attribute(mut self, name: (&str, &str), value: &str) -> Self {
+        if !name.0.is_empty() {
+            if let Some(ns) = self.writer.namespaces.as_ref() {
+                ns.declare(&mut self.start_tag, name.0.as_bytes());
+            }
+        }
+        self.start_tag
+            .push_attribute((qualified_name(name).as_str(), value));
+        self
+    }
+
     /// Add additional attributes to this element using an iterator.
     ///
     /// The yielded items must be convertible to [`Attribute`] using `Into`.
@@ -299,9 +683,131 @@ impl<'a, W: Write> ElementWriter<'a, W> {
     }
 }
 
+/// Namespace bindings of a [`Writer`]
+#[derive(Clone, Default)]
+struct Namespaces {
+    /// Prefix to escaped namespace name mappings, registered by
+    /// [`Writer::bind_namespace`]. Later bindings take precedence
+    known: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Namespace declarations in scope of the currently written element
+    scope: NamespaceBindings,
+}
+
+impl Namespaces {
+    /// Adds a declaration of the namespace, bound to `prefix`, to the `start`
+    /// element, if it is not in scope and not yet declared on that element
+    fn declare(&self, start: &mut BytesStart, prefix: &[u8]) {
+        let namespace = match self.known.iter().rfind(|(p, _)| p == prefix) {
+            Some((_, namespace)) => namespace,
+            None => return,
+        };
+        // A name with the prefix, resolving which gives the namespace for that prefix
+        let mut qname = prefix.to_vec();
+        if !prefix.is_empty() {
+            qname.push(b':');
+        }
+        if resolve_namespace(&qname, &self.scope) == Some(namespace) {
+            return;
+        }
+
+        let mut key = b"xmlns".to_vec();
+        if !prefix.is_empty() {
+            key.push(b':');
+            key.extend_from_slice(prefix);
+        }
+        let declared = start
+            .attributes()
+            .with_checks(false)
+            .any(|a| matches!(a, Ok(a) if a.key == key.as_slice()));
+        if !declared {
+            start.push_attribute((key.as_slice(), namespace.as_slice()));
+        }
+    }
+}
+
+/// Returns kind of the event and the element name, if applicable, to report
+/// in [`Error::WriteEvent`] and [`Error::StartNotFound`]
+pub(crate) fn describe(event: &Event) -> (&'static str, Option<String>) {
+    let name = |e: &[u8]| Some(String::from_utf8_lossy(e).into_owned());
+    match event {
+        Event::Start(e) => ("Start", name(e.name())),
+        Event::End(e) => ("End", name(e.name())),
+        Event::Empty(e) => ("Empty", name(e.name())),
+        Event::Text(_) => ("Text", None),
+        Event::Comment(_) => ("Comment", None),
+        Event::CData(_) => ("CData", None),
+        Event::Decl(_) => ("Decl", None),
+        Event::PI(_) => ("PI", None),
+        Event::DocType(_) => ("DocType", None),
+        Event::GeneralRef(_) => ("GeneralRef", None),
+        Event::Eof => ("Eof", None),
+    }
+}
+
+/// Checks that the content of the event contains only characters, allowed by
+/// the XML 1.0 [`Char`] production. Content is expected to be in UTF-8, where
+/// the only disallowed characters are control characters (except tab, carriage
+/// return and line feed) and the `U+FFFE` and `U+FFFF` noncharacters
+///
+/// [`Char`]: https://www.w3.org/TR/xml/#NT-Char
+fn check_chars(event: &Event) -> Result<()> {
+    let content: &[u8] = event;
+    for (offset, &b) in content.iter().enumerate() {
+        let ch = match b {
+            b'\t' | b'\n' | b'\r' => continue,
+            0..=0x1F => b as char,
+            0xEF => match content.get(offset + 1..offset + 3) {
+                Some(b"\xBF\xBE") => '\u{FFFE}',
+                Some(b"\xBF\xBF") => '\u{FFFF}',
+                _ => continue,
+            },
+            _ => continue,
+        };
+        return Err(Error::InvalidCharacter { ch, offset });
+    }
+    Ok(())
+}
+
+/// Joins a `(prefix, local name)` pair into a qualified name
+fn qualified_name((prefix, local): (&str, &str)) -> String {
+    if prefix.is_empty() {
+        local.to_owned()
+    } else {
+        format!("{}:{}", prefix, local)
+    }
+}
+
+/// A line ending, that is written by a [`Writer`] with indentation before
+/// each indented line. See [`Writer::line_ending`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix line ending: `\n`
+    Lf,
+    /// Windows line ending: `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    #[inline]
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Lf => b"\n",
+            Self::CrLf => b"\r\n",
+        }
+    }
+}
+
+impl Default for LineEnding {
+    #[inline]
+    fn default() -> Self {
+        Self::Lf
+    }
+}
+
 #[derive(Clone)]
 struct Indentation {
     should_line_break: bool,
+    line_ending: LineEnding,
     indent_char: u8,
     indent_size: usize,
     indents: Vec<u8>,
@@ -312,6 +818,7 @@ impl Indentation {
     fn new(indent_char: u8, indent_size: usize) -> Indentation {
         Indentation {
             should_line_break: false,
+            line_ending: LineEnding::Lf,
             indent_char,
             indent_size,
             indents: vec![indent_char; 128],
@@ -504,6 +1011,75 @@ mod indentation {
 </paired>"#
         );
     }
+
+    /// Writes a nested element with a multi-line text with the specified line ending
+    fn write_with_line_ending(ending: LineEnding) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new_with_indent(&mut buffer, b'\t', 1);
+        writer.line_ending(ending);
+
+        writer
+            .create_element("outer")
+            .write_inner_content(|writer| {
+                writer
+                    .create_element("text")
+                    .write_text_content(BytesText::from_plain_str("line 1\nline 2"))?;
+                writer
+                    .create_element("inner")
+                    .write_inner_content(|writer| {
+                        writer.create_element("empty").write_empty()?;
+                        Ok(())
+                    })?;
+                writer.write_indent()?;
+                writer.write_event(Event::Comment(BytesText::from_escaped_str("comment")))?;
+                Ok(())
+            })
+            .expect("failure");
+        buffer
+    }
+
+    #[test]
+    fn line_ending_lf() {
+        assert_eq!(
+            std::str::from_utf8(&write_with_line_ending(LineEnding::Lf)).unwrap(),
+            "<outer>\n\t<text>line 1\nline 2</text>\n\t<inner>\n\t\t<empty/>\n\t</inner>\n\t\n\t<!--comment-->\n</outer>"
+        );
+    }
+
+    /// Line endings inside the text are not changed
+    #[test]
+    fn line_ending_crlf() {
+        assert_eq!(
+            std::str::from_utf8(&write_with_line_ending(LineEnding::CrLf)).unwrap(),
+            "<outer>\r\n\t<text>line 1\nline 2</text>\r\n\t<inner>\r\n\t\t<empty/>\r\n\t</inner>\r\n\t\r\n\t<!--comment-->\r\n</outer>"
+        );
+    }
+
+    #[test]
+    fn raw() {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new_with_indent(&mut buffer, b' ', 4);
+
+        writer
+            .create_element("outer")
+            .write_inner_content(|writer| {
+                writer.create_element("before").write_empty()?;
+                writer.write_raw("<raw><inner/></raw>")?;
+                writer.create_element("after").write_empty()?;
+                Ok(())
+            })
+            .expect("failure");
+
+        assert_eq!(
+            std::str::from_utf8(&buffer).unwrap(),
+            r#"<outer>
+    <before/>
+    <raw><inner/></raw>
+    <after/>
+</outer>"#
+        );
+    }
+
     #[test]
     fn element_writer_empty() {
         let mut buffer = Vec::new();
@@ -347,3 +347,206 @@ fn default_ns_shadowing_expanded() {
         e => panic!("Expected End event (</outer>), got {:?}", e),
     }
 }
+
+/// Resolution using standalone bindings, without the namespaced reading mode
+mod bindings {
+    use fast_xml::events::BytesStart;
+    use fast_xml::events::Event::*;
+    use fast_xml::{resolve_namespace, NamespaceBindings, Reader};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn prefixed() {
+        let mut bindings = NamespaceBindings::new();
+        bindings.push(&BytesStart::borrowed(br#"a xmlns:p="urn:p""#, 1));
+        assert_eq!(
+            resolve_namespace(b"p:b", &bindings),
+            Some(b"urn:p".as_ref())
+        );
+
+        // Inner declaration shadows the outer one
+        bindings.push(&BytesStart::borrowed(br#"p:b xmlns:p="urn:inner""#, 3));
+        assert_eq!(
+            resolve_namespace(b"p:b", &bindings),
+            Some(b"urn:inner".as_ref())
+        );
+
+        bindings.pop();
+        assert_eq!(
+            resolve_namespace(b"p:b", &bindings),
+            Some(b"urn:p".as_ref())
+        );
+    }
+
+    #[test]
+    fn default() {
+        let mut bindings = NamespaceBindings::new();
+        assert_eq!(resolve_namespace(b"a", &bindings), None);
+
+        bindings.push(&BytesStart::borrowed(br#"a xmlns="urn:default""#, 1));
+        assert_eq!(
+            resolve_namespace(b"a", &bindings),
+            Some(b"urn:default".as_ref())
+        );
+
+        // Default namespace can be unbound
+        bindings.push(&BytesStart::borrowed(br#"b xmlns="""#, 1));
+        assert_eq!(resolve_namespace(b"b", &bindings), None);
+
+        bindings.pop();
+        assert_eq!(
+            resolve_namespace(b"b", &bindings),
+            Some(b"urn:default".as_ref())
+        );
+    }
+
+    #[test]
+    fn undeclared() {
+        let mut bindings = NamespaceBindings::new();
+        bindings.push(&BytesStart::borrowed(br#"a xmlns:p="urn:p""#, 1));
+        assert_eq!(resolve_namespace(b"q:b", &bindings), None);
+
+        bindings.pop();
+        // Popping more scopes than pushed does nothing
+        bindings.pop();
+        assert_eq!(resolve_namespace(b"p:b", &bindings), None);
+    }
+
+    /// Bindings follow events of an ordinary reader
+    #[test]
+    fn with_reader() {
+        let mut reader =
+            Reader::from_str(r#"<a xmlns:p="urn:p"><p:b xmlns:p="urn:inner"/><p:c/></a>"#);
+        let mut bindings = NamespaceBindings::new();
+        let mut buf = Vec::new();
+        let mut resolved = Vec::new();
+        loop {
+            match reader.read_event(&mut buf).unwrap() {
+                Start(e) => {
+                    bindings.push(&e);
+                    resolved.push(resolve_namespace(e.name(), &bindings).map(<[u8]>::to_vec));
+                }
+                Empty(e) => {
+                    bindings.push(&e);
+                    resolved.push(resolve_namespace(e.name(), &bindings).map(<[u8]>::to_vec));
+                    bindings.pop();
+                }
+                End(_) => bindings.pop(),
+                Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        assert_eq!(
+            resolved,
+            vec![None, Some(b"urn:inner".to_vec()), Some(b"urn:p".to_vec())]
+        );
+    }
+}
+
+/// Qualified names are compared with prefixes, namespaces should be compared
+/// separately
+#[test]
+fn qname_comparison() {
+    let mut r = Reader::from_str("<a xmlns:x='www1' xmlns='www2'><x:b/><b/></a>");
+    r.trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    let mut names = Vec::new();
+    loop {
+        match r.read_namespaced_event(&mut buf, &mut ns_buf) {
+            Ok((ns, Empty(e))) => {
+                let name = e.qname();
+                assert!(name.local_name() == "b");
+                names.push((ns.map(|ns| ns.to_vec()), name == "b", name == "x:b"));
+            }
+            Ok((_, Eof)) => break,
+            Ok(_) => (),
+            e => panic!("Unexpected event {:?}", e),
+        }
+        buf.clear();
+    }
+    assert_eq!(
+        names,
+        vec![
+            (Some(b"www1".to_vec()), false, true),
+            (Some(b"www2".to_vec()), true, false),
+        ]
+    );
+}
+
+/// Snapshots of the namespace bindings, that are in scope after each start
+/// and end event
+#[test]
+fn in_scope_bindings() {
+    let mut r = Reader::from_str(
+        r#"<root xmlns="urn:default" xmlns:p="urn:p">
+            <p:a xmlns:p="urn:other" xmlns:q="urn:q">
+                <b xmlns=""/>
+            </p:a>
+            <c xmlns:q="urn:q2"/>
+        </root>"#,
+    );
+    r.trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    let mut snapshots = Vec::new();
+    loop {
+        match r.read_namespaced_event(&mut buf, &mut ns_buf) {
+            Ok((_, Start(e))) | Ok((_, Empty(e))) => {
+                let bindings: Vec<_> = r
+                    .namespaces(&ns_buf)
+                    .map(|(prefix, ns)| {
+                        (
+                            prefix.map(|p| String::from_utf8(p.to_vec()).unwrap()),
+                            String::from_utf8(ns.to_vec()).unwrap(),
+                        )
+                    })
+                    .collect();
+                snapshots.push((String::from_utf8(e.name().to_vec()).unwrap(), bindings));
+            }
+            Ok((_, Eof)) => break,
+            Ok(_) => (),
+            e => panic!("Unexpected event {:?}", e),
+        }
+        buf.clear();
+    }
+
+    let binding = |prefix: Option<&str>, ns: &str| (prefix.map(str::to_string), ns.to_string());
+    assert_eq!(
+        snapshots,
+        vec![
+            (
+                "root".to_string(),
+                vec![binding(None, "urn:default"), binding(Some("p"), "urn:p")],
+            ),
+            (
+                "p:a".to_string(),
+                vec![
+                    binding(None, "urn:default"),
+                    binding(Some("p"), "urn:other"),
+                    binding(Some("q"), "urn:q"),
+                ],
+            ),
+            // Default namespace is removed from scope
+            (
+                "b".to_string(),
+                vec![binding(Some("p"), "urn:other"), binding(Some("q"), "urn:q")],
+            ),
+            // Bindings of `<p:a>` are out of scope
+            (
+                "c".to_string(),
+                vec![
+                    binding(None, "urn:default"),
+                    binding(Some("p"), "urn:p"),
+                    binding(Some("q"), "urn:q2"),
+                ],
+            ),
+        ]
+    );
+
+    // All bindings are out of scope after the end of the document
+    assert_eq!(r.namespaces(&ns_buf).count(), 0);
+}
@@ -441,6 +441,49 @@ mod unit {
         let data: Unit = from_str(r#"<root><![CDATA[excess CDATA]]></root>"#).unwrap();
         assert_eq!(data, Unit);
     }
+
+    /// Tests for [`Deserializer::strict_unit`]
+    mod strict {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn strict<T: serde::de::DeserializeOwned>(xml: &str) -> Result<T, DeError> {
+            let mut de = Deserializer::from_str(xml);
+            de.strict_unit(true);
+            T::deserialize(&mut de)
+        }
+
+        #[test]
+        fn empty() {
+            let data: Unit = strict("<root/>").unwrap();
+            assert_eq!(data, Unit);
+
+            let data: Unit = strict("<root></root>").unwrap();
+            assert_eq!(data, Unit);
+
+            let data: () = strict("<root />").unwrap();
+            assert_eq!(data, ());
+        }
+
+        macro_rules! rejects {
+            ($name:ident: $xml:literal) => {
+                #[test]
+                fn $name() {
+                    match strict::<Unit>($xml) {
+                        Err(DeError::ExpectedEmpty(tag)) => assert_eq!(tag, b"root"),
+                        x => panic!("Expected `Err(ExpectedEmpty(root))`, but got `{:?}`", x),
+                    }
+                }
+            };
+        }
+
+        rejects!(excess_attribute: r#"<root excess="attribute"/>"#);
+        rejects!(excess_attribute_with_end: r#"<root excess="attribute"></root>"#);
+        rejects!(excess_element: "<root><excess>element</excess></root>");
+        rejects!(excess_empty_element: "<root><excess/></root>");
+        rejects!(excess_text: "<root>excess text</root>");
+        rejects!(excess_cdata: "<root><![CDATA[excess CDATA]]></root>");
+    }
 }
 
 mod newtype {
@@ -461,6 +504,83 @@ mod newtype {
         let data: Newtype = from_str(r#"<root excess="attribute">true</root>"#).unwrap();
         assert_eq!(data, Newtype(true));
     }
+
+    /// A newtype over a sequence is represented by an element, which children
+    /// are items of the sequence
+    mod sequence {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Tags(Vec<String>);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Shape {
+            Circle(u32),
+            Square { side: u32 },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Shapes(Vec<Shape>);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Holder {
+            tags: Tags,
+            shapes: Shapes,
+            after: u32,
+        }
+
+        #[test]
+        fn strings() {
+            let data: Tags = from_str("<tags><tag>a</tag><tag>b</tag></tags>").unwrap();
+            assert_eq!(data, Tags(vec!["a".into(), "b".into()]));
+        }
+
+        #[test]
+        fn empty() {
+            let data: Tags = from_str("<tags/>").unwrap();
+            assert_eq!(data, Tags(vec![]));
+        }
+
+        /// Children with different names are items of the same sequence
+        #[test]
+        fn enums() {
+            let data: Shapes = from_str(
+                r#"<shapes><Circle>1</Circle><Square side="2"/><Circle>3</Circle></shapes>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Shapes(vec![
+                    Shape::Circle(1),
+                    Shape::Square { side: 2 },
+                    Shape::Circle(3),
+                ])
+            );
+        }
+
+        #[test]
+        fn fields() {
+            let data: Holder = from_str(
+                r#"
+                <root>
+                    <tags><tag>a</tag><tag>b</tag></tags>
+                    <shapes><Square side="2"/><Circle>1</Circle></shapes>
+                    <after>42</after>
+                </root>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Holder {
+                    tags: Tags(vec!["a".into(), "b".into()]),
+                    shapes: Shapes(vec![Shape::Square { side: 2 }, Shape::Circle(1)]),
+                    after: 42,
+                }
+            );
+        }
+    }
 }
 
 mod tuple {
@@ -486,6 +606,208 @@ mod tuple {
         .unwrap();
         assert_eq!(data, (42.0, "answer".into()));
     }
+
+    /// Sequence of tuples in a `$value` field, each filled from the consecutive
+    /// elements with different names
+    mod alternating {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pairs {
+            #[serde(rename = "$value")]
+            items: Vec<(String, String)>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pair(String, u32);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct TupleStructs {
+            #[serde(rename = "$value")]
+            items: Vec<Pair>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(from = "(String, String)")]
+        struct Entry {
+            term: String,
+            definition: String,
+        }
+        impl From<(String, String)> for Entry {
+            fn from((term, definition): (String, String)) -> Self {
+                Entry { term, definition }
+            }
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Entries {
+            #[serde(rename = "$value")]
+            items: Vec<Entry>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Document {
+            dl: Entries,
+            other: String,
+        }
+
+        #[test]
+        fn tuples() {
+            let data: Pairs =
+                from_str("<dl><dt>a</dt><dd>1</dd><dt>b</dt><dd>2</dd></dl>").unwrap();
+            assert_eq!(
+                data,
+                Pairs {
+                    items: vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+                }
+            );
+        }
+
+        #[test]
+        fn tuple_structs() {
+            let data: TupleStructs =
+                from_str("<dl><dt>a</dt><dd>1</dd><dt>b</dt><dd>2</dd></dl>").unwrap();
+            assert_eq!(
+                data,
+                TupleStructs {
+                    items: vec![Pair("a".into(), 1), Pair("b".into(), 2)],
+                }
+            );
+        }
+
+        #[test]
+        fn structs() {
+            let data: Document = from_str(
+                "<doc><dl><dt>a</dt><dd>1</dd><dt>b</dt><dd>2</dd></dl><other>x</other></doc>",
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Document {
+                    dl: Entries {
+                        items: vec![
+                            Entry {
+                                term: "a".into(),
+                                definition: "1".into(),
+                            },
+                            Entry {
+                                term: "b".into(),
+                                definition: "2".into(),
+                            },
+                        ],
+                    },
+                    other: "x".into(),
+                }
+            );
+        }
+
+        #[test]
+        fn unpaired() {
+            match from_str::<Pairs>("<dl><dt>a</dt><dd>1</dd><dt>b</dt></dl>") {
+                Err(DeError::InvalidLength { got, expected }) => {
+                    assert_eq!(got, 1);
+                    assert_eq!(expected, "a tuple of size 2");
+                }
+                x => panic!("Expected `Err(InvalidLength(_))`, but got `{:?}`", x),
+            }
+        }
+    }
+
+    /// Tuple as a struct field, filled from the repeated elements with the
+    /// name of the field
+    mod field {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            coords: (f64, f64, f64),
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Mixed {
+            item: (u32, String, bool),
+            other: String,
+        }
+
+        #[test]
+        fn simple() {
+            let data: Point = from_str(
+                r#"<root><coords>1</coords><coords>-2.5</coords><coords>3e2</coords></root>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Point {
+                    coords: (1.0, -2.5, 300.0)
+                }
+            );
+        }
+
+        #[test]
+        fn heterogeneous() {
+            let data: Mixed = from_str(
+                r#"<root><item>42</item><item>answer</item><item>true</item><other>x</other></root>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Mixed {
+                    item: (42, "answer".into(), true),
+                    other: "x".into(),
+                }
+            );
+        }
+
+        #[test]
+        fn not_enough() {
+            let data = from_str::<Point>(r#"<root><coords>1</coords><coords>2</coords></root>"#);
+            match data {
+                Err(DeError::InvalidLength { got, expected }) => {
+                    assert_eq!(got, 2);
+                    assert_eq!(expected, "a tuple of size 3");
+                }
+                e => panic!("Expected `InvalidLength`, found {:?}", e),
+            }
+
+            // Other element terminates the tuple
+            let data = from_str::<Mixed>(
+                r#"<root><item>42</item><item>answer</item><other>x</other></root>"#,
+            );
+            match data {
+                Err(DeError::InvalidLength { got, expected }) => {
+                    assert_eq!(got, 2);
+                    assert_eq!(expected, "a tuple of size 3");
+                }
+                e => panic!("Expected `InvalidLength`, found {:?}", e),
+            }
+        }
+
+        /// Tuple consumes only the required number of elements, so the
+        /// excess element is treated as a duplicated field
+        #[test]
+        fn too_many() {
+            let data = from_str::<Point>(
+                r#"<root><coords>1</coords><coords>2</coords><coords>3</coords><coords>4</coords></root>"#,
+            );
+            match data {
+                Err(DeError::DuplicateField(field)) => assert_eq!(field, "coords"),
+                e => panic!("Expected `DuplicateField`, found {:?}", e),
+            }
+        }
+
+        #[test]
+        fn invalid_element() {
+            let data = from_str::<Point>(
+                r#"<root><coords>1</coords><coords>two</coords><coords>3</coords></root>"#,
+            );
+            match data {
+                Err(DeError::InvalidFloat(_)) => {}
+                e => panic!("Expected `InvalidFloat`, found {:?}", e),
+            }
+        }
+    }
 }
 
 mod tuple_struct {
@@ -594,6 +916,298 @@ macro_rules! maplike_errors {
     };
 }
 
+/// Tests for deserializing sequences of elements
+mod seq {
+    use super::*;
+
+    /// Check that top-level sequences can be deserialized from a multi-root documents
+    mod top_level {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn simple() {
+            from_str::<[(); 3]>("<root/><root>42</root><root>answer</root>").unwrap();
+
+            let data: Vec<()> = from_str("<root/><root>42</root><root>answer</root>").unwrap();
+            assert_eq!(data, vec![(), (), ()]);
+        }
+
+        /// Special case: empty sequence
+        #[test]
+        fn empty() {
+            from_str::<[(); 0]>("").unwrap();
+
+            let data: Vec<()> = from_str("").unwrap();
+            assert_eq!(data, vec![]);
+        }
+
+        #[test]
+        fn mixed_content() {
+            from_str::<[(); 3]>(
+                r#"
+                <element/>
+                text
+                <![CDATA[cdata]]>
+                "#,
+            )
+            .unwrap();
+
+            let data: Vec<()> = from_str(
+                r#"
+                <element/>
+                text
+                <![CDATA[cdata]]>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(data, vec![(), (), ()]);
+        }
+
+        /// The same tests as above, but XML is read from a `BufRead` source
+        /// instead of the in-memory string
+        mod from_reader {
+            use super::*;
+            use fast_xml::de::from_reader;
+            use pretty_assertions::assert_eq;
+            use std::io::Cursor;
+
+            #[test]
+            fn simple() {
+                from_reader::<_, [(); 3]>(Cursor::new("<root/><root>42</root><root>answer</root>"))
+                    .unwrap();
+
+                let data: Vec<()> =
+                    from_reader(Cursor::new("<root/><root>42</root><root>answer</root>")).unwrap();
+                assert_eq!(data, vec![(), (), ()]);
+            }
+
+            #[test]
+            fn mixed_content() {
+                from_reader::<_, [(); 3]>(Cursor::new(
+                    r#"
+                    <element/>
+                    text
+                    <![CDATA[cdata]]>
+                    "#,
+                ))
+                .unwrap();
+
+                let data: Vec<()> = from_reader(Cursor::new(
+                    r#"
+                    <element/>
+                    text
+                    <![CDATA[cdata]]>
+                    "#,
+                ))
+                .unwrap();
+                assert_eq!(data, vec![(), (), ()]);
+            }
+
+            /// End of the input should terminate sequence of unknown length
+            #[test]
+            fn eof() {
+                #[derive(Debug, Deserialize, PartialEq)]
+                struct Item {
+                    name: String,
+                }
+
+                let mut de = Deserializer::from_reader(Cursor::new(
+                    r#"<item name="first"/><item name="second"/>"#,
+                ));
+                let data = Vec::<Item>::deserialize(&mut de).unwrap();
+                assert_eq!(
+                    data,
+                    vec![
+                        Item {
+                            name: "first".into()
+                        },
+                        Item {
+                            name: "second".into()
+                        },
+                    ]
+                );
+
+                // Sequence should consume all events
+                match <()>::deserialize(&mut de) {
+                    Err(DeError::UnexpectedEof) => (),
+                    e => panic!("Expected `UnexpectedEof`, but got {:?}", e),
+                }
+            }
+
+            /// Too short sequence of fixed size should be reported
+            #[test]
+            fn too_short() {
+                match from_reader::<_, [(); 3]>(Cursor::new("<root/><root/>")) {
+                    Err(DeError::InvalidLength { got, expected }) => {
+                        assert_eq!(got, 2);
+                        assert_eq!(expected, "an array of length 3");
+                    }
+                    e => panic!("Expected `InvalidLength`, but got {:?}", e),
+                }
+            }
+
+            /// Data from a reader can not be borrowed
+            #[test]
+            fn borrowed() {
+                #[derive(Debug, Deserialize, PartialEq)]
+                struct Item<'a> {
+                    name: &'a str,
+                }
+
+                let mut de = Deserializer::from_reader(Cursor::new(
+                    r#"<item name="first"/><item name="second"/>"#,
+                ));
+                match Vec::<Item>::deserialize(&mut de) {
+                    Err(DeError::Custom(e)) => assert_eq!(
+                        e,
+                        r#"invalid type: string "first", expected a borrowed string"#
+                    ),
+                    e => panic!("Expected `Custom`, but got {:?}", e),
+                }
+            }
+        }
+    }
+
+    /// Sequences, deserialized from in-memory input, report the number of items
+    /// in advance, so a `Vec` can be allocated once
+    mod size_hint {
+        use super::*;
+        use fast_xml::de::from_reader;
+        use pretty_assertions::assert_eq;
+        use serde::de::{SeqAccess, Visitor};
+        use std::fmt;
+        use std::io::Cursor;
+
+        /// Remembers the size hint of the sequence and the actual number of items
+        #[derive(Debug, PartialEq)]
+        struct Hinted {
+            hint: Option<usize>,
+            len: usize,
+        }
+
+        impl<'de> Deserialize<'de> for Hinted {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct HintedVisitor;
+
+                impl<'de> Visitor<'de> for HintedVisitor {
+                    type Value = Hinted;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a sequence")
+                    }
+
+                    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Hinted, A::Error> {
+                        let hint = seq.size_hint();
+                        let mut len = 0;
+                        while seq.next_element::<IgnoredAny>()?.is_some() {
+                            len += 1;
+                        }
+                        Ok(Hinted { hint, len })
+                    }
+                }
+
+                deserializer.deserialize_seq(HintedVisitor)
+            }
+        }
+
+        #[test]
+        fn top_level() {
+            let data: Hinted = from_str("<item/><item>1</item><item><item/></item>").unwrap();
+            assert_eq!(
+                data,
+                Hinted {
+                    hint: Some(3),
+                    len: 3
+                }
+            );
+        }
+
+        #[test]
+        fn mixed_content() {
+            let data: Hinted = from_str(
+                r#"
+                <item/>
+                text
+                <!-- comment -->
+                <![CDATA[cdata]]>
+                <item/>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Hinted {
+                    hint: Some(4),
+                    len: 4
+                }
+            );
+        }
+
+        #[test]
+        fn field() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Root {
+                item: Hinted,
+                other: (),
+            }
+
+            let data: Root = from_str(
+                r#"
+                <root>
+                    <item/>
+                    <item><other/></item>
+                    <other/>
+                </root>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                data.item,
+                Hinted {
+                    hint: Some(2),
+                    len: 2
+                }
+            );
+        }
+
+        /// Size is not known in advance when reading from a `BufRead` source
+        #[test]
+        fn from_reader_unknown() {
+            let data: Hinted = from_reader(Cursor::new("<item/><item/>")).unwrap();
+            assert_eq!(data, Hinted { hint: None, len: 2 });
+        }
+
+        /// Sequences with the size hint should be the same as without it
+        #[test]
+        fn same_as_unhinted() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Item {
+                id: usize,
+                #[serde(rename = "$value")]
+                name: String,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct List {
+                item: Vec<Item>,
+            }
+
+            let mut xml = String::from("<list>");
+            for i in 0..10_000 {
+                xml.push_str(&format!(r#"<item id="{0}">item {0}</item>"#, i));
+            }
+            xml.push_str("</list>");
+
+            let hinted: List = from_str(&xml).unwrap();
+            let unhinted: List = from_reader(Cursor::new(&xml)).unwrap();
+
+            assert_eq!(hinted.item.len(), 10_000);
+            assert_eq!(hinted, unhinted);
+        }
+    }
+}
+
 mod map {
     use super::*;
     use pretty_assertions::assert_eq;
@@ -626,6 +1240,54 @@ mod map {
         );
     }
 
+    /// Maps are distinguished from structs with flattened fields by the way,
+    /// how keys are requested, not by the description of the visitor
+    #[test]
+    fn struct_like_visitor() {
+        use serde::de::{MapAccess, Visitor};
+        use std::fmt;
+
+        #[derive(Debug, PartialEq)]
+        struct Custom(HashMap<String, String>);
+
+        impl<'de> Deserialize<'de> for Custom {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct CustomVisitor;
+
+                impl<'de> Visitor<'de> for CustomVisitor {
+                    type Value = Custom;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("struct Custom")
+                    }
+
+                    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Custom, A::Error> {
+                        let mut result = HashMap::new();
+                        while let Some((key, value)) = map.next_entry()? {
+                            result.insert(key, value);
+                        }
+                        Ok(Custom(result))
+                    }
+                }
+
+                deserializer.deserialize_map(CustomVisitor)
+            }
+        }
+
+        // Prefix is stripped only from keys of maps
+        let mut de = Deserializer::from_str(r#"<root n-attr="1"><n-a>2</n-a><n-b>3</n-b></root>"#);
+        de.map_key_prefix("n-");
+        let data = Custom::deserialize(&mut de).unwrap();
+        assert_eq!(
+            data,
+            Custom(HashMap::from_iter(
+                [("attr", "1"), ("a", "2"), ("b", "3")]
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+            ))
+        );
+    }
+
     #[test]
     fn attribute_and_element() {
         let data: HashMap<(), ()> = from_str(
@@ -644,25 +1306,549 @@ mod map {
     }
 
     maplike_errors!(HashMap<(), ()>);
-}
 
-mod struct_ {
-    use super::*;
-    use pretty_assertions::assert_eq;
+    /// Keys are deserialized from names of attributes and elements by the key type
+    mod keys {
+        use super::*;
+        use pretty_assertions::assert_eq;
 
-    #[derive(Debug, Deserialize, PartialEq)]
-    struct Struct {
-        float: f64,
-        string: String,
-    }
+        fn map<K: std::hash::Hash + Eq + Clone>(entries: &[(K, &str)]) -> HashMap<K, String> {
+            entries
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_string()))
+                .collect()
+        }
 
-    #[test]
-    fn elements() {
-        let data: Struct = from_str(
-            // Comment for prevent unnecessary formatting - we use the same style in all tests
-            r#"<root><float>42</float><string>answer</string></root>"#,
-        )
-        .unwrap();
+        fn with_prefix<T: serde::de::DeserializeOwned>(xml: &str) -> Result<T, DeError> {
+            let mut de = Deserializer::from_str(xml);
+            de.map_key_prefix("n");
+            T::deserialize(&mut de)
+        }
+
+        #[test]
+        fn integer_elements() {
+            let data: HashMap<u32, String> =
+                with_prefix("<root><n1>one</n1><n20>twenty</n20></root>").unwrap();
+            assert_eq!(data, map(&[(1, "one"), (20, "twenty")]));
+        }
+
+        #[test]
+        fn integer_attributes() {
+            let data: HashMap<u32, String> =
+                with_prefix(r#"<root n1="one" n20="twenty"/>"#).unwrap();
+            assert_eq!(data, map(&[(1, "one"), (20, "twenty")]));
+        }
+
+        #[test]
+        fn integer_attribute_and_element() {
+            let data: HashMap<i64, String> =
+                with_prefix(r#"<root n-1="minus one"><n2>two</n2></root>"#).unwrap();
+            assert_eq!(data, map(&[(-1, "minus one"), (2, "two")]));
+        }
+
+        #[test]
+        fn char() {
+            let data: HashMap<char, String> =
+                from_str(r#"<root a="attribute"><b>element</b></root>"#).unwrap();
+            assert_eq!(data, map(&[('a', "attribute"), ('b', "element")]));
+        }
+
+        /// Names without a prefix are used as is
+        #[test]
+        fn without_prefix() {
+            match with_prefix::<HashMap<u32, String>>("<root><n1>one</n1><x2>two</x2></root>") {
+                Err(DeError::InvalidInt(_)) => {}
+                x => panic!("Expected `Err(InvalidInt(_))`, but got `{:?}`", x),
+            }
+
+            let data: HashMap<String, String> =
+                with_prefix("<root><n1>one</n1><x2>two</x2></root>").unwrap();
+            assert_eq!(data, map(&[("1".to_string(), "one"), ("x2".into(), "two")]));
+        }
+
+        /// Namespaced attributes are keyed by their qualified names, so they
+        /// do not collide with attributes with the same local name
+        #[test]
+        fn qualified_attributes() {
+            let xml = r#"<link xmlns:xlink="urn:xlink" xlink:href="remote" href="local"/>"#;
+
+            let data: HashMap<String, String> = from_str(xml).unwrap();
+            assert_eq!(
+                data,
+                map(&[
+                    ("xmlns:xlink".to_string(), "urn:xlink"),
+                    ("xlink:href".into(), "remote"),
+                    ("href".into(), "local"),
+                ])
+            );
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Link {
+                href: String,
+                #[serde(flatten)]
+                other: HashMap<String, String>,
+            }
+
+            let data: Link = from_str(xml).unwrap();
+            assert_eq!(
+                data,
+                Link {
+                    href: "local".into(),
+                    other: map(&[
+                        ("xmlns:xlink".to_string(), "urn:xlink"),
+                        ("xlink:href".into(), "remote"),
+                    ]),
+                }
+            );
+        }
+
+        /// Prefix is not stripped from names of fields
+        #[test]
+        fn struct_fields() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Root {
+                name: String,
+                numbers: HashMap<u32, String>,
+            }
+
+            let data: Root = with_prefix(
+                r#"
+                <root name="root">
+                    <numbers n1="one"><n2>two</n2></numbers>
+                </root>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Root {
+                    name: "root".into(),
+                    numbers: map(&[(1, "one"), (2, "two")]),
+                }
+            );
+        }
+    }
+
+    /// Keys are returned in the document order, so order-preserving maps
+    /// capture the original sequence
+    mod ordered {
+        use super::*;
+        use indexmap::IndexMap;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn elements() {
+            let data: IndexMap<String, String> =
+                from_str(r#"<root><zeta>1</zeta><alpha>2</alpha><mu>3</mu><beta>4</beta></root>"#)
+                    .unwrap();
+
+            assert_eq!(
+                data.into_iter().collect::<Vec<_>>(),
+                vec![
+                    ("zeta".to_string(), "1".to_string()),
+                    ("alpha".to_string(), "2".to_string()),
+                    ("mu".to_string(), "3".to_string()),
+                    ("beta".to_string(), "4".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn attributes_and_elements() {
+            let data: IndexMap<String, String> = from_str(
+                r#"
+                <root zeta="1" alpha="2">
+                    <mu>3</mu>
+                    <beta>4</beta>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data.keys().collect::<Vec<_>>(),
+                vec!["zeta", "alpha", "mu", "beta"]
+            );
+        }
+    }
+
+    /// Map in the `$value` field captures all elements that are not matched by
+    /// other fields
+    mod value_field {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Known {
+            first: String,
+            second: u32,
+            /// `default` is required to allow documents without unmatched elements
+            #[serde(rename = "$value", default)]
+            extras: HashMap<String, String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Repeated {
+            first: String,
+            #[serde(rename = "$value")]
+            extras: HashMap<String, Vec<String>>,
+        }
+
+        fn map<V: Clone>(entries: &[(&str, V)]) -> HashMap<String, V> {
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect()
+        }
+
+        #[test]
+        fn extras_after_known() {
+            let data: Known = from_str(
+                r#"
+                <root>
+                    <first>one</first>
+                    <second>2</second>
+                    <a>x</a>
+                    <b>y</b>
+                    <c/>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data,
+                Known {
+                    first: "one".into(),
+                    second: 2,
+                    extras: map(&[("a", "x".to_string()), ("b", "y".into()), ("c", "".into())]),
+                }
+            );
+        }
+
+        #[test]
+        fn extras_before_known() {
+            let data: Known = from_str(
+                r#"
+                <root>
+                    <a>x</a>
+                    <b>y</b>
+                    <first>one</first>
+                    <second>2</second>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data,
+                Known {
+                    first: "one".into(),
+                    second: 2,
+                    extras: map(&[("a", "x".to_string()), ("b", "y".into())]),
+                }
+            );
+        }
+
+        #[test]
+        fn extras_between_known() {
+            let data: Known = from_str(
+                r#"
+                <root>
+                    <first>one</first>
+                    <a>x</a>
+                    <b>y</b>
+                    <second>2</second>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data,
+                Known {
+                    first: "one".into(),
+                    second: 2,
+                    extras: map(&[("a", "x".to_string()), ("b", "y".into())]),
+                }
+            );
+        }
+
+        #[test]
+        fn no_extras() {
+            let data: Known =
+                from_str(r#"<root><first>one</first><second>2</second></root>"#).unwrap();
+
+            assert_eq!(
+                data,
+                Known {
+                    first: "one".into(),
+                    second: 2,
+                    extras: HashMap::new(),
+                }
+            );
+        }
+
+        /// Unmatched elements are read in a streaming fashion, so they should
+        /// be grouped together
+        #[test]
+        fn interleaved() {
+            let data: Result<Known, _> = from_str(
+                r#"
+                <root>
+                    <a>x</a>
+                    <first>one</first>
+                    <b>y</b>
+                    <second>2</second>
+                </root>
+                "#,
+            );
+
+            match data {
+                Err(DeError::DuplicateField(field)) => assert_eq!(field, "$value"),
+                e => panic!(
+                    r#"Expected `Err(DuplicateField("$value"))`, but found `{:?}`"#,
+                    e
+                ),
+            }
+        }
+
+        /// Repeated elements with the same name overwrite the previous value
+        #[test]
+        fn repeated_last_wins() {
+            let data: Known = from_str(
+                r#"
+                <root>
+                    <first>one</first>
+                    <second>2</second>
+                    <a>x</a>
+                    <a>y</a>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data,
+                Known {
+                    first: "one".into(),
+                    second: 2,
+                    extras: map(&[("a", "y".to_string())]),
+                }
+            );
+        }
+
+        /// Repeated elements with the same name are collected when value is a sequence
+        #[test]
+        fn repeated_into_vec() {
+            let data: Repeated = from_str(
+                r#"
+                <root>
+                    <first>one</first>
+                    <a>x</a>
+                    <a>y</a>
+                    <b>z</b>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data,
+                Repeated {
+                    first: "one".into(),
+                    extras: map(&[
+                        ("a", vec!["x".to_string(), "y".into()]),
+                        ("b", vec!["z".into()]),
+                    ]),
+                }
+            );
+        }
+    }
+
+    /// Sequences in map values collect all elements with the same name when
+    /// `Deserializer::group_repeated_elements` is enabled
+    mod grouped {
+        use super::*;
+        use indexmap::IndexMap;
+        use pretty_assertions::assert_eq;
+
+        fn grouped<'de, T: Deserialize<'de>>(xml: &'de str) -> Result<T, DeError> {
+            let mut de = Deserializer::from_str(xml);
+            de.group_repeated_elements(true);
+            T::deserialize(&mut de)
+        }
+
+        fn map(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+                .collect()
+        }
+
+        #[test]
+        fn consecutive() {
+            let data: HashMap<String, Vec<String>> =
+                grouped(r#"<root><color>red</color><color>green</color><size>10</size></root>"#)
+                    .unwrap();
+
+            assert_eq!(
+                data,
+                map(&[("color", &["red", "green"]), ("size", &["10"])])
+            );
+        }
+
+        #[test]
+        fn interleaved() {
+            let data: HashMap<String, Vec<String>> =
+                grouped(r#"<root><color>red</color><size>10</size><color>green</color></root>"#)
+                    .unwrap();
+
+            assert_eq!(
+                data,
+                map(&[("color", &["red", "green"]), ("size", &["10"])])
+            );
+        }
+
+        /// Without the setting only the last group of consecutive elements is kept
+        #[test]
+        fn disabled() {
+            let data: HashMap<String, Vec<String>> =
+                from_str(r#"<root><color>red</color><size>10</size><color>green</color></root>"#)
+                    .unwrap();
+
+            assert_eq!(data, map(&[("color", &["green"]), ("size", &["10"])]));
+        }
+
+        /// Skipped elements are returned as next entries in the document order
+        #[test]
+        fn order() {
+            let data: IndexMap<String, Vec<u32>> = grouped(
+                r#"
+                <root>
+                    <b>1</b>
+                    <a>2</a>
+                    <b>3</b>
+                    <c>4</c>
+                    <a>5</a>
+                    <b>6</b>
+                </root>
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                data.into_iter().collect::<Vec<_>>(),
+                vec![
+                    ("b".to_string(), vec![1, 3, 6]),
+                    ("a".to_string(), vec![2, 5]),
+                    ("c".to_string(), vec![4]),
+                ]
+            );
+        }
+
+        /// Skipped elements with nested elements are restored completely and
+        /// the fields after the map are not affected
+        #[test]
+        fn nested() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Item {
+                #[serde(default)]
+                a: Option<String>,
+                #[serde(default)]
+                b: Vec<String>,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Root {
+                map: IndexMap<String, Vec<Item>>,
+                after: String,
+            }
+
+            let xml = r#"
+                <root>
+                    <map>
+                        <x><a>1</a></x>
+                        <y><b>2</b><b>3</b></y>
+                        <x><b>4</b><a>5</a></x>
+                        <z/>
+                        <y/>
+                    </map>
+                    <after>ok</after>
+                </root>
+            "#;
+            let mut de = Deserializer::from_reader(xml.as_bytes());
+            de.group_repeated_elements(true);
+            let data = Root::deserialize(&mut de).unwrap();
+
+            assert_eq!(data.after, "ok");
+            assert_eq!(
+                data.map.into_iter().collect::<Vec<_>>(),
+                vec![
+                    (
+                        "x".to_string(),
+                        vec![
+                            Item {
+                                a: Some("1".into()),
+                                b: vec![],
+                            },
+                            Item {
+                                a: Some("5".into()),
+                                b: vec!["4".into()],
+                            },
+                        ]
+                    ),
+                    (
+                        "y".to_string(),
+                        vec![
+                            Item {
+                                a: None,
+                                b: vec!["2".into(), "3".into()],
+                            },
+                            Item { a: None, b: vec![] },
+                        ]
+                    ),
+                    ("z".to_string(), vec![Item { a: None, b: vec![] }]),
+                ]
+            );
+        }
+
+        /// Structs are not affected by the setting
+        #[test]
+        fn struct_() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Struct {
+                color: Vec<String>,
+                size: String,
+            }
+
+            match grouped::<Struct>(
+                r#"<root><color>red</color><size>10</size><color>green</color></root>"#,
+            ) {
+                Err(DeError::DuplicateField(field)) => assert_eq!(field, "color"),
+                x => panic!("Expected `Err(DuplicateField(_))`, but got `{:?}`", x),
+            }
+        }
+    }
+}
+
+mod struct_ {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Struct {
+        float: f64,
+        string: String,
+    }
+
+    #[test]
+    fn elements() {
+        let data: Struct = from_str(
+            // Comment for prevent unnecessary formatting - we use the same style in all tests
+            r#"<root><float>42</float><string>answer</string></root>"#,
+        )
+        .unwrap();
         assert_eq!(
             data,
             Struct {
@@ -746,6 +1932,289 @@ mod struct_ {
     }
 
     maplike_errors!(Struct);
+
+    /// Fields with `#[serde(skip_deserializing)]` should get their default values
+    /// and should not consume any XML nodes
+    mod skip_deserializing {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Mixed {
+            float: f64,
+            #[serde(skip_deserializing)]
+            computed: Vec<u32>,
+            string: String,
+        }
+
+        #[test]
+        fn elements() {
+            let data: Mixed = from_str(
+                // Comment for prevent unnecessary formatting - we use the same style in all tests
+                r#"<root><float>42</float><string>answer</string></root>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Mixed {
+                    float: 42.0,
+                    computed: vec![],
+                    string: "answer".into()
+                }
+            );
+        }
+
+        #[test]
+        fn attributes() {
+            let data: Mixed = from_str(
+                // Comment for prevent unnecessary formatting - we use the same style in all tests
+                r#"<root float="42" string="answer"/>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Mixed {
+                    float: 42.0,
+                    computed: vec![],
+                    string: "answer".into()
+                }
+            );
+        }
+
+        /// Nodes with the name of a skipped field are ignored like any other
+        /// unknown nodes
+        #[test]
+        fn present_in_xml() {
+            let data: Mixed = from_str(
+                r#"
+                <root computed="1">
+                    <float>42</float>
+                    <computed>2</computed>
+                    <computed>3</computed>
+                    <string>answer</string>
+                </root>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Mixed {
+                    float: 42.0,
+                    computed: vec![],
+                    string: "answer".into()
+                }
+            );
+        }
+
+        /// Skipped fields does not break deserialization of a sequence of structs
+        #[test]
+        fn in_sequence() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct List {
+                item: Vec<Mixed>,
+            }
+
+            let data: List = from_str(
+                r#"
+                <root>
+                    <item float="1"><computed/><string>one</string></item>
+                    <item float="2"><string>two</string></item>
+                </root>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                List {
+                    item: vec![
+                        Mixed {
+                            float: 1.0,
+                            computed: vec![],
+                            string: "one".into()
+                        },
+                        Mixed {
+                            float: 2.0,
+                            computed: vec![],
+                            string: "two".into()
+                        },
+                    ],
+                }
+            );
+        }
+    }
+
+    /// Fields with `#[serde(alias)]` should be matched by any of their names
+    mod alias {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Paint {
+            #[serde(alias = "colour")]
+            color: String,
+            size: u32,
+        }
+
+        /// Elements not matched by any name of any field should go to `$value`
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct WithValue {
+            #[serde(alias = "colour")]
+            color: String,
+            #[serde(rename = "$value", default)]
+            rest: Vec<String>,
+        }
+
+        fn paint() -> Paint {
+            Paint {
+                color: "red".into(),
+                size: 1,
+            }
+        }
+
+        #[test]
+        fn element() {
+            let data: Paint = from_str("<root><color>red</color><size>1</size></root>").unwrap();
+            assert_eq!(data, paint());
+        }
+
+        #[test]
+        fn element_alias() {
+            let data: Paint = from_str("<root><colour>red</colour><size>1</size></root>").unwrap();
+            assert_eq!(data, paint());
+        }
+
+        #[test]
+        fn attribute() {
+            let data: Paint = from_str(r#"<root color="red" size="1"/>"#).unwrap();
+            assert_eq!(data, paint());
+        }
+
+        #[test]
+        fn attribute_alias() {
+            let data: Paint = from_str(r#"<root colour="red" size="1"/>"#).unwrap();
+            assert_eq!(data, paint());
+        }
+
+        #[test]
+        fn attribute_and_element_alias() {
+            let data: Paint = from_str(r#"<root size="1"><colour>red</colour></root>"#).unwrap();
+            assert_eq!(data, paint());
+        }
+
+        #[test]
+        fn with_value_field() {
+            let data: WithValue = from_str(
+                r#"
+                <root>
+                    <colour>red</colour>
+                    <other>green</other>
+                    <other>blue</other>
+                </root>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                WithValue {
+                    color: "red".into(),
+                    rest: vec!["green".into(), "blue".into()],
+                }
+            );
+        }
+    }
+
+    /// Attributes are deserialized into fields, while all children go to
+    /// the `$value` sequence
+    mod attributes_and_value {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Cell {
+            #[serde(default)]
+            span: Option<u32>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Table {
+            cols: u32,
+            rows: u32,
+            #[serde(rename = "$value", default)]
+            cells: Vec<Cell>,
+        }
+
+        /// Children with the names of the attribute fields
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Child {
+            #[serde(rename = "cell")]
+            Cell,
+            #[serde(rename = "cols")]
+            Cols,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Grid {
+            cols: u32,
+            rows: u32,
+            #[serde(rename = "$value")]
+            children: Vec<Child>,
+        }
+
+        #[test]
+        fn children() {
+            let data: Table = from_str(
+                r#"
+                <table cols="3" rows="2">
+                    <cell/>
+                    <cell span="2"/>
+                    <cell></cell>
+                    <cell span="3"/>
+                </table>
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                Table {
+                    cols: 3,
+                    rows: 2,
+                    cells: vec![
+                        Cell { span: None },
+                        Cell { span: Some(2) },
+                        Cell { span: None },
+                        Cell { span: Some(3) },
+                    ],
+                }
+            );
+        }
+
+        #[test]
+        fn no_children() {
+            let data: Table = from_str(r#"<table cols="3" rows="2"/>"#).unwrap();
+            assert_eq!(
+                data,
+                Table {
+                    cols: 3,
+                    rows: 2,
+                    cells: vec![],
+                }
+            );
+        }
+
+        /// Children are not matched to the fields, that already got their
+        /// values from attributes
+        #[test]
+        fn children_named_as_attributes() {
+            let data: Grid =
+                from_str(r#"<grid cols="3" rows="2"><cell/><cols/><cell/></grid>"#).unwrap();
+            assert_eq!(
+                data,
+                Grid {
+                    cols: 3,
+                    rows: 2,
+                    children: vec![Child::Cell, Child::Cols, Child::Cell],
+                }
+            );
+        }
+    }
 }
 
 mod nested_struct {
@@ -794,17 +2263,493 @@ mod nested_struct {
             }
         );
     }
+
+    /// Nested structs, that are deserialized only from attributes, from elements
+    /// without content
+    mod attributes_only {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            #[serde(rename = "@x")]
+            x: i32,
+            #[serde(rename = "@y")]
+            y: i32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Root {
+            point: Point,
+            after: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct List {
+            point: Vec<Point>,
+            after: String,
+        }
+
+        fn root() -> Root {
+            Root {
+                point: Point { x: 1, y: -2 },
+                after: "answer".into(),
+            }
+        }
+
+        #[test]
+        fn self_closed() {
+            let data: Root = from_str(
+                // Comment for prevent unnecessary formatting - we use the same style in all tests
+                r#"<root><point x="1" y="-2"/><after>answer</after></root>"#,
+            )
+            .unwrap();
+            assert_eq!(data, root());
+        }
+
+        #[test]
+        fn explicitly_closed() {
+            let data: Root = from_str(
+                // Comment for prevent unnecessary formatting - we use the same style in all tests
+                r#"<root><point x="1" y="-2"></point><after>answer</after></root>"#,
+            )
+            .unwrap();
+            assert_eq!(data, root());
+
+            let data: Root = from_str(
+                // Comment for prevent unnecessary formatting - we use the same style in all tests
+                r#"<root><point x="1" y="-2">  </point><after>answer</after></root>"#,
+            )
+            .unwrap();
+            assert_eq!(data, root());
+        }
+
+        #[test]
+        fn list() {
+            let data: List = from_str(
+                r#"<root><point x="1" y="2"/><point x="3" y="4"></point><after>answer</after></root>"#,
+            )
+            .unwrap();
+            assert_eq!(
+                data,
+                List {
+                    point: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+                    after: "answer".into(),
+                }
+            );
+        }
+
+        /// Empty elements should be consumed by an `IoReader` in the same way
+        #[test]
+        fn from_reader() {
+            let xml = r#"<root><point x="1" y="-2"></point><after>answer</after></root>"#;
+            let mut de = Deserializer::from_reader(xml.as_bytes());
+            assert_eq!(Root::deserialize(&mut de).unwrap(), root());
+
+            let xml = r#"<root><point x="1" y="-2"/><after>answer</after></root>"#;
+            let mut de = Deserializer::from_reader(xml.as_bytes());
+            assert_eq!(Root::deserialize(&mut de).unwrap(), root());
+        }
+    }
 }
 
-mod flatten_struct {
+/// Special field `$name` receives a local name of the element
+mod element_name {
     use super::*;
     use pretty_assertions::assert_eq;
 
     #[derive(Debug, Deserialize, PartialEq)]
-    struct Struct {
-        #[serde(flatten)]
-        nested: Nested,
-        string: String,
+    struct Vehicle {
+        #[serde(rename = "$name")]
+        tag: String,
+        wheels: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Garage {
+        #[serde(alias = "car", alias = "truck")]
+        vehicle: Vehicle,
+    }
+
+    #[test]
+    fn root() {
+        let data: Vehicle = from_str(r#"<ns:car wheels="4"/>"#).unwrap();
+        assert_eq!(
+            data,
+            Vehicle {
+                tag: "car".into(),
+                wheels: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn aliased() {
+        let data: Garage = from_str(r#"<garage><car wheels="4"/></garage>"#).unwrap();
+        assert_eq!(
+            data,
+            Garage {
+                vehicle: Vehicle {
+                    tag: "car".into(),
+                    wheels: 4,
+                },
+            }
+        );
+
+        let data: Garage =
+            from_str(r#"<garage><truck><wheels>6</wheels></truck></garage>"#).unwrap();
+        assert_eq!(
+            data,
+            Garage {
+                vehicle: Vehicle {
+                    tag: "truck".into(),
+                    wheels: 6,
+                },
+            }
+        );
+    }
+
+    /// `$name` field does not consume any XML nodes
+    #[test]
+    fn with_value() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Text {
+            #[serde(rename = "$name")]
+            tag: String,
+            #[serde(rename = "$value")]
+            text: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct List {
+            #[serde(rename = "a")]
+            items: Vec<Text>,
+        }
+
+        let data: List = from_str(r#"<root><a>first</a><a>second</a></root>"#).unwrap();
+        assert_eq!(
+            data,
+            List {
+                items: vec![
+                    Text {
+                        tag: "a".into(),
+                        text: "first".into(),
+                    },
+                    Text {
+                        tag: "a".into(),
+                        text: "second".into(),
+                    },
+                ],
+            }
+        );
+    }
+
+    /// Attribute with the name `$name` cannot exist, so nothing is duplicated
+    #[test]
+    fn with_attribute_name() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Named {
+            #[serde(rename = "$name")]
+            tag: String,
+            name: String,
+        }
+
+        let data: Named = from_str(r#"<element name="value"/>"#).unwrap();
+        assert_eq!(
+            data,
+            Named {
+                tag: "element".into(),
+                name: "value".into(),
+            }
+        );
+    }
+}
+
+/// Tests for the `fast_xml::de::text_entries` helper
+mod text_entries_helper {
+    use super::*;
+    use fast_xml::de::text_entries;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Rect {
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Shape {
+        #[serde(flatten, deserialize_with = "text_entries")]
+        rect: Rect,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Labeled {
+        label: String,
+        #[serde(flatten, deserialize_with = "text_entries")]
+        rect: Rect,
+        child: String,
+    }
+
+    #[test]
+    fn only_attributes() {
+        let data: Shape = from_str(r#"<shape x="1" y="-2" w="30" h="40"/>"#).unwrap();
+        assert_eq!(
+            data,
+            Shape {
+                rect: Rect {
+                    x: 1,
+                    y: -2,
+                    w: 30,
+                    h: 40,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn with_other_fields() {
+        let data: Labeled = from_str(
+            r#"
+            <shape label="box" x="1" y="-2" w="30" h="40">
+                <child>text</child>
+                <ignored><nested/></ignored>
+            </shape>
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Labeled {
+                label: "box".into(),
+                rect: Rect {
+                    x: 1,
+                    y: -2,
+                    w: 30,
+                    h: 40,
+                },
+                child: "text".into(),
+            }
+        );
+    }
+
+    /// Child elements with a text are passed to the flattened field too
+    #[test]
+    fn elements() {
+        let data: Shape = from_str(r#"<shape x="1" y="-2"><w>30</w><h>40</h></shape>"#).unwrap();
+        assert_eq!(
+            data,
+            Shape {
+                rect: Rect {
+                    x: 1,
+                    y: -2,
+                    w: 30,
+                    h: 40,
+                },
+            }
+        );
+    }
+
+    /// Values are decoded from the encoding of the document only once
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn encoding() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Name {
+            first: String,
+            last: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Person {
+            #[serde(flatten, deserialize_with = "text_entries")]
+            name: Name,
+        }
+
+        // "Иван" and "Петров" in windows-1251
+        let xml: &[u8] = b"<?xml version=\"1.0\" encoding=\"windows-1251\"?>\
+            <person first=\"\xC8\xE2\xE0\xED\"><last>\xCF\xE5\xF2\xF0\xEE\xE2</last></person>";
+        let data: Person = fast_xml::de::from_slice(xml).unwrap();
+        assert_eq!(
+            data,
+            Person {
+                name: Name {
+                    first: "Иван".into(),
+                    last: "Петров".into(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn missing() {
+        match from_str::<Shape>(r#"<shape x="1" y="-2" w="30"/>"#) {
+            Err(DeError::Custom(e)) => assert_eq!(e, "missing field `h`"),
+            e => panic!(
+                r#"Expected `Err(Custom("missing field `h`"))`, but found `{:?}`"#,
+                e
+            ),
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        match from_str::<Shape>(r#"<shape x="1" y="-2" w="-30" h="40"/>"#) {
+            Err(DeError::Custom(e)) => assert_eq!(e, "invalid digit found in string"),
+            e => panic!(
+                r#"Expected `Err(Custom("invalid digit found in string"))`, but found `{:?}`"#,
+                e
+            ),
+        }
+    }
+}
+
+mod attributes_and_text_helper {
+    use super::*;
+    use fast_xml::de::attributes_and_text;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Measure(String, f64);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        #[serde(deserialize_with = "attributes_and_text")]
+        measure: Measure,
+    }
+
+    #[test]
+    fn attribute_and_text() {
+        let data: Item = from_str(r#"<item><measure unit="cm">12.5</measure></item>"#).unwrap();
+        assert_eq!(
+            data,
+            Item {
+                measure: Measure("cm".into(), 12.5),
+            }
+        );
+    }
+
+    /// Slots are filled in the document order, names of attributes are ignored
+    #[test]
+    fn several_attributes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Range(u32, u32, String);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            #[serde(deserialize_with = "attributes_and_text")]
+            range: Range,
+        }
+
+        let data: Item = from_str(r#"<item><range max="10" min="1">days</range></item>"#).unwrap();
+        assert_eq!(
+            data,
+            Item {
+                range: Range(10, 1, "days".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn tuple() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            #[serde(deserialize_with = "attributes_and_text")]
+            measure: (String, f64),
+        }
+
+        let data: Item = from_str(r#"<item><measure unit="cm">12.5</measure></item>"#).unwrap();
+        assert_eq!(
+            data,
+            Item {
+                measure: ("cm".into(), 12.5),
+            }
+        );
+    }
+
+    /// Child elements with a text content occupy slots in the document order,
+    /// other child elements are skipped
+    #[test]
+    fn with_children() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            #[serde(deserialize_with = "attributes_and_text")]
+            measure: (String, u32, f64),
+        }
+
+        let data: Item = from_str(
+            r#"<item><measure unit="cm"><scale>2</scale>12.5<note><empty/></note></measure></item>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Item {
+                measure: ("cm".into(), 2, 12.5),
+            }
+        );
+    }
+
+    /// Values are decoded from the encoding of the document only once
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn encoding() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            #[serde(deserialize_with = "attributes_and_text")]
+            measure: (String, f64),
+        }
+
+        // "см" in windows-1251
+        let xml: &[u8] = b"<?xml version=\"1.0\" encoding=\"windows-1251\"?>\
+            <item><measure unit=\"\xF1\xEC\">12.5</measure></item>";
+        let data: Item = fast_xml::de::from_slice(xml).unwrap();
+        assert_eq!(
+            data,
+            Item {
+                measure: ("см".into(), 12.5),
+            }
+        );
+    }
+
+    #[test]
+    fn too_few() {
+        match from_str::<Item>(r#"<item><measure>12.5</measure></item>"#) {
+            Err(DeError::Custom(e)) => assert_eq!(
+                e,
+                "invalid length 1, expected tuple struct Measure with 2 elements"
+            ),
+            e => panic!(
+                r#"Expected `Err(Custom("invalid length 1, expected tuple struct Measure with 2 elements"))`, but found `{:?}`"#,
+                e
+            ),
+        }
+    }
+
+    #[test]
+    fn too_many() {
+        match from_str::<Item>(r#"<item><measure unit="cm" scale="2">12.5</measure></item>"#) {
+            Err(DeError::Custom(e)) => {
+                assert_eq!(e, "invalid length 3, expected 2 elements in sequence")
+            }
+            e => panic!(
+                r#"Expected `Err(Custom("invalid length 3, expected 2 elements in sequence"))`, but found `{:?}`"#,
+                e
+            ),
+        }
+    }
+}
+
+mod flatten_struct {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Struct {
+        #[serde(flatten)]
+        nested: Nested,
+        string: String,
     }
 
     #[derive(Debug, Deserialize, PartialEq)]
@@ -814,7 +2759,6 @@ mod flatten_struct {
     }
 
     #[test]
-    #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
     fn elements() {
         let data: Struct = from_str(
             // Comment for prevent unnecessary formatting - we use the same style in all tests
@@ -845,58 +2789,297 @@ mod flatten_struct {
             }
         );
     }
-}
 
-mod enum_ {
-    use super::*;
+    /// Flattened struct takes its fields from both attributes and elements
+    #[test]
+    fn mixed() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            #[serde(flatten)]
+            details: Details,
+            string: String,
+        }
 
-    mod externally_tagged {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Details {
+            id: String,
+            name: String,
+            size: Size,
+            note: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Size {
+            unit: String,
+            #[serde(rename = "$value")]
+            value: String,
+        }
+
+        let data: Outer = from_str(
+            r#"<root id="1"><name>first</name><size unit="cm">42</size><string>answer</string></root>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Outer {
+                details: Details {
+                    id: "1".into(),
+                    name: "first".into(),
+                    size: Size {
+                        unit: "cm".into(),
+                        value: "42".into(),
+                    },
+                    note: None,
+                },
+                string: "answer".into(),
+            }
+        );
+    }
+
+    /// `$value` sequence in a flattened struct captures all elements that are
+    /// not matched by own fields of the outer struct
+    mod value_seq {
         use super::*;
         use pretty_assertions::assert_eq;
 
         #[derive(Debug, Deserialize, PartialEq)]
-        enum Node {
-            Unit,
-            Newtype(bool),
-            //TODO: serde bug https://github.com/serde-rs/serde/issues/1904
-            // Tuple(f64, String),
-            Struct {
-                float: f64,
-                string: String,
-            },
-            Holder {
-                nested: Nested,
-                string: String,
-            },
-            Flatten {
-                #[serde(flatten)]
-                nested: Nested,
-                string: String,
-            },
+        enum Item {
+            A(String),
+            B { name: String },
+            C,
         }
 
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Nested {
-            //TODO: change to f64 after fixing https://github.com/serde-rs/serde/issues/1183
-            float: String,
+        struct Items {
+            #[serde(rename = "$value", default)]
+            items: Vec<Item>,
         }
 
-        /// Workaround for serde bug https://github.com/serde-rs/serde/issues/1904
         #[derive(Debug, Deserialize, PartialEq)]
-        enum Workaround {
-            Tuple(f64, String),
+        struct Root {
+            name: String,
+            #[serde(flatten)]
+            items: Items,
         }
 
-        #[test]
-        fn unit() {
-            let data: Node = from_str("<Unit/>").unwrap();
-            assert_eq!(data, Node::Unit);
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Child {
+            value: String,
         }
 
-        #[test]
-        fn newtype() {
-            let data: Node = from_str("<Newtype>true</Newtype>").unwrap();
-            assert_eq!(data, Node::Newtype(true));
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct WithChild {
+            child: Child,
+            #[serde(flatten)]
+            items: Items,
+        }
+
+        #[test]
+        fn elements() {
+            let data: Root =
+                from_str(r#"<root name="list"><A>text</A><B name="b"/><C/><A>&lt;</A></root>"#)
+                    .unwrap();
+            assert_eq!(
+                data,
+                Root {
+                    name: "list".into(),
+                    items: Items {
+                        items: vec![
+                            Item::A("text".into()),
+                            Item::B { name: "b".into() },
+                            Item::C,
+                            Item::A("<".into()),
+                        ],
+                    },
+                }
+            );
+        }
+
+        /// Absent content gives an empty vector
+        #[test]
+        fn empty() {
+            let data: Root = from_str(r#"<root name="list"/>"#).unwrap();
+            assert_eq!(
+                data,
+                Root {
+                    name: "list".into(),
+                    items: Items { items: vec![] },
+                }
+            );
+
+            let data: Root = from_str(r#"<root name="list"></root>"#).unwrap();
+            assert_eq!(
+                data,
+                Root {
+                    name: "list".into(),
+                    items: Items { items: vec![] },
+                }
+            );
+        }
+
+        /// Elements of own fields are not included into the sequence
+        #[test]
+        fn with_own_fields() {
+            let data: WithChild =
+                from_str(r#"<root><C/><child value="v"/><A>text</A></root>"#).unwrap();
+            assert_eq!(
+                data,
+                WithChild {
+                    child: Child { value: "v".into() },
+                    items: Items {
+                        items: vec![Item::C, Item::A("text".into())],
+                    },
+                }
+            );
+        }
+
+        /// Flattened struct can be nested in another flattened struct
+        #[test]
+        fn nested_flatten() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Outer {
+                #[serde(flatten)]
+                root: Root,
+            }
+
+            let data: Outer = from_str(r#"<root name="list"><B name="b"/><C/></root>"#).unwrap();
+            assert_eq!(
+                data,
+                Outer {
+                    root: Root {
+                        name: "list".into(),
+                        items: Items {
+                            items: vec![Item::B { name: "b".into() }, Item::C],
+                        },
+                    },
+                }
+            );
+        }
+    }
+
+    /// Flattened newtypes around primitives, sourced from attributes
+    mod newtype {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Id(u32);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Name(String);
+
+        #[test]
+        #[ignore = "Prime cause: serde accepts only structs and maps in flattened fields"]
+        fn flatten_u32() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Struct {
+                #[serde(flatten)]
+                id: Id,
+            }
+
+            let data: Struct = from_str(r#"<root id="42"/>"#).unwrap();
+            assert_eq!(data, Struct { id: Id(42) });
+        }
+
+        #[test]
+        #[ignore = "Prime cause: serde accepts only structs and maps in flattened fields"]
+        fn flatten_string() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Struct {
+                #[serde(flatten)]
+                name: Name,
+            }
+
+            let data: Struct = from_str(r#"<root name="answer"/>"#).unwrap();
+            assert_eq!(
+                data,
+                Struct {
+                    name: Name("answer".into())
+                }
+            );
+        }
+
+        /// Until serde supports flattening of newtypes, they should be used as
+        /// ordinary fields. The field name is matched against the attribute name
+        #[test]
+        fn u32_() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Struct {
+                id: Id,
+            }
+
+            let data: Struct = from_str(r#"<root id="42"/>"#).unwrap();
+            assert_eq!(data, Struct { id: Id(42) });
+        }
+
+        #[test]
+        fn string() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Struct {
+                name: Name,
+            }
+
+            let data: Struct = from_str(r#"<root name="answer"/>"#).unwrap();
+            assert_eq!(
+                data,
+                Struct {
+                    name: Name("answer".into())
+                }
+            );
+        }
+    }
+}
+
+mod enum_ {
+    use super::*;
+
+    mod externally_tagged {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Node {
+            Unit,
+            Newtype(bool),
+            //TODO: serde bug https://github.com/serde-rs/serde/issues/1904
+            // Tuple(f64, String),
+            Struct {
+                float: f64,
+                string: String,
+            },
+            Holder {
+                nested: Nested,
+                string: String,
+            },
+            Flatten {
+                #[serde(flatten)]
+                nested: Nested,
+                string: String,
+            },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Nested {
+            //TODO: change to f64 after fixing https://github.com/serde-rs/serde/issues/1183
+            float: String,
+        }
+
+        /// Workaround for serde bug https://github.com/serde-rs/serde/issues/1904
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Workaround {
+            Tuple(f64, String),
+        }
+
+        #[test]
+        fn unit() {
+            let data: Node = from_str("<Unit/>").unwrap();
+            assert_eq!(data, Node::Unit);
+        }
+
+        #[test]
+        fn newtype() {
+            let data: Node = from_str("<Newtype>true</Newtype>").unwrap();
+            assert_eq!(data, Node::Newtype(true));
         }
 
         #[test]
@@ -983,7 +3166,6 @@ mod enum_ {
             use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     // Comment for prevent unnecessary formatting - we use the same style in all tests
@@ -1015,6 +3197,330 @@ mod enum_ {
                 );
             }
         }
+
+        /// Unknown variants are mapped to a `#[serde(other)]` variant, content
+        /// of such variants is skipped
+        mod other {
+            use super::*;
+            use pretty_assertions::assert_eq;
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            enum Shape {
+                Circle {
+                    radius: u32,
+                },
+                Square(u32),
+                #[serde(other)]
+                Unknown,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Holder {
+                shape: Shape,
+                after: String,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Shapes {
+                #[serde(rename = "$value")]
+                shapes: Vec<Shape>,
+            }
+
+            #[test]
+            fn unit() {
+                let data: Shape = from_str("<Triangle/>").unwrap();
+                assert_eq!(data, Shape::Unknown);
+            }
+
+            #[test]
+            fn with_content() {
+                let data: Shape = from_str(
+                    r#"<Triangle side="3"><Triangle/>text<Circle radius="1"/></Triangle>"#,
+                )
+                .unwrap();
+                assert_eq!(data, Shape::Unknown);
+            }
+
+            /// Skipped subtree does not affect following fields
+            #[test]
+            fn field() {
+                let data: Holder = from_str(
+                    r#"<root><shape><Triangle><side>3</side></Triangle></shape><after>ok</after></root>"#,
+                )
+                .unwrap();
+                assert_eq!(
+                    data,
+                    Holder {
+                        shape: Shape::Unknown,
+                        after: "ok".into(),
+                    }
+                );
+            }
+
+            /// Skipped subtree does not affect following elements of a sequence
+            #[test]
+            fn sequence() {
+                let data: Shapes = from_str(
+                    r#"<root><Circle radius="1"/><Triangle><Square>2</Square></Triangle><Square>4</Square></root>"#,
+                )
+                .unwrap();
+                assert_eq!(
+                    data,
+                    Shapes {
+                        shapes: vec![
+                            Shape::Circle { radius: 1 },
+                            Shape::Unknown,
+                            Shape::Square(4),
+                        ],
+                    }
+                );
+            }
+        }
+
+        /// A variant is selected by the name of the single child of a wrapper
+        /// element, as in RPC responses, that contain either a result or a fault
+        mod wrapped {
+            use super::*;
+            use pretty_assertions::assert_eq;
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Value {
+                value: u32,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Fault {
+                code: u32,
+                message: String,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            enum Body {
+                #[serde(rename = "result")]
+                Success(Value),
+                #[serde(rename = "fault")]
+                Fault(Fault),
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Response {
+                #[serde(rename = "$value")]
+                body: Body,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct StdResponse {
+                #[serde(rename = "$value")]
+                body: Result<Value, Fault>,
+            }
+
+            #[test]
+            fn success() {
+                let data: Response =
+                    from_str("<response><result><value>42</value></result></response>").unwrap();
+                assert_eq!(
+                    data,
+                    Response {
+                        body: Body::Success(Value { value: 42 }),
+                    }
+                );
+            }
+
+            #[test]
+            fn fault() {
+                let data: Response = from_str(
+                    r#"
+                    <response>
+                        <fault>
+                            <code>500</code>
+                            <message>Internal error</message>
+                        </fault>
+                    </response>
+                    "#,
+                )
+                .unwrap();
+                assert_eq!(
+                    data,
+                    Response {
+                        body: Body::Fault(Fault {
+                            code: 500,
+                            message: "Internal error".into(),
+                        }),
+                    }
+                );
+            }
+
+            #[test]
+            fn unknown() {
+                match from_str::<Response>("<response><other/></response>") {
+                    Err(DeError::Custom(e)) => {
+                        assert_eq!(e, "unknown variant `other`, expected `result` or `fault`")
+                    }
+                    e => panic!("Expected `Custom`, but got {:?}", e),
+                }
+            }
+
+            #[test]
+            fn std_result() {
+                let data: StdResponse =
+                    from_str(r#"<response><Ok value="42"/></response>"#).unwrap();
+                assert_eq!(
+                    data,
+                    StdResponse {
+                        body: Ok(Value { value: 42 }),
+                    }
+                );
+
+                let data: StdResponse =
+                    from_str(r#"<response><Err code="500" message="Internal error"/></response>"#)
+                        .unwrap();
+                assert_eq!(
+                    data,
+                    StdResponse {
+                        body: Err(Fault {
+                            code: 500,
+                            message: "Internal error".into(),
+                        }),
+                    }
+                );
+            }
+
+            #[test]
+            fn sequence() {
+                let data: Vec<Response> = from_str(
+                    r#"<response><fault code="1" message="bad"/></response><response><result value="2"/></response>"#,
+                )
+                .unwrap();
+                assert_eq!(
+                    data,
+                    vec![
+                        Response {
+                            body: Body::Fault(Fault {
+                                code: 1,
+                                message: "bad".into(),
+                            }),
+                        },
+                        Response {
+                            body: Body::Success(Value { value: 2 }),
+                        },
+                    ]
+                );
+            }
+        }
+
+        /// A variant is selected by the text of an element, named after a field
+        mod text {
+            use super::*;
+            use pretty_assertions::assert_eq;
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            #[serde(rename_all = "lowercase")]
+            enum Status {
+                Active,
+                Inactive,
+                #[serde(other)]
+                Unknown,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            #[serde(rename_all = "lowercase")]
+            enum Strict {
+                Active,
+                Inactive,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Account {
+                status: Status,
+                id: u32,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct StrictAccount {
+                status: Strict,
+            }
+
+            #[test]
+            fn known() {
+                let data: Account =
+                    from_str("<account><status>active</status><id>1</id></account>").unwrap();
+                assert_eq!(
+                    data,
+                    Account {
+                        status: Status::Active,
+                        id: 1,
+                    }
+                );
+            }
+
+            #[test]
+            fn cdata() {
+                let data: Account =
+                    from_str("<account><status><![CDATA[inactive]]></status><id>1</id></account>")
+                        .unwrap();
+                assert_eq!(
+                    data,
+                    Account {
+                        status: Status::Inactive,
+                        id: 1,
+                    }
+                );
+            }
+
+            /// Unknown text is mapped to the `#[serde(other)]` variant
+            #[test]
+            fn unknown() {
+                let data: Account =
+                    from_str("<account><status>deleted</status><id>1</id></account>").unwrap();
+                assert_eq!(
+                    data,
+                    Account {
+                        status: Status::Unknown,
+                        id: 1,
+                    }
+                );
+            }
+
+            #[test]
+            fn unknown_strict() {
+                match from_str::<StrictAccount>("<account><status>deleted</status></account>") {
+                    Err(DeError::Custom(e)) => assert_eq!(
+                        e,
+                        "unknown variant `deleted`, expected `active` or `inactive`"
+                    ),
+                    x => panic!("Expected `Err(Custom(_))`, but got `{:?}`", x),
+                }
+            }
+
+            #[test]
+            fn optional() {
+                #[derive(Debug, Deserialize, PartialEq)]
+                struct Holder {
+                    status: Option<Strict>,
+                }
+
+                let data: Holder =
+                    from_str("<account><status>inactive</status></account>").unwrap();
+                assert_eq!(
+                    data,
+                    Holder {
+                        status: Some(Strict::Inactive),
+                    }
+                );
+            }
+
+            #[test]
+            fn map() {
+                use std::collections::HashMap;
+
+                let data: HashMap<String, Strict> =
+                    from_str("<accounts><first>active</first><second>inactive</second></accounts>")
+                        .unwrap();
+                assert_eq!(data.len(), 2);
+                assert_eq!(data["first"], Strict::Active);
+                assert_eq!(data["second"], Strict::Inactive);
+            }
+        }
     }
 
     mod internally_tagged {
@@ -1103,7 +3609,6 @@ mod enum_ {
             use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     r#"<root><tag>Struct</tag><float>42</float><string>answer</string></root>"#,
@@ -1133,14 +3638,39 @@ mod enum_ {
                     }
                 );
             }
-        }
-
-        mod nested_struct {
-            use super::*;
-            use pretty_assertions::assert_eq;
+
+            /// The tag and the fields can be in attributes and elements independently
+            #[test]
+            fn mixed() {
+                let data: Node =
+                    from_str(r#"<root tag="Struct" float="42"><string>answer</string></root>"#)
+                        .unwrap();
+                assert_eq!(
+                    data,
+                    Node::Struct {
+                        float: "42".into(),
+                        string: "answer".into()
+                    }
+                );
+
+                let data: Node =
+                    from_str(r#"<root string="answer"><tag>Struct</tag><float>42</float></root>"#)
+                        .unwrap();
+                assert_eq!(
+                    data,
+                    Node::Struct {
+                        float: "42".into(),
+                        string: "answer".into()
+                    }
+                );
+            }
+        }
+
+        mod nested_struct {
+            use super::*;
+            use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     r#"<root><tag>Holder</tag><string>answer</string><nested><float>42</float></nested></root>"#,
@@ -1176,7 +3706,6 @@ mod enum_ {
             use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     r#"<root><tag>Flatten</tag><float>42</float><string>answer</string></root>"#,
@@ -1392,7 +3921,6 @@ mod enum_ {
             use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     r#"<root><tag>Flatten</tag><content><float>42</float><string>answer</string></content></root>"#,
@@ -1532,7 +4060,6 @@ mod enum_ {
             use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     r#"<root><string>answer</string><nested><float>42</float></nested></root>"#,
@@ -1569,7 +4096,6 @@ mod enum_ {
             use pretty_assertions::assert_eq;
 
             #[test]
-            #[ignore = "Prime cause: deserialize_any under the hood + https://github.com/serde-rs/serde/issues/1183"]
             fn elements() {
                 let data: Node = from_str(
                     // Comment for prevent unnecessary formatting - we use the same style in all tests
@@ -1603,3 +4129,1896 @@ mod enum_ {
         }
     }
 }
+
+/// Tests for string types other than `String`, which are deserialized by serde
+/// using the same `deserialize_str` / `deserialize_string` calls
+mod boxed_str {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Strings {
+        boxed: Box<str>,
+        rc: Rc<str>,
+        arc: Arc<str>,
+    }
+
+    #[test]
+    fn elements() {
+        let data: Strings = from_str(
+            r#"<root><boxed>&lt;box&gt;</boxed><rc>R&amp;C</rc><arc>&quot;&#x41;rc&quot;</arc></root>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Strings {
+                boxed: "<box>".into(),
+                rc: "R&C".into(),
+                arc: "\"Arc\"".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let data: Strings =
+            from_str(r#"<root boxed="&lt;box&gt;" rc="R&amp;C" arc='"&#x41;rc"'/>"#).unwrap();
+        assert_eq!(
+            data,
+            Strings {
+                boxed: "<box>".into(),
+                rc: "R&C".into(),
+                arc: "\"Arc\"".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn split_text() {
+        let data: Strings = from_str(
+            r#"<root><boxed>b<!--comment-->o<![CDATA[x]]></boxed><rc/><arc></arc></root>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Strings {
+                boxed: "box".into(),
+                rc: "".into(),
+                arc: "".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn top_level() {
+        let data: Box<str> = from_str(r#"<root>&lt;box&gt;</root>"#).unwrap();
+        assert_eq!(data, "<box>".into());
+
+        let data: Rc<str> = from_str(r#"<root>R&amp;C</root>"#).unwrap();
+        assert_eq!(data, "R&C".into());
+
+        let data: Arc<str> = from_str(r#"<root>&#x41;rc</root>"#).unwrap();
+        assert_eq!(data, "Arc".into());
+    }
+
+    #[test]
+    fn sequence() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct List {
+            item: Vec<Arc<str>>,
+        }
+
+        let data: List =
+            from_str(r#"<list><item>a&amp;b</item><item/><item>c</item></list>"#).unwrap();
+        assert_eq!(
+            data,
+            List {
+                item: vec!["a&b".into(), "".into(), "c".into()],
+            }
+        );
+    }
+}
+
+/// Attribute values without escapes are borrowed from the input
+mod borrowed_attributes {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::borrow::Cow;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+    }
+
+    #[test]
+    fn unescaped() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item<'a> {
+            name: &'a str,
+        }
+
+        let xml = r#"<item name="first"/>"#;
+        let item: Item = from_str(xml).unwrap();
+        assert_eq!(item, Item { name: "first" });
+        // The value is a part of the input
+        assert_eq!(item.name.as_ptr(), xml[12..].as_ptr());
+    }
+
+    #[test]
+    fn unescaped_cow() {
+        let item: Item = from_str(r#"<item name="first"/>"#).unwrap();
+        assert!(matches!(item.name, Cow::Borrowed("first")));
+    }
+
+    #[test]
+    fn escaped() {
+        let item: Item = from_str(r#"<item name="first &amp; second"/>"#).unwrap();
+        assert!(matches!(item.name, Cow::Owned(ref name) if name == "first & second"));
+    }
+
+    #[test]
+    fn escaped_str() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item<'a> {
+            name: &'a str,
+        }
+
+        match from_str::<Item>(r#"<item name="first &amp; second"/>"#) {
+            Err(DeError::Custom(e)) => assert_eq!(
+                e,
+                r#"invalid type: string "first & second", expected a borrowed string"#
+            ),
+            e => panic!("Expected `Custom`, but got {:?}", e),
+        }
+    }
+
+    /// Data from a reader can not be borrowed
+    #[test]
+    fn from_reader() {
+        let mut de = Deserializer::from_reader(r#"<item name="first"/>"#.as_bytes());
+        let item = Item::deserialize(&mut de).unwrap();
+        assert!(matches!(item.name, Cow::Owned(ref name) if name == "first"));
+    }
+}
+
+/// Tests for special float values in the XML Schema spelling
+mod xsd_floats {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize)]
+    struct Floats {
+        #[serde(rename = "$value")]
+        text: f64,
+        attr: f32,
+    }
+
+    /// Deserializes `Floats` from XML with the same value in the attribute and the text
+    fn floats(value: &str, xsd: bool) -> Result<Floats, DeError> {
+        let xml = format!(r#"<root attr="{0}">{0}</root>"#, value);
+        let mut de = Deserializer::from_str(&xml);
+        de.xsd_floats(xsd);
+        Floats::deserialize(&mut de)
+    }
+
+    #[test]
+    fn inf() {
+        let data = floats("INF", true).unwrap();
+        assert_eq!(data.text, f64::INFINITY);
+        assert_eq!(data.attr, f32::INFINITY);
+
+        let mut de = Deserializer::from_str("<root>INF</root>");
+        de.xsd_floats(true);
+        assert_eq!(f64::deserialize(&mut de).unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn negative_inf() {
+        let data = floats("-INF", true).unwrap();
+        assert_eq!(data.text, f64::NEG_INFINITY);
+        assert_eq!(data.attr, f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn nan() {
+        let data = floats("NaN", true).unwrap();
+        assert!(data.text.is_nan());
+        assert!(data.attr.is_nan());
+    }
+
+    #[test]
+    fn finite() {
+        let data = floats("-4.2e1", true).unwrap();
+        assert_eq!(data.text, -42.0);
+        assert_eq!(data.attr, -42.0);
+    }
+
+    /// Spellings accepted by Rust but not by XML Schema
+    #[test]
+    fn non_xsd() {
+        for value in &["inf", "+INF", "Infinity", "-infinity", "nan", "NAN"] {
+            match floats(value, true) {
+                Err(DeError::Custom(e)) => assert_eq!(
+                    e,
+                    format!(
+                        "invalid value: string \"{}\", expected `INF`, `-INF`, `NaN` or a finite number",
+                        value
+                    )
+                ),
+                e => panic!("Expected `Custom`, but got {:?}", e),
+            }
+        }
+    }
+
+    /// By default any spelling, that Rust understands, is accepted, except `INF` and `-INF`
+    #[test]
+    fn default() {
+        for value in &["inf", "+inf", "Inf", "infinity", "Infinity", "+INF"] {
+            let data = floats(value, false).unwrap();
+            assert_eq!(data.text, f64::INFINITY);
+            assert_eq!(data.attr, f32::INFINITY);
+        }
+        assert_eq!(floats("-inf", false).unwrap().text, f64::NEG_INFINITY);
+        assert!(floats("NaN", false).unwrap().text.is_nan());
+        assert!(floats("nan", false).unwrap().attr.is_nan());
+        assert_eq!(floats("-4.2e1", false).unwrap().text, -42.0);
+    }
+
+    /// By default the XML Schema spellings of infinities are rejected
+    #[test]
+    fn default_xsd() {
+        for value in &["INF", "-INF"] {
+            match floats(value, false) {
+                Err(DeError::Custom(e)) => assert_eq!(
+                    e,
+                    format!(
+                        "invalid value: string \"{}\", expected a float number. \
+                         Use `Deserializer::xsd_floats` to accept `INF` and `-INF`",
+                        value
+                    )
+                ),
+                e => panic!("Expected `Custom`, but got {:?}", e),
+            }
+        }
+
+        let mut de = Deserializer::from_str("<root>INF</root>");
+        match f64::deserialize(&mut de) {
+            Err(DeError::Custom(_)) => {}
+            e => panic!("Expected `Custom`, but got {:?}", e),
+        }
+    }
+}
+
+/// A custom `Deserialize` implementation based on `deserialize_str` receives the
+/// text of an element, attributes of the element are ignored
+mod custom_text {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde::de::{Error, Visitor};
+    use std::fmt;
+
+    /// An ISO 8601 duration, which is only checked for the leading `P`
+    #[derive(Debug, PartialEq)]
+    struct Duration(String);
+
+    impl<'de> Deserialize<'de> for Duration {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct DurationVisitor;
+
+            impl<'de> Visitor<'de> for DurationVisitor {
+                type Value = Duration;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("an ISO 8601 duration")
+                }
+
+                fn visit_str<E: Error>(self, v: &str) -> Result<Duration, E> {
+                    if v.starts_with('P') {
+                        Ok(Duration(v.to_owned()))
+                    } else {
+                        Err(E::custom(format!("invalid duration `{}`", v)))
+                    }
+                }
+            }
+
+            deserializer.deserialize_str(DurationVisitor)
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Task {
+        dur: Duration,
+        name: String,
+    }
+
+    #[test]
+    fn with_attributes() {
+        let data: Task = from_str(
+            r#"<task><dur unit="iso" precision="month">P1Y2M</dur><name>build</name></task>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Task {
+                dur: Duration("P1Y2M".into()),
+                name: "build".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn top_level() {
+        let data: Duration = from_str(r#"<dur unit="iso">P1Y2M</dur>"#).unwrap();
+        assert_eq!(data, Duration("P1Y2M".into()));
+    }
+
+    #[test]
+    fn sequence() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct List {
+            dur: Vec<Duration>,
+        }
+
+        let data: List =
+            from_str(r#"<list><dur unit="iso">P1Y</dur><dur>P2M</dur><dur a="b">P3D</dur></list>"#)
+                .unwrap();
+        assert_eq!(
+            data,
+            List {
+                dur: vec![
+                    Duration("P1Y".into()),
+                    Duration("P2M".into()),
+                    Duration("P3D".into()),
+                ],
+            }
+        );
+    }
+
+    /// Text, split by comments, processing instructions and CDATA sections,
+    /// is concatenated and unescaped
+    #[test]
+    fn split_text() {
+        let data: Task = from_str(
+            r#"<task><dur unit="iso">P1Y<!--years-->2M<?pi?>&#x33;D<![CDATA[T4H]]></dur><name>build</name></task>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Task {
+                dur: Duration("P1Y2M3DT4H".into()),
+                name: "build".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        match from_str::<Duration>(r#"<dur unit="iso">1Y</dur>"#) {
+            Err(DeError::Custom(e)) => assert_eq!(e, "invalid duration `1Y`"),
+            x => panic!("Expected `Err(Custom(_))`, but got `{:?}`", x),
+        }
+    }
+}
+
+mod number_sanitizer {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::borrow::Cow;
+
+    /// Converts `1.234,56` into `1234.56`
+    fn european(text: &str) -> Cow<str> {
+        Cow::Owned(text.replace('.', "").replace(',', "."))
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Numbers {
+        float: f64,
+        int: u32,
+        #[serde(rename = "$value")]
+        text: f32,
+    }
+
+    #[test]
+    fn float() {
+        let mut de = Deserializer::from_str("<root>1.234,56</root>");
+        de.number_sanitizer(european);
+        assert_eq!(f64::deserialize(&mut de).unwrap(), 1234.56);
+    }
+
+    #[test]
+    fn elements_and_attributes() {
+        let mut de =
+            Deserializer::from_str(r#"<root float="1.234,56" int="1.000.000">-0,5</root>"#);
+        de.number_sanitizer(european);
+        assert_eq!(
+            Numbers::deserialize(&mut de).unwrap(),
+            Numbers {
+                float: 1234.56,
+                int: 1_000_000,
+                text: -0.5,
+            }
+        );
+    }
+
+    #[test]
+    fn default() {
+        let mut de = Deserializer::from_str("<root>1.234,56</root>");
+        match f64::deserialize(&mut de) {
+            Err(DeError::InvalidFloat(_)) => {}
+            x => panic!("Expected `Err(InvalidFloat(_))`, but got `{:?}`", x),
+        }
+    }
+}
+
+mod presence_bool {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Flags {
+        #[serde(default)]
+        flag: bool,
+    }
+
+    fn flags(xml: &str) -> Result<Flags, DeError> {
+        let mut de = Deserializer::from_str(xml);
+        de.presence_bool(true);
+        Flags::deserialize(&mut de)
+    }
+
+    #[test]
+    fn present_empty() {
+        assert_eq!(flags("<root><flag/></root>").unwrap(), Flags { flag: true });
+        assert_eq!(
+            flags("<root><flag></flag></root>").unwrap(),
+            Flags { flag: true }
+        );
+    }
+
+    #[test]
+    fn present_with_text() {
+        assert_eq!(
+            flags("<root><flag>true</flag></root>").unwrap(),
+            Flags { flag: true }
+        );
+        assert_eq!(
+            flags("<root><flag>false</flag></root>").unwrap(),
+            Flags { flag: false }
+        );
+    }
+
+    #[test]
+    fn absent() {
+        assert_eq!(flags("<root/>").unwrap(), Flags { flag: false });
+    }
+
+    /// Without `#[serde(default)]` an absent field is still an error
+    #[test]
+    fn absent_without_default() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Required {
+            flag: bool,
+        }
+
+        let mut de = Deserializer::from_str("<root/>");
+        de.presence_bool(true);
+        match Required::deserialize(&mut de) {
+            Err(DeError::MissingField(field)) => assert_eq!(field, "flag"),
+            x => panic!("Expected `Err(MissingField(_))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn top_level() {
+        let mut de = Deserializer::from_str("<flag/>");
+        de.presence_bool(true);
+        assert_eq!(bool::deserialize(&mut de).unwrap(), true);
+    }
+
+    #[test]
+    fn disabled() {
+        match from_str::<Flags>("<root><flag/></root>") {
+            Err(DeError::InvalidBoolean(_)) => {}
+            x => panic!("Expected `Err(InvalidBoolean(_))`, but got `{:?}`", x),
+        }
+    }
+}
+
+mod strict_types {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Inner {
+        value: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Root {
+        field: Inner,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Text {
+        #[serde(rename = "$value")]
+        value: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Scalar {
+        field: String,
+    }
+
+    fn strict<T: serde::de::DeserializeOwned>(xml: &str) -> Result<T, DeError> {
+        let mut de = Deserializer::from_str(xml);
+        de.strict_types(true);
+        T::deserialize(&mut de)
+    }
+
+    #[test]
+    fn lenient() {
+        let data: Root = from_str("<root><field>text</field></root>").unwrap();
+        assert_eq!(
+            data,
+            Root {
+                field: Inner { value: None }
+            }
+        );
+
+        let data: Scalar = from_str(r#"<root><field attr="1">text</field></root>"#).unwrap();
+        assert_eq!(
+            data,
+            Scalar {
+                field: "text".into()
+            }
+        );
+    }
+
+    #[test]
+    fn text_for_struct() {
+        match strict::<Root>("<root><field>text</field></root>") {
+            Err(DeError::UnexpectedText(text)) => assert_eq!(text, b"text"),
+            x => panic!("Expected `Err(UnexpectedText(text))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn cdata_for_struct() {
+        match strict::<Root>("<root><field><![CDATA[cdata]]></field></root>") {
+            Err(DeError::UnexpectedText(text)) => assert_eq!(text, b"cdata"),
+            x => panic!("Expected `Err(UnexpectedText(cdata))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn struct_for_struct() {
+        let data: Root = strict("<root><field><value>text</value></field></root>").unwrap();
+        assert_eq!(
+            data,
+            Root {
+                field: Inner {
+                    value: Some("text".into())
+                }
+            }
+        );
+
+        let data: Root = strict("<root><field/></root>").unwrap();
+        assert_eq!(
+            data,
+            Root {
+                field: Inner { value: None }
+            }
+        );
+    }
+
+    /// Structs with a `$value` field are deserialized from the text
+    #[test]
+    fn text_for_value_field() {
+        let data: Text = strict("<root>text</root>").unwrap();
+        assert_eq!(
+            data,
+            Text {
+                value: "text".into()
+            }
+        );
+    }
+
+    /// Structs with a `$value` field are not checked even if the next event is a text
+    #[test]
+    fn text_before_value_field() {
+        let data: Text = strict("text<root>inner</root>").unwrap();
+        assert_eq!(
+            data,
+            Text {
+                value: "inner".into()
+            }
+        );
+    }
+
+    #[test]
+    fn attributes_for_scalar() {
+        match strict::<Scalar>(r#"<root><field attr="1">text</field></root>"#) {
+            Err(DeError::UnexpectedStart(tag)) => assert_eq!(tag, b"field"),
+            x => panic!("Expected `Err(UnexpectedStart(field))`, but got `{:?}`", x),
+        }
+    }
+
+    /// Nested elements are rejected regardless of the mode
+    #[test]
+    fn element_for_scalar() {
+        let xml = "<root><field><value>text</value></field></root>";
+        match from_str::<Scalar>(xml) {
+            Err(DeError::UnexpectedStart(tag)) => assert_eq!(tag, b"value"),
+            x => panic!("Expected `Err(UnexpectedStart(value))`, but got `{:?}`", x),
+        }
+        match strict::<Scalar>(xml) {
+            Err(DeError::UnexpectedStart(tag)) => assert_eq!(tag, b"value"),
+            x => panic!("Expected `Err(UnexpectedStart(value))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn text_for_scalar() {
+        let data: Scalar = strict("<root><field>text</field></root>").unwrap();
+        assert_eq!(
+            data,
+            Scalar {
+                field: "text".into()
+            }
+        );
+    }
+}
+
+/// Tests for the `Collapsed` wrapper, that collapses whitespaces in a text
+mod collapsed {
+    use super::*;
+    use fast_xml::de::Collapsed;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Root {
+        #[serde(rename = "@attr")]
+        attr: Collapsed,
+        element: Collapsed,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Text {
+        #[serde(rename = "$value")]
+        value: Collapsed,
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(Collapsed::new("  a \t b \n c  "), Collapsed("a b c".into()));
+        assert_eq!(Collapsed::new("a\r\n\r\nb"), Collapsed("a b".into()));
+        assert_eq!(Collapsed::new(" \t\r\n "), Collapsed("".into()));
+        // Only XML whitespaces are collapsed
+        assert_eq!(
+            Collapsed::new("a\u{A0}\u{A0}b"),
+            Collapsed("a\u{A0}\u{A0}b".into())
+        );
+    }
+
+    #[test]
+    fn attribute_and_element() {
+        let data: Root =
+            from_str("<root attr='  a \t b \n c  '><element>  a \t b \n c  </element></root>")
+                .unwrap();
+        assert_eq!(
+            data,
+            Root {
+                attr: Collapsed("a b c".into()),
+                element: Collapsed("a b c".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn text() {
+        let data: Text = from_str("<root>  a \t b \n c  </root>").unwrap();
+        assert_eq!(data.value, Collapsed("a b c".into()));
+    }
+
+    #[test]
+    fn escaped() {
+        // Whitespaces from character references are collapsed too
+        let data: Text = from_str("<root>&#x20;a&#x9;&#x9;b&#xA;</root>").unwrap();
+        assert_eq!(data.value, Collapsed("a b".into()));
+    }
+
+    #[test]
+    fn cdata() {
+        let data: Text = from_str("<root><![CDATA[  a \t b \n c  ]]></root>").unwrap();
+        assert_eq!(data.value, Collapsed("a b c".into()));
+    }
+}
+
+/// Tests for the `OneOrMany` list, that can be deserialized from a single value
+mod one_or_many {
+    use super::*;
+    use fast_xml::de::OneOrMany;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tags {
+        #[serde(default)]
+        tag: OneOrMany<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Numbers {
+        number: OneOrMany<u32>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Points {
+        point: OneOrMany<Point>,
+    }
+
+    #[test]
+    fn zero() {
+        let data: Tags = from_str("<root/>").unwrap();
+        assert_eq!(data.tag, OneOrMany(vec![]));
+
+        match from_str::<Numbers>("<root/>") {
+            Err(DeError::MissingField(field)) => assert_eq!(field, "number"),
+            x => panic!("Expected `Err(MissingField(_))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn one() {
+        let data: Tags = from_str("<root><tag>a</tag></root>").unwrap();
+        assert_eq!(data.tag, OneOrMany(vec!["a".to_string()]));
+
+        let data: Numbers = from_str("<root><number>42</number></root>").unwrap();
+        assert_eq!(data.number, OneOrMany(vec![42]));
+
+        let data: Points = from_str(r#"<root><point x="1" y="2"/></root>"#).unwrap();
+        assert_eq!(data.point, OneOrMany(vec![Point { x: 1, y: 2 }]));
+    }
+
+    /// A single value can be written in an attribute
+    #[test]
+    fn attribute() {
+        let data: Tags = from_str(r#"<root tag="a &amp; b"/>"#).unwrap();
+        assert_eq!(data.tag, OneOrMany(vec!["a & b".to_string()]));
+
+        let data: Numbers = from_str(r#"<root number="42"/>"#).unwrap();
+        assert_eq!(data.number, OneOrMany(vec![42]));
+    }
+
+    #[test]
+    fn many() {
+        let data: Tags = from_str("<root><tag>a</tag><tag>b</tag><tag>c</tag></root>").unwrap();
+        assert_eq!(
+            data.tag,
+            OneOrMany(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+
+        let data: Numbers = from_str("<root><number>1</number><number>2</number></root>").unwrap();
+        assert_eq!(data.number, OneOrMany(vec![1, 2]));
+
+        let data: Points =
+            from_str(r#"<root><point x="1" y="2"/><point><x>3</x><y>4</y></point></root>"#)
+                .unwrap();
+        assert_eq!(
+            data.point,
+            OneOrMany(vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }])
+        );
+    }
+}
+
+/// Tests for the `Deserializer::empty_string_as_none` setting
+mod empty_string_as_none {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Root {
+        optional: Option<String>,
+        required: String,
+        after: Option<u32>,
+    }
+
+    fn root(xml: &str, empty_as_none: bool) -> Root {
+        let mut de = Deserializer::from_str(xml);
+        de.empty_string_as_none(empty_as_none);
+        Root::deserialize(&mut de).unwrap()
+    }
+
+    /// Empty elements are `Some("")` by default
+    #[test]
+    fn default() {
+        let data: Root =
+            from_str("<root><optional></optional><required></required><after>1</after></root>")
+                .unwrap();
+        assert_eq!(
+            data,
+            Root {
+                optional: Some("".into()),
+                required: "".into(),
+                after: Some(1),
+            }
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let expected = Root {
+            optional: None,
+            required: "".into(),
+            after: Some(1),
+        };
+        let xml = "<root><optional></optional><required></required><after>1</after></root>";
+        assert_eq!(root(xml, true), expected);
+
+        let xml = "<root><optional/><required/><after>1</after></root>";
+        assert_eq!(root(xml, true), expected);
+
+        let xml = "<root><optional><![CDATA[]]></optional><required/><after>1</after></root>";
+        assert_eq!(root(xml, true), expected);
+    }
+
+    #[test]
+    fn whitespaces() {
+        let xml = "<root><optional> \t\n </optional><required> </required><after>1</after></root>";
+        assert_eq!(
+            root(xml, true),
+            Root {
+                optional: None,
+                required: "".into(),
+                after: Some(1),
+            }
+        );
+        assert_eq!(
+            root(xml, false),
+            Root {
+                optional: Some("".into()),
+                required: "".into(),
+                after: Some(1),
+            }
+        );
+    }
+
+    #[test]
+    fn non_empty() {
+        let xml = "<root><optional> text </optional><required>x</required><after>1</after></root>";
+        assert_eq!(
+            root(xml, true),
+            Root {
+                optional: Some("text".into()),
+                required: "x".into(),
+                after: Some(1),
+            }
+        );
+    }
+
+    /// Elements with attributes are not empty
+    #[test]
+    fn with_attributes() {
+        let xml = r#"<root><optional attr="1"/><required/><after>1</after></root>"#;
+        assert_eq!(
+            root(xml, true),
+            Root {
+                optional: Some("".into()),
+                required: "".into(),
+                after: Some(1),
+            }
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct List {
+            item: Vec<Option<String>>,
+        }
+
+        let mut de = Deserializer::from_str("<root><item>a</item><item/><item>b</item></root>");
+        de.empty_string_as_none(true);
+        assert_eq!(
+            List::deserialize(&mut de).unwrap(),
+            List {
+                item: vec![Some("a".into()), None, Some("b".into())],
+            }
+        );
+    }
+}
+
+/// Recursive structures are deserialized from nested elements with the same name
+mod recursive {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Node {
+        #[serde(rename = "@name")]
+        name: String,
+        #[serde(rename = "node", default)]
+        children: Vec<Node>,
+    }
+
+    fn node(name: &str, children: Vec<Node>) -> Node {
+        Node {
+            name: name.into(),
+            children,
+        }
+    }
+
+    #[test]
+    fn leaf() {
+        let data: Node = from_str(r#"<node name="a"/>"#).unwrap();
+        assert_eq!(data, node("a", vec![]));
+    }
+
+    #[test]
+    fn several_levels() {
+        let data: Node = from_str(
+            r#"<node name="a"><node name="b"><node name="c"><node name="d"/></node></node></node>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            node(
+                "a",
+                vec![node("b", vec![node("c", vec![node("d", vec![])])])]
+            )
+        );
+    }
+
+    /// Siblings after a nested subtree belong to the parent of that subtree
+    #[test]
+    fn siblings() {
+        let data: Node = from_str(
+            r#"
+            <node name="a">
+                <node name="b">
+                    <node name="c"/>
+                    <node name="d"><node name="e"/></node>
+                </node>
+                <node name="f"/>
+            </node>
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            node(
+                "a",
+                vec![
+                    node(
+                        "b",
+                        vec![node("c", vec![]), node("d", vec![node("e", vec![])])]
+                    ),
+                    node("f", vec![]),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn deep() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Node {
+            node: Option<Box<Node>>,
+        }
+
+        let depth = 100;
+        let xml = "<node>".repeat(depth) + &"</node>".repeat(depth);
+        let mut data: Node = from_str(&xml).unwrap();
+
+        let mut levels = 1;
+        while let Some(child) = data.node {
+            data = *child;
+            levels += 1;
+        }
+        assert_eq!(levels, depth);
+    }
+
+    /// Recursion through a `$value` field with an enum, that selects a variant
+    /// by the element name
+    #[test]
+    fn value_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Item {
+            #[serde(rename = "node")]
+            Node {
+                #[serde(rename = "$value", default)]
+                children: Vec<Item>,
+            },
+            #[serde(rename = "leaf")]
+            Leaf(String),
+        }
+
+        let data: Item = from_str(
+            r#"<node><leaf>1</leaf><node><node><leaf>2</leaf></node><node/></node><leaf>3</leaf></node>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Item::Node {
+                children: vec![
+                    Item::Leaf("1".into()),
+                    Item::Node {
+                        children: vec![
+                            Item::Node {
+                                children: vec![Item::Leaf("2".into())],
+                            },
+                            Item::Node { children: vec![] },
+                        ],
+                    },
+                    Item::Leaf("3".into()),
+                ],
+            }
+        );
+    }
+}
+
+mod stats {
+    use super::*;
+    use fast_xml::de::DeStats;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Library {
+        #[serde(rename = "@name")]
+        name: String,
+        book: Vec<Book>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Book {
+        #[serde(rename = "@id")]
+        id: u32,
+        title: String,
+    }
+
+    const XML: &str = r#"
+        <library name="city" city="Paris">
+            <book id="1"><title>Dune</title><author><name>Herbert</name></author></book>
+            <book id="2" lang="en"><title><![CDATA[Emma]]></title></book>
+            <address>Main street</address>
+        </library>
+    "#;
+
+    fn expected() -> Library {
+        Library {
+            name: "city".into(),
+            book: vec![
+                Book {
+                    id: 1,
+                    title: "Dune".into(),
+                },
+                Book {
+                    id: 2,
+                    title: "Emma".into(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let mut de = Deserializer::from_str(XML);
+        de.with_stats(true);
+        assert_eq!(Library::deserialize(&mut de).unwrap(), expected());
+        assert_eq!(
+            de.stats(),
+            DeStats {
+                // <library>, two <book>s, two <title>s, <author> and <address>
+                elements: 7,
+                attributes: 5,
+                // Dune and Emma
+                texts: 2,
+                // <author> and <address>
+                skipped: 2,
+            }
+        );
+    }
+
+    /// Counters do not depend on the source of data
+    #[test]
+    fn from_reader() {
+        let mut de = Deserializer::from_reader(XML.as_bytes());
+        de.with_stats(true);
+        assert_eq!(Library::deserialize(&mut de).unwrap(), expected());
+        assert_eq!(
+            de.stats(),
+            DeStats {
+                elements: 7,
+                attributes: 5,
+                texts: 2,
+                skipped: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn disabled() {
+        let mut de = Deserializer::from_str(XML);
+        assert_eq!(Library::deserialize(&mut de).unwrap(), expected());
+        assert_eq!(de.stats(), DeStats::default());
+    }
+}
+
+/// Unknown attributes and elements are captured by a flattened map with distinct
+/// names when `Deserializer::flatten_as_map` is set
+mod flatten_as_map {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Book {
+        #[serde(rename = "@id")]
+        id: u32,
+        title: String,
+        #[serde(flatten)]
+        extra: HashMap<String, String>,
+    }
+
+    fn map(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn from_str<'de, T: Deserialize<'de>>(xml: &'de str) -> Result<T, DeError> {
+        let mut de = Deserializer::from_str(xml);
+        de.flatten_as_map(true);
+        T::deserialize(&mut de)
+    }
+
+    #[test]
+    fn attributes_and_elements() {
+        let data: Book = from_str(
+            r#"
+            <book id="1" lang="en" year="1965">
+                <title>Dune</title>
+                <author>Herbert</author>
+                <lang>English</lang>
+            </book>
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            data,
+            Book {
+                id: 1,
+                title: "Dune".into(),
+                extra: map(&[
+                    ("@lang", "en"),
+                    ("@year", "1965"),
+                    ("author", "Herbert"),
+                    ("lang", "English"),
+                ]),
+            }
+        );
+    }
+
+    #[test]
+    fn nothing_unknown() {
+        let data: Book = from_str(r#"<book id="1"><title>Dune</title></book>"#).unwrap();
+
+        assert_eq!(
+            data,
+            Book {
+                id: 1,
+                title: "Dune".into(),
+                extra: HashMap::new(),
+            }
+        );
+    }
+
+    /// Fields of flattened structs should be renamed with the `@` prefix
+    /// to be deserialized from attributes
+    #[test]
+    fn flattened_struct() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            #[serde(rename = "@lang")]
+            lang: String,
+            author: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            title: String,
+            #[serde(flatten)]
+            inner: Inner,
+        }
+
+        let data: Outer =
+            from_str(r#"<book lang="en"><title>Dune</title><author>Herbert</author></book>"#)
+                .unwrap();
+
+        assert_eq!(
+            data,
+            Outer {
+                title: "Dune".into(),
+                inner: Inner {
+                    lang: "en".into(),
+                    author: "Herbert".into(),
+                },
+            }
+        );
+    }
+
+    /// By default attributes are captured without the prefix
+    #[test]
+    fn disabled() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            #[serde(flatten)]
+            extra: HashMap<String, String>,
+        }
+
+        let data: Book = super::from_str(r#"<book lang="en"><title>Dune</title></book>"#).unwrap();
+
+        assert_eq!(
+            data,
+            Book {
+                title: "Dune".into(),
+                extra: map(&[("lang", "en")]),
+            }
+        );
+    }
+}
+
+/// Variants of enums are selected by the `xsi:type` attribute when
+/// `Deserializer::xsi_type_dispatch` is set
+mod xsi_type_dispatch {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Shape {
+        Circle { radius: f64 },
+        Square { side: f64 },
+        Polygon(Polygon),
+        Point,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Polygon {
+        #[serde(rename = "@sides")]
+        sides: u32,
+    }
+
+    fn from_str<'de, T: Deserialize<'de>>(xml: &'de str) -> Result<T, DeError> {
+        let mut de = Deserializer::from_str(xml);
+        de.xsi_type_dispatch(true);
+        T::deserialize(&mut de)
+    }
+
+    #[test]
+    fn struct_variants() {
+        let data: Shape =
+            from_str(r#"<shape xsi:type="ns:Circle"><radius>1</radius></shape>"#).unwrap();
+        assert_eq!(data, Shape::Circle { radius: 1.0 });
+
+        let data: Shape =
+            from_str(r#"<shape xsi:type="ns:Square"><side>2</side></shape>"#).unwrap();
+        assert_eq!(data, Shape::Square { side: 2.0 });
+    }
+
+    /// Value of the attribute without the namespace prefix is also accepted
+    #[test]
+    fn without_prefix() {
+        let data: Shape = from_str(r#"<shape xsi:type="Square"><side>2</side></shape>"#).unwrap();
+        assert_eq!(data, Shape::Square { side: 2.0 });
+    }
+
+    #[test]
+    fn newtype_variant() {
+        let data: Shape = from_str(r#"<shape xsi:type="ns:Polygon" sides="5"/>"#).unwrap();
+        assert_eq!(data, Shape::Polygon(Polygon { sides: 5 }));
+    }
+
+    #[test]
+    fn unit_variant() {
+        let data: Shape = from_str(r#"<shape xsi:type="ns:Point"><ignored/></shape>"#).unwrap();
+        assert_eq!(data, Shape::Point);
+    }
+
+    #[test]
+    fn field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Drawing {
+            shape: Vec<Shape>,
+            title: String,
+        }
+
+        let data: Drawing = from_str(
+            r#"
+            <drawing xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+                <shape xsi:type="ns:Circle"><radius>1</radius></shape>
+                <shape xsi:type="ns:Square"><side>2</side></shape>
+                <title>Shapes</title>
+            </drawing>
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            Drawing {
+                shape: vec![Shape::Circle { radius: 1.0 }, Shape::Square { side: 2.0 }],
+                title: "Shapes".into(),
+            }
+        );
+    }
+
+    /// Elements without the attribute select variants by their names
+    #[test]
+    fn no_attribute() {
+        let data: Shape = from_str(r#"<Circle><radius>1</radius></Circle>"#).unwrap();
+        assert_eq!(data, Shape::Circle { radius: 1.0 });
+    }
+
+    #[test]
+    fn unknown_variant() {
+        match from_str::<Shape>(r#"<shape xsi:type="ns:Triangle"/>"#) {
+            Err(DeError::Custom(e)) => assert!(e.starts_with("unknown variant `Triangle`")),
+            x => panic!("Expected `Err(Custom)`, but got `{:?}`", x),
+        }
+    }
+
+    /// By default the attribute is ignored and variants are selected by names of elements
+    #[test]
+    fn disabled() {
+        let data: Shape =
+            super::from_str(r#"<Circle xsi:type="ns:Square"><radius>1</radius></Circle>"#).unwrap();
+        assert_eq!(data, Shape::Circle { radius: 1.0 });
+    }
+}
+
+mod name_collision {
+    use super::*;
+    use fast_xml::de::NameCollision;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Value {
+        value: String,
+    }
+
+    const XML: &str = r#"<e value="a"><value>b</value></e>"#;
+
+    fn value(policy: NameCollision) -> Result<Value, DeError> {
+        let mut de = Deserializer::from_str(XML);
+        de.name_collision_policy(policy);
+        Value::deserialize(&mut de)
+    }
+
+    #[test]
+    fn attribute_first() {
+        assert_eq!(
+            value(NameCollision::AttributeFirst).unwrap(),
+            Value { value: "a".into() }
+        );
+    }
+
+    #[test]
+    fn element_first() {
+        assert_eq!(
+            value(NameCollision::ElementFirst).unwrap(),
+            Value { value: "b".into() }
+        );
+    }
+
+    #[test]
+    fn error() {
+        match value(NameCollision::Error) {
+            Err(DeError::DuplicateField(field)) => assert_eq!(field, "value"),
+            x => panic!("Expected `Err(DuplicateField(_))`, but got `{:?}`", x),
+        }
+    }
+
+    /// The default policy is [`NameCollision::Error`]
+    #[test]
+    fn default() {
+        match from_str::<Value>(XML) {
+            Err(DeError::DuplicateField(field)) => assert_eq!(field, "value"),
+            x => panic!("Expected `Err(DuplicateField(_))`, but got `{:?}`", x),
+        }
+    }
+
+    /// Without a collision each policy takes the only available value
+    #[test]
+    fn no_collision() {
+        for policy in [
+            NameCollision::AttributeFirst,
+            NameCollision::ElementFirst,
+            NameCollision::Error,
+        ] {
+            let mut de = Deserializer::from_str(r#"<e value="a"/>"#);
+            de.name_collision_policy(policy);
+            assert_eq!(
+                Value::deserialize(&mut de).unwrap(),
+                Value { value: "a".into() }
+            );
+
+            let mut de = Deserializer::from_str("<e><value>b</value></e>");
+            de.name_collision_policy(policy);
+            assert_eq!(
+                Value::deserialize(&mut de).unwrap(),
+                Value { value: "b".into() }
+            );
+        }
+    }
+
+    /// A field with the `@` prefix is deserialized only from an attribute
+    #[test]
+    fn disambiguated() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Both {
+            #[serde(rename = "@value")]
+            attribute: String,
+            value: String,
+        }
+
+        for policy in [
+            NameCollision::AttributeFirst,
+            NameCollision::ElementFirst,
+            NameCollision::Error,
+        ] {
+            let mut de = Deserializer::from_str(XML);
+            de.name_collision_policy(policy);
+            assert_eq!(
+                Both::deserialize(&mut de).unwrap(),
+                Both {
+                    attribute: "a".into(),
+                    value: "b".into(),
+                }
+            );
+        }
+    }
+}
+
+mod field_name_normalizer {
+    use super::*;
+    use fast_xml::de::NameCollision;
+    use pretty_assertions::assert_eq;
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
+
+    fn lowercase(name: &str) -> Cow<str> {
+        Cow::Owned(name.to_lowercase())
+    }
+
+    fn normalized<'de, T: Deserialize<'de>>(xml: &'de str) -> Result<T, DeError> {
+        let mut de = Deserializer::from_str(xml);
+        de.field_name_normalizer(lowercase);
+        T::deserialize(&mut de)
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Root {
+        item: Item,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        name: String,
+    }
+
+    #[test]
+    fn struct_() {
+        assert_eq!(
+            normalized::<Root>(r#"<ROOT><ITEM NAME="x"/></ROOT>"#).unwrap(),
+            Root {
+                item: Item { name: "x".into() },
+            }
+        );
+    }
+
+    /// Names are used as is by default
+    #[test]
+    fn default() {
+        match from_str::<Root>(r#"<ROOT><ITEM NAME="x"/></ROOT>"#) {
+            Err(DeError::MissingField(field)) => assert_eq!(field, "item"),
+            x => panic!("Expected `Err(MissingField(_))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn map() {
+        let data: BTreeMap<String, String> =
+            normalized(r#"<root Key="1"><Value>2</Value></root>"#).unwrap();
+        assert_eq!(
+            data.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("key".to_string(), "1".to_string()),
+                ("value".to_string(), "2".to_string()),
+            ]
+        );
+    }
+
+    /// Elements with normalized names of other fields are not routed to the
+    /// `$value` field
+    #[test]
+    fn value_field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Choice {
+            One,
+            Two,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Root {
+            name: String,
+            #[serde(rename = "$value")]
+            choice: Choice,
+        }
+
+        assert_eq!(
+            normalized::<Root>("<root><NAME>x</NAME><Two/></root>").unwrap(),
+            Root {
+                name: "x".into(),
+                choice: Choice::Two,
+            }
+        );
+    }
+
+    /// Names are normalized before detection of collisions
+    #[test]
+    fn name_collision() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Value {
+            value: String,
+        }
+
+        let xml = r#"<e VALUE="a"><Value>b</Value></e>"#;
+        for (policy, expected) in [
+            (NameCollision::AttributeFirst, "a"),
+            (NameCollision::ElementFirst, "b"),
+        ] {
+            let mut de = Deserializer::from_str(xml);
+            de.field_name_normalizer(lowercase)
+                .name_collision_policy(policy);
+            assert_eq!(
+                Value::deserialize(&mut de).unwrap(),
+                Value {
+                    value: expected.into()
+                }
+            );
+        }
+    }
+}
+
+mod validate_balanced_upfront {
+    use super::*;
+    use fast_xml::Error;
+    use pretty_assertions::assert_eq;
+    use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+    use std::cell::Cell;
+    use std::fmt;
+    use std::rc::Rc;
+
+    /// Number of deserialized items, shared by the seeds
+    type Counter = Rc<Cell<usize>>;
+
+    #[derive(Debug)]
+    struct Item;
+
+    /// Deserializes an item and counts it
+    struct ItemSeed(Counter);
+    impl<'de> DeserializeSeed<'de> for ItemSeed {
+        type Value = Item;
+
+        fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Item, D::Error> {
+            u32::deserialize(deserializer)?;
+            self.0.set(self.0.get() + 1);
+            Ok(Item)
+        }
+    }
+
+    /// Deserializes a sequence of counted items
+    struct ItemsSeed(Counter);
+    impl<'de> DeserializeSeed<'de> for ItemsSeed {
+        type Value = Vec<Item>;
+
+        fn deserialize<D: de::Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Vec<Item>, D::Error> {
+            deserializer.deserialize_seq(self)
+        }
+    }
+    impl<'de> Visitor<'de> for ItemsSeed {
+        type Value = Vec<Item>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence of items")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<Item>, A::Error> {
+            let mut items = Vec::new();
+            while let Some(item) = seq.next_element_seed(ItemSeed(self.0.clone()))? {
+                items.push(item);
+            }
+            Ok(items)
+        }
+    }
+
+    #[derive(Debug)]
+    struct List {
+        item: Vec<Item>,
+    }
+
+    /// Deserializes a list with counted items
+    struct ListSeed(Counter);
+    impl<'de> DeserializeSeed<'de> for ListSeed {
+        type Value = List;
+
+        fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<List, D::Error> {
+            deserializer.deserialize_struct("List", &["item"], self)
+        }
+    }
+    impl<'de> Visitor<'de> for ListSeed {
+        type Value = List;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a list of items")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<List, A::Error> {
+            let mut item = None;
+            while let Some(key) = map.next_key::<String>()? {
+                if key == "item" {
+                    item = Some(map.next_value_seed(ItemsSeed(self.0.clone()))?);
+                } else {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            Ok(List {
+                item: item.ok_or_else(|| de::Error::missing_field("item"))?,
+            })
+        }
+    }
+
+    /// Deserializes a list and returns the number of deserialized items
+    /// together with the result
+    fn list(xml: &str, validate: bool) -> (Result<List, DeError>, usize) {
+        let items = Counter::default();
+        let mut de = Deserializer::from_str(xml);
+        de.validate_balanced_upfront(validate);
+        let result = ListSeed(items.clone()).deserialize(&mut de);
+        (result, items.get())
+    }
+
+    /// A document with 100 items, truncated inside the last one
+    fn truncated() -> String {
+        let mut xml = String::from("<list>");
+        for i in 0..100 {
+            xml.push_str(&format!("<item>{}</item>", i));
+        }
+        xml.truncate(xml.len() - "</item>".len());
+        xml
+    }
+
+    #[test]
+    fn well_formed() {
+        let xml = "<list><item>1</item><item>2</item></list>";
+        let (result, items) = list(xml, true);
+        assert_eq!(result.unwrap().item.len(), 2);
+        assert_eq!(items, 2);
+    }
+
+    #[test]
+    fn truncated_lazy() {
+        match list(&truncated(), false) {
+            (Err(DeError::UnexpectedEof), items) => assert_eq!(items, 99),
+            x => panic!("Expected `Err(UnexpectedEof)`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn truncated_upfront() {
+        match list(&truncated(), true) {
+            (Err(DeError::UnexpectedEof), items) => assert_eq!(items, 0),
+            x => panic!("Expected `Err(UnexpectedEof)`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn mismatched_end() {
+        let xml = "<list><item>1</item><item>2</item><item>3</other></list>";
+        match list(xml, true) {
+            (Err(DeError::InvalidXml(Error::EndEventMismatch { expected, found })), items) => {
+                assert_eq!(expected, "item");
+                assert_eq!(found, "other");
+                assert_eq!(items, 0);
+            }
+            x => panic!(
+                "Expected `Err(InvalidXml(EndEventMismatch))`, but got `{:?}`",
+                x
+            ),
+        }
+    }
+}
+
+mod lenient {
+    use super::*;
+    use fast_xml::de::from_str_lenient;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    /// Second of three items has a value of wrong type
+    #[test]
+    fn invalid_value() {
+        let (items, errors) = from_str_lenient::<Item>(
+            r#"
+            <item><id>1</id><name>first</name></item>
+            <item><id>two</id><name>second</name></item>
+            <item id="3" name="third"/>
+            "#,
+        );
+        assert_eq!(
+            items,
+            vec![
+                Item {
+                    id: 1,
+                    name: "first".into(),
+                },
+                Item {
+                    id: 3,
+                    name: "third".into(),
+                },
+            ]
+        );
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            DeError::InvalidInt(_) => {}
+            e => panic!("Expected `InvalidInt`, but got {:?}", e),
+        }
+    }
+
+    /// Second of three items has mismatched closing tag inside
+    #[test]
+    fn mismatched_end() {
+        let (items, errors) = from_str_lenient::<Item>(
+            r#"
+            <item><id>1</id><name>first</name></item>
+            <item><id>2</idx><name>second</name></item>
+            <item><id>3</id><name>third</name></item>
+            "#,
+        );
+        assert_eq!(
+            items,
+            vec![
+                Item {
+                    id: 1,
+                    name: "first".into(),
+                },
+                Item {
+                    id: 3,
+                    name: "third".into(),
+                },
+            ]
+        );
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            DeError::InvalidXml(fast_xml::Error::EndEventMismatch { expected, found }) => {
+                assert_eq!(expected, "id");
+                assert_eq!(found, "idx");
+            }
+            e => panic!("Expected `InvalidXml(EndEventMismatch)`, but got {:?}", e),
+        }
+    }
+
+    /// Parsing stops at the unclosed element, because the next sibling
+    /// could not be found
+    #[test]
+    fn unclosed() {
+        let (items, errors) =
+            from_str_lenient::<Item>(r#"<item id="1" name="first"/><item><id>2</id>"#);
+        assert_eq!(
+            items,
+            vec![Item {
+                id: 1,
+                name: "first".into(),
+            }]
+        );
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            DeError::UnexpectedEof => {}
+            e => panic!("Expected `UnexpectedEof`, but got {:?}", e),
+        }
+    }
+}
+
+mod prefix {
+    use super::*;
+    use fast_xml::de::from_str_prefix;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Root {
+        id: u32,
+        name: String,
+    }
+
+    #[test]
+    fn trailing() {
+        let (root, rest) =
+            from_str_prefix::<Root>(r#"<root><id>1</id><name>first</name></root><trailing/>"#)
+                .unwrap();
+        assert_eq!(
+            root,
+            Root {
+                id: 1,
+                name: "first".into(),
+            }
+        );
+        assert_eq!(rest, "<trailing/>");
+    }
+
+    /// Trailing content could be malformed, it is not read
+    #[test]
+    fn malformed_trailing() {
+        let (root, rest) =
+            from_str_prefix::<Root>(r#"<?xml version="1.0"?><root id="1" name="first"/></root>"#)
+                .unwrap();
+        assert_eq!(
+            root,
+            Root {
+                id: 1,
+                name: "first".into(),
+            }
+        );
+        assert_eq!(rest, "</root>");
+    }
+
+    /// The rest of the input can be used to read the next element
+    #[test]
+    fn several() {
+        let mut input = r#"<root id="1" name="first"/> <root><id>2</id><name>second</name></root>"#;
+        let mut roots = Vec::new();
+        while !input.trim().is_empty() {
+            let (root, rest) = from_str_prefix::<Root>(input).unwrap();
+            roots.push(root);
+            input = rest;
+        }
+        assert_eq!(
+            roots,
+            vec![
+                Root {
+                    id: 1,
+                    name: "first".into(),
+                },
+                Root {
+                    id: 2,
+                    name: "second".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed() {
+        match from_str_prefix::<Root>(r#"<root><id>1</id>"#) {
+            Err(DeError::UnexpectedEof) => {}
+            x => panic!("Expected `Err(UnexpectedEof)`, but got `{:?}`", x),
+        }
+    }
+}
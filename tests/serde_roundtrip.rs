@@ -180,3 +180,1176 @@ fn escapes_in_cdata() {
         r#"{37.3k,268,msb}<-1,1|1,-1>(T=1,(7,-6,3,D:4,1:1,T:1,1:2,0:8,F:8,15:4,C:4,-79m,T=0)+){C =(D:4+4*T+9+F:4+F:4:4+15)&15} [D:0..15,F:0..255]"#
     );
 }
+
+#[test]
+fn nested_collection() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Project {
+        name: String,
+
+        #[serde(rename = "item", default)]
+        items: Vec<Item>,
+    }
+
+    let project = Project {
+        name: "my_project".to_string(),
+        items: vec![
+            Item {
+                name: "hello1".to_string(),
+                source: "world1.rs".to_string(),
+            },
+            Item {
+                name: "hello2".to_string(),
+                source: "world2.rs".to_string(),
+            },
+        ],
+    };
+
+    let xml = to_string(&project).unwrap();
+    assert_eq!(
+        xml,
+        r#"<Project name="my_project"><item name="hello1" source="world1.rs"/><item name="hello2" source="world2.rs"/></Project>"#
+    );
+    assert_eq!(from_str::<Project>(&xml).unwrap(), project);
+}
+
+/// Items of sequences of primitives are written as elements named after the field
+#[test]
+fn collection_of_primitives() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Tags {
+        #[serde(rename = "tag")]
+        tags: Vec<String>,
+        size: Vec<u32>,
+    }
+
+    let tags = Tags {
+        tags: vec!["a".to_string(), "b & c".to_string()],
+        size: vec![1, 2],
+    };
+
+    let xml = to_string(&tags).unwrap();
+    assert_eq!(
+        xml,
+        "<Tags><tag>a</tag><tag>b &amp; c</tag><size>1</size><size>2</size></Tags>"
+    );
+    assert_eq!(from_str::<Tags>(&xml).unwrap(), tags);
+}
+
+/// `None` and empty collections are not written by default
+mod empty {
+    use super::*;
+    use fast_xml::se::Serializer;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+    struct Struct {
+        #[serde(default)]
+        optional: Option<String>,
+        #[serde(default)]
+        list: Vec<Item>,
+        #[serde(default)]
+        map: BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn skipped() {
+        let data = Struct::default();
+
+        let xml = to_string(&data).unwrap();
+        assert_eq!(xml, "<Struct/>");
+        assert_eq!(from_str::<Struct>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn not_empty() {
+        let mut map = BTreeMap::new();
+        map.insert("key".to_string(), "value".to_string());
+        let data = Struct {
+            optional: Some("some".into()),
+            list: vec![Item {
+                name: "Banana".into(),
+                source: "Store".into(),
+            }],
+            map,
+        };
+
+        let xml = to_string(&data).unwrap();
+        assert_eq!(
+            xml,
+            r#"<Struct optional="some"><list name="Banana" source="Store"/><map><key>value</key></map></Struct>"#
+        );
+        assert_eq!(from_str::<Struct>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn write_empty_collections() {
+        let data = Struct::default();
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.write_empty_collections(true);
+        data.serialize(&mut ser).unwrap();
+
+        let xml = String::from_utf8(buffer).unwrap();
+        // `None` is still skipped
+        assert_eq!(xml, "<Struct><list/><map/></Struct>");
+    }
+
+    /// Empty map with explicit root tag is written as an empty root element,
+    /// like an empty struct
+    #[test]
+    fn root_map() {
+        let data: BTreeMap<String, String> = BTreeMap::new();
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::with_root(fast_xml::Writer::new(&mut buffer), Some("root"));
+        data.serialize(&mut ser).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "<root/>");
+        assert_eq!(
+            from_str::<BTreeMap<String, String>>("<root/>").unwrap(),
+            data
+        );
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::with_root(fast_xml::Writer::new(&mut buffer), Some("root"));
+        ser.write_empty_collections(true);
+        data.serialize(&mut ser).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "<root/>");
+    }
+}
+
+/// Sequences of sequences are serialized as repeating groups of repeating items
+mod nested_seq {
+    use super::*;
+    use fast_xml::se::Serializer;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Matrix {
+        row: Vec<Vec<u32>>,
+    }
+
+    #[test]
+    fn field() {
+        let data = Matrix {
+            row: vec![vec![1, 2], vec![], vec![3]],
+        };
+
+        let xml = to_string(&data).unwrap();
+        assert_eq!(
+            xml,
+            "<Matrix>\
+                <row><item>1</item><item>2</item></row>\
+                <row/>\
+                <row><item>3</item></row>\
+            </Matrix>"
+        );
+        assert_eq!(from_str::<Matrix>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn root() {
+        let data: Vec<Vec<u32>> = vec![vec![1, 2], vec![3]];
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.nested_seq_tags(Some("group"), "value");
+        data.serialize(&mut ser).unwrap();
+
+        let xml = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            xml,
+            "<group><value>1</value><value>2</value></group>\
+             <group><value>3</value></group>"
+        );
+        assert_eq!(from_str::<Vec<Vec<u32>>>(&xml).unwrap(), data);
+    }
+
+    /// Without a group name nested sequences cannot be written
+    #[test]
+    fn no_group_name() {
+        let data: Vec<Vec<u32>> = vec![vec![1]];
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        assert!(data.serialize(&mut ser).is_err());
+    }
+}
+
+/// Unit structs are written as empty elements, so they can be used as
+/// presence markers
+mod unit_struct {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Marker;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum Level {
+        Low,
+        High,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Flags {
+        level: Level,
+        #[serde(default)]
+        required: Option<Marker>,
+        #[serde(default)]
+        deprecated: Option<Marker>,
+        #[serde(rename = "$value")]
+        status: Level,
+    }
+
+    #[test]
+    fn top_level() {
+        let xml = to_string(&Marker).unwrap();
+        assert_eq!(xml, "<Marker/>");
+        assert_eq!(from_str::<Marker>(&xml).unwrap(), Marker);
+    }
+
+    #[test]
+    fn in_struct() {
+        let data = Flags {
+            level: Level::High,
+            required: Some(Marker),
+            deprecated: None,
+            status: Level::Low,
+        };
+
+        // Unit variants are written as texts of elements in ordinary fields and
+        // as empty elements in the `$value` field
+        let xml = to_string(&data).unwrap();
+        assert_eq!(xml, "<Flags><level>High</level><required/><Low/></Flags>");
+        assert_eq!(from_str::<Flags>(&xml).unwrap(), data);
+    }
+}
+
+/// Special characters in strings are escaped according to the `EscapeMode`
+mod escape {
+    use super::*;
+    use fast_xml::se::{EscapeMode, Serializer};
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Node {
+        attr: String,
+        sign: char,
+        element: Inner,
+        #[serde(rename = "$value")]
+        text: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Inner {
+        #[serde(rename = "$value")]
+        text: String,
+    }
+
+    fn node() -> Node {
+        Node {
+            attr: r#"<"a" & 'b'>"#.into(),
+            sign: '"',
+            element: Inner {
+                text: r#"<"c" & 'd'>"#.into(),
+            },
+            text: r#"<"e" & 'f'>"#.into(),
+        }
+    }
+
+    fn serialize(data: &Node, mode: EscapeMode) -> String {
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.escape(mode);
+        data.serialize(&mut ser).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn full() {
+        let data = node();
+
+        let xml = serialize(&data, EscapeMode::Full);
+        assert_eq!(
+            xml,
+            "<Node attr=\"&lt;&quot;a&quot; &amp; &apos;b&apos;&gt;\" sign=\"&quot;\">\
+                <element>&lt;&quot;c&quot; &amp; &apos;d&apos;&gt;</element>\
+                &lt;&quot;e&quot; &amp; &apos;f&apos;&gt;\
+            </Node>"
+        );
+        assert_eq!(from_str::<Node>(&xml).unwrap(), data);
+        // Full escaping is the default
+        assert_eq!(to_string(&data).unwrap(), xml);
+    }
+
+    #[test]
+    fn minimal() {
+        let data = node();
+
+        let xml = serialize(&data, EscapeMode::Minimal);
+        assert_eq!(
+            xml,
+            "<Node attr=\"&lt;&quot;a&quot; &amp; 'b'&gt;\" sign=\"&quot;\">\
+                <element>&lt;\"c\" &amp; 'd'&gt;</element>\
+                &lt;\"e\" &amp; 'f'&gt;\
+            </Node>"
+        );
+        assert_eq!(from_str::<Node>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn numeric() {
+        let data = node();
+
+        let xml = serialize(&data, EscapeMode::Numeric);
+        assert_eq!(
+            xml,
+            "<Node attr=\"&lt;&#34;a&#34; &amp; &#39;b&#39;&gt;\" sign=\"&#34;\">\
+                <element>&lt;&#34;c&#34; &amp; &#39;d&#39;&gt;</element>\
+                &lt;&#34;e&#34; &amp; &#39;f&#39;&gt;\
+            </Node>"
+        );
+        assert_eq!(from_str::<Node>(&xml).unwrap(), data);
+    }
+
+    /// Keys of maps are written as attribute values of `<enum>` elements and
+    /// always have quotes escaped
+    #[test]
+    fn minimal_map_key() {
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::with_root(fast_xml::Writer::new(&mut buffer), Some("root"));
+        ser.escape(EscapeMode::Minimal);
+        {
+            use serde::ser::{SerializeMap, Serializer as _};
+
+            let mut map = (&mut ser).serialize_map(None).unwrap();
+            map.serialize_key(r#""key""#).unwrap();
+            map.serialize_value(r#""value""#).unwrap();
+            map.end().unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"<root><enum key="&quot;key&quot;"/>"value"</root>"#
+        );
+    }
+}
+
+/// Enums are serialized in the form, that deserializer expects for each
+/// representation
+mod enum_ {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fmt::Debug;
+
+    /// Serializes `value`, checks the result against `xml` and deserializes it back
+    fn roundtrip<T>(value: T, xml: &str)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+    {
+        let serialized = to_string(&value).unwrap();
+        assert_eq!(serialized, xml);
+
+        let deserialized: T = from_str(&serialized).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Nested {
+        float: f64,
+    }
+
+    mod externally_tagged {
+        use super::*;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        enum Node {
+            Unit,
+            Newtype(bool),
+            Tuple(f64, String),
+            Struct { float: f64, string: String },
+            Holder { nested: Nested, string: String },
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrapper {
+            node: Node,
+        }
+
+        #[test]
+        fn unit() {
+            roundtrip(Node::Unit, "<Unit/>");
+        }
+
+        /// Unit variant in a field is written as a text of the field element
+        #[test]
+        fn unit_in_field() {
+            roundtrip(
+                Wrapper { node: Node::Unit },
+                "<Wrapper><node>Unit</node></Wrapper>",
+            );
+        }
+
+        #[test]
+        fn newtype() {
+            roundtrip(Node::Newtype(true), "<Newtype>true</Newtype>");
+        }
+
+        #[test]
+        fn tuple_struct() {
+            roundtrip(
+                Node::Tuple(42.0, "answer".into()),
+                "<Tuple>42</Tuple><Tuple>answer</Tuple>",
+            );
+        }
+
+        #[test]
+        fn struct_() {
+            roundtrip(
+                Node::Struct {
+                    float: 42.0,
+                    string: "answer".into(),
+                },
+                r#"<Struct float="42" string="answer"/>"#,
+            );
+        }
+
+        #[test]
+        fn nested_struct() {
+            roundtrip(
+                Node::Holder {
+                    nested: Nested { float: 42.0 },
+                    string: "answer".into(),
+                },
+                r#"<Holder string="answer"><nested float="42"/></Holder>"#,
+            );
+        }
+
+        /// Variants with data in a `$value` field are written as elements with
+        /// the variant names
+        mod value {
+            use super::*;
+
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
+            enum Content {
+                Unit,
+                Text(String),
+                Struct {
+                    nested: Nested,
+                    #[serde(default)]
+                    list: Vec<Nested>,
+                },
+            }
+
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
+            struct Holder {
+                #[serde(rename = "$value")]
+                content: Content,
+            }
+
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
+            struct List {
+                #[serde(rename = "$value")]
+                content: Vec<Content>,
+            }
+
+            #[test]
+            fn unit() {
+                roundtrip(
+                    Holder {
+                        content: Content::Unit,
+                    },
+                    "<Holder><Unit/></Holder>",
+                );
+            }
+
+            /// Content of a newtype variant is a text of the element
+            #[test]
+            fn newtype() {
+                roundtrip(
+                    Content::Text("hi <&> there".into()),
+                    "<Text>hi &lt;&amp;&gt; there</Text>",
+                );
+                roundtrip(
+                    Holder {
+                        content: Content::Text("hi".into()),
+                    },
+                    "<Holder><Text>hi</Text></Holder>",
+                );
+            }
+
+            /// Fields of a struct variant are children of the element
+            #[test]
+            fn struct_() {
+                roundtrip(
+                    Holder {
+                        content: Content::Struct {
+                            nested: Nested { float: 1.0 },
+                            list: vec![Nested { float: 2.0 }, Nested { float: 3.0 }],
+                        },
+                    },
+                    r#"<Holder><Struct><nested float="1"/><list float="2"/><list float="3"/></Struct></Holder>"#,
+                );
+            }
+
+            #[test]
+            fn sequence() {
+                roundtrip(
+                    List {
+                        content: vec![
+                            Content::Unit,
+                            Content::Text("hi".into()),
+                            Content::Struct {
+                                nested: Nested { float: 1.0 },
+                                list: vec![],
+                            },
+                        ],
+                    },
+                    r#"<List><Unit/><Text>hi</Text><Struct><nested float="1"/></Struct></List>"#,
+                );
+            }
+        }
+    }
+
+    /// Only variants with string fields are checked, because serde buffers
+    /// content of internally tagged enums and cannot parse numbers from strings
+    mod internally_tagged {
+        use super::*;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Content {
+            string: String,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        #[serde(tag = "tag")]
+        enum Node {
+            Unit,
+            Newtype(Content),
+            Struct { string: String },
+        }
+
+        #[test]
+        fn unit() {
+            roundtrip(Node::Unit, r#"<Node tag="Unit"/>"#);
+        }
+
+        #[test]
+        fn newtype() {
+            roundtrip(
+                Node::Newtype(Content {
+                    string: "answer".into(),
+                }),
+                r#"<Content tag="Newtype" string="answer"/>"#,
+            );
+        }
+
+        #[test]
+        fn struct_() {
+            roundtrip(
+                Node::Struct {
+                    string: "answer".into(),
+                },
+                r#"<Node tag="Struct" string="answer"/>"#,
+            );
+        }
+    }
+
+    mod adjacently_tagged {
+        use super::*;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        #[serde(tag = "tag", content = "content")]
+        enum Node {
+            Unit,
+            Newtype(String),
+            Tuple(f64, String),
+            Struct { float: f64, string: String },
+            Holder { nested: Nested, string: String },
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrapper {
+            node: Node,
+        }
+
+        #[test]
+        fn unit() {
+            roundtrip(Node::Unit, "<Node><tag>Unit</tag></Node>");
+        }
+
+        #[test]
+        fn unit_in_field() {
+            roundtrip(
+                Wrapper { node: Node::Unit },
+                "<Wrapper><node><tag>Unit</tag></node></Wrapper>",
+            );
+        }
+
+        /// Primitive content is written as an attribute, which precedes the tag,
+        /// so serde buffers it and only strings can be read back
+        #[test]
+        fn newtype() {
+            roundtrip(
+                Node::Newtype("answer".into()),
+                r#"<Node content="answer"><tag>Newtype</tag></Node>"#,
+            );
+        }
+
+        #[test]
+        fn tuple_struct() {
+            roundtrip(
+                Node::Tuple(42.0, "answer".into()),
+                r#"<Node><tag>Tuple</tag><content>42</content><content>answer</content></Node>"#,
+            );
+        }
+
+        #[test]
+        fn struct_() {
+            roundtrip(
+                Node::Struct {
+                    float: 42.0,
+                    string: "answer".into(),
+                },
+                r#"<Node><tag>Struct</tag><content float="42" string="answer"/></Node>"#,
+            );
+        }
+
+        #[test]
+        fn nested_struct() {
+            roundtrip(
+                Node::Holder {
+                    nested: Nested { float: 42.0 },
+                    string: "answer".into(),
+                },
+                r#"<Node><tag>Holder</tag><content string="answer"><nested float="42"/></content></Node>"#,
+            );
+        }
+    }
+
+    /// Only variants with string fields are checked, because serde buffers
+    /// content of untagged enums and cannot parse numbers from strings
+    mod untagged {
+        use super::*;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        #[serde(untagged)]
+        enum Node {
+            Struct { string: String },
+            Holder { nested: Inner, other: String },
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Inner {
+            value: String,
+        }
+
+        #[test]
+        fn struct_() {
+            roundtrip(
+                Node::Struct {
+                    string: "answer".into(),
+                },
+                r#"<Node string="answer"/>"#,
+            );
+        }
+
+        #[test]
+        fn nested_struct() {
+            roundtrip(
+                Node::Holder {
+                    nested: Inner { value: "42".into() },
+                    other: "answer".into(),
+                },
+                r#"<Node other="answer"><nested value="42"/></Node>"#,
+            );
+        }
+    }
+}
+
+/// Attributes are written sorted by name when `Serializer::sort_attributes` is enabled
+mod sort_attributes {
+    use super::*;
+    use fast_xml::se::Serializer;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Node {
+        zeta: u32,
+        #[serde(rename = "xmlns:b")]
+        ns_b: String,
+        alpha: String,
+        child: Child,
+        #[serde(rename = "xmlns")]
+        ns: String,
+        mu: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Child {
+        y: u32,
+        x: u32,
+    }
+
+    fn node() -> Node {
+        Node {
+            zeta: 1,
+            ns_b: "urn:b".into(),
+            alpha: "a".into(),
+            child: Child { y: 2, x: 3 },
+            ns: "urn:default".into(),
+            mu: true,
+        }
+    }
+
+    fn serialize(data: &Node, sort: bool) -> String {
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.sort_attributes(sort);
+        data.serialize(&mut ser).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn unsorted() {
+        assert_eq!(
+            serialize(&node(), false),
+            r#"<Node zeta="1" xmlns:b="urn:b" alpha="a" xmlns="urn:default" mu="true"><child y="2" x="3"/></Node>"#
+        );
+    }
+
+    #[test]
+    fn sorted() {
+        let data = node();
+
+        let xml = serialize(&data, true);
+        assert_eq!(
+            xml,
+            r#"<Node xmlns="urn:default" xmlns:b="urn:b" alpha="a" mu="true" zeta="1"><child x="3" y="2"/></Node>"#
+        );
+        assert_eq!(from_str::<Node>(&xml).unwrap(), data);
+    }
+}
+
+mod scalars_as_attributes {
+    use super::*;
+    use fast_xml::se::Serializer;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum Kind {
+        Novel,
+        Poem,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Author {
+        name: String,
+        born: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Book {
+        #[serde(rename = "@id")]
+        id: u32,
+        title: String,
+        kind: Kind,
+        available: bool,
+        price: Option<f64>,
+        tag: Vec<String>,
+        author: Author,
+    }
+
+    fn book() -> Book {
+        Book {
+            id: 42,
+            title: "Dune & Co".into(),
+            kind: Kind::Novel,
+            available: true,
+            price: Some(9.5),
+            tag: vec!["sf".into(), "classic".into()],
+            author: Author {
+                name: "Frank Herbert".into(),
+                born: 1920,
+            },
+        }
+    }
+
+    fn serialize(data: &Book, attributes: bool) -> String {
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.scalars_as_attributes(attributes);
+        data.serialize(&mut ser).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn attributes() {
+        let data = book();
+
+        let xml = serialize(&data, true);
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<Book id="42" title="Dune &amp; Co" available="true" price="9.5">"#,
+                r#"<kind>Novel</kind><tag>sf</tag><tag>classic</tag>"#,
+                r#"<author name="Frank Herbert" born="1920"/>"#,
+                r#"</Book>"#,
+            )
+        );
+        assert_eq!(from_str::<Book>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn elements() {
+        let data = book();
+
+        let xml = serialize(&data, false);
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<Book id="42">"#,
+                r#"<title>Dune &amp; Co</title>"#,
+                r#"<kind>Novel</kind>"#,
+                r#"<available>true</available>"#,
+                r#"<price>9.5</price>"#,
+                r#"<tag>sf</tag><tag>classic</tag>"#,
+                r#"<author><name>Frank Herbert</name><born>1920</born></author>"#,
+                r#"</Book>"#,
+            )
+        );
+        assert_eq!(from_str::<Book>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn none() {
+        let data = Book {
+            price: None,
+            ..book()
+        };
+
+        let xml = serialize(&data, false);
+        assert!(!xml.contains("price"));
+        assert_eq!(from_str::<Book>(&xml).unwrap(), data);
+    }
+}
+
+/// Types with custom `Serialize` implementations, that produce strings, can be
+/// used as attribute values
+mod custom_attribute {
+    use super::*;
+    use fast_xml::se::Serializer;
+    use pretty_assertions::assert_eq;
+    use serde::Deserializer;
+
+    /// A date with a time zone offset in hours, serialized in RFC 3339 format
+    #[derive(Debug, PartialEq)]
+    struct Date {
+        year: u16,
+        month: u8,
+        day: u8,
+        offset: i8,
+    }
+
+    impl Serialize for Date {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(&format_args!(
+                "{:04}-{:02}-{:02}T00:00:00{:+03}:00",
+                self.year, self.month, self.day, self.offset
+            ))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Date {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let s = String::deserialize(deserializer)?;
+            let field = |range: std::ops::Range<usize>| {
+                s.get(range)
+                    .and_then(|f| f.parse().ok())
+                    .ok_or_else(|| serde::de::Error::custom(format!("invalid date `{}`", s)))
+            };
+            Ok(Date {
+                year: field(0..4)?,
+                month: field(5..7)? as u8,
+                day: field(8..10)? as u8,
+                offset: field(19..22)? as i8,
+            })
+        }
+    }
+
+    /// A newtype wrapper, which is serialized as its content
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Note(String);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(rename = "@date")]
+        date: Date,
+        #[serde(rename = "@note")]
+        note: Note,
+        name: String,
+    }
+
+    fn event() -> Event {
+        Event {
+            date: Date {
+                year: 2022,
+                month: 5,
+                day: 1,
+                offset: 2,
+            },
+            note: Note(r#"say "hi" & <leave>"#.into()),
+            name: "meeting".into(),
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        let data = event();
+
+        let xml = to_string(&data).unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<Event date="2022-05-01T00:00:00+02:00" "#,
+                r#"note="say &quot;hi&quot; &amp; &lt;leave&gt;" name="meeting"/>"#,
+            )
+        );
+        assert_eq!(from_str::<Event>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn elements() {
+        let data = event();
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.scalars_as_attributes(false);
+        data.serialize(&mut ser).unwrap();
+        let xml = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<Event date="2022-05-01T00:00:00+02:00" "#,
+                r#"note="say &quot;hi&quot; &amp; &lt;leave&gt;">"#,
+                r#"<name>meeting</name>"#,
+                r#"</Event>"#,
+            )
+        );
+        assert_eq!(from_str::<Event>(&xml).unwrap(), data);
+    }
+}
+
+/// Attributes of flattened structs are merged into attributes of the parent element
+mod flatten_attributes {
+    use super::*;
+    use fast_xml::{se::Serializer, Writer};
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    /// Serde requests structs with flattened fields as maps, so names of their
+    /// fields are unknown to the deserializer and attributes are matched to them
+    /// by plain names. That is why fields have aliases without the `@` prefix.
+    /// Numbers cannot be used because of https://github.com/serde-rs/serde/issues/1183
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Root {
+        name: String,
+        #[serde(flatten)]
+        nested: Nested,
+        item: Vec<String>,
+        #[serde(rename = "@id", alias = "id")]
+        id: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Nested {
+        #[serde(rename = "@x", alias = "x")]
+        x: String,
+        #[serde(rename = "@y", alias = "y")]
+        y: String,
+    }
+
+    fn serialize(data: &Root, sort_attributes: bool) -> String {
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+        ser.sort_attributes(sort_attributes);
+        ser.map_attributes(true);
+        data.serialize(&mut ser).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn root() -> Root {
+        Root {
+            name: "answer".into(),
+            nested: Nested {
+                x: "42".into(),
+                y: r#"say "hi""#.into(),
+            },
+            item: vec!["first".into(), "second".into()],
+            id: "1".into(),
+        }
+    }
+
+    #[test]
+    fn attributes_before_children() {
+        let data = root();
+
+        let xml = serialize(&data, false);
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<root x="42" y="say &quot;hi&quot;" id="1">"#,
+                r#"<name>answer</name>"#,
+                r#"<item>first</item>"#,
+                r#"<item>second</item>"#,
+                r#"</root>"#,
+            )
+        );
+        assert_eq!(from_str::<Root>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn sorted() {
+        let data = root();
+
+        let xml = serialize(&data, true);
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<root id="1" x="42" y="say &quot;hi&quot;">"#,
+                r#"<name>answer</name>"#,
+                r#"<item>first</item>"#,
+                r#"<item>second</item>"#,
+                r#"</root>"#,
+            )
+        );
+        assert_eq!(from_str::<Root>(&xml).unwrap(), data);
+    }
+
+    /// Attributes cannot be written when the name of the element is unknown
+    #[test]
+    fn without_root() {
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::new(&mut buffer);
+        ser.map_attributes(true);
+        assert!(root().serialize(&mut ser).is_err());
+    }
+
+    /// Keys of ordinary maps are written as element names, unless requested otherwise
+    #[test]
+    fn map_keys() {
+        let mut map = BTreeMap::new();
+        map.insert("@key", "value");
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+        map.serialize(&mut ser).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "<root><@key>value</@key></root>"
+        );
+
+        let mut buffer = Vec::new();
+        let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+        ser.map_attributes(true);
+        map.serialize(&mut ser).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), r#"<root key="value"/>"#);
+    }
+}
+
+/// Recursive structures are serialized to nested elements with the same name
+/// and deserialized back to the same tree
+mod recursive {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "node")]
+    struct Node {
+        #[serde(rename = "@name")]
+        name: String,
+        #[serde(rename = "node", default)]
+        children: Vec<Node>,
+    }
+
+    fn node(name: &str, children: Vec<Node>) -> Node {
+        Node {
+            name: name.into(),
+            children,
+        }
+    }
+
+    #[test]
+    fn struct_() {
+        let data = node(
+            "a",
+            vec![
+                node("b", vec![node("c", vec![node("d", vec![])])]),
+                node("e", vec![]),
+            ],
+        );
+
+        let xml = to_string(&data).unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<node name="a">"#,
+                r#"<node name="b"><node name="c"><node name="d"/></node></node>"#,
+                r#"<node name="e"/>"#,
+                r#"</node>"#,
+            )
+        );
+        assert_eq!(from_str::<Node>(&xml).unwrap(), data);
+    }
+
+    #[test]
+    fn value_enum() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename = "tree")]
+        struct Tree {
+            #[serde(rename = "$value", default)]
+            children: Vec<Item>,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Item {
+            #[serde(rename = "node")]
+            Node {
+                #[serde(rename = "$value", default)]
+                children: Vec<Item>,
+            },
+            #[serde(rename = "leaf")]
+            Leaf(String),
+        }
+
+        let data = Tree {
+            children: vec![
+                Item::Leaf("1".into()),
+                Item::Node {
+                    children: vec![
+                        Item::Node {
+                            children: vec![Item::Leaf("2".into())],
+                        },
+                        Item::Node { children: vec![] },
+                    ],
+                },
+                Item::Leaf("3".into()),
+            ],
+        };
+
+        let xml = to_string(&data).unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<tree>"#,
+                r#"<leaf>1</leaf>"#,
+                r#"<node><node><leaf>2</leaf></node><node/></node>"#,
+                r#"<leaf>3</leaf>"#,
+                r#"</tree>"#,
+            )
+        );
+        assert_eq!(from_str::<Tree>(&xml).unwrap(), data);
+    }
+}
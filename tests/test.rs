@@ -75,6 +75,72 @@ fn test_attribute_equal() {
     }
 }
 
+/// Checks that all attributes of an attribute-dense document, used in benchmarks,
+/// are parsed with and without duplicate checks, and values with quotes of
+/// another kind are not truncated
+#[test]
+fn test_attributes_dense() {
+    let src = include_bytes!("documents/attributes_dense.svg");
+    let mut r = Reader::from_reader(src as &[u8]);
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    let mut count = 0;
+    loop {
+        match r.read_event(&mut buf) {
+            Ok(Start(e)) | Ok(Empty(e)) => {
+                let checked: Vec<_> = e.attributes().map(Result::unwrap).collect();
+                let unchecked: Vec<_> = e
+                    .attributes()
+                    .with_checks(false)
+                    .map(Result::unwrap)
+                    .collect();
+                assert_eq!(checked, unchecked);
+                for attr in &checked {
+                    assert!(!attr.value.contains(&b'"'));
+                }
+                count += checked.len();
+            }
+            Ok(Eof) => break,
+            Ok(_) => (),
+            Err(e) => panic!("{:?} at {}", e, r.buffer_position()),
+        }
+        buf.clear();
+    }
+    assert_eq!(count, 3322);
+
+    let src = br#"<a first='"quoted"' second="'quoted'" empty=''/>"#;
+    let mut r = Reader::from_reader(src as &[u8]);
+    r.trim_text(true);
+    match r.read_event(&mut buf) {
+        Ok(Empty(e)) => {
+            let mut attrs = e.attributes();
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"first",
+                    value: Cow::Borrowed(br#""quoted""#),
+                }))
+            );
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"second",
+                    value: Cow::Borrowed(b"'quoted'"),
+                }))
+            );
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"empty",
+                    value: Cow::Borrowed(b""),
+                }))
+            );
+            assert_eq!(attrs.next(), None);
+        }
+        e => panic!("Expecting Empty event, got {:?}", e),
+    }
+}
+
 #[test]
 fn test_comment_starting_with_gt() {
     let src = b"<a /><!-->-->";
@@ -1008,3 +1074,27 @@ fn test_issue305_unflatten_nesting() -> Result<(), fast_xml::DeError> {
 
     Ok(())
 }
+
+#[test]
+fn test_event_spans() {
+    let src: &[u8] = include_bytes!("sample_rss.xml");
+    let mut buf = Vec::new();
+    let mut r = Reader::from_reader(src);
+    r.trim_text(true)
+        .expand_empty_elements(true)
+        .with_event_spans(true);
+
+    let mut restored = Vec::with_capacity(src.len());
+    let mut last = 0;
+    loop {
+        let (event, span) = r.read_event_with_span(&mut buf).unwrap();
+        assert_eq!(span.start, last, "gap or overlap before {:?}", event);
+        last = span.end;
+        restored.extend_from_slice(&src[span]);
+        if let Eof = event {
+            break;
+        }
+        buf.clear();
+    }
+    assert_eq!(restored, src);
+}
@@ -3,8 +3,8 @@ use std::io::Cursor;
 use std::str::from_utf8;
 
 use fast_xml::events::attributes::{AttrError, Attribute};
-use fast_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
-use fast_xml::{events::Event::*, Reader, Result, Writer};
+use fast_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesRef, BytesStart, BytesText, Event};
+use fast_xml::{events::Event::*, PushReader, Reader, Result, Writer};
 
 use pretty_assertions::assert_eq;
 
@@ -294,6 +294,82 @@ fn test_writer_indent_cdata() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_qname_comparison() -> Result<()> {
+    let mut reader = Reader::from_str("<root><item/><x:item/><other>text</other></root>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut items = 0;
+    let mut prefixed = 0;
+    loop {
+        match reader.read_event(&mut buf)? {
+            Start(e) | Empty(e) => {
+                let name = e.qname();
+                if name == "item" {
+                    items += 1;
+                    assert!(name == b"item");
+                    let item: &[u8] = b"item";
+                    assert!(name == item);
+                    assert!("item" == name);
+                    assert!(b"item" == name);
+                } else if name.local_name() == "item" {
+                    prefixed += 1;
+                    // The prefix is included into the qualified name
+                    assert!(name != "item");
+                    assert!(name == "x:item");
+                    assert_eq!(name.prefix(), Some(&b"x"[..]));
+                } else {
+                    assert!(name == "root" || name == "other", "{:?}", name);
+                }
+            }
+            End(e) => assert!(e.qname() == "root" || e.qname() == b"other"),
+            Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    assert_eq!(items, 1);
+    assert_eq!(prefixed, 1);
+    Ok(())
+}
+
+#[test]
+fn test_writer_raw() -> Result<()> {
+    let mut writer = Writer::new(Vec::new());
+    writer.write_event(Start(BytesStart::borrowed_name(b"root")))?;
+    writer
+        .create_element("before")
+        .write_text_content(BytesText::from_plain_str("a < b"))?;
+    writer.write_raw(r#"<cached id="1">x &amp; y<inner/></cached>"#)?;
+    writer.create_element("after").write_empty()?;
+    writer.write_event(End(BytesEnd::borrowed(b"root")))?;
+
+    let result = writer.into_inner();
+    assert_eq!(
+        from_utf8(&result).unwrap(),
+        r#"<root><before>a &lt; b</before><cached id="1">x &amp; y<inner/></cached><after/></root>"#
+    );
+
+    // The whole result, including the raw fragment, is a well-formed document
+    let mut reader = Reader::from_reader(&result[..]);
+    let mut buf = Vec::new();
+    let mut names = Vec::new();
+    loop {
+        match reader.read_event(&mut buf)? {
+            Start(e) | Empty(e) => names.push(from_utf8(e.name()).unwrap().to_owned()),
+            Text(e) if e.unescaped()?.as_ref() == b"x & y" => names.push("x & y".to_owned()),
+            Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    assert_eq!(
+        names,
+        vec!["root", "before", "cached", "x & y", "inner", "after"]
+    );
+    Ok(())
+}
+
 #[test]
 fn test_write_empty_element_attrs() -> Result<()> {
     let str_from = r#"<source attr="val"/>"#;
@@ -314,6 +390,338 @@ fn test_write_empty_element_attrs() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_write_ns_nested_scopes() -> Result<()> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    writer
+        .bind_namespace("", "urn:default")
+        .bind_namespace("a", "urn:a")
+        .bind_namespace("xlink", "urn:xlink&link");
+
+    writer
+        .create_ns_element(("", "root"))
+        .write_inner_content(|writer| {
+            // Prefix is declared on the first element that uses it
+            writer
+                .create_ns_element(("a", "outer"))
+                .with_ns_attribute(("a", "attr"), "1")
+                .write_inner_content(|writer| {
+                    // Already in scope, not redeclared
+                    writer.create_ns_element(("a", "inner")).write_empty()?;
+                    // Unprefixed attributes do not declare the default namespace
+                    writer
+                        .create_ns_element(("", "inner"))
+                        .with_ns_attribute(("", "plain"), "2")
+                        .with_ns_attribute(("xlink", "href"), "#id")
+                        .write_empty()?;
+                    Ok(())
+                })?;
+            // Out of the scope of `outer`, so declared again
+            writer.create_ns_element(("a", "sibling")).write_empty()?;
+            // Unbound prefixes are written as is
+            writer.create_ns_element(("unknown", "tag")).write_empty()?;
+            Ok(())
+        })?;
+
+    let result = writer.into_inner().into_inner();
+    assert_eq!(
+        String::from_utf8(result).unwrap(),
+        concat!(
+            r#"<root xmlns="urn:default">"#,
+            r#"<a:outer xmlns:a="urn:a" a:attr="1">"#,
+            r#"<a:inner/>"#,
+            r##"<inner plain="2" xmlns:xlink="urn:xlink&amp;link" xlink:href="#id"/>"##,
+            r#"</a:outer>"#,
+            r#"<a:sibling xmlns:a="urn:a"/>"#,
+            r#"<unknown:tag/>"#,
+            r#"</root>"#,
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_write_ns_rebind() -> Result<()> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    writer.bind_namespace("p", "urn:first");
+
+    writer
+        .create_ns_element(("p", "root"))
+        .write_inner_content(|writer| {
+            writer.create_ns_element(("p", "same")).write_empty()?;
+            Ok(())
+        })?;
+    writer.bind_namespace("p", "urn:second");
+    // Declarations written by hand are in scope too
+    writer.write_event(Start(BytesStart::borrowed(
+        br#"root xmlns:p="urn:second""#,
+        4,
+    )))?;
+    writer.create_ns_element(("p", "child")).write_empty()?;
+    writer.write_event(End(BytesEnd::borrowed(b"root")))?;
+
+    let result = writer.into_inner().into_inner();
+    assert_eq!(
+        String::from_utf8(result).unwrap(),
+        concat!(
+            r#"<p:root xmlns:p="urn:first"><p:same/></p:root>"#,
+            r#"<root xmlns:p="urn:second"><p:child/></root>"#,
+        )
+    );
+    Ok(())
+}
+
+/// A writer that fails after the specified number of bytes was written
+struct FailingWriter {
+    written: Vec<u8>,
+    limit: usize,
+}
+
+fn disk_full() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::WriteZero, "disk full")
+}
+
+impl std::io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let available = self.limit - self.written.len();
+        if available == 0 {
+            return Err(disk_full());
+        }
+        let len = buf.len().min(available);
+        self.written.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.written.len() < self.limit {
+            Ok(())
+        } else {
+            Err(disk_full())
+        }
+    }
+}
+
+#[test]
+fn test_write_error_context() {
+    let mut writer = Writer::new(FailingWriter {
+        written: Vec::new(),
+        limit: 20,
+    });
+    writer
+        .write_event(Event::Start(BytesStart::borrowed_name(b"root")))
+        .unwrap();
+    writer
+        .write_event(Event::Text(BytesText::from_plain_str("content")))
+        .unwrap();
+    match writer.write_event(Event::Start(BytesStart::borrowed_name(b"element"))) {
+        Err(fast_xml::Error::WriteEvent {
+            event: "Start",
+            name: Some(name),
+            source,
+        }) => {
+            assert_eq!(name, "element");
+            assert_eq!(source.to_string(), "disk full");
+        }
+        x => panic!("Expected `Err(WriteEvent)`, but got `{:?}`", x),
+    }
+    match writer.write_event(Event::Text(BytesText::from_plain_str("text"))) {
+        Err(e) => assert_eq!(
+            e.to_string(),
+            "I/O error while writing `Text` event: disk full"
+        ),
+        x => panic!("Expected `Err(WriteEvent)`, but got `{:?}`", x),
+    }
+    match writer.write_event(Event::End(BytesEnd::borrowed(b"element"))) {
+        Err(e) => assert_eq!(
+            e.to_string(),
+            "I/O error while writing `End` event of element <element>: disk full"
+        ),
+        x => panic!("Expected `Err(WriteEvent)`, but got `{:?}`", x),
+    }
+    assert_eq!(writer.inner().written, b"<root>content<elemen");
+}
+
+#[test]
+fn test_write_check_output() {
+    let events = || {
+        vec![
+            Event::Start(
+                BytesStart::borrowed_name(b"root").with_attributes(vec![("attr", "a\0b")]),
+            ),
+            Event::Text(BytesText::from_plain_str("a\tb\r\nc\0")),
+            Event::CData(BytesCData::from_str("\u{FFFF}")),
+            Event::End(BytesEnd::borrowed(b"root")),
+        ]
+    };
+
+    // Invalid characters are written as is by default
+    let mut writer = Writer::new(Vec::new());
+    for event in events() {
+        writer.write_event(event).unwrap();
+    }
+    assert_eq!(
+        writer.into_inner(),
+        "<root attr=\"a\0b\">a\tb\r\nc\0<![CDATA[\u{FFFF}]]></root>".as_bytes()
+    );
+
+    let mut writer = Writer::new(Vec::new());
+    writer.check_output(true);
+    let errors: Vec<_> = events()
+        .into_iter()
+        .map(|event| match writer.write_event(event) {
+            Err(fast_xml::Error::InvalidCharacter { ch, offset }) => Some((ch, offset)),
+            Err(e) => panic!("Expected `Err(InvalidCharacter)`, but got `Err({:?})`", e),
+            Ok(()) => None,
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            Some(('\0', 12)),
+            Some(('\0', 6)),
+            Some(('\u{FFFF}', 0)),
+            None
+        ]
+    );
+    // Valid control characters are allowed
+    writer
+        .write_event(Event::Text(BytesText::from_plain_str("a\tb\r\nc")))
+        .unwrap();
+    assert_eq!(writer.into_inner(), b"</root>a\tb\r\nc");
+
+    let error = fast_xml::Error::InvalidCharacter {
+        ch: '\0',
+        offset: 7,
+    };
+    assert_eq!(
+        error.to_string(),
+        "Invalid character '\\0' (U+0000) at position 7"
+    );
+}
+
+#[test]
+fn test_write_check_nesting_mismatch() {
+    let mut writer = Writer::new(Vec::new());
+    writer.check_nesting(true);
+    writer
+        .write_event(Event::Start(BytesStart::borrowed_name(b"root")))
+        .unwrap();
+    writer
+        .write_event(Event::Start(BytesStart::borrowed_name(b"a")))
+        .unwrap();
+    match writer.write_event(Event::End(BytesEnd::borrowed(b"b"))) {
+        Err(fast_xml::Error::EndEventMismatch { expected, found }) => {
+            assert_eq!(expected, "a");
+            assert_eq!(found, "b");
+        }
+        x => panic!("Expected `Err(EndEventMismatch)`, but got `{:?}`", x),
+    }
+    // Mismatched end tag is not written and does not change the nesting
+    writer
+        .write_event(Event::End(BytesEnd::borrowed(b"a")))
+        .unwrap();
+    writer
+        .write_event(Event::End(BytesEnd::borrowed(b"root")))
+        .unwrap();
+    writer.finish().unwrap();
+    assert_eq!(writer.into_inner(), b"<root><a></a></root>");
+}
+
+#[test]
+fn test_write_check_nesting_unbalanced_end() {
+    let mut writer = Writer::new(Vec::new());
+    writer.check_nesting(true);
+    writer
+        .write_event(Event::Empty(BytesStart::borrowed_name(b"root")))
+        .unwrap();
+    match writer.write_event(Event::End(BytesEnd::borrowed(b"root"))) {
+        Err(e) => assert_eq!(e.to_string(), "Expecting </> found </root>"),
+        x => panic!("Expected `Err(EndEventMismatch)`, but got `{:?}`", x),
+    }
+    assert_eq!(writer.into_inner(), b"<root/>");
+}
+
+#[test]
+fn test_write_check_nesting_unclosed() {
+    let mut writer = Writer::new(Vec::new());
+    writer.check_nesting(true);
+    writer
+        .create_element("root")
+        .write_inner_content(|writer| {
+            writer
+                .create_element("item")
+                .write_text_content(BytesText::from_plain_str("text"))?;
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"unclosed")))?;
+            writer.finish()
+        })
+        .map(|_| ())
+        .unwrap_err();
+
+    match writer.finish() {
+        Err(fast_xml::Error::UnclosedElement { name }) => assert_eq!(name, "unclosed"),
+        x => panic!("Expected `Err(UnclosedElement)`, but got `{:?}`", x),
+    }
+    writer
+        .write_event(Event::End(BytesEnd::borrowed(b"unclosed")))
+        .unwrap();
+    match writer.finish() {
+        Err(e) => assert_eq!(e.to_string(), "Element <root> is not closed"),
+        x => panic!("Expected `Err(UnclosedElement)`, but got `{:?}`", x),
+    }
+}
+
+/// Nesting is not checked by default
+#[test]
+fn test_write_check_nesting_disabled() {
+    let mut writer = Writer::new(Vec::new());
+    writer
+        .write_event(Event::Start(BytesStart::borrowed_name(b"a")))
+        .unwrap();
+    writer
+        .write_event(Event::End(BytesEnd::borrowed(b"b")))
+        .unwrap();
+    writer
+        .write_event(Event::Start(BytesStart::borrowed_name(b"c")))
+        .unwrap();
+    writer.finish().unwrap();
+    assert_eq!(writer.into_inner(), b"<a></b><c>");
+}
+
+#[test]
+fn test_write_flush() {
+    let mut writer = Writer::new(FailingWriter {
+        written: Vec::new(),
+        limit: 8,
+    });
+    writer
+        .write_event(Event::Empty(BytesStart::borrowed_name(b"a")))
+        .unwrap();
+    writer.flush().unwrap();
+
+    writer
+        .write_event(Event::Empty(BytesStart::borrowed_name(b"b")))
+        .unwrap();
+    match writer.flush() {
+        Err(fast_xml::Error::Io(e)) => assert_eq!(e.to_string(), "disk full"),
+        x => panic!("Expected `Err(Io)`, but got `{:?}`", x),
+    }
+}
+
+#[test]
+fn test_write_parsed_start() -> Result<()> {
+    let mut writer = Writer::new(Vec::new());
+    let start = BytesStart::parse(r#"item id="5" class='new' title="a &amp; b""#)?;
+    writer.write_event(Event::Start(start.to_borrowed()))?;
+    writer.write_event(Event::Empty(BytesStart::parse("child  empty='' ")?))?;
+    writer.write_event(Event::End(start.to_end()))?;
+
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        r#"<item id="5" class='new' title="a &amp; b"><child  empty='' /></item>"#
+    );
+    Ok(())
+}
+
 #[test]
 fn test_write_attrs() -> Result<()> {
     type AttrResult<T> = std::result::Result<T, AttrError>;
@@ -347,6 +755,99 @@ fn test_write_attrs() -> Result<()> {
     Ok(())
 }
 
+/// Values with both quote characters are enclosed in double quotes,
+/// that are escaped inside the value
+#[test]
+fn test_push_attribute_quotes() -> Result<()> {
+    let mut start = BytesStart::borrowed_name(b"tag");
+    start.push_attribute((&b"double"[..], &br#"say "hi""#[..]));
+    start.push_attribute((&b"single"[..], &b"it's"[..]));
+    start.push_attribute((&b"both"[..], &br#"it's "hi""#[..]));
+
+    let mut writer = Writer::new(Vec::new());
+    writer.write_event(Empty(start))?;
+    let result = writer.into_inner();
+    assert_eq!(
+        String::from_utf8(result.clone()).unwrap(),
+        r#"<tag double='say "hi"' single="it's" both="it's &quot;hi&quot;"/>"#
+    );
+
+    let mut reader = Reader::from_reader(&result[..]);
+    reader.trim_text(true);
+    match reader.read_event(&mut Vec::new())? {
+        Empty(elem) => {
+            let attrs = elem
+                .attributes()
+                .map(|a| a.map(|a| (a.key.to_vec(), a.unescaped_value().unwrap().into_owned())))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            assert_eq!(
+                attrs,
+                vec![
+                    (b"double".to_vec(), br#"say "hi""#.to_vec()),
+                    (b"single".to_vec(), b"it's".to_vec()),
+                    (b"both".to_vec(), br#"it's "hi""#.to_vec()),
+                ]
+            );
+        }
+        e => panic!("Expected `Empty` event, found {:?}", e),
+    }
+    Ok(())
+}
+
+/// Read -> mutate -> write -> read cycle of the start events
+#[test]
+fn test_edit_attrs() -> Result<()> {
+    let str_from = r#"<source a="1" b="2" c="3"><inner x='&lt;'/></source>"#;
+    let expected = r#"<copy a="1" c="3" d="&quot;4&quot;"><inner x='&lt;' y="5"/></copy>"#;
+    let mut reader = Reader::from_str(str_from);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    let mut buf = Vec::new();
+    loop {
+        let event = match reader.read_event(&mut buf)? {
+            Eof => break,
+            Start(mut elem) => {
+                elem.set_name(b"copy");
+                assert!(elem.remove_attribute("b")?);
+                elem.push_attribute(("d", r#""4""#));
+                Start(elem)
+            }
+            Empty(mut elem) => {
+                elem.push_attribute(("y", "5"));
+                Empty(elem)
+            }
+            End(_) => End(BytesEnd::borrowed(b"copy")),
+            e => e,
+        };
+        assert!(writer.write_event(event).is_ok());
+        buf.clear();
+    }
+
+    let result = writer.into_inner().into_inner();
+    assert_eq!(String::from_utf8(result.clone()).unwrap(), expected);
+
+    // Check that result can be parsed back
+    let mut reader = Reader::from_reader(&result[..]);
+    reader.trim_text(true);
+    match reader.read_event(&mut buf)? {
+        Start(elem) => {
+            let attrs = elem
+                .attributes()
+                .map(|a| a.map(|a| (a.key.to_vec(), a.unescaped_value().unwrap().into_owned())))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            assert_eq!(
+                attrs,
+                vec![
+                    (b"a".to_vec(), b"1".to_vec()),
+                    (b"c".to_vec(), b"3".to_vec()),
+                    (b"d".to_vec(), br#""4""#.to_vec()),
+                ]
+            );
+        }
+        e => panic!("Expected `Start` event, found {:?}", e),
+    }
+    Ok(())
+}
+
 #[test]
 fn test_new_xml_decl_full() {
     let mut writer = Writer::new(Vec::new());
@@ -667,60 +1168,1220 @@ fn test_read_write_roundtrip_escape_text() -> Result<()> {
     Ok(())
 }
 
+/// Custom entities are preserved when text passes through the reader helpers
+/// with disabled expansion of entities
 #[test]
-fn test_closing_bracket_in_single_quote_attr() {
-    let mut r = Reader::from_str("<a attr='>' check='2'></a>");
-    r.trim_text(true);
+fn test_read_write_roundtrip_unexpanded_entities() -> Result<()> {
+    let input = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <!DOCTYPE section [<!ENTITY custom "value">]>
+        <section label="&custom; &amp; &#x41;">data &custom; &lt;escaped&gt;</section>
+    "#;
+
+    let mut reader = Reader::from_str(input);
+    reader.trim_text(false).expand_entities(false);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
     let mut buf = Vec::new();
-    match r.read_event(&mut buf) {
-        Ok(Start(e)) => {
-            let mut attrs = e.attributes();
-            assert_eq!(
-                attrs.next(),
-                Some(Ok(Attribute {
-                    key: b"attr",
-                    value: Cow::Borrowed(b">"),
-                }))
-            );
-            assert_eq!(
-                attrs.next(),
-                Some(Ok(Attribute {
-                    key: b"check",
-                    value: Cow::Borrowed(b"2"),
-                }))
-            );
-            assert_eq!(attrs.next(), None);
+    loop {
+        match reader.read_event(&mut buf)? {
+            Eof => break,
+            Start(e) => {
+                let attr = e.attributes().next().unwrap()?;
+                let value = attr.unescape_and_decode_value(&reader)?;
+                assert_eq!(value, "&custom; &amp; &#x41;");
+
+                let mut start = BytesStart::owned_name(e.name());
+                start.push_attribute(Attribute {
+                    key: b"label",
+                    value: Cow::Borrowed(value.as_bytes()),
+                });
+                assert!(writer.write_event(Event::Start(start)).is_ok());
+            }
+            Text(e) => {
+                let t = e.unescape_and_decode(&reader)?;
+                assert!(writer
+                    .write_event(Event::Text(BytesText::from_escaped_str(&t)))
+                    .is_ok());
+            }
+            e => assert!(writer.write_event(e).is_ok()),
         }
-        x => panic!("expected <a attr='>'>, got {:?}", x),
     }
-    next_eq!(r, End, b"a");
+
+    let result = writer.into_inner().into_inner();
+    assert_eq!(String::from_utf8(result).unwrap(), input.to_string());
+    Ok(())
 }
 
 #[test]
-fn test_closing_bracket_in_double_quote_attr() {
-    let mut r = Reader::from_str(r#"<a attr=">" check="2"></a>"#);
-    r.trim_text(true);
+fn test_expand_entities() {
+    let mut reader = Reader::from_str("<a>&custom; &lt;</a>text &lt;");
+    reader.trim_text(true);
     let mut buf = Vec::new();
-    match r.read_event(&mut buf) {
-        Ok(Start(e)) => {
-            let mut attrs = e.attributes();
-            assert_eq!(
-                attrs.next(),
-                Some(Ok(Attribute {
-                    key: b"attr",
-                    value: Cow::Borrowed(b">"),
-                }))
-            );
-            assert_eq!(
-                attrs.next(),
-                Some(Ok(Attribute {
-                    key: b"check",
-                    value: Cow::Borrowed(b"2"),
-                }))
-            );
-            assert_eq!(attrs.next(), None);
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event(&mut buf) {
+        Ok(Text(e)) => {
+            // Unknown entities could not be expanded
+            assert!(e.unescape_and_decode(&reader).is_err());
+            reader.expand_entities(false);
+            assert_eq!(e.unescape_and_decode(&reader).unwrap(), "&custom; &lt;");
         }
-        x => panic!("expected <a attr='>'>, got {:?}", x),
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+    assert!(matches!(reader.read_event(&mut buf), Ok(End(_))));
+    match reader.read_event(&mut buf) {
+        Ok(Text(e)) => {
+            assert_eq!(e.unescape_and_decode(&reader).unwrap(), "text &lt;");
+            reader.expand_entities(true);
+            assert_eq!(e.unescape_and_decode(&reader).unwrap(), "text <");
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_general_refs_strict() {
+    let mut reader = Reader::from_str("<a>text &unknown; &amp;</a>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event(&mut buf) {
+        Ok(Text(e)) => {
+            assert_eq!(e.escaped(), b"text &unknown; &amp;");
+            assert!(e.unescape_and_decode(&reader).is_err());
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+    assert!(matches!(reader.read_event(&mut buf), Ok(End(_))));
+}
+
+#[test]
+fn test_general_refs_lenient() {
+    let xml = "<a>&unknown;text &amp; &#x41; & ; &;&other;&lt;&last;</a>";
+    let mut reader = Reader::from_str(xml);
+    reader
+        .trim_text(true)
+        .emit_general_refs(true)
+        .with_event_spans(true);
+    let mut buf = Vec::new();
+
+    let mut events = Vec::new();
+    loop {
+        match reader.read_event_with_span(&mut buf).unwrap() {
+            (Eof, _) => break,
+            (e, span) => {
+                // Spans of events cover exactly their parts of the text
+                if let GeneralRef(ref r) = e {
+                    assert_eq!(&xml.as_bytes()[span.start + 1..span.end - 1], r.name());
+                } else if let Text(ref t) = e {
+                    assert_eq!(&xml.as_bytes()[span.clone()], t.escaped());
+                }
+                events.push(e.into_owned());
+            }
+        }
+    }
+    assert_eq!(
+        events,
+        vec![
+            Start(BytesStart::borrowed_name(b"a")),
+            GeneralRef(BytesRef::new(b"unknown".as_ref())),
+            Text(BytesText::from_escaped(
+                b"text &amp; &#x41; & ; &;".as_ref()
+            )),
+            GeneralRef(BytesRef::new(b"other".as_ref())),
+            Text(BytesText::from_escaped(b"&lt;".as_ref())),
+            GeneralRef(BytesRef::new(b"last".as_ref())),
+            End(BytesEnd::borrowed(b"a")),
+        ]
+    );
+}
+
+/// Positions and spans after references count the original line endings
+#[test]
+fn test_general_refs_lenient_crlf() {
+    let xml = "<p>&x;a\r\nb\r\nc&y;d\re</p>";
+    let mut reader = Reader::from_str(xml);
+    reader
+        .trim_text(true)
+        .emit_general_refs(true)
+        .with_event_spans(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event_with_span(&mut buf) {
+        Ok((GeneralRef(ref r), span)) => {
+            assert_eq!(r.name(), b"x");
+            assert_eq!(span, 3..6);
+        }
+        e => panic!("Expecting GeneralRef(x), found {:?}", e),
+    }
+    assert_eq!(reader.buffer_position(), 6);
+    match reader.read_event_with_span(&mut buf) {
+        Ok((Text(ref t), span)) => {
+            assert_eq!(&**t, b"a\nb\nc");
+            assert_eq!(span, 6..13);
+        }
+        e => panic!("Expecting Text, found {:?}", e),
+    }
+    assert_eq!(reader.buffer_position(), 13);
+    match reader.read_event_with_span(&mut buf) {
+        Ok((GeneralRef(ref r), span)) => {
+            assert_eq!(r.name(), b"y");
+            assert_eq!(span, 13..16);
+        }
+        e => panic!("Expecting GeneralRef(y), found {:?}", e),
+    }
+    match reader.read_event_with_span(&mut buf) {
+        Ok((Text(ref t), span)) => {
+            assert_eq!(&**t, b"d\ne");
+            assert_eq!(span, 16..19);
+        }
+        e => panic!("Expecting Text, found {:?}", e),
+    }
+    assert_eq!(reader.buffer_position(), 19);
+    assert!(matches!(reader.read_event(&mut buf), Ok(End(_))));
+}
+
+/// References split texts, which are read into a buffer
+#[test]
+fn test_general_refs_lenient_buffered() -> Result<()> {
+    let xml = "<a>one &x; two &y;</a>";
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    reader.trim_text(true).emit_general_refs(true);
+    let mut writer = Writer::new(Vec::new());
+    let mut buf = Vec::new();
+
+    let mut refs = Vec::new();
+    loop {
+        match reader.read_event(&mut buf)? {
+            Eof => break,
+            GeneralRef(e) => {
+                refs.push(from_utf8(e.name()).unwrap().to_string());
+                writer.write_event(GeneralRef(e))?;
+            }
+            e => writer.write_event(e)?,
+        }
+        buf.clear();
+    }
+    assert_eq!(refs, ["x", "y"]);
+    assert_eq!(writer.into_inner(), xml.as_bytes());
+    Ok(())
+}
+
+#[cfg(feature = "custom-entities")]
+#[test]
+fn test_doctype_entities() -> Result<()> {
+    let xml = r#"<!DOCTYPE root [
+        <!ENTITY company "ACME &amp; Co">
+        <!ENTITY year '2022'>
+        <!ENTITY company "ignored redeclaration">
+        <!ENTITY % parameter "excluded">
+        <!ENTITY external SYSTEM "excluded.xml">
+    ]>
+    <root>&company; &year;</root>"#;
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    assert!(reader.doctype_entities().is_empty());
+    assert!(matches!(reader.read_event(&mut buf)?, DocType(_)));
+
+    let mut entities: Vec<_> = reader
+        .doctype_entities()
+        .iter()
+        .map(|(k, v)| (from_utf8(k).unwrap(), from_utf8(v).unwrap()))
+        .collect();
+    entities.sort();
+    assert_eq!(entities, [("company", "ACME &amp; Co"), ("year", "2022")]);
+
+    assert!(matches!(reader.read_event(&mut buf)?, Start(_)));
+    match reader.read_event(&mut buf)? {
+        Text(e) => assert_eq!(
+            e.unescape_and_decode_with_custom_entities(&reader, reader.doctype_entities())?,
+            "ACME &amp; Co 2022"
+        ),
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_all() -> Result<()> {
+    let input = r#"<?xml version="1.0"?>
+<!-- comment -->
+<root attr="value">
+    <child>text &amp; more</child>
+    <empty/>
+    <![CDATA[data]]>
+</root>
+"#;
+    let mut reader = Reader::from_str(input);
+    let events = reader.parse_all()?;
+
+    // The cached list can be iterated many times
+    for _ in 0..2 {
+        let starts: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                Start(e) | Empty(e) => Some(e.name()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(starts, vec![&b"root"[..], b"child", b"empty"]);
+    }
+    // Reader is exhausted
+    assert!(matches!(reader.read_event_unbuffered()?, Eof));
+    assert!(!events.iter().any(|e| matches!(e, Eof)));
+
+    // Events reconstruct the whole document
+    let mut writer = Writer::new(Vec::new());
+    for event in events {
+        writer.write_event(event)?;
+    }
+    assert_eq!(from_utf8(&writer.into_inner()).unwrap(), input);
+    Ok(())
+}
+
+#[test]
+fn test_max_token_size_under_limit() {
+    let text = "x".repeat(100_000);
+    let xml = format!("<a>{}</a>", text);
+    // Small internal buffer to force many reads of one text node
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader
+        .trim_text(true)
+        .set_buffer_capacity(4096)
+        .max_token_size(100_000);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    buf.clear();
+    match reader.read_event(&mut buf) {
+        Ok(Text(e)) => assert_eq!(e.len(), text.len()),
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+    buf.clear();
+    assert!(matches!(reader.read_event(&mut buf), Ok(End(_))));
+}
+
+#[test]
+fn test_max_token_size_over_limit() {
+    let text = "x".repeat(100_000);
+    let xml = format!("<a>{}</a>", text);
+
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader.trim_text(true).max_token_size(1000);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    buf.clear();
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::TokenTooLarge { limit: 1000 }) => {}
+        e => panic!("Expected TokenTooLarge error, got {:?}", e),
+    }
+    // Reading stops as soon as the limit is reached
+    assert!(buf.len() <= 1000);
+    assert!(matches!(reader.read_event(&mut buf), Ok(Eof)));
+
+    let mut reader = Reader::from_str(&xml);
+    reader.trim_text(true).max_token_size(1000);
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::TokenTooLarge { limit: 1000 }) => {}
+        e => panic!("Expected TokenTooLarge error, got {:?}", e),
+    }
+    // Position points to the start of the text
+    assert_eq!(reader.buffer_position(), 3);
+}
+
+#[test]
+fn test_max_token_size_unclosed_markup() {
+    let xml = format!("<!--{}", "x".repeat(100_000));
+
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader.trim_text(true).max_token_size(1000);
+    let mut buf = Vec::new();
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::TokenTooLarge { limit: 1000 }) => {}
+        e => panic!("Expected TokenTooLarge error, got {:?}", e),
+    }
+    assert!(buf.len() <= 1000);
+
+    let xml = format!("<a{}", " b='c'".repeat(1000));
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader.trim_text(true).max_token_size(1000);
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::TokenTooLarge { limit: 1000 }) => {}
+        e => panic!("Expected TokenTooLarge error, got {:?}", e),
+    }
+}
+
+/// After the `TokenTooLarge` error both readers point to the start of the token
+#[test]
+fn test_max_token_size_position() {
+    let long = "x".repeat(1000);
+    let cases = [
+        (format!("<a>{}</a>", long), 3),
+        (format!("<a><!--{}--></a>", long), 4),
+        (format!("<a><![CDATA[{}]]></a>", long), 4),
+        (format!("<a><?pi {}?></a>", long), 4),
+        (format!("<a><b attr='{}'/></a>", long), 4),
+        (format!("<a></{}>", long), 4),
+    ];
+    for (xml, position) in cases.iter() {
+        let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+        reader.trim_text(true).max_token_size(100);
+        let mut buf = Vec::new();
+        assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+        match reader.read_event(&mut buf) {
+            Err(fast_xml::Error::TokenTooLarge { limit: 100 }) => {}
+            e => panic!("Expected TokenTooLarge error, got {:?}", e),
+        }
+        assert_eq!(reader.buffer_position(), *position, "buffered: {}", xml);
+
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true).max_token_size(100);
+        assert!(matches!(reader.read_event_unbuffered(), Ok(Start(_))));
+        match reader.read_event_unbuffered() {
+            Err(fast_xml::Error::TokenTooLarge { limit: 100 }) => {}
+            e => panic!("Expected TokenTooLarge error, got {:?}", e),
+        }
+        assert_eq!(reader.buffer_position(), *position, "borrowed: {}", xml);
+    }
+}
+
+/// Reads all events, returns the number of `Empty` events or the error
+fn count_items(reader: &mut Reader<impl std::io::BufRead>) -> Result<usize> {
+    let mut buf = Vec::new();
+    let mut count = 0;
+    loop {
+        match reader.read_event(&mut buf)? {
+            Empty(_) => count += 1,
+            Eof => return Ok(count),
+            _ => (),
+        }
+        buf.clear();
+    }
+}
+
+#[test]
+fn test_max_document_size_under_limit() {
+    let xml = format!("<root>{}</root>", "<item/>".repeat(1000));
+
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader.max_document_size(xml.len());
+    assert_eq!(count_items(&mut reader).unwrap(), 1000);
+    assert_eq!(reader.buffer_position(), xml.len());
+
+    let mut reader = Reader::from_str(&xml);
+    reader.max_document_size(xml.len());
+    assert_eq!(count_items(&mut reader).unwrap(), 1000);
+}
+
+#[test]
+fn test_max_document_size_over_limit() {
+    let xml = format!("<root>{}</root>", "<item/>".repeat(1000));
+    let limit = xml.len() - 1;
+
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader.max_document_size(limit);
+    match count_items(&mut reader) {
+        Err(fast_xml::Error::SizeLimitExceeded { limit: l }) => assert_eq!(l, limit),
+        e => panic!("Expected SizeLimitExceeded error, got {:?}", e),
+    }
+    // Only the closing tag is not read
+    assert_eq!(reader.buffer_position(), xml.len());
+    let mut buf = Vec::new();
+    assert!(matches!(reader.read_event(&mut buf), Ok(Eof)));
+
+    // Reading stops partway through the document
+    let mut reader = Reader::from_str(&xml);
+    reader.max_document_size(100);
+    match count_items(&mut reader) {
+        Err(fast_xml::Error::SizeLimitExceeded { limit: 100 }) => {}
+        e => panic!("Expected SizeLimitExceeded error, got {:?}", e),
+    }
+    assert!(reader.buffer_position() <= 100 + "<item/>".len());
+}
+
+#[test]
+fn test_max_document_size_large_token() {
+    let xml = format!("<a>{}</a>", "x".repeat(100_000));
+
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(64, xml.as_bytes()));
+    reader
+        .trim_text(true)
+        .max_document_size(1000)
+        .max_token_size(10_000);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    buf.clear();
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::SizeLimitExceeded { limit: 1000 }) => {}
+        e => panic!("Expected SizeLimitExceeded error, got {:?}", e),
+    }
+    // The text is not read completely
+    assert!(buf.len() <= 1000);
+
+    // The token limit is reported, when it is reached first
+    let mut reader = Reader::from_str(&xml);
+    reader
+        .trim_text(true)
+        .max_document_size(10_000)
+        .max_token_size(1000);
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::TokenTooLarge { limit: 1000 }) => {}
+        e => panic!("Expected TokenTooLarge error, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_check_names_valid() {
+    let xml = r#"<_root xmlns:x="urn:x" a-b.c="1" x:attr='2'>
+        <x:child Ünïcödé="3"><日本語 a·b="4"></日本語 ></x:child></_root>"#;
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true).check_names(true);
+    loop {
+        match reader.read_event_unbuffered() {
+            Ok(Eof) => break,
+            Ok(_) => {}
+            Err(e) => panic!("Unexpected error at {}: {:?}", reader.buffer_position(), e),
+        }
+    }
+}
+
+#[test]
+fn test_check_names_starts_with_digit() {
+    let mut reader = Reader::from_str("<1tag/>");
+    reader.trim_text(true).check_names(true);
+    match reader.read_event_unbuffered() {
+        Err(fast_xml::Error::IllFormedName { offset: 1 }) => {}
+        e => panic!("Expected IllFormedName error, got {:?}", e),
+    }
+
+    let mut reader = Reader::from_str("<root><tag 9attr='1'>");
+    reader.trim_text(true).check_names(true);
+    assert!(matches!(reader.read_event_unbuffered(), Ok(Start(_))));
+    match reader.read_event_unbuffered() {
+        Err(fast_xml::Error::IllFormedName { offset: 11 }) => {}
+        e => panic!("Expected IllFormedName error, got {:?}", e),
+    }
+
+    // Not checked by default
+    let mut reader = Reader::from_str("<1tag/>");
+    reader.trim_text(true);
+    assert!(matches!(reader.read_event_unbuffered(), Ok(Empty(_))));
+}
+
+#[test]
+fn test_check_names_with_space() {
+    let mut reader = Reader::from_str("<tag></tag name>");
+    reader
+        .trim_text(true)
+        .check_names(true)
+        .check_end_names(false);
+    assert!(matches!(reader.read_event_unbuffered(), Ok(Start(_))));
+    match reader.read_event_unbuffered() {
+        Err(fast_xml::Error::IllFormedName { offset: 10 }) => {}
+        e => panic!("Expected IllFormedName error, got {:?}", e),
+    }
+}
+
+/// Buffered and borrowed readers report the same error for an unclosed tag
+#[test]
+fn test_check_names_unclosed_element() {
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(2, b"<tag".as_ref()));
+    reader.trim_text(true).check_names(true);
+    let mut buf = Vec::new();
+    match reader.read_event(&mut buf) {
+        Err(fast_xml::Error::UnexpectedEof(s)) => assert_eq!(s, "Element"),
+        e => panic!("Expected `UnexpectedEof(Element)`, got {:?}", e),
+    }
+    let buffered = reader.buffer_position();
+
+    let mut reader = Reader::from_str("<tag");
+    reader.trim_text(true).check_names(true);
+    match reader.read_event_unbuffered() {
+        Err(fast_xml::Error::UnexpectedEof(s)) => assert_eq!(s, "Element"),
+        e => panic!("Expected `UnexpectedEof(Element)`, got {:?}", e),
+    }
+    assert_eq!(reader.buffer_position(), buffered);
+}
+
+/// Reads events of the `<root>` element with mixed text and CDATA content
+fn mixed_content(merge_cdata_as_text: bool) -> Vec<Event<'static>> {
+    let mut reader = Reader::from_str("<root>a &amp; b<![CDATA[<c> & 'd']]>e<![CDATA[]]></root>");
+    reader
+        .trim_text(true)
+        .merge_cdata_as_text(merge_cdata_as_text);
+    let mut buf = Vec::new();
+    let mut events = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            Start(_) | End(_) => {}
+            Eof => return events,
+            e => events.push(e.into_owned()),
+        }
+        buf.clear();
+    }
+}
+
+#[test]
+fn test_cdata_not_merged() {
+    assert_eq!(
+        mixed_content(false),
+        vec![
+            Text(BytesText::from_escaped_str("a &amp; b")),
+            CData(BytesCData::new(b"<c> & 'd'".as_ref())),
+            Text(BytesText::from_escaped_str("e")),
+            CData(BytesCData::new(b"".as_ref())),
+        ]
+    );
+}
+
+#[test]
+fn test_cdata_merged_as_text() {
+    let events = mixed_content(true);
+    assert_eq!(
+        events,
+        vec![
+            Text(BytesText::from_escaped_str("a &amp; b")),
+            Text(BytesText::from_escaped_str("&lt;c&gt; &amp; &apos;d&apos;")),
+            Text(BytesText::from_escaped_str("e")),
+            Text(BytesText::from_escaped_str("")),
+        ]
+    );
+
+    let unescaped: Vec<_> = events
+        .iter()
+        .map(|e| match e {
+            Text(e) => String::from_utf8(e.unescaped().unwrap().into_owned()).unwrap(),
+            e => panic!("Expected Text event, got {:?}", e),
+        })
+        .collect();
+    assert_eq!(unescaped, vec!["a & b", "<c> & 'd'", "e", ""]);
+}
+
+/// Reads all `Text` events from the `<data>` element and checks, that each one
+/// is a well-formed chunk of text
+fn text_chunks<R: std::io::BufRead>(reader: &mut Reader<R>, chunk: usize) -> Vec<String> {
+    let mut buf = Vec::new();
+    let mut chunks = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Start(_)) => break,
+            // An empty text before the root
+            Ok(Text(e)) if e.is_empty() => buf.clear(),
+            e => panic!("Expected Start event, got {:?}", e),
+        }
+    }
+    loop {
+        buf.clear();
+        match reader.read_event(&mut buf) {
+            Ok(Text(e)) => {
+                // Each chunk is valid UTF-8 and can be unescaped independently
+                let text = from_utf8(&e).expect("chunk splits a character").to_owned();
+                e.unescaped().expect("chunk splits a reference");
+                chunks.push(text);
+            }
+            Ok(End(_)) => break,
+            e => panic!("Expected Text or End event, got {:?}", e),
+        }
+    }
+    buf.clear();
+    assert!(matches!(reader.read_event(&mut buf), Ok(Eof)));
+
+    // All chunks except the last one has the requested size, but can be
+    // longer to finish a character or a reference
+    let (last, others) = chunks.split_last().unwrap();
+    assert!(last.len() <= chunk + 8, "{:?}", last);
+    for c in others {
+        assert!(c.len() >= chunk && c.len() <= chunk + 8, "{:?}", c);
+    }
+    chunks
+}
+
+#[test]
+fn test_text_chunk_size() {
+    // Multi-byte characters and references of different lengths
+    let text = "ab€c&amp;d&#x20AC;e😀&lt;f".repeat(50);
+    let xml = format!("<data>{}</data>", text);
+
+    for chunk in [1, 2, 3, 5, 7, 16, 100] {
+        let mut reader = Reader::from_str(&xml);
+        reader.text_chunk_size(chunk);
+        let chunks = text_chunks(&mut reader, chunk);
+        assert_eq!(chunks.concat(), text);
+        assert!(chunks.len() > 1);
+
+        // Small internal buffer to force chunk boundaries that does not
+        // coincide with boundaries of reads
+        let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(5, xml.as_bytes()));
+        reader.text_chunk_size(chunk);
+        assert_eq!(text_chunks(&mut reader, chunk), chunks);
+    }
+}
+
+#[test]
+fn test_text_chunk_size_short_text() {
+    let mut reader = Reader::from_str("<data>short</data>");
+    reader.text_chunk_size(100);
+    assert_eq!(text_chunks(&mut reader, 100), vec!["short"]);
+}
+
+/// `\r\n` on a chunk boundary is normalized to one line feed
+#[test]
+fn test_text_chunk_size_crlf() {
+    fn texts<R: std::io::BufRead>(reader: &mut Reader<R>) -> Vec<String> {
+        let mut buf = Vec::new();
+        let mut texts = Vec::new();
+        loop {
+            match reader.read_event(&mut buf).unwrap() {
+                Text(e) => texts.push(from_utf8(&e).unwrap().to_owned()),
+                Eof => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        texts
+    }
+
+    let xml = "<a>ab\r\ncd\r\r\nef</a>";
+    for chunk in [1, 2, 3, 4, 5, 6] {
+        let mut reader = Reader::from_str(xml);
+        reader.text_chunk_size(chunk);
+        let chunks = texts(&mut reader);
+        assert_eq!(chunks.concat(), "ab\ncd\n\nef", "chunk size {}", chunk);
+
+        let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(5, xml.as_bytes()));
+        reader.text_chunk_size(chunk);
+        assert_eq!(texts(&mut reader), chunks, "chunk size {}", chunk);
+    }
+}
+
+/// Only the first chunk is trimmed from the start and only the last one from the end
+#[test]
+fn test_text_chunk_size_trim() {
+    let mut reader = Reader::from_str("<data>  abc def ghi  </data>");
+    reader.trim_text(true).text_chunk_size(4);
+    let mut buf = Vec::new();
+    let mut chunks = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            Text(e) => chunks.push(from_utf8(&e).unwrap().to_owned()),
+            Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    assert_eq!(chunks, vec!["abc ", "def ", "ghi ", ""]);
+}
+
+#[test]
+fn test_max_attributes() {
+    let xml = format!("<many{}/><few a='1' b='2'/>", " a='1'".repeat(10_000));
+    let mut reader = Reader::from_str(&xml);
+    reader.trim_text(true).max_attributes(2);
+    let mut buf = Vec::new();
+
+    match reader.read_event(&mut buf) {
+        Ok(Empty(e)) => {
+            let mut attrs = e.attributes();
+            attrs.with_checks(false);
+            assert!(attrs.next().unwrap().is_ok());
+            assert!(attrs.next().unwrap().is_ok());
+            assert_eq!(attrs.next(), Some(Err(AttrError::TooMany(17))));
+            assert_eq!(attrs.next(), None);
+        }
+        e => panic!("Expected `Empty`, got {:?}", e),
+    }
+    // Elements within the limit are not affected
+    match reader.read_event(&mut buf) {
+        Ok(Empty(e)) => assert_eq!(e.attributes().count(), 2),
+        e => panic!("Expected `Empty`, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_max_attribute_value_len() {
+    let xml = format!("<tag long='{}' short='abc'/>", "x".repeat(100_000));
+    let mut reader = Reader::from_str(&xml);
+    reader.trim_text(true).max_attribute_value_len(3);
+    let mut buf = Vec::new();
+
+    match reader.read_event(&mut buf) {
+        Ok(Empty(e)) => {
+            let mut attrs = e.attributes();
+            assert_eq!(attrs.next(), Some(Err(AttrError::TooLong(10))));
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"short",
+                    value: Cow::Borrowed(b"abc"),
+                }))
+            );
+            assert_eq!(attrs.next(), None);
+            // Limits are kept in the owned copies
+            assert_eq!(
+                e.into_owned().attributes().next(),
+                Some(Err(AttrError::TooLong(10)))
+            );
+        }
+        e => panic!("Expected `Empty`, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_emit_whitespace() {
+    let xml = "<root>\n  <a> text </a>\n\t<b/>\r\n</root>";
+    let mut reader = Reader::from_str(xml);
+    reader.emit_whitespace(true);
+    let mut buf = Vec::new();
+
+    let mut texts = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            // Skip the empty text before the root element
+            Text(e) if e.is_empty() => assert!(!e.is_whitespace()),
+            Text(e) => texts.push((e.escaped().to_vec(), e.is_whitespace())),
+            Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    assert_eq!(
+        texts,
+        vec![
+            (b"\n  ".to_vec(), true),
+            (b" text ".to_vec(), false),
+            (b"\n\t".to_vec(), true),
+            // Line endings are normalized by default
+            (b"\n".to_vec(), true),
+        ]
+    );
+}
+
+/// The whitespace mark does not affect comparison of texts
+#[test]
+fn test_emit_whitespace_eq() {
+    let mut reader = Reader::from_str("<root>\n</root>");
+    reader.emit_whitespace(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Text(e)) if e.is_empty()));
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event(&mut buf) {
+        Ok(Text(e)) => {
+            assert!(e.is_whitespace());
+            assert_eq!(e, BytesText::from_escaped_str("\n"));
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_emit_whitespace_disabled() {
+    let mut reader = Reader::from_str("<root>\n  <a/>\n</root>");
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Text(e)) if e.is_empty()));
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event(&mut buf) {
+        Ok(Text(e)) => {
+            assert_eq!(&*e, b"\n  ");
+            assert!(!e.is_whitespace());
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+    assert!(!BytesText::from_escaped_str(" ").is_whitespace());
+}
+
+/// Reads all texts from the document with `trim_text(true)` and the specified
+/// extra whitespaces, checking that a slice and a buffered reader return the same
+fn texts_with_extra_whitespace(xml: &str, extra: &[u8]) -> Vec<Vec<u8>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true).extra_whitespace(extra);
+    let mut texts = Vec::new();
+    loop {
+        match reader.read_event_unbuffered().unwrap() {
+            Text(e) => texts.push(e.escaped().to_vec()),
+            Eof => break,
+            _ => (),
+        }
+    }
+
+    let mut reader = Reader::from_reader(Cursor::new(xml.as_bytes()));
+    reader.trim_text(true).extra_whitespace(extra);
+    let mut buf = Vec::new();
+    let mut buffered = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            Text(e) => buffered.push(e.escaped().to_vec()),
+            Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    assert_eq!(texts, buffered);
+    texts
+}
+
+#[test]
+fn test_extra_whitespace() {
+    let xml = "<root>\x0C text\x0B\x0C<a>\x0C\x0C</a>\x0C</root>";
+
+    // Form feeds and vertical tabs are not whitespaces by default
+    assert_eq!(
+        texts_with_extra_whitespace(xml, b""),
+        vec![
+            b"\x0C text\x0B\x0C".to_vec(),
+            b"\x0C\x0C".to_vec(),
+            b"\x0C".to_vec(),
+        ]
+    );
+    assert_eq!(
+        texts_with_extra_whitespace(xml, b"\x0C"),
+        vec![b"text\x0B".to_vec()]
+    );
+    assert_eq!(
+        texts_with_extra_whitespace(xml, b"\x0B\x0C"),
+        vec![b"text".to_vec()]
+    );
+}
+
+#[test]
+fn test_extra_whitespace_names() {
+    // Names and attributes are parsed according to the specification
+    let mut reader = Reader::from_str("<a\x0Cb='1'/>");
+    reader.trim_text(true).extra_whitespace(b"\x0C");
+    match reader.read_event_unbuffered().unwrap() {
+        Empty(e) => {
+            assert_eq!(e.name(), b"a\x0Cb='1'");
+            assert!(e.attributes().next().is_none());
+        }
+        e => panic!("Expected `Empty`, got {:?}", e),
+    }
+}
+
+#[test]
+#[should_panic(expected = "extra whitespace characters must be ASCII")]
+fn test_extra_whitespace_non_ascii() {
+    // U+00A0 NO-BREAK SPACE is encoded as `\xC2\xA0` in UTF-8
+    Reader::from_str("<a>\u{A0}text\u{A0}</a>").extra_whitespace(b"\xA0");
+}
+
+#[test]
+fn test_normalize_line_endings() {
+    let xml = "<root a='1\r\n2\r3'>one\r\ntwo\rthree\r\r\n<![CDATA[x\r\ny\rz]]><!--\r\n--></root>";
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    match reader.read_event(&mut buf).unwrap() {
+        Start(e) => {
+            let attr = e.attributes().next().unwrap().unwrap();
+            assert_eq!(attr.value, Cow::Borrowed(b"1\n2\n3"));
+        }
+        e => panic!("Expected `Start`, got {:?}", e),
+    }
+    assert_eq!(
+        reader.read_event(&mut buf).unwrap(),
+        Text(BytesText::from_escaped_str("one\ntwo\nthree"))
+    );
+    assert_eq!(
+        reader.read_event(&mut buf).unwrap(),
+        CData(BytesCData::from_str("x\ny\nz"))
+    );
+    assert_eq!(
+        reader.read_event(&mut buf).unwrap(),
+        Comment(BytesText::from_escaped_str("\n"))
+    );
+}
+
+#[test]
+fn test_normalize_line_endings_disabled() {
+    let xml = "<root a='1\r\n2\r3'>one\r\ntwo\rthree<![CDATA[x\r\ny]]></root>";
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true).normalize_line_endings(false);
+    let mut buf = Vec::new();
+
+    match reader.read_event(&mut buf).unwrap() {
+        Start(e) => {
+            let attr = e.attributes().next().unwrap().unwrap();
+            assert_eq!(attr.value, Cow::Borrowed(b"1\r\n2\r3"));
+        }
+        e => panic!("Expected `Start`, got {:?}", e),
+    }
+    assert_eq!(
+        reader.read_event(&mut buf).unwrap(),
+        Text(BytesText::from_escaped_str("one\r\ntwo\rthree"))
+    );
+    assert_eq!(
+        reader.read_event(&mut buf).unwrap(),
+        CData(BytesCData::from_str("x\r\ny"))
+    );
+}
+
+#[test]
+fn test_peek_event() {
+    let mut reader = Reader::from_str("<a><b/>text</a>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    let start = Start(BytesStart::borrowed_name(b"a"));
+    assert_eq!(reader.peek_event(&mut buf).unwrap(), &start);
+    // Peeking again returns the same event
+    assert_eq!(reader.peek_event(&mut buf).unwrap(), &start);
+    assert_eq!(reader.read_event(&mut buf).unwrap(), start);
+
+    // Events are not peeked
+    assert_eq!(
+        reader.read_event(&mut buf).unwrap(),
+        Empty(BytesStart::borrowed_name(b"b"))
+    );
+
+    let text = Text(BytesText::from_escaped_str("text"));
+    assert_eq!(reader.peek_event(&mut buf).unwrap(), &text);
+    assert_eq!(reader.read_event(&mut buf).unwrap(), text);
+
+    let end = End(BytesEnd::borrowed(b"a"));
+    assert_eq!(reader.peek_event(&mut buf).unwrap(), &end);
+    assert_eq!(reader.read_event(&mut buf).unwrap(), end);
+
+    assert_eq!(reader.peek_event(&mut buf).unwrap(), &Eof);
+    assert_eq!(reader.read_event(&mut buf).unwrap(), Eof);
+}
+
+#[test]
+fn test_peek_event_unbuffered() {
+    let mut reader = Reader::from_str("<a>text</a>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    let text = Text(BytesText::from_escaped_str("text"));
+    assert!(matches!(reader.read_event_unbuffered().unwrap(), Start(_)));
+    assert_eq!(reader.peek_event(&mut buf).unwrap(), &text);
+    assert_eq!(reader.read_event_unbuffered().unwrap(), text);
+    assert!(matches!(reader.read_event_unbuffered().unwrap(), End(_)));
+}
+
+#[test]
+fn test_peek_event_position() {
+    let xml = "<a>text</a>";
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true).with_event_spans(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf).unwrap(), Start(_)));
+    assert_eq!(reader.buffer_position(), 3);
+
+    // Position does not change until the peeked event is consumed
+    assert!(matches!(reader.peek_event(&mut buf).unwrap(), Text(_)));
+    assert_eq!(reader.buffer_position(), 3);
+
+    match reader.read_event_with_span(&mut buf).unwrap() {
+        (Text(e), span) => {
+            assert_eq!(&*e, b"text");
+            assert_eq!(&xml[span], "text");
+        }
+        e => panic!("Expected `Text`, got {:?}", e),
+    }
+    assert_eq!(reader.buffer_position(), 7);
+}
+
+#[test]
+fn test_peek_event_check_end_names() {
+    let mut reader = Reader::from_str("<a><b></b></c>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf).unwrap(), Start(_)));
+    assert!(matches!(reader.peek_event(&mut buf).unwrap(), Start(_)));
+    assert!(matches!(reader.read_event(&mut buf).unwrap(), Start(_)));
+    // The end name is checked when the event is peeked
+    assert!(matches!(reader.peek_event(&mut buf).unwrap(), End(_)));
+    assert!(matches!(reader.read_event(&mut buf).unwrap(), End(_)));
+    match reader.peek_event(&mut buf) {
+        Err(fast_xml::Error::EndEventMismatch { expected, found }) => {
+            assert_eq!(expected, "a");
+            assert_eq!(found, "c");
+        }
+        e => panic!("Expected `EndEventMismatch`, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_expect_start() {
+    let xml = r#"<?xml version="1.0"?>
+        <!DOCTYPE root>
+        <!-- comment -->
+        <?pi?>
+        <root attr="1">
+            <item>text</item>
+            <empty/>
+        </root>"#;
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+
+    let root = reader.expect_start("root", &mut buf).unwrap();
+    assert_eq!(root.name(), b"root");
+    assert_eq!(root.attributes_raw(), br#" attr="1""#);
+
+    let item = reader.expect_start("item", &mut buf).unwrap();
+    assert_eq!(reader.read_text(item.name(), &mut buf).unwrap(), "text");
+
+    let empty = reader.expect_start("empty", &mut buf).unwrap();
+    assert_eq!(empty.name(), b"empty");
+}
+
+#[test]
+fn test_expect_start_wrong_name() {
+    let mut reader = Reader::from_str("<root><other/></root>");
+    let mut buf = Vec::new();
+
+    reader.expect_start("root", &mut buf).unwrap();
+    match reader.expect_start("item", &mut buf) {
+        Err(fast_xml::Error::StartNotFound { expected, found }) => {
+            assert_eq!(expected, "item");
+            assert_eq!(found, "`Empty` event of element <other>");
+        }
+        x => panic!("Expected `Err(StartNotFound)`, but got `{:?}`", x),
+    }
+}
+
+#[test]
+fn test_expect_start_wrong_event() {
+    let mut reader = Reader::from_str("<root>text<![CDATA[cdata]]></root>");
+    let mut buf = Vec::new();
+
+    reader.expect_start("root", &mut buf).unwrap();
+    match reader.expect_start("item", &mut buf) {
+        Err(e @ fast_xml::Error::StartNotFound { .. }) => assert_eq!(
+            e.to_string(),
+            "Expecting start tag <item>, but found `Text` event"
+        ),
+        x => panic!("Expected `Err(StartNotFound)`, but got `{:?}`", x),
+    }
+    match reader.expect_start("item", &mut buf) {
+        Err(fast_xml::Error::StartNotFound { found, .. }) => {
+            assert_eq!(found, "`CData` event")
+        }
+        x => panic!("Expected `Err(StartNotFound)`, but got `{:?}`", x),
+    }
+    match reader.expect_start("item", &mut buf) {
+        Err(fast_xml::Error::StartNotFound { found, .. }) => {
+            assert_eq!(found, "`End` event of element <root>")
+        }
+        x => panic!("Expected `Err(StartNotFound)`, but got `{:?}`", x),
+    }
+    match reader.expect_start("item", &mut buf) {
+        Err(fast_xml::Error::UnexpectedEof(e)) => assert_eq!(e, "<item>"),
+        x => panic!("Expected `Err(UnexpectedEof)`, but got `{:?}`", x),
+    }
+}
+
+#[test]
+fn test_text_raw_and_unescaped() {
+    let xml = "<tag>fish &amp; chips &#x26; peas</tag>";
+    let mut reader = Reader::from_str(xml);
+    reader.with_event_spans(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Text(e)) if e.is_empty()));
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event_with_span(&mut buf) {
+        Ok((Text(e), span)) => {
+            // Raw content is exactly the source slice
+            assert_eq!(e.escaped(), xml[span].as_bytes());
+            assert_eq!(&*e, b"fish &amp; chips &#x26; peas");
+            assert_eq!(&*e.unescaped().unwrap(), b"fish & chips & peas");
+            assert_eq!(e.unescape().unwrap(), "fish & chips & peas");
+            assert_ne!(e.escaped(), &*e.unescaped().unwrap());
+            assert_eq!(
+                e.into_inner(),
+                Cow::Borrowed(b"fish &amp; chips &#x26; peas".as_ref())
+            );
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_text_raw_trimmed() {
+    let xml = "<tag>\n  a &lt; b\n</tag>";
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true).with_event_spans(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf), Ok(Start(_))));
+    match reader.read_event_with_span(&mut buf) {
+        Ok((Text(e), span)) => {
+            // Trimmed whitespaces are not stored in the event, but are covered by the span
+            assert_eq!(&*e, b"a &lt; b");
+            assert_eq!(&xml[span], "\n  a &lt; b\n");
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_closing_bracket_in_single_quote_attr() {
+    let mut r = Reader::from_str("<a attr='>' check='2'></a>");
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    match r.read_event(&mut buf) {
+        Ok(Start(e)) => {
+            let mut attrs = e.attributes();
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"attr",
+                    value: Cow::Borrowed(b">"),
+                }))
+            );
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"check",
+                    value: Cow::Borrowed(b"2"),
+                }))
+            );
+            assert_eq!(attrs.next(), None);
+        }
+        x => panic!("expected <a attr='>'>, got {:?}", x),
+    }
+    next_eq!(r, End, b"a");
+}
+
+#[test]
+fn test_closing_bracket_in_double_quote_attr() {
+    let mut r = Reader::from_str(r#"<a attr=">" check="2"></a>"#);
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    match r.read_event(&mut buf) {
+        Ok(Start(e)) => {
+            let mut attrs = e.attributes();
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"attr",
+                    value: Cow::Borrowed(b">"),
+                }))
+            );
+            assert_eq!(
+                attrs.next(),
+                Some(Ok(Attribute {
+                    key: b"check",
+                    value: Cow::Borrowed(b"2"),
+                }))
+            );
+            assert_eq!(attrs.next(), None);
+        }
+        x => panic!("expected <a attr='>'>, got {:?}", x),
     }
     next_eq!(r, End, b"a");
 }
@@ -862,3 +2523,255 @@ fn test_unescape_and_decode_without_bom_does_nothing_if_no_bom_exists() {
     }
     assert!(txt.is_empty());
 }
+
+#[test]
+fn test_decoder_matches_events() {
+    let mut reader = Reader::from_str(r#"<tag attr="значение" other="&lt;"/>"#);
+    reader.trim_text(true);
+    let decoder = reader.decoder();
+
+    match reader.read_event(&mut Vec::new()).unwrap() {
+        Empty(e) => {
+            let mut attrs = e.attributes();
+
+            let attr = attrs.next().unwrap().unwrap();
+            let decoded = decoder.decode(&attr.value).unwrap();
+            assert_eq!(decoded, "значение");
+            assert_eq!(decoded, attr.unescape_and_decode_value(&reader).unwrap());
+
+            // Decoder does not unescape
+            let attr = attrs.next().unwrap().unwrap();
+            assert_eq!(decoder.decode(&attr.value).unwrap(), "&lt;");
+            assert_eq!(attr.unescape_and_decode_value(&reader).unwrap(), "<");
+
+            assert_eq!(decoder.decode(e.name()).unwrap(), "tag");
+        }
+        e => panic!("Expected Empty event, got {:?}", e),
+    }
+}
+
+#[test]
+#[cfg(not(feature = "encoding"))]
+fn test_decoder_invalid_utf8() {
+    let reader = Reader::from_str("");
+    assert!(reader.decoder().decode(b"\xFF").is_err());
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_decoder_declared_encoding() {
+    // "Привет" in windows-1251
+    let xml: &[u8] = b"<?xml version=\"1.0\" encoding=\"windows-1251\"?><tag attr=\"\xCF\xF0\xE8\xE2\xE5\xF2\"/>";
+    let mut reader = Reader::from_reader(xml);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    assert!(matches!(reader.read_event(&mut buf).unwrap(), Decl(_)));
+    let decoder = reader.decoder();
+    assert_eq!(decoder.encoding(), encoding_rs::WINDOWS_1251);
+
+    match reader.read_event(&mut buf).unwrap() {
+        Empty(e) => {
+            let attr = e.attributes().next().unwrap().unwrap();
+            let decoded = decoder.decode(&attr.value).unwrap();
+            assert_eq!(decoded, "Привет");
+            assert_eq!(decoded, attr.unescape_and_decode_value(&reader).unwrap());
+        }
+        e => panic!("Expected Empty event, got {:?}", e),
+    }
+}
+
+#[test]
+fn test_assume_ascii() {
+    let mut reader = Reader::from_str("<tag>caf\u{e9} au lait</tag>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    assert!(matches!(reader.read_event(&mut buf).unwrap(), Start(_)));
+
+    match reader.read_event(&mut buf).unwrap() {
+        Text(e) => {
+            assert_eq!(e.unescape_and_decode(&reader).unwrap(), "caf\u{e9} au lait");
+
+            // A valid UTF-8 sequence is rejected, because it is not ASCII
+            reader.assume_ascii(true);
+            match e.unescape_and_decode(&reader) {
+                Err(fast_xml::Error::Utf8(e)) => assert_eq!(e.valid_up_to(), 3),
+                x => panic!("Expected `Err(Utf8)`, but got `{:?}`", x),
+            }
+            match reader.decoder().decode(&e) {
+                Err(fast_xml::Error::Utf8(e)) => assert_eq!(e.valid_up_to(), 3),
+                x => panic!("Expected `Err(Utf8)`, but got `{:?}`", x),
+            }
+        }
+        e => panic!("Expected Text event, got {:?}", e),
+    }
+    // ASCII data is decoded as usual
+    assert_eq!(reader.decode(b"tag").unwrap(), "tag");
+}
+
+/// Reads names of all start elements
+fn start_names<R: std::io::BufRead>(mut reader: Reader<R>) -> Vec<String> {
+    let mut buf = Vec::new();
+    let mut names = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            Start(e) => names.push(from_utf8(e.name()).unwrap().to_owned()),
+            Eof => return names,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Binary frames, read directly from the underlying reader, are accounted
+/// by `set_buffer_position`, so spans of the following events are correct
+#[test]
+fn test_set_buffer_position_after_raw_read() {
+    use std::io::Read;
+
+    let xml: &[u8] = b"<stream><frame len='3'/>\x01\x02\x03<frame len='2'/>\x04\x05</stream>";
+    // Small internal buffer, so raw reads cross its boundaries
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(8, xml));
+    reader.trim_text(true).with_event_spans(true);
+    let mut buf = Vec::new();
+    let mut frames = Vec::new();
+
+    loop {
+        match reader.read_event_with_span(&mut buf) {
+            Ok((Empty(e), span)) => {
+                assert!(xml[span.clone()].starts_with(b"<frame"));
+                let len: usize = e
+                    .try_get_attribute("len")
+                    .unwrap()
+                    .unwrap()
+                    .unescape_and_decode_value(&reader)
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                let mut frame = vec![0; len];
+                reader.get_mut().read_exact(&mut frame).unwrap();
+                assert_eq!(&xml[span.end..span.end + len], &*frame);
+                frames.push(frame);
+
+                reader.set_buffer_position(span.end + len);
+            }
+            Ok((Start(_), span)) => assert_eq!(span, 0..8),
+            Ok((End(_), span)) => {
+                assert_eq!(&xml[span], b"</stream>");
+                assert_eq!(reader.buffer_position(), xml.len());
+            }
+            Ok((Eof, _)) => break,
+            e => panic!("Unexpected event {:?}", e),
+        }
+        buf.clear();
+    }
+    assert_eq!(frames, vec![vec![1, 2, 3], vec![4, 5]]);
+}
+
+#[test]
+fn test_dyn_reader() {
+    let mut input = Cursor::new("<a><b>text</b><c/></a>");
+    let input: &mut dyn std::io::BufRead = &mut input;
+    assert_eq!(start_names(Reader::from_dyn_reader(input)), vec!["a", "b"]);
+}
+
+#[test]
+fn test_boxed_dyn_reader() {
+    let input: Box<dyn std::io::BufRead> = Box::new(Cursor::new("<a><b>text</b><c/></a>"));
+    assert_eq!(start_names(Reader::from_reader(input)), vec!["a", "b"]);
+}
+
+/// Reads all events from the document, fed to the `PushReader` in chunks of `chunk` bytes
+fn push_events(xml: &str, chunk: usize) -> Vec<Event<'static>> {
+    let mut reader = PushReader::new();
+    let mut events = Vec::new();
+    for bytes in xml.as_bytes().chunks(chunk) {
+        reader.feed(bytes);
+        while let Some(event) = reader.next_event().unwrap() {
+            events.push(event.into_owned());
+        }
+    }
+    reader.finish();
+    loop {
+        let event = reader.next_event().unwrap().unwrap().into_owned();
+        events.push(event.clone());
+        if event == Eof {
+            return events;
+        }
+    }
+}
+
+#[test]
+fn test_push_reader_matches_reader() {
+    let xml = "<?xml version='1.0'?>\
+        <!DOCTYPE root>\
+        <root xmlns:x='urn:x' attr=\"value\">\
+            text &amp; entity\
+            <x:child/>\
+            <![CDATA[<cdata>]]>\
+            <!-- comment -->\
+            <?pi data?>\
+            привет\
+        </root>\
+        tail";
+
+    let mut reader = Reader::from_str(xml);
+    let mut expected = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        let event = reader.read_event(&mut buf).unwrap().into_owned();
+        expected.push(event.clone());
+        if event == Eof {
+            break;
+        }
+        buf.clear();
+    }
+
+    for chunk in 1..=xml.len() {
+        assert_eq!(push_events(xml, chunk), expected, "chunk size {}", chunk);
+    }
+}
+
+#[test]
+fn test_push_reader_needs_more_input() {
+    let mut reader = PushReader::new();
+    reader.reader_mut().trim_text(true);
+    assert_eq!(reader.next_event().unwrap(), None);
+
+    reader.feed(b"<tag attr='val");
+    assert_eq!(reader.next_event().unwrap(), None);
+    assert_eq!(reader.buffer_position(), 0);
+
+    reader.feed(b"ue'>text");
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Start(BytesStart::borrowed(b"tag attr='value'", 3)))
+    );
+    // The end of the text is unknown until the next markup or the end of input
+    assert_eq!(reader.next_event().unwrap(), None);
+    assert_eq!(reader.buffer_position(), 18);
+
+    reader.finish();
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Text(BytesText::from_escaped_str("text")))
+    );
+    assert_eq!(reader.next_event().unwrap(), Some(Eof));
+}
+
+#[test]
+fn test_push_reader_eof_after_finish() {
+    let mut reader = PushReader::new();
+    reader.reader_mut().trim_text(true);
+    reader.feed(b"<root/>");
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Empty(BytesStart::borrowed_name(b"root")))
+    );
+    // More input could follow
+    assert_eq!(reader.next_event().unwrap(), None);
+    assert_eq!(reader.next_event().unwrap(), None);
+
+    reader.finish();
+    assert_eq!(reader.next_event().unwrap(), Some(Eof));
+}